@@ -2,37 +2,323 @@ use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use crate::drums::DrumKind;
-use crate::effects::FilterMode;
-use crate::save::{DelaySave, DistSave, DrumsSave, FilterSave, ReverbSave, RoutingSave,
-                  SaveFile, SeqSave, SidechainSave, TrackSave};
+use crate::audio::{self, AudioEngine, LOW_LATENCY_FRAMES};
+use crate::automation::{AutoState, Automation};
+use crate::command::{self, Command};
+use crate::dump;
+use crate::drums::{DrumKind, DrumMachine, DEFAULT_DECAY, MAX_DECAY, MAX_FIT_STEPS, MAX_TUNE, MIN_DECAY};
+use crate::groove::{self, Groove};
+use crate::midi_in::{CcBinding, MidiInput};
+use crate::param::Param;
+use crate::effects::{AudioEffect, AUTO_GAIN_MAX_CUT_DB, ClipMode, EffectChain, FilterMode, InsertKind, Interp, MAX_INSERTS,
+                     CLIP_DRIVE_MAX, CLIP_DRIVE_MIN, LIMITER_CEILING_MAX_DB, LIMITER_CEILING_MIN_DB,
+                     LIMITER_LOOKAHEAD_MS, MIX_CLIP_LEVEL, OVERSAMPLE_FACTORS, WIDENER_MAX_WIDTH};
+use crate::save::{CcMapSave, DelaySave, DistSave, DrumsSave, FilterSave, OutputSave, PatchSave,
+                  ReverbSave, RoutingSave, SaveFile, SeqSave, SidechainSave, TrackSave};
+use crate::rack::{self, RackFile};
+use crate::render;
+use crate::sample;
 use crate::scale::{Scale, ScaleQuantizer};
-use crate::synth::{ChordType, Synth, WaveType, note_name};
+use crate::solo::{SoloState, SoloTarget};
+use crate::sequencer::{samples_per_step, Direction, Sequencer, VelocityRamp, FULL_PROB, MAX_NUDGE, MAX_SEQ_STEPS, MAX_SWING};
+use crate::tempo::{bar_steps, valid_time_sig, TempoRamp, COMMON_TIME_SIGS, MAX_RAMP_BARS};
+use crate::tuner::TunerPitch;
+use crate::synth::{ChordType, EnvCurve, Patch, Retrigger, Synth, SynthMode, WaveType, DEFAULT_MAX_VOICES, MAX_ANTI_CLICK_MS,
+                   FM_RATIO_RANGE, MAX_ENV_S, MAX_FM_INDEX, MAX_LANES, MAX_VOICES_RANGE, MAX_WAVE_TRIM, note_name};
+
+/// Fallback mode: a piano key counts as released once it has not repeated
+/// for this long.
+pub const FALLBACK_RELEASE_THRESHOLD: Duration = Duration::from_millis(600);
+
+/// Steps per beat the grids can highlight (`grid`); the first is the default
+/// (16th notes in 4/4).
+pub const GRID_BEATS: [usize; 5] = [4, 3, 2, 5, 6];
+
+/// Steps per beat of a time signature on the 16th-note grid: a quarter is 4
+/// steps, an eighth 2, and compound eighth meters (3/8, 6/8, 9/8, 12/8) beat
+/// in dotted quarters of 6.
+pub fn time_sig_beat((beats, unit): (u8, u8)) -> usize {
+    match unit {
+        8 if beats.is_multiple_of(3) => 6,
+        8 => 2,
+        _ => 4,
+    }
+}
+
+/// Nudge change per key press, in percent of a step.
+const NUDGE_STEP: i8 = 5;
+
+/// Octave the drone plays the scale root in when following it (C2 = MIDI 36).
+const DRONE_OCTAVE: u8 = 2;
+
+/// How long the title bar stays lit after a downbeat (a few frames at 60 fps).
+const BEAT_FLASH: Duration = Duration::from_millis(120);
+/// How long the voices meter stays lit after a voice steal.
+const STEAL_FLASH: Duration = Duration::from_millis(300);
+/// BPM change per PageUp/PageDown press, and per fine (Shift/Alt) press.
+const BPM_STEP: f32 = 5.0;
+const BPM_FINE_STEP: f32 = 1.0;
+/// A held BPM key speeds up: the step grows by `BPM_STEP` every this many
+/// repeats, up to `BPM_MAX_ACCEL` × `BPM_STEP`.
+const BPM_ACCEL_REPEATS: u32 = 8;
+const BPM_MAX_ACCEL: u32 = 4;
+/// Presses this close together count as a repeat (terminals without key
+/// repeat events send a held key as presses).
+const BPM_REPEAT_GAP: Duration = Duration::from_millis(150);
+/// How long the clip warning stays lit after the mix last went over.
+const CLIP_HOLD: Duration = Duration::from_millis(1000);
+/// How long the keyboard title marks a piano key folded back into range.
+const FOLD_FLASH: Duration = Duration::from_millis(300);
+/// Drum-preview key repeats (fallback mode) `ROLL_SLOW` apart play at
+/// `ROLL_MIN_VELOCITY`, `ROLL_FAST` or closer at full velocity.  A press more
+/// than `ROLL_GAP` after the last one starts a new hit (full velocity).
+const ROLL_SLOW: Duration = Duration::from_millis(150);
+const ROLL_FAST: Duration = Duration::from_millis(30);
+const ROLL_GAP:  Duration = Duration::from_millis(250);
+const ROLL_MIN_VELOCITY: f32 = 0.4;
+
+/// Project file offered by the Ctrl+S / Ctrl+L prompts, and loaded at startup
+/// when no other project is remembered.
+pub const DEFAULT_PROJECT: &str = "rusttuisynth.json";
+/// Remembers the last project saved or loaded, in the working directory
+/// (like `racks/`), so the next launch reopens it.
+const LAST_PROJECT_FILE: &str = ".tuibeat-last";
+
+/// Steps in a drum track meter (`App::drum_meters`).
+pub const METER_LEVELS: u8 = 8;
+
+/// Meter step for a peak amplitude: square-root scaled, so quiet hits and
+/// tails still register.
+fn meter_level(peak: f32) -> u8 {
+    (peak.clamp(0.0, 1.0).sqrt() * METER_LEVELS as f32).round() as u8
+}
+
+/// Largest step-entry auto-advance (`advance <n>`).
+pub const MAX_SEQ_ADVANCE: usize = 16;
+
+/// Default chance, in percent, that `mutate` changes any one step.
+const DEFAULT_MUTATE_PCT: u8 = 10;
+
+/// Number of selectable rows in the Effects panel.
+pub const EFFECT_ROWS: usize = 10;
 
-const FALLBACK_RELEASE_THRESHOLD: Duration = Duration::from_millis(600);
+/// Effects panel row holding the master-bus insert chain.
+pub const MASTER_FX_ROW: usize = 7;
+/// Effects panel row holding the insert chain of one drum track (`App::fx_drum_track`).
+pub const DRUM_FX_ROW: usize = 8;
+/// Effects panel row holding a melodic bus insert chain (`App::fx_synth_bus`).
+pub const SYNTH_FX_ROW: usize = 9;
+
+/// Compact pan position: `C`, `L35`, `R40`.
+pub fn pan_label(pan: f32) -> String {
+    let pct = (pan.abs() * 100.0).round() as u32;
+    if pct == 0 { "C".to_string() }
+    else        { format!("{}{}", if pan < 0.0 { 'L' } else { 'R' }, pct) }
+}
+
+/// Status text for a master clip mode change.
+fn clip_status(mode: ClipMode) -> String {
+    match mode {
+        ClipMode::Off => "Master Clip: Off (watch the peak meter for overs)".to_string(),
+        m => format!("Master Clip: {}", m.name()),
+    }
+}
+
+/// Cursor position one place back or on (`forward`) from `pos` in `0..n`:
+/// past an edge it wraps round with `wrap`, else it stays at the edge.
+fn move_cursor(pos: usize, n: usize, forward: bool, wrap: bool) -> usize {
+    match (forward, wrap) {
+        (false, _) if pos > 0 => pos - 1,
+        (false, true)         => n - 1,
+        (false, false)        => 0,
+        (true, true)          => (pos + 1) % n,
+        (true, false)         => (pos + 1).min(n - 1),
+    }
+}
+
+/// Status suffix after a step-count change made with the length lock on.
+fn length_lock_note(lock: bool) -> &'static str {
+    if lock { "  (length lock: pattern stretched)" } else { "" }
+}
+
+/// FM operator settings, e.g. `×2.00 idx 2.0` (modulator ratio, index).
+fn fm_label(s: &Synth) -> String {
+    format!("×{:.2} idx {:.1}", s.fm_ratio, s.fm_index)
+}
+
+fn fm_status(s: &Synth) -> String {
+    match s.synth_mode {
+        SynthMode::Fm => format!("Synth mode: FM  (modulator {}; idx 0 = plain carrier)", fm_label(s)),
+        m => format!("Synth mode: {}  (FM {} when on)", m.name(), fm_label(s)),
+    }
+}
+
+fn limiter_status(s: &Synth) -> String {
+    let l = &s.limiter;
+    if !l.enabled { return "Limiter: OFF".to_string(); }
+    format!("Limiter: ON  ceiling {:.1} dB  lookahead {}", l.ceiling_db,
+        if l.lookahead { format!("ON (+{:.0} ms latency)", LIMITER_LOOKAHEAD_MS) } else { "OFF".to_string() })
+}
+
+/// True for the Effects rows that edit an `EffectChain` (add/remove/rack).
+pub fn is_chain_row(sel: usize) -> bool {
+    matches!(sel, MASTER_FX_ROW | DRUM_FX_ROW | SYNTH_FX_ROW)
+}
 
 // ── Key → MIDI note mapping ───────────────────────────────────────────────────
 
-pub fn key_to_note(key: char, base_octave: i32) -> Option<u8> {
-    let (st, oct): (i32, i32) = match key {
-        // Lower row – white keys
-        'z' => (0,0), 'x' => (2,0), 'c' => (4,0), 'v' => (5,0),
-        'b' => (7,0), 'n' => (9,0), 'm' => (11,0),
-        ',' => (12,0), '.' => (14,0), '/' => (16,0),
-        // Lower row – black keys
-        's' => (1,0), 'd' => (3,0), 'g' => (6,0),
-        'h' => (8,0), 'j' => (10,0), 'l' => (13,0), ';' => (15,0),
-        // Upper row – white keys
-        'q' => (0,1), 'w' => (2,1), 'e' => (4,1), 'r' => (5,1),
-        't' => (7,1), 'y' => (9,1), 'u' => (11,1),
-        'i' => (12,1), 'o' => (14,1), 'p' => (16,1),
-        // Upper row – black keys
-        '2' => (1,1), '3' => (3,1), '5' => (6,1),
-        '6' => (8,1), '7' => (10,1), '9' => (13,1), '0' => (15,1),
-        _ => return None,
-    };
-    let note = (base_octave + oct) * 12 + 12 + st;
-    if (0..=127).contains(&note) { Some(note as u8) } else { None }
+/// How the computer keyboard maps onto notes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyLayout {
+    /// Z-row and Q-row are two stacked octaves, sharps on the row above each.
+    TwoOctave,
+    /// One octave across two rows: naturals on A-row, sharps on Q-row
+    /// (`a w s e d f t g y h u j k`), running on into the next octave.
+    SingleOctave,
+    /// Every key one semitone right of its neighbour, every row a fourth
+    /// (5 semitones) above the one below, like a bass or Linnstrument.
+    Isomorphic,
+}
+
+impl KeyLayout {
+    pub const ALL: [KeyLayout; 3] = [Self::TwoOctave, Self::SingleOctave, Self::Isomorphic];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::TwoOctave    => "Two-octave",
+            Self::SingleOctave => "Single-octave",
+            Self::Isomorphic   => "Isomorphic",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() as usize + 1) % Self::ALL.len()]
+    }
+
+    /// Position in `ALL` (saved in projects).
+    pub fn index(self) -> u8 {
+        Self::ALL.iter().position(|&l| l == self).unwrap_or(0) as u8
+    }
+
+    /// Inverse of `index`; unknown values fall back to the two-octave layout.
+    pub fn from_index(i: u8) -> Self {
+        Self::ALL.get(i as usize).copied().unwrap_or(Self::TwoOctave)
+    }
+
+    /// Semitones above the base octave's C for `key`.
+    fn offset(self, key: char) -> Option<i32> {
+        match self {
+            Self::TwoOctave => {
+                let (st, oct) = match key {
+                    // Lower row – white keys
+                    'z' => (0,0), 'x' => (2,0), 'c' => (4,0), 'v' => (5,0),
+                    'b' => (7,0), 'n' => (9,0), 'm' => (11,0),
+                    ',' => (12,0), '.' => (14,0), '/' => (16,0),
+                    // Lower row – black keys
+                    's' => (1,0), 'd' => (3,0), 'g' => (6,0),
+                    'h' => (8,0), 'j' => (10,0), 'l' => (13,0), ';' => (15,0),
+                    // Upper row – white keys
+                    'q' => (0,1), 'w' => (2,1), 'e' => (4,1), 'r' => (5,1),
+                    't' => (7,1), 'y' => (9,1), 'u' => (11,1),
+                    'i' => (12,1), 'o' => (14,1), 'p' => (16,1),
+                    // Upper row – black keys
+                    '2' => (1,1), '3' => (3,1), '5' => (6,1),
+                    '6' => (8,1), '7' => (10,1), '9' => (13,1), '0' => (15,1),
+                    _ => return None,
+                };
+                Some(oct * 12 + st)
+            }
+            Self::SingleOctave => Some(match key {
+                'a' => 0, 'w' => 1, 's' => 2, 'e' => 3, 'd' => 4, 'f' => 5, 't' => 6,
+                'g' => 7, 'y' => 8, 'h' => 9, 'u' => 10, 'j' => 11,
+                'k' => 12, 'o' => 13, 'l' => 14, 'p' => 15, ';' => 16,
+                _ => return None,
+            }),
+            Self::Isomorphic => {
+                const ROWS: [&str; 4] = ["zxcvbnm,./", "asdfghjkl;", "qwertyuiop", "1234567890"];
+                ROWS.iter().enumerate().find_map(|(row, keys)| {
+                    keys.find(key).map(|col| row as i32 * 5 + col as i32)
+                })
+            }
+        }
+    }
+}
+
+/// Which held piano key sounds when synth 1 plays one voice at a time
+/// (`voices 1`): after every press and release the winner among the held
+/// keys is (re)started.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NotePriority {
+    /// The most recently pressed key.
+    Last,
+    Highest,
+    Lowest,
+}
+
+impl NotePriority {
+    pub const ALL: [NotePriority; 3] = [Self::Last, Self::Highest, Self::Lowest];
+
+    pub fn name(self) -> &'static str {
+        match self { Self::Last => "Last", Self::Highest => "Highest", Self::Lowest => "Lowest" }
+    }
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() as usize + 1) % Self::ALL.len()]
+    }
+
+    /// Position in `ALL` (saved in projects).
+    pub fn index(self) -> u8 {
+        Self::ALL.iter().position(|&p| p == self).unwrap_or(0) as u8
+    }
+
+    pub fn from_index(i: u8) -> Self {
+        Self::ALL.get(i as usize).copied().unwrap_or(Self::Last)
+    }
+
+    /// The note that sounds out of `held` (oldest press first).
+    pub fn pick(self, held: &[(char, u8)]) -> Option<u8> {
+        let mut notes = held.iter().map(|&(_, n)| n);
+        match self {
+            Self::Last    => notes.next_back(),
+            Self::Highest => notes.max(),
+            Self::Lowest  => notes.min(),
+        }
+    }
+}
+
+/// Start the note `priority` picks from `held` if it is not already the one
+/// sounding (`sounding`), stopping the old one first.
+fn mono_apply(s: &mut Synth, held: &[(char, u8)], sounding: &mut Option<u8>, priority: NotePriority) {
+    let want = priority.pick(held);
+    if want == *sounding { return; }
+    if let Some(n) = sounding.take() { s.note_off(n); }
+    if let Some(n) = want { s.note_on(n); }
+    *sounding = want;
+}
+
+/// Every key any layout uses, in label preference order (see `note_key`):
+/// the main rows first, the two-octave overlap keys (`, . / l ;`) last.
+const NOTE_KEYS: &str = "zxcvbnmasdfghjkqwertyuiop1234567890,./l;";
+
+/// MIDI note played by `key` in `layout`, or `None` for a non-note key.
+pub fn key_to_note(key: char, base_octave: i32, layout: KeyLayout) -> Option<u8> {
+    key_to_note_folded(key, base_octave, layout, false).map(|(n, _)| n)
+}
+
+/// Like `key_to_note`, but with `fold` a note past 0–127 is moved back into
+/// range by whole octaves instead of dropped.  The flag says it was folded.
+pub fn key_to_note_folded(key: char, base_octave: i32, layout: KeyLayout, fold: bool) -> Option<(u8, bool)> {
+    let note = (base_octave + 1) * 12 + layout.offset(key)?;
+    if (0..=127).contains(&note) { return Some((note as u8, false)); }
+    if !fold { return None; }
+    let folded = if note > 127 { 127 - (127 - note).rem_euclid(12) } else { note.rem_euclid(12) };
+    Some((folded as u8, true))
+}
+
+/// The key that plays `note` in `layout` (first in `NOTE_KEYS` when several
+/// do), for the piano labels.
+pub fn note_key(note: u8, base_octave: i32, layout: KeyLayout) -> Option<char> {
+    NOTE_KEYS.chars().find(|&k| key_to_note(k, base_octave, layout) == Some(note))
 }
 
 // ── Pattern bank types ────────────────────────────────────────────────────────
@@ -40,12 +326,83 @@ pub fn key_to_note(key: char, base_octave: i32) -> Option<u8> {
 #[derive(Clone)]
 struct SeqPattern {
     steps:     Vec<Option<u8>>,
+    nudge:     Vec<i8>,
     num_steps: usize,
+    loop_pts:  (usize, usize),
+    direction: Direction,
+    velocity_ramp: Option<VelocityRamp>,
+    accent:    Vec<bool>,
+    slide:     Vec<bool>,
+    prob:      Vec<u8>,
 }
 
 impl SeqPattern {
     fn empty() -> Self {
-        Self { steps: vec![None; 16], num_steps: 16 }
+        Self {
+            steps: vec![None; 16], nudge: vec![0; 16], num_steps: 16, loop_pts: (0, 15),
+            direction: Direction::Forward, velocity_ramp: None,
+            accent: vec![false; 16], slide: vec![false; 16], prob: vec![FULL_PROB; 16],
+        }
+    }
+
+    fn capture(seq: &Sequencer) -> Self {
+        Self {
+            steps:     seq.steps.clone(),
+            nudge:     seq.nudge.clone(),
+            num_steps: seq.num_steps,
+            loop_pts:  (seq.loop_start, seq.loop_end),
+            direction: seq.direction,
+            velocity_ramp: seq.velocity_ramp,
+            accent:    seq.accent.clone(),
+            slide:     seq.slide.clone(),
+            prob:      seq.prob.clone(),
+        }
+    }
+
+    fn restore(&self, seq: &mut Sequencer) {
+        seq.num_steps = self.num_steps;
+        seq.steps = self.steps.clone();
+        seq.steps.resize(self.num_steps, None);
+        seq.nudge = clamp_nudge(&self.nudge, self.num_steps);
+        (seq.loop_start, seq.loop_end) = clamp_loop(self.loop_pts, self.num_steps);
+        seq.direction = self.direction;
+        seq.velocity_ramp = self.velocity_ramp;
+        seq.accent = self.accent.clone();
+        seq.accent.resize(self.num_steps, false);
+        seq.slide = self.slide.clone();
+        seq.slide.resize(self.num_steps, false);
+        seq.prob = self.prob.iter().map(|&p| p.min(FULL_PROB)).collect();
+        seq.prob.resize(self.num_steps, FULL_PROB);
+    }
+
+    fn from_save(sv: &SeqSave) -> Self {
+        let num_steps = sv.num_steps.clamp(1, 32);
+        Self {
+            steps:    sv.steps.clone(),
+            nudge:    sv.nudge.clone(),
+            num_steps,
+            loop_pts: (sv.loop_start, sv.loop_end.unwrap_or(num_steps - 1)),
+            direction: Direction::from_index(sv.direction),
+            velocity_ramp: sv.velocity_ramp.map(VelocityRamp::sanitize),
+            accent:   sv.accent.clone(),
+            slide:    sv.slide.clone(),
+            prob:     sv.prob.clone(),
+        }
+    }
+
+    fn to_save(&self) -> SeqSave {
+        SeqSave {
+            num_steps:  self.num_steps,
+            steps:      self.steps.clone(),
+            nudge:      self.nudge.clone(),
+            loop_start: self.loop_pts.0,
+            loop_end:   Some(self.loop_pts.1),
+            direction:  self.direction.index(),
+            velocity_ramp: self.velocity_ramp,
+            accent:     self.accent.clone(),
+            slide:      self.slide.clone(),
+            prob:       self.prob.clone(),
+        }
     }
 }
 
@@ -54,11 +411,203 @@ struct DrumPattern {
     num_steps:   usize,
     swing:       f32,
     track_steps: Vec<Vec<u8>>,  // 8 tracks × up to 32 steps
+    track_nudge: Vec<Vec<i8>>,
+    track_decay: Vec<Vec<u8>>,
+    loop_pts:    (usize, usize),
+    direction:   Direction,
 }
 
 impl DrumPattern {
     fn empty() -> Self {
-        Self { num_steps: 16, swing: 0.0, track_steps: vec![vec![0u8; 16]; 8] }
+        Self {
+            num_steps:   16,
+            swing:       0.0,
+            track_steps: vec![vec![0u8; 16]; 8],
+            track_nudge: vec![vec![0i8; 16]; 8],
+            track_decay: vec![vec![DEFAULT_DECAY; 16]; 8],
+            loop_pts:    (0, 15),
+            direction:   Direction::Forward,
+        }
+    }
+
+    fn capture(dm: &DrumMachine) -> Self {
+        Self {
+            num_steps:   dm.num_steps,
+            swing:       dm.swing,
+            track_steps: dm.tracks.iter().map(|t| t.steps.clone()).collect(),
+            track_nudge: dm.tracks.iter().map(|t| t.nudge.clone()).collect(),
+            track_decay: dm.tracks.iter().map(|t| t.decay.clone()).collect(),
+            loop_pts:    (dm.loop_start, dm.loop_end),
+            direction:   dm.direction,
+        }
+    }
+
+    fn restore(&self, dm: &mut DrumMachine) {
+        dm.num_steps = self.num_steps;
+        dm.swing = self.swing;
+        (dm.loop_start, dm.loop_end) = clamp_loop(self.loop_pts, self.num_steps);
+        dm.direction = self.direction;
+        let n_tracks = dm.tracks.len().min(self.track_steps.len());
+        for (i, t) in dm.tracks.iter_mut().take(n_tracks).enumerate() {
+            t.steps = self.track_steps[i].clone();
+            t.steps.resize(self.num_steps, 0);
+            let nudge = self.track_nudge.get(i).map(Vec::as_slice).unwrap_or(&[]);
+            t.nudge = clamp_nudge(nudge, self.num_steps);
+            let decay = self.track_decay.get(i).map(Vec::as_slice).unwrap_or(&[]);
+            t.decay = clamp_decay(decay, self.num_steps);
+        }
+    }
+
+    fn from_save(sv: &DrumsSave) -> Self {
+        let num_steps = sv.num_steps.clamp(1, 32);
+        Self {
+            num_steps,
+            swing:       sv.swing.clamp(0.0, 0.5),
+            track_steps: sv.tracks.iter().map(|t| t.steps.clone()).collect(),
+            track_nudge: sv.tracks.iter().map(|t| t.nudge.clone()).collect(),
+            track_decay: sv.tracks.iter().map(|t| t.decay.clone()).collect(),
+            loop_pts:    (sv.loop_start, sv.loop_end.unwrap_or(num_steps - 1)),
+            direction:   Direction::from_index(sv.direction),
+        }
+    }
+}
+
+/// Velocity of a drum-preview repeat `interval` after the previous hit.
+fn roll_velocity_for(interval: Duration) -> f32 {
+    if interval > ROLL_GAP { return 1.0; }
+    let t = (ROLL_SLOW.saturating_sub(interval).as_secs_f32() / (ROLL_SLOW - ROLL_FAST).as_secs_f32()).min(1.0);
+    ROLL_MIN_VELOCITY + (1.0 - ROLL_MIN_VELOCITY) * t
+}
+
+/// Loaded loop points, reset to the whole pattern if they don't fit.
+fn clamp_loop((start, end): (usize, usize), n: usize) -> (usize, usize) {
+    if start <= end && end < n { (start, end) } else { (0, n - 1) }
+}
+
+/// Solo range grown to cover `step` (a new one-step solo if there is none).
+fn extend_solo(range: Option<(usize, usize)>, step: usize) -> (usize, usize) {
+    match range {
+        Some((a, b)) => (a.min(step), b.max(step)),
+        None         => (step, step),
+    }
+}
+
+fn loop_points_status(who: &str, (first, len): (usize, usize), num_steps: usize) -> String {
+    if len == num_steps {
+        format!("{} loop: whole pattern", who)
+    } else {
+        format!("{} loop: {}–{}", who, first + 1, first + len)
+    }
+}
+
+fn solo_status(seq: &str, range: Option<(usize, usize)>) -> String {
+    match range {
+        Some((a, b)) if a == b => format!("{} solo: step {}", seq, a + 1),
+        Some((a, b))           => format!("{} solo: steps {}–{}", seq, a + 1, b + 1),
+        None                   => format!("{} solo off", seq),
+    }
+}
+
+/// Resize a loaded nudge list to `n` steps and clamp it to the legal range.
+fn clamp_nudge(nudge: &[i8], n: usize) -> Vec<i8> {
+    let mut v: Vec<i8> = nudge.iter().map(|x| (*x).clamp(-MAX_NUDGE, MAX_NUDGE)).collect();
+    v.resize(n, 0);
+    v
+}
+
+/// Resize a loaded drum decay list to `n` steps and clamp it to the legal range.
+fn clamp_decay(decay: &[u8], n: usize) -> Vec<u8> {
+    let mut v: Vec<u8> = decay.iter().map(|d| (*d).clamp(MIN_DECAY, MAX_DECAY)).collect();
+    v.resize(n, DEFAULT_DECAY);
+    v
+}
+
+// ── Undo ──────────────────────────────────────────────────────────────────────
+
+/// Undo history depth; the oldest entry is dropped beyond this.
+const UNDO_DEPTH: usize = 32;
+
+/// A pattern as it was before an edit, and the bank it belongs to.
+enum Undo {
+    Seq1(usize, SeqPattern),
+    /// A synth 1 lane other than the main one (lanes are not banked).
+    Lane(usize, SeqPattern),
+    Seq2(usize, SeqPattern),
+    Drums(usize, DrumPattern),
+}
+
+// ── Repeat last edit ──────────────────────────────────────────────────────────
+
+/// A cursor-step edit Ctrl+Y can repeat (`App::repeat_last`).  Replayed at
+/// the current cursor, like vim's `.`; notes are stored as played, so an
+/// octave change in between does not move them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Action {
+    SeqNote(u8),
+    SeqClear,
+    SeqNudge(bool),
+    SeqProb(bool),
+    Seq2Note(u8),
+    Seq2Clear,
+    Seq2Nudge(bool),
+    Seq2Prob(bool),
+    Seq2Accent,
+    Seq2Slide,
+    DrumToggle,
+    DrumClear,
+    DrumProb(bool),
+    DrumNudge(bool),
+    DrumDecay(bool),
+}
+
+impl Action {
+    fn label(self) -> String {
+        let dir = |b: bool, yes: &str, no: &str| if b { yes.to_string() } else { no.to_string() };
+        match self {
+            Self::SeqNote(n)    => format!("Seq note {}", note_name(n)),
+            Self::SeqClear      => "Seq clear".to_string(),
+            Self::SeqNudge(l)   => format!("Seq nudge {}", dir(l, "later", "earlier")),
+            Self::SeqProb(up)   => format!("Seq probability {}", dir(up, "up", "down")),
+            Self::Seq2Note(n)   => format!("Seq2 note {}", note_name(n)),
+            Self::Seq2Clear     => "Seq2 clear".to_string(),
+            Self::Seq2Nudge(l)  => format!("Seq2 nudge {}", dir(l, "later", "earlier")),
+            Self::Seq2Prob(up)  => format!("Seq2 probability {}", dir(up, "up", "down")),
+            Self::Seq2Accent    => "Seq2 accent toggle".to_string(),
+            Self::Seq2Slide     => "Seq2 slide toggle".to_string(),
+            Self::DrumToggle    => "Drum step toggle".to_string(),
+            Self::DrumClear     => "Drum step clear".to_string(),
+            Self::DrumProb(up)  => format!("Drum probability {}", dir(up, "up", "down")),
+            Self::DrumNudge(l)  => format!("Drum nudge {}", dir(l, "later", "earlier")),
+            Self::DrumDecay(l)  => format!("Drum decay {}", dir(l, "longer", "shorter")),
+        }
+    }
+}
+
+// ── Output device picker ─────────────────────────────────────────────────────
+
+/// Overlay listing the output devices found when it was opened (`device`).
+pub struct DevicePicker {
+    pub names: Vec<String>,
+    /// Selected row: 0 = the host default, `i + 1` = `names[i]`.
+    pub sel:   usize,
+}
+
+// ── Mute groups (performance view) ──────────────────────────────────────────
+
+/// A named set of drum tracks muted/unmuted together from the performance view.
+pub struct MuteGroup {
+    pub name:   &'static str,
+    pub tracks: Vec<usize>,  // indices into DrumMachine::tracks
+}
+
+impl MuteGroup {
+    fn defaults() -> Vec<MuteGroup> {
+        vec![
+            MuteGroup { name: "Kick",       tracks: vec![0] },
+            MuteGroup { name: "Snare/Clap", tracks: vec![1, 4] },
+            MuteGroup { name: "Hats",       tracks: vec![2, 3] },
+            MuteGroup { name: "Toms",       tracks: vec![5, 6, 7] },
+        ]
     }
 }
 
@@ -78,6 +627,17 @@ pub enum AppMode {
     Effects,
 }
 
+// ── Screen layout ─────────────────────────────────────────────────────────────
+
+/// How much of the UI `ui::draw` lays out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LayoutMode {
+    /// Every panel stacked (the default).
+    Full,
+    /// One transport line plus the focused panel, for short terminals.
+    Compact,
+}
+
 // ── Input mode (file path prompt) ─────────────────────────────────────────────
 
 #[derive(Debug, Clone, PartialEq)]
@@ -85,6 +645,205 @@ pub enum InputMode {
     None,
     Save,
     Load,
+    /// Name for saving the selected insert chain as a rack.
+    RackSave,
+    /// Command palette line (`command.rs`).
+    Command,
+}
+
+// ── State dump ────────────────────────────────────────────────────────────────
+
+/// UI-side half of the debug dump (`App::dump_state`); the audio side is
+/// `dump::SynthState`.
+#[derive(Debug)]
+#[allow(dead_code)] // read only through `Debug`
+struct AppState<'a> {
+    mode:          &'a AppMode,
+    input_mode:    &'a InputMode,
+    base_octave:   i32,
+    key_layout:    KeyLayout,
+    note_priority: NotePriority,
+    mono_held:     &'a [(char, u8)],
+    mono_note:     Option<u8>,
+    scale:         &'a ScaleQuantizer,
+    /// Held keys, sorted, with the time since each was last seen (fallback mode).
+    pressed_keys:  Vec<(char, Option<Duration>)>,
+    run_notes:     Vec<(char, u8)>,
+    chord_keys:    bool,
+    chord_notes:   Vec<(char, [u8; 3])>,
+    note_fold:     bool,
+    pitch_entry:   Option<u8>,
+    scale_run:     bool,
+    drone_follow_root: bool,
+    banks:         (usize, usize, usize),
+    undo_depth:    usize,
+    perf_view:     bool,
+    ab_active:     Option<usize>,
+    tab_pauses:    bool,
+    layout:        LayoutMode,
+    bass_view:     bool,
+}
+
+fn sorted<V: Copy>(map: &HashMap<char, V>) -> Vec<(char, V)> {
+    let mut v: Vec<(char, V)> = map.iter().map(|(&k, &v)| (k, v)).collect();
+    v.sort_by_key(|&(k, _)| k);
+    v
+}
+
+// ── Effect state DTOs ─────────────────────────────────────────────────────────
+
+/// All effect parameters as save DTOs.  Shared by session save/load and the
+/// A/B mix snapshots so both clamp and restore values the same way.
+#[derive(Clone, Debug)]
+struct FxState {
+    reverb:     ReverbSave,
+    delay:      DelaySave,
+    distortion: DistSave,
+    sidechain:  SidechainSave,
+    filter1:    FilterSave,
+    filter2:    FilterSave,
+    routing:    RoutingSave,
+    output:     OutputSave,
+}
+
+impl FxState {
+    fn capture(s: &Synth) -> Self {
+        let reverb = ReverbSave {
+            enabled:   s.reverb.enabled,
+            room_size: s.reverb.room_size,
+            damping:   s.reverb.damping,
+            mix:       s.reverb.mix,
+        };
+        let delay = DelaySave {
+            enabled:  s.delay.enabled,
+            time_ms:  s.delay.time_ms,
+            feedback: s.delay.feedback,
+            mix:      s.delay.mix,
+            interp:   s.delay.interp.index(),
+        };
+        let distortion = DistSave {
+            enabled: s.distortion.enabled,
+            drive:   s.distortion.drive,
+            tone:    s.distortion.tone,
+            level:   s.distortion.level,
+        };
+        let sidechain = SidechainSave {
+            enabled:    s.sidechain.enabled,
+            depth:      s.sidechain.depth,
+            release_ms: s.sidechain.release_ms,
+            duck_s1:    s.sidechain.duck_s1,
+            duck_s2:    s.sidechain.duck_s2,
+        };
+        let filter1 = FilterSave {
+            enabled: s.filter1.enabled,
+            mode:    s.filter1.mode.index(),
+            cutoff:  s.filter1.cutoff,
+            q:       s.filter1.q,
+        };
+        let filter2 = FilterSave {
+            enabled: s.filter2.enabled,
+            mode:    s.filter2.mode.index(),
+            cutoff:  s.filter2.cutoff,
+            q:       s.filter2.q,
+        };
+        let routing = RoutingSave {
+            s1_reverb: s.fx_routing.s1_reverb, s1_delay: s.fx_routing.s1_delay, s1_dist: s.fx_routing.s1_dist,
+            s2_reverb: s.fx_routing.s2_reverb, s2_delay: s.fx_routing.s2_delay, s2_dist: s.fx_routing.s2_dist,
+            dr_reverb: s.fx_routing.dr_reverb, dr_delay: s.fx_routing.dr_delay, dr_dist: s.fx_routing.dr_dist,
+        };
+        let output = OutputSave {
+            width: s.widener.width,
+            clip:  s.clipper.mode.index(),
+            drive: s.clipper.drive,
+            limiter:         s.limiter.enabled,
+            limiter_ceiling: s.limiter.ceiling_db,
+            lookahead:       s.limiter.lookahead,
+            auto_gain:       s.mix_meter.auto_gain,
+            voice_spread:    s.voice_spread,
+        };
+        Self { reverb, delay, distortion, sidechain, filter1, filter2, routing, output }
+    }
+
+    fn apply(&self, s: &mut Synth) {
+        // Reverb
+        s.reverb.enabled   = self.reverb.enabled;
+        s.reverb.room_size = self.reverb.room_size.clamp(0.0, 1.0);
+        s.reverb.damping   = self.reverb.damping.clamp(0.0, 1.0);
+        s.reverb.mix       = self.reverb.mix.clamp(0.0, 1.0);
+
+        // Delay
+        s.delay.enabled  = self.delay.enabled;
+        s.delay.time_ms  = self.delay.time_ms.clamp(10.0, 1000.0);
+        s.delay.feedback = self.delay.feedback.clamp(0.0, 0.95);
+        s.delay.mix      = self.delay.mix.clamp(0.0, 1.0);
+        s.delay.interp   = Interp::from_index(self.delay.interp);
+
+        // Distortion
+        s.distortion.enabled = self.distortion.enabled;
+        s.distortion.drive   = self.distortion.drive.clamp(1.0, 10.0);
+        s.distortion.tone    = self.distortion.tone.clamp(0.0, 1.0);
+        s.distortion.level   = self.distortion.level.clamp(0.0, 1.0);
+
+        // Sidechain
+        s.sidechain.enabled    = self.sidechain.enabled;
+        s.sidechain.depth      = self.sidechain.depth.clamp(0.0, 1.0);
+        s.sidechain.release_ms = self.sidechain.release_ms.clamp(10.0, 500.0);
+        s.sidechain.duck_s1    = self.sidechain.duck_s1;
+        s.sidechain.duck_s2    = self.sidechain.duck_s2;
+
+        // Filter 1
+        s.filter1.enabled = self.filter1.enabled;
+        s.filter1.mode    = FilterMode::from_index(self.filter1.mode);
+        s.filter1.cutoff = self.filter1.cutoff.clamp(80.0, 18000.0);
+        s.filter1.q      = self.filter1.q.clamp(0.5, 10.0);
+        if s.filter1.enabled { s.filter1.reset_state(); }
+
+        // Filter 2
+        s.filter2.enabled = self.filter2.enabled;
+        s.filter2.mode    = FilterMode::from_index(self.filter2.mode);
+        s.filter2.cutoff = self.filter2.cutoff.clamp(80.0, 18000.0);
+        s.filter2.q      = self.filter2.q.clamp(0.5, 10.0);
+        if s.filter2.enabled { s.filter2.reset_state(); }
+
+        // Output
+        s.widener.width = self.output.width.clamp(0.0, WIDENER_MAX_WIDTH);
+        s.clipper.mode  = ClipMode::from_index(self.output.clip);
+        s.clipper.drive = self.output.drive.clamp(CLIP_DRIVE_MIN, CLIP_DRIVE_MAX);
+        s.limiter.set(self.output.limiter, self.output.lookahead);
+        s.mix_meter.set_auto_gain(self.output.auto_gain);
+        s.voice_spread = self.output.voice_spread.clamp(0.0, 1.0);
+        s.limiter.ceiling_db = self.output.limiter_ceiling.clamp(LIMITER_CEILING_MIN_DB, LIMITER_CEILING_MAX_DB);
+
+        // Routing
+        s.fx_routing.s1_reverb = self.routing.s1_reverb.clamp(0.0, 1.0);
+        s.fx_routing.s1_delay  = self.routing.s1_delay.clamp(0.0, 1.0);
+        s.fx_routing.s1_dist   = self.routing.s1_dist.clamp(0.0, 1.0);
+        s.fx_routing.s2_reverb = self.routing.s2_reverb.clamp(0.0, 1.0);
+        s.fx_routing.s2_delay  = self.routing.s2_delay.clamp(0.0, 1.0);
+        s.fx_routing.s2_dist   = self.routing.s2_dist.clamp(0.0, 1.0);
+        s.fx_routing.dr_reverb = self.routing.dr_reverb.clamp(0.0, 1.0);
+        s.fx_routing.dr_delay  = self.routing.dr_delay.clamp(0.0, 1.0);
+        s.fx_routing.dr_dist   = self.routing.dr_dist.clamp(0.0, 1.0);
+    }
+}
+
+pub fn ab_name(slot: usize) -> &'static str {
+    if slot == 0 { "A" } else { "B" }
+}
+
+// ── A/B mix snapshots ─────────────────────────────────────────────────────────
+
+/// Everything that shapes the mix but not the arrangement: bus and drum track
+/// gains, drum mutes, all effect parameters and every insert chain.
+#[derive(Clone)]
+pub struct MixSnapshot {
+    volume:        f32,
+    volume2:       f32,
+    track_volumes: Vec<f32>,
+    track_muted:   Vec<bool>,
+    fx:            FxState,
+    /// Insert chains in `Synth::chains()` order.
+    chains:        Vec<RackFile>,
 }
 
 // ── App state ─────────────────────────────────────────────────────────────────
@@ -92,16 +851,57 @@ pub enum InputMode {
 pub struct App {
     pub synth:        Arc<Mutex<Synth>>,
     pub base_octave:  i32,
+    pub key_layout:   KeyLayout,
     pub pressed_keys: HashSet<char>,
     key_last_seen:    HashMap<char, Instant>,
+    /// Scale run: key repeat on a held piano key climbs the active scale.
+    pub scale_run:    bool,
+    /// Note each held key has climbed to (only keys that have repeated).
+    run_notes:        HashMap<char, u8>,
+    /// Chord-key mode: each piano key plays a diatonic triad (`ScaleQuantizer::triad`).
+    pub chord_keys:   bool,
+    /// Piano keys past MIDI 0–127 fold back by octaves instead of staying
+    /// silent (`fold [on|off]`; saved), and when one last did.
+    pub note_fold:    bool,
+    fold_at:          Option<Instant>,
+    /// An octave change re-plays held piano keys at the new octave (default)
+    /// instead of releasing them (`octshift [retrigger|release]`; not saved).
+    pub octave_retrigger: bool,
+    /// Notes started by each held key in chord-key mode, released together.
+    chord_notes:      HashMap<char, [u8; 3]>,
+    /// Which held key sounds when synth 1 is limited to one voice.
+    pub note_priority: NotePriority,
+    /// Keys held while synth 1 is monophonic, oldest first, with their
+    /// notes, and the one of those notes that is sounding.
+    mono_held:        Vec<(char, u8)>,
+    mono_note:        Option<u8>,
+    /// Drone pitch tracks the scale root (in `DRONE_OCTAVE`) until a note is picked.
+    pub drone_follow_root: bool,
     pub active_notes: Vec<u8>,
     pub should_quit:  bool,
     pub status_msg:   String,
+    /// Project file last saved or loaded this session (shown in the title bar).
+    pub project:      Option<String>,
 
     pub mode: AppMode,
 
     // Melodic sequencer 1 cursor
     pub seq_cursor: usize,
+    /// Synth 1 lane the Seq1 panel edits (index into `Synth::lanes`).
+    pub seq_lane: usize,
+    /// Steps the cursor moves after entering a note on either melodic
+    /// sequencer (0 = stay put).
+    pub seq_advance: usize,
+    /// Pitch entry (Insert / `entry`; not saved): in SynthSeq/SynthSeq2 focus
+    /// ↑/↓ move `pending_note` by scale degree and Enter places it at the
+    /// cursor, instead of BPM and play.
+    pub pitch_entry:  bool,
+    pub pending_note: u8,
+    /// Arrow keys wrap the sequencer and drum cursors round at the edges
+    /// (default) or stop there (`cursor [wrap|clamp]`; not saved).
+    pub cursor_wrap: bool,
+    /// Chance per step, in percent, that a mutation (Ctrl+E) changes it.
+    pub mutate_pct: u8,
 
     // Melodic sequencer 2 cursor
     pub seq2_cursor: usize,
@@ -111,8 +911,13 @@ pub struct App {
     pub drum_step:  usize,  // selected step (column)
 
     // Effects panel cursors
-    pub effects_sel:   usize,  // 0=Reverb 1=Delay 2=Distortion
+    pub effects_sel:   usize,  // row index, 0..EFFECT_ROWS (see ui::draw_effects)
     pub effects_param: usize,  // 0-2 = effect param; 3-5 = S1/S2/DR send level
+    pub insert_pick:   InsertKind, // effect type added by [Enter] on a chain row
+    pub fx_drum_track: usize,      // drum track edited by the DRM-FX row
+    pub fx_synth_bus:  usize,      // 0 = S1 (`fx`), 1 = S2 (`fx2`) for the SYN-FX row
+    pub racks:         Vec<RackFile>, // factory + user racks, refreshed on browse
+    pub rack_pick:     usize,      // index into `racks` for [Enter] on the Rack column
 
     // Scale quantizer (input layer — no audio thread involvement)
     pub scale_q: ScaleQuantizer,
@@ -125,9 +930,97 @@ pub struct App {
     drum_banks:   [DrumPattern; 4],
     pub drum_bank: usize,
 
+    // Undo history for whole-pattern edits (Ctrl+Z), newest last
+    undo: Vec<Undo>,
+    /// Last repeatable step edit (Ctrl+Y).
+    last_action: Option<Action>,
+
     // File path prompt state
     pub input_mode: InputMode,
     pub input_buf:  String,
+
+    // Performance view: number keys toggle mute groups
+    pub perf_view:   bool,
+    pub mute_groups: Vec<MuteGroup>,
+
+    // A/B comparison: two mix snapshots, one of which is "live"
+    ab_slots:      [Option<MixSnapshot>; 2],
+    pub ab_active: Option<usize>,
+
+    // Beat flash: drum playhead from the last snapshot, and when bar 1 last hit
+    pub beat_flash:  bool,
+    /// Steps per beat in the grid display: beat dividers, step numbers and
+    /// the lighter downbeat shading (`grid`; set from `Synth::time_sig`).
+    pub grid_beat:   usize,
+    drum_playhead:   Option<usize>,
+    downbeat_at:     Option<Instant>,
+
+    /// When true, Tab also pauses every running transport (default: leave running).
+    pub tab_pauses:  bool,
+    /// Full panel stack or the compact single-panel view (Ctrl+N; not saved).
+    pub layout:      LayoutMode,
+    /// Seq2 panel drawn as the 303 bass lane (`'` in Seq2 focus; not saved).
+    pub bass_view:   bool,
+
+    /// Last BPM key step (time, upwards) and how many repeats it has run
+    /// for, for the hold acceleration.
+    bpm_last:        Option<(Instant, bool)>,
+    bpm_repeats:     u32,
+
+    // Voices meter: per-bus voice counts and limit from the last snapshot,
+    // and when a voice was last stolen
+    pub voice_count: [usize; 2],
+    pub max_voices:  usize,
+    voice_steals:    u64,
+    steal_at:        Option<Instant>,
+    /// Drum track meters from the last snapshot, 0–`METER_LEVELS` per track.
+    pub drum_meters: Vec<u8>,
+    // Mix meter: pre-tanh level (0–`METER_LEVELS`, full at `MIX_CLIP_LEVEL`),
+    // auto-gain cut in whole dB, and when the mix last went over
+    pub mix_level:   u8,
+    pub mix_cut_db:  i32,
+    clip_at:         Option<Instant>,
+    /// `Synth::clip_latch` as of the last snapshot (the status bar's clip LED).
+    pub clip_led:    bool,
+
+    /// Output stream (`None` headless, or after the device could not be reopened).
+    pub audio:    Option<AudioEngine>,
+    /// Output device picked with `device` (`None` = the host default).
+    pub audio_device: Option<String>,
+    /// Output device picker overlay, while open.
+    pub device_picker: Option<DevicePicker>,
+
+    // MIDI controller input and the CC → parameter bindings learned from it
+    pub midi_in:  Option<MidiInput>,
+    pub cc_map:   Vec<CcBinding>,
+    /// Parameter bound to the next controller moved (`learn <param>`).
+    pub cc_learn: Option<Param>,
+    /// Show the MIDI mappings panel in place of the help text.
+    pub cc_panel: bool,
+
+    /// Fallback mode: drum-preview key repeats set the hit velocity from the
+    /// repeat interval (rolls); the key and time of the last preview hit.
+    pub roll_velocity: bool,
+    drum_last_hit:     Option<(char, Instant)>,
+    /// Drum record arm: preview hits during playback are written to the
+    /// nearest step.  `drum_rec_undo` is set once the take's undo entry
+    /// has been pushed, so one Ctrl+Z removes the whole take.
+    pub drum_rec:      bool,
+    drum_rec_undo:     bool,
+    /// Length lock: changing the step count (`]`) of the drums / the
+    /// melodic sequencers moves hits and notes to the same place in the bar
+    /// instead of keeping their indices (`lengthlock`).
+    pub drum_length_lock: bool,
+    pub seq_length_lock:  bool,
+
+    // Mix automation lanes, and whether their panel replaces the help text
+    pub automation: Automation,
+    pub auto_panel: bool,
+
+    /// Show the fallback-keyboard notice in place of the help text.  Set at
+    /// startup when the terminal lacks the enhanced protocol; cleared for the
+    /// session by Ctrl+W.
+    pub fallback_notice: bool,
 }
 
 impl App {
@@ -135,18 +1028,42 @@ impl App {
         Self {
             synth,
             base_octave:  4,
+            key_layout:   KeyLayout::TwoOctave,
             pressed_keys: HashSet::new(),
             key_last_seen: HashMap::new(),
+            scale_run:    false,
+            run_notes:    HashMap::new(),
+            chord_keys:   false,
+            note_fold:    false,
+            fold_at:      None,
+            octave_retrigger: true,
+            chord_notes:  HashMap::new(),
+            note_priority: NotePriority::Last,
+            mono_held:    Vec::new(),
+            mono_note:    None,
+            drone_follow_root: true,
             active_notes: Vec::new(),
             should_quit:  false,
             status_msg:   String::new(),
             mode:         AppMode::Play,
             seq_cursor:   0,
+            seq_lane:     0,
+            seq_advance:  1,
+            cursor_wrap:  true,
+            pitch_entry:  false,
+            pending_note: 60,
+            mutate_pct:   DEFAULT_MUTATE_PCT,
+            project:      None,
             seq2_cursor:  0,
             drum_track:   0,
             drum_step:    0,
             effects_sel:   0,
             effects_param: 0,
+            insert_pick:   InsertKind::Filter,
+            fx_drum_track: 0,
+            fx_synth_bus:  0,
+            racks:         rack::available(),
+            rack_pick:     0,
             scale_q:       ScaleQuantizer::new(),
             seq1_banks:    std::array::from_fn(|_| SeqPattern::empty()),
             seq1_bank:     0,
@@ -154,8 +1071,48 @@ impl App {
             seq2_bank:     0,
             drum_banks:    std::array::from_fn(|_| DrumPattern::empty()),
             drum_bank:     0,
+            undo:          Vec::new(),
+            last_action:   None,
             input_mode:    InputMode::None,
             input_buf:     String::new(),
+            perf_view:     false,
+            mute_groups:   MuteGroup::defaults(),
+            ab_slots:      [None, None],
+            ab_active:     None,
+            beat_flash:    true,
+            grid_beat:     GRID_BEATS[0],
+            drum_playhead: None,
+            downbeat_at:   None,
+            tab_pauses:    false,
+            layout:        LayoutMode::Full,
+            bass_view:     false,
+            voice_count:   [0, 0],
+            max_voices:    DEFAULT_MAX_VOICES,
+            voice_steals:  0,
+            steal_at:      None,
+            bpm_last:      None,
+            bpm_repeats:   0,
+            mix_level:     0,
+            mix_cut_db:    0,
+            clip_at:       None,
+            clip_led:      false,
+            drum_meters:   Vec::new(),
+            audio:         None,
+            audio_device:  None,
+            device_picker: None,
+            midi_in:       None,
+            cc_map:        Vec::new(),
+            cc_learn:      None,
+            cc_panel:      false,
+            roll_velocity: true,
+            drum_last_hit: None,
+            drum_rec: false,
+            drum_rec_undo: false,
+            drum_length_lock: false,
+            seq_length_lock:  false,
+            automation:    Automation::new(),
+            auto_panel:    false,
+            fallback_notice: false,
         }
     }
 
@@ -164,32 +1121,335 @@ impl App {
     pub fn key_press(&mut self, key: char) {
         if self.pressed_keys.contains(&key) { return; }
         self.pressed_keys.insert(key);
-        if let Some(note) = key_to_note(key, self.base_octave) {
-            self.synth.lock().unwrap().note_on(self.scale_q.quantize(note));
+        self.key_note_on(key);
+    }
+
+    /// Start what a piano key plays: its quantized note, or in chord-key mode
+    /// the triad on it (remembered so the release stops exactly those notes).
+    fn key_note_on(&mut self, key: char) {
+        let Some(note) = self.key_note(key) else { return };
+        let note = self.scale_q.quantize(note);
+        let mut s = self.synth.lock().unwrap();
+        if self.chord_keys {
+            let chord = self.scale_q.triad(note);
+            for &n in &chord { s.note_on(n); }
+            self.chord_notes.insert(key, chord);
+        } else if s.max_voices == 1 {
+            self.mono_held.push((key, note));
+            mono_apply(&mut s, &self.mono_held, &mut self.mono_note, self.note_priority);
+        } else {
+            s.note_on(note);
         }
     }
 
     pub fn key_release(&mut self, key: char) {
         if !self.pressed_keys.remove(&key) { return; }
-        if let Some(note) = key_to_note(key, self.base_octave) {
-            self.synth.lock().unwrap().note_off(self.scale_q.quantize(note));
+        if let Some(chord) = self.chord_notes.remove(&key) {
+            let mut s = self.synth.lock().unwrap();
+            for &n in &chord { s.note_off(n); }
+            return;
+        }
+        if let Some(i) = self.mono_held.iter().position(|&(k, _)| k == key) {
+            self.mono_held.remove(i);
+            self.run_notes.remove(&key);
+            let mut s = self.synth.lock().unwrap();
+            mono_apply(&mut s, &self.mono_held, &mut self.mono_note, self.note_priority);
+            return;
+        }
+        let note = self.run_notes.remove(&key)
+            .or_else(|| self.key_note_quiet(key).map(|n| self.scale_q.quantize(n)));
+        if let Some(note) = note {
+            self.synth.lock().unwrap().note_off(note);
         }
     }
 
+    /// Key-repeat on a piano key.  In scale-run mode the held key moves one
+    /// scale degree up per repeat; otherwise (and for a held chord) it just
+    /// keeps the notes held.
+    pub fn key_repeat(&mut self, key: char) {
+        if !self.scale_run || !self.pressed_keys.contains(&key) || self.chord_notes.contains_key(&key) {
+            self.key_press_fallback(key);
+            return;
+        }
+        let Some(base) = self.key_note_quiet(key) else { return };
+        let from = self.run_notes.get(&key).copied().unwrap_or_else(|| self.scale_q.quantize(base));
+        let to = self.scale_q.step_up(from);
+        if to == from { return; }
+        self.run_notes.insert(key, to);
+        let mut s = self.synth.lock().unwrap();
+        if let Some(held) = self.mono_held.iter_mut().find(|(k, _)| *k == key) {
+            held.1 = to;
+            mono_apply(&mut s, &self.mono_held, &mut self.mono_note, self.note_priority);
+            return;
+        }
+        s.note_off(from);
+        s.note_on(to);
+    }
+
+    fn drone_root_note(&self) -> u8 {
+        (DRONE_OCTAVE + 1) * 12 + self.scale_q.root
+    }
+
+    /// Start/stop the drone (`None` toggles).
+    pub fn drone_set(&mut self, on: Option<bool>) {
+        let root = self.drone_root_note();
+        let mut s = self.synth.lock().unwrap();
+        s.drone.on = on.unwrap_or(!s.drone.on);
+        if s.drone.on && self.drone_follow_root { s.drone.set_note(root); }
+        self.status_msg = if s.drone.on {
+            format!("Drone: ON  {}", note_name(s.drone.note))
+        } else {
+            "Drone: OFF".to_string()
+        };
+    }
+
+    /// Start/stop the tuner reference tone (`None` toggles).
+    pub fn tuner_set(&mut self, on: Option<bool>) {
+        let mut s = self.synth.lock().unwrap();
+        s.tuner.on = on.unwrap_or(!s.tuner.on);
+        self.status_msg = if s.tuner.on { format!("Tuner: ON  {}", s.tuner.label()) }
+                          else          { "Tuner: OFF".to_string() };
+    }
+
+    /// Turn the debug step click on/off (`None` toggles).
+    pub fn step_click_set(&mut self, on: Option<bool>) {
+        let mut s = self.synth.lock().unwrap();
+        s.step_click.on = on.unwrap_or(!s.step_click.on);
+        if !s.step_click.on { s.step_click.reset(); }
+        self.status_msg = if s.step_click.on {
+            "Step click: ON  (every melodic sequencer step; not in renders)".to_string()
+        } else {
+            "Step click: OFF".to_string()
+        };
+    }
+
+    /// Pick the tuner pitch and start it.
+    pub fn tuner_pick(&mut self, pitch: TunerPitch) {
+        let mut s = self.synth.lock().unwrap();
+        s.tuner.pitch = pitch;
+        s.tuner.on = true;
+        self.status_msg = format!("Tuner: ON  {}", s.tuner.label());
+    }
+
+    /// Show (`None`) or set the tuner's A4 reference.
+    pub fn tuner_ref(&mut self, hz: Option<f32>) {
+        let mut s = self.synth.lock().unwrap();
+        if let Some(hz) = hz { s.tuner.a4 = hz; }
+        self.status_msg = format!("Tuner reference: A4 = {:.2} Hz{}", s.tuner.a4,
+            if s.tuner.on { format!("  (now {})", s.tuner.label()) } else { String::new() });
+    }
+
+    /// Pick the drone pitch (`None` = follow the scale root) and start it.
+    pub fn drone_pick(&mut self, note: Option<u8>) {
+        self.drone_follow_root = note.is_none();
+        let note = note.unwrap_or_else(|| self.drone_root_note());
+        let mut s = self.synth.lock().unwrap();
+        s.drone.set_note(note);
+        s.drone.on = true;
+        self.status_msg = format!("Drone: ON  {}{}", note_name(note),
+            if self.drone_follow_root { " (follows root)" } else { "" });
+    }
+
+    pub fn toggle_scale_run(&mut self) {
+        self.scale_run = !self.scale_run;
+        self.status_msg = format!("Scale run: {}  (hold a piano key to climb the scale)",
+            if self.scale_run { "ON" } else { "OFF" });
+    }
+
     pub fn key_press_fallback(&mut self, key: char) {
         self.key_last_seen.insert(key, Instant::now());
         if self.pressed_keys.contains(&key) { return; }
         self.pressed_keys.insert(key);
-        if let Some(note) = key_to_note(key, self.base_octave) {
-            self.synth.lock().unwrap().note_on(self.scale_q.quantize(note));
+        self.key_note_on(key);
+    }
+
+    /// Note a piano key plays at the current octave and layout (before scale
+    /// quantize), folded into range with `note_fold`; a fold lights the
+    /// keyboard title.
+    fn key_note(&mut self, key: char) -> Option<u8> {
+        let (note, folded) = key_to_note_folded(key, self.base_octave, self.key_layout, self.note_fold)?;
+        if folded { self.fold_at = Some(Instant::now()); }
+        Some(note)
+    }
+
+    /// `key_note` without the indicator, for releases and held-key checks.
+    fn key_note_quiet(&self, key: char) -> Option<u8> {
+        key_to_note_folded(key, self.base_octave, self.key_layout, self.note_fold).map(|(n, _)| n)
+    }
+
+    /// Fold out-of-range piano keys back into range (`None` toggles; held
+    /// notes are released first).
+    pub fn set_note_fold(&mut self, on: Option<bool>) {
+        self.release_all();
+        self.note_fold = on.unwrap_or(!self.note_fold);
+        self.status_msg = if self.note_fold {
+            "Note fold: ON  (keys past the MIDI range wrap back by octaves)".to_string()
+        } else {
+            "Note fold: OFF  (keys past the MIDI range are silent)".to_string()
+        };
+    }
+
+    /// True shortly after a piano key was folded back into range.
+    pub fn fold_flash(&self) -> bool {
+        self.fold_at.is_some_and(|t| t.elapsed() < FOLD_FLASH)
+    }
+
+    /// Toggle chord-key mode (held notes are released first).
+    pub fn toggle_chord_keys(&mut self) {
+        self.release_all();
+        self.chord_keys = !self.chord_keys;
+        self.status_msg = if !self.chord_keys {
+            "Chord keys: OFF".to_string()
+        } else if self.scale_q.active() {
+            format!("Chord keys: ON  (diatonic triads in {} {})", self.scale_q.root_name(), self.scale_q.scale.name())
+        } else {
+            format!("Chord keys: ON  (triads in {} Major — F6 picks a scale)", self.scale_q.root_name())
+        };
+    }
+
+    /// Switch between the full panel stack and the compact layout (`None`
+    /// toggles).
+    pub fn set_compact(&mut self, on: Option<bool>) {
+        let on = on.unwrap_or(self.layout == LayoutMode::Full);
+        self.layout = if on { LayoutMode::Compact } else { LayoutMode::Full };
+        self.status_msg = if on {
+            "Layout: compact (Tab/Alt+1–5 pick the panel, Ctrl+N for full)".to_string()
+        } else {
+            "Layout: full".to_string()
+        };
+    }
+
+    /// Master-clock position as 1-based (bar, beat, step in the beat), with
+    /// `grid_beat` steps per beat.
+    pub fn bar_position(&self) -> (u64, usize, usize) {
+        let (step, bar) = {
+            let s = self.synth.lock().unwrap();
+            ((s.master_clock as f64 / samples_per_step(s.sample_rate, s.bpm)) as u64, bar_steps(s.time_sig))
+        };
+        let in_bar = (step % bar) as usize;
+        (step / bar + 1, in_bar / self.grid_beat + 1, in_bar % self.grid_beat + 1)
+    }
+
+    /// Set the time signature (`None` cycles through `COMMON_TIME_SIGS`); the
+    /// grids' beat dividers follow it until the next `grid`.
+    pub fn set_time_sig(&mut self, sig: Option<(u8, u8)>) {
+        let mut s = self.synth.lock().unwrap();
+        let sig = sig.unwrap_or_else(|| {
+            let i = COMMON_TIME_SIGS.iter().position(|&t| t == s.time_sig);
+            COMMON_TIME_SIGS[i.map_or(0, |i| (i + 1) % COMMON_TIME_SIGS.len())]
+        });
+        s.time_sig = sig;
+        self.grid_beat = time_sig_beat(sig);
+        self.status_msg = format!("Time signature: {}/{}  (beats of {} steps, {}-step bars)",
+            sig.0, sig.1, self.grid_beat, bar_steps(sig));
+    }
+
+    /// Low-latency mode: the stream runs with a small buffer and the scope is
+    /// not drawn.
+    pub fn low_latency(&self) -> bool {
+        self.audio.as_ref().is_some_and(|a| a.low_latency)
+    }
+
+    /// Ctrl+U: reopen the output stream in (or out of) low-latency mode.  The
+    /// old stream is dropped first so the device is free; the synth keeps
+    /// its state and only drops out for the moment the stream is rebuilt.
+    pub fn set_low_latency(&mut self, on: Option<bool>) {
+        let on = on.unwrap_or(!self.low_latency());
+        let device = self.audio_device.clone();
+        self.status_msg = match self.open_audio(device.as_deref(), on) {
+            Ok(audio) => {
+                match (on, audio.buffer_frames, audio.latency_ms()) {
+                    (false, ..) => "Low-latency mode off: default buffer, scope on".to_string(),
+                    (true, Some(n), Some(ms)) =>
+                        format!("Low-latency mode: {}-frame buffer ({:.1} ms), scope off", n, ms),
+                    (true, ..) => format!(
+                        "Low-latency mode: the device refused a {}-frame buffer — using its default; scope off",
+                        LOW_LATENCY_FRAMES),
+                }
+            }
+            Err(e) => format!("Audio: cannot reopen the output ({:#}) — silent (`device` to pick one)", e),
+        };
+    }
+
+    /// Drop the output stream and open `device` (`None` = default) in its
+    /// place.  The old stream goes first so the device is free; the synth
+    /// keeps its state and only drops out while the stream is rebuilt.  On
+    /// failure there is no stream (silent) until the next successful open.
+    fn open_audio(&mut self, device: Option<&str>, low_latency: bool) -> anyhow::Result<&AudioEngine> {
+        self.audio = None;
+        let audio = AudioEngine::new(Arc::clone(&self.synth), device, low_latency)?;
+        Ok(self.audio.insert(audio))
+    }
+
+    /// Switch the output to `device` (`None` = the host default), keeping
+    /// low-latency mode.  If it can't be opened the default device is tried
+    /// next, then silence.
+    pub fn switch_device(&mut self, device: Option<String>) {
+        let low_latency = self.low_latency();
+        match self.open_audio(device.as_deref(), low_latency) {
+            Ok(audio) => {
+                self.status_msg = format!("Output: {} ({:.0} Hz)", audio.device, audio.sample_rate);
+                self.audio_device = device;
+            }
+            Err(e) => {
+                let failed = format!("Output: cannot open {} ({:#})", device.as_deref().unwrap_or("the default device"), e);
+                self.audio_device = None;
+                self.status_msg = match device.is_some().then(|| self.open_audio(None, low_latency)) {
+                    Some(Ok(audio)) => format!("{} — back on the default, {}", failed, audio.device),
+                    _ => format!("{} — silent", failed),
+                };
+            }
         }
     }
 
+    /// Notice a removed output device (checked once per frame) and fall back
+    /// to the default device, or silence.  Returns true if it happened.
+    pub fn poll_audio(&mut self) -> bool {
+        let Some(lost) = self.audio.as_ref().filter(|a| a.is_lost()).map(|a| a.device.clone()) else { return false };
+        self.audio_device = None;
+        let low_latency = self.low_latency();
+        self.status_msg = match self.open_audio(None, low_latency) {
+            Ok(audio) => format!("Output device lost: {} — switched to the default, {}", lost, audio.device),
+            Err(_)    => format!("Output device lost: {} — no output device, silent (`device` to pick one)", lost),
+        };
+        true
+    }
+
+    /// Open the device picker on a fresh scan of the output devices, with the
+    /// device in use selected.
+    pub fn open_device_picker(&mut self) {
+        let names = audio::output_devices();
+        let current = self.audio.as_ref().map(|a| a.device.as_str());
+        let sel = names.iter().position(|n| Some(n.as_str()) == current).map_or(0, |i| i + 1);
+        self.status_msg = format!("{} output device{} found", names.len(), if names.len() == 1 { "" } else { "s" });
+        self.device_picker = Some(DevicePicker { names, sel });
+    }
+
+    /// Move the picker selection by `delta` rows (row 0 = host default).
+    pub fn device_picker_move(&mut self, delta: isize) {
+        let Some(p) = &mut self.device_picker else { return };
+        p.sel = (p.sel as isize + delta).clamp(0, p.names.len() as isize) as usize;
+    }
+
+    /// Enter in the picker: switch to the selected device and close it.
+    pub fn device_picker_pick(&mut self) {
+        let Some(p) = self.device_picker.take() else { return };
+        let device = p.sel.checked_sub(1).and_then(|i| p.names.get(i).cloned());
+        self.switch_device(device);
+    }
+
+    /// Ctrl+W: hide the fallback-keyboard notice for the rest of the session.
+    pub fn dismiss_fallback_notice(&mut self) {
+        if !self.fallback_notice { return; }
+        self.fallback_notice = false;
+        self.status_msg = "Notice dismissed — the title bar still shows [fallback]".to_string();
+    }
+
     pub fn tick_fallback_release(&mut self) {
         let now = Instant::now();
         let stale: Vec<char> = self.pressed_keys.iter().copied()
             .filter(|k| {
-                key_to_note(*k, self.base_octave).is_some()
+                self.key_note_quiet(*k).is_some()
                     && self.key_last_seen.get(k)
                         .map(|t| now.duration_since(*t) >= FALLBACK_RELEASE_THRESHOLD)
                         .unwrap_or(true)
@@ -199,27 +1459,141 @@ impl App {
     }
 
     pub fn release_all(&mut self) {
+        // Drop the mono stack first so the releases don't restart the
+        // remaining held notes one by one.
+        self.mono_held.clear();
+        if let Some(n) = self.mono_note.take() { self.synth.lock().unwrap().note_off(n); }
         let keys: Vec<char> = self.pressed_keys.iter().copied().collect();
         for k in keys { self.key_release(k); }
         self.key_last_seen.clear();
     }
 
+    /// Hidden debug key (Ctrl+X): write the full app + synth state to a log
+    /// file for bug reports.  The synth lock is held only for the copy.
+    pub fn dump_state(&mut self) {
+        let (synth, fx) = {
+            let s = self.synth.lock().unwrap();
+            (dump::SynthState::capture(&s), FxState::capture(&s))
+        };
+        let mut pressed: Vec<(char, Option<Duration>)> = self.pressed_keys.iter()
+            .map(|&k| (k, self.key_last_seen.get(&k).map(Instant::elapsed)))
+            .collect();
+        pressed.sort_by_key(|&(k, _)| k);
+        let app = AppState {
+            mode:          &self.mode,
+            input_mode:    &self.input_mode,
+            base_octave:   self.base_octave,
+            key_layout:    self.key_layout,
+            note_priority: self.note_priority,
+            mono_held:     &self.mono_held,
+            mono_note:     self.mono_note,
+            scale:         &self.scale_q,
+            pressed_keys:  pressed,
+            run_notes:     sorted(&self.run_notes),
+            chord_keys:    self.chord_keys,
+            chord_notes:   sorted(&self.chord_notes),
+            note_fold:     self.note_fold,
+            pitch_entry:   self.pitch_entry.then_some(self.pending_note),
+            scale_run:     self.scale_run,
+            drone_follow_root: self.drone_follow_root,
+            banks:         (self.seq1_bank, self.seq2_bank, self.drum_bank),
+            undo_depth:    self.undo.len(),
+            perf_view:     self.perf_view,
+            ab_active:     self.ab_active,
+            tab_pauses:    self.tab_pauses,
+            layout:        self.layout,
+            bass_view:     self.bass_view,
+        };
+        self.status_msg = match dump::write(&[("App", &app), ("Synth", &synth), ("Effects", &fx)]) {
+            Ok(path) => format!("State dumped to {}", path.display()),
+            Err(e)   => format!("State dump failed: {}", e),
+        };
+    }
+
+    /// Panic: release held keys, kill every voice and clear all effect state.
+    pub fn panic(&mut self) {
+        self.release_all();
+        self.synth.lock().unwrap().panic();
+        self.status_msg = "Panic: all voices stopped, effects cleared".to_string();
+    }
+
+    /// Soft stop (F11 / `stop`): stop the transport and release every note,
+    /// letting releases and effect tails ring out (F12 cuts them).
+    pub fn soft_stop(&mut self) {
+        self.release_all();
+        self.synth.lock().unwrap().soft_stop();
+        self.status_msg = "Stop: transport stopped, notes releasing  (F12 panic cuts the tails)".to_string();
+    }
+
+    /// Capture the last scope window and loop it as a pad, or release it.
+    pub fn freeze_toggle(&mut self) {
+        let mut guard = self.synth.lock().unwrap();
+        let s = &mut *guard;
+        if s.freeze.active {
+            s.freeze.active = false;
+            self.status_msg = "Freeze: OFF".to_string();
+        } else if render::is_silent(&s.scope_buf) {
+            self.status_msg = "Freeze: nothing playing to capture".to_string();
+        } else {
+            s.freeze.capture(&s.scope_buf, s.scope_pos);
+            s.freeze.active = true;
+            self.status_msg = "Freeze: ON (Ctrl+F to release)".to_string();
+        }
+    }
+
     // ── Global controls ───────────────────────────────────────────────────
 
     pub fn octave_up(&mut self) {
-        if self.base_octave < 8 {
-            self.release_all();
-            self.base_octave += 1;
-            self.status_msg = format!("Octave: {}", self.base_octave);
-        }
+        if self.base_octave < 8 { self.shift_octave(1); }
     }
 
     pub fn octave_down(&mut self) {
-        if self.base_octave > 0 {
+        if self.base_octave > 0 { self.shift_octave(-1); }
+    }
+
+    /// Move the base octave by `by`.  Held keys are released, then with
+    /// `octave_retrigger` pressed again so they sound at the new octave; in
+    /// mono mode the stack is rebuilt in press order and re-picked once, so
+    /// no intermediate note blips.
+    fn shift_octave(&mut self, by: i32) {
+        if !self.octave_retrigger {
             self.release_all();
-            self.base_octave -= 1;
+            self.base_octave += by;
             self.status_msg = format!("Octave: {}", self.base_octave);
+            return;
+        }
+        let mono: Vec<char> = self.mono_held.iter().map(|&(k, _)| k).collect();
+        let mut others: Vec<char> = self.pressed_keys.iter().copied().filter(|k| !mono.contains(k)).collect();
+        others.sort_unstable();
+        self.mono_held.clear();
+        if let Some(n) = self.mono_note.take() { self.synth.lock().unwrap().note_off(n); }
+        for &k in mono.iter().chain(&others) { self.key_release(k); }
+
+        self.base_octave += by;
+        for k in others { self.key_press(k); }
+        for k in mono {
+            self.pressed_keys.insert(k);
+            if let Some(n) = self.key_note(k) { self.mono_held.push((k, self.scale_q.quantize(n))); }
+        }
+        if !self.mono_held.is_empty() {
+            let mut s = self.synth.lock().unwrap();
+            mono_apply(&mut s, &self.mono_held, &mut self.mono_note, self.note_priority);
         }
+        self.status_msg = format!("Octave: {}", self.base_octave);
+    }
+
+    /// `octshift [retrigger|release]`: what an octave change does to held keys.
+    pub fn set_octave_retrigger(&mut self, on: Option<bool>) {
+        self.octave_retrigger = on.unwrap_or(!self.octave_retrigger);
+        self.status_msg = format!("Octave change: held keys {}",
+            if self.octave_retrigger { "re-trigger at the new octave" } else { "are released" });
+    }
+
+    /// Switch to the next keyboard layout (held notes are released first).
+    pub fn cycle_key_layout(&mut self) {
+        self.release_all();
+        self.key_layout = self.key_layout.next();
+        self.status_msg = format!("Keys: {} layout", self.key_layout.name());
     }
 
     pub fn cycle_wave(&mut self) {
@@ -259,16 +1633,23 @@ impl App {
     }
 
     /// Shared master BPM — affects both the melodic and drum sequencers.
-    pub fn bpm_up(&mut self) {
-        let mut s = self.synth.lock().unwrap();
-        s.bpm = (s.bpm + 5.0).clamp(30.0, 300.0);
-        self.status_msg = format!("BPM: {:.0}", s.bpm);
-    }
-
-    pub fn bpm_down(&mut self) {
+    /// Step the BPM for a key press or key repeat (`repeat`).  A single press
+    /// moves `BPM_STEP`; holding the key accelerates; `fine` moves
+    /// `BPM_FINE_STEP` without acceleration.  Cancels a tempo ramp.
+    pub fn bpm_step(&mut self, up: bool, fine: bool, repeat: bool) {
+        let now = Instant::now();
+        let held = repeat || self.bpm_last.is_some_and(|(t, u)| u == up && now - t < BPM_REPEAT_GAP);
+        self.bpm_repeats = if held { self.bpm_repeats + 1 } else { 0 };
+        self.bpm_last = Some((now, up));
+        let step = if fine {
+            BPM_FINE_STEP
+        } else {
+            BPM_STEP * (1 + self.bpm_repeats / BPM_ACCEL_REPEATS).min(BPM_MAX_ACCEL) as f32
+        };
         let mut s = self.synth.lock().unwrap();
-        s.bpm = (s.bpm - 5.0).clamp(30.0, 300.0);
-        self.status_msg = format!("BPM: {:.0}", s.bpm);
+        s.tempo_ramp = None;
+        s.bpm = (s.bpm + if up { step } else { -step }).clamp(30.0, 300.0);
+        self.status_msg = format!("BPM: {:.0}  ({}{:.0})", s.bpm, if up { "+" } else { "-" }, step);
     }
 
     pub fn cycle_scale(&mut self) {
@@ -284,6 +1665,9 @@ impl App {
     pub fn cycle_scale_root(&mut self) {
         self.release_all();
         self.scale_q.cycle_root();
+        if self.drone_follow_root {
+            self.synth.lock().unwrap().drone.set_note(self.drone_root_note());
+        }
         self.status_msg = if self.scale_q.scale == Scale::Off {
             format!("Root: {}", self.scale_q.root_name())
         } else {
@@ -303,23 +1687,327 @@ impl App {
         self.status_msg = format!("S2 Chord: {}", s.chord2.name());
     }
 
+    fn push_undo(&mut self, entry: Undo) {
+        if self.undo.len() == UNDO_DEPTH { self.undo.remove(0); }
+        self.undo.push(entry);
+    }
+
+    /// Undo entry for an edit of the Seq1 lane being edited.
+    fn seq1_undo(&self, before: SeqPattern) -> Undo {
+        if self.seq_lane == 0 { Undo::Seq1(self.seq1_bank, before) } else { Undo::Lane(self.seq_lane, before) }
+    }
+
+    /// Ctrl+Z: put back the pattern saved before the last undoable edit.
+    /// An entry recorded in a bank that is no longer active is discarded
+    /// rather than written over the wrong pattern.
+    pub fn undo(&mut self) {
+        let Some(entry) = self.undo.pop() else {
+            self.status_msg = "Nothing to undo".to_string();
+            return;
+        };
+        let mut s = self.synth.lock().unwrap();
+        self.status_msg = match entry {
+            Undo::Seq1(bank, p) if bank == self.seq1_bank => { p.restore(&mut s.lanes[0]); "Undo: Seq1 pattern".to_string() }
+            Undo::Lane(lane, p) => match s.lanes.get_mut(lane) {
+                Some(seq) => { p.restore(seq); format!("Undo: Seq1 lane {}", lane + 1) }
+                None      => "Undo skipped: lane was removed".to_string(),
+            },
+            Undo::Seq2(bank, p) if bank == self.seq2_bank => { p.restore(&mut s.sequencer2); "Undo: Seq2 pattern".to_string() }
+            Undo::Drums(bank, p) if bank == self.drum_bank => { p.restore(&mut s.drum_machine); "Undo: drum pattern".to_string() }
+            Undo::Seq1(bank, _) | Undo::Seq2(bank, _) | Undo::Drums(bank, _) =>
+                format!("Undo skipped: edit was made in bank {}", bank + 1),
+        };
+        // Undoing a `double` shrinks the pattern back under the cursor.
+        if self.seq_cursor  >= s.lanes[self.seq_lane].num_steps { self.seq_cursor = 0; }
+        if self.seq2_cursor >= s.sequencer2.num_steps { self.seq2_cursor = 0; }
+    }
+
+    /// Ctrl+Y: repeat the last step edit at the current cursor.  Each repeat
+    /// is one undo entry (the edits themselves are not undoable one by one).
+    pub fn repeat_last(&mut self) {
+        let Some(action) = self.last_action else {
+            self.status_msg = "Nothing to repeat — edit a step first".to_string();
+            return;
+        };
+        let before = {
+            let s = self.synth.lock().unwrap();
+            match action {
+                Action::SeqNote(_) | Action::SeqClear | Action::SeqNudge(_) | Action::SeqProb(_) =>
+                    self.seq1_undo(SeqPattern::capture(&s.lanes[self.seq_lane])),
+                Action::Seq2Note(_) | Action::Seq2Clear | Action::Seq2Nudge(_) | Action::Seq2Prob(_)
+                | Action::Seq2Accent | Action::Seq2Slide =>
+                    Undo::Seq2(self.seq2_bank, SeqPattern::capture(&s.sequencer2)),
+                _ => Undo::Drums(self.drum_bank, DrumPattern::capture(&s.drum_machine)),
+            }
+        };
+        self.status_msg.clear();
+        match action {
+            Action::SeqNote(n)   => self.seq_put_note(n),
+            Action::SeqClear     => self.seq_clear_step(),
+            Action::SeqNudge(l)  => self.seq_nudge(l),
+            Action::SeqProb(up)  => self.seq_prob(up),
+            Action::Seq2Note(n)  => self.seq2_put_note(n),
+            Action::Seq2Clear    => self.seq2_clear_step(),
+            Action::Seq2Nudge(l) => self.seq2_nudge(l),
+            Action::Seq2Prob(up) => self.seq2_prob(up),
+            Action::Seq2Accent   => self.seq2_accent(),
+            Action::Seq2Slide    => self.seq2_slide(),
+            Action::DrumToggle   => self.drum_toggle_step(),
+            Action::DrumClear    => self.drum_clear_step(),
+            Action::DrumProb(up) => if up { self.drum_prob_up() } else { self.drum_prob_down() },
+            Action::DrumNudge(l) => self.drum_nudge(l),
+            Action::DrumDecay(l) => self.drum_decay(l),
+        }
+        self.push_undo(before);
+        let what = if self.status_msg.is_empty() { action.label() } else { std::mem::take(&mut self.status_msg) };
+        self.status_msg = format!("Repeat: {}  (Ctrl+Z undo)", what);
+    }
+
+    /// Ctrl+O: step the focused sequencer (Seq1, Seq2 or drums) to the next
+    /// play direction.
+    pub fn cycle_direction(&mut self) {
+        let mut s = self.synth.lock().unwrap();
+        let (label, dir) = match self.mode {
+            AppMode::SynthSeq => {
+                let dir = s.lanes[self.seq_lane].direction.next();
+                if let Some(note) = s.lanes[self.seq_lane].set_direction(dir) { s.note_off(note); }
+                ("Seq", dir)
+            }
+            AppMode::SynthSeq2 => {
+                let dir = s.sequencer2.direction.next();
+                if let Some(note) = s.sequencer2.set_direction(dir) { s.note_off2(note); }
+                ("Seq2", dir)
+            }
+            AppMode::Drums => {
+                s.drum_machine.direction = s.drum_machine.direction.next();
+                ("Drum", s.drum_machine.direction)
+            }
+            _ => {
+                self.status_msg = "Direction: focus a sequencer or the drums".to_string();
+                return;
+            }
+        };
+        self.status_msg = format!("{} direction: {}", label, dir.name());
+    }
+
+    /// Show the focused sequencer's velocity ramp, or replace it
+    /// (`Some(None)` goes back to flat velocity).
+    fn velocity_ramp(&mut self, set: Option<Option<VelocityRamp>>) {
+        let mut s = self.synth.lock().unwrap();
+        let (label, seq) = match self.mode {
+            AppMode::SynthSeq  => ("Seq", &mut s.lanes[self.seq_lane]),
+            AppMode::SynthSeq2 => ("Seq2", &mut s.sequencer2),
+            _ => {
+                self.status_msg = "Velocity ramp: focus Seq1 or Seq2".to_string();
+                return;
+            }
+        };
+        if let Some(ramp) = set { seq.velocity_ramp = ramp; }
+        self.status_msg = match seq.velocity_ramp {
+            Some(r) => format!("{} velocity ramp: {}", label, r.label()),
+            None    => format!("{} velocity: flat", label),
+        };
+    }
+
+    /// Rotate the whole Seq1 pattern one step (Ctrl+←/→), wrapping.
+    pub fn seq_shift_left(&mut self)  { self.seq_shift(false); }
+    pub fn seq_shift_right(&mut self) { self.seq_shift(true); }
+
+    fn seq_shift(&mut self, right: bool) {
+        let before = {
+            let mut s = self.synth.lock().unwrap();
+            let before = SeqPattern::capture(&s.lanes[self.seq_lane]);
+            s.lanes[self.seq_lane].shift(right);
+            before
+        };
+        self.push_undo(self.seq1_undo(before));
+        self.status_msg = format!("Seq pattern shifted {}  (Ctrl+Z undo)", if right { "right" } else { "left" });
+    }
+
+    /// Rotate the whole Seq2 pattern one step (Ctrl+←/→), wrapping.
+    pub fn seq2_shift_left(&mut self)  { self.seq2_shift(false); }
+    pub fn seq2_shift_right(&mut self) { self.seq2_shift(true); }
+
+    fn seq2_shift(&mut self, right: bool) {
+        let before = {
+            let mut s = self.synth.lock().unwrap();
+            let before = SeqPattern::capture(&s.sequencer2);
+            s.sequencer2.shift(right);
+            before
+        };
+        self.push_undo(Undo::Seq2(self.seq2_bank, before));
+        self.status_msg = format!("Seq2 pattern shifted {}  (Ctrl+Z undo)", if right { "right" } else { "left" });
+    }
+
+    /// Rotate every drum track one step (Ctrl+←/→), wrapping.
+    pub fn drum_shift_left(&mut self)  { self.drum_shift(false); }
+    pub fn drum_shift_right(&mut self) { self.drum_shift(true); }
+
+    fn drum_shift(&mut self, right: bool) {
+        let before = {
+            let mut s = self.synth.lock().unwrap();
+            let before = DrumPattern::capture(&s.drum_machine);
+            s.drum_machine.shift(right);
+            before
+        };
+        self.push_undo(Undo::Drums(self.drum_bank, before));
+        self.status_msg = format!("Drum pattern shifted {}  (Ctrl+Z undo)", if right { "right" } else { "left" });
+    }
+
+    /// `double`: grow the focused sequencer's pattern to twice its length,
+    /// copying it into the new steps.
+    fn seq_double(&mut self) {
+        let mut s = self.synth.lock().unwrap();
+        let (label, seq) = match self.mode {
+            AppMode::SynthSeq  => ("Seq", &mut s.lanes[self.seq_lane]),
+            AppMode::SynthSeq2 => ("Seq2", &mut s.sequencer2),
+            _ => {
+                self.status_msg = "Double: focus Seq1 or Seq2".to_string();
+                return;
+            }
+        };
+        let before = SeqPattern::capture(seq);
+        if !seq.double() {
+            self.status_msg = format!("{} pattern already at {} steps — can't double past {}",
+                label, seq.num_steps, MAX_SEQ_STEPS);
+            return;
+        }
+        self.status_msg = format!("{} pattern doubled: {} → {} steps  (Ctrl+Z undo)",
+            label, before.num_steps, seq.num_steps);
+        drop(s);
+        let entry = if self.mode == AppMode::SynthSeq { self.seq1_undo(before) }
+                    else                              { Undo::Seq2(self.seq2_bank, before) };
+        self.push_undo(entry);
+    }
+
+    /// Ctrl+E / `mutate`: nudge the focused pattern at random — Seq1/Seq2
+    /// notes move a scale step, drum steps flip on playing tracks — each step
+    /// with a `mutate_pct` chance.  Draws from the shared RNG.
+    pub fn mutate(&mut self) {
+        let pct = self.mutate_pct;
+        let mut guard = self.synth.lock().unwrap();
+        let s = &mut *guard;
+        let (label, changed, what, entry) = match self.mode {
+            AppMode::SynthSeq => {
+                let before = SeqPattern::capture(&s.lanes[self.seq_lane]);
+                let n = s.lanes[self.seq_lane].mutate(&mut s.rng, pct, &self.scale_q);
+                ("Seq", n, "notes moved", self.seq1_undo(before))
+            }
+            AppMode::SynthSeq2 => {
+                let before = SeqPattern::capture(&s.sequencer2);
+                let n = s.sequencer2.mutate(&mut s.rng, pct, &self.scale_q);
+                ("Seq2", n, "notes moved", Undo::Seq2(self.seq2_bank, before))
+            }
+            AppMode::Drums => {
+                let before = DrumPattern::capture(&s.drum_machine);
+                let n = s.drum_machine.mutate(&mut s.rng, pct);
+                ("Drum", n, "steps flipped", Undo::Drums(self.drum_bank, before))
+            }
+            _ => {
+                self.status_msg = "Mutate: focus Seq1, Seq2 or the drums".to_string();
+                return;
+            }
+        };
+        drop(guard);
+        if changed == 0 {
+            self.status_msg = format!("{} mutate ({}%): nothing changed", label, pct);
+            return;
+        }
+        self.push_undo(entry);
+        self.status_msg = format!("{} mutate ({}%): {} {}  (Ctrl+Z undo)", label, pct, changed, what);
+    }
+
+    // ── Synth 1 lanes ─────────────────────────────────────────────────────
+
+    /// `lane` / `lane <n>`: show or pick the lane the Seq1 panel edits.
+    fn lane_select(&mut self, n: Option<usize>) {
+        let (len, steps) = {
+            let s = self.synth.lock().unwrap();
+            (s.lanes.len(), n.and_then(|n| s.lanes.get(n)).map(|l| l.num_steps))
+        };
+        if let Some(n) = n {
+            let Some(steps) = steps else {
+                self.status_msg = format!("No lane {}  (1–{})", n + 1, len);
+                return;
+            };
+            self.seq_lane = n;
+            if self.seq_cursor >= steps { self.seq_cursor = 0; }
+        }
+        self.status_msg = format!("Seq1 lane {}/{}", self.seq_lane + 1, len);
+    }
+
+    /// `lane add`: append an empty lane and start editing it.
+    fn lane_add(&mut self) {
+        let added = self.synth.lock().unwrap().add_lane();
+        match added {
+            Some(i) => {
+                self.seq_lane = i;
+                self.status_msg = format!("Seq1 lane {} added", i + 1);
+            }
+            None => self.status_msg = format!("Seq1 already has {} lanes", MAX_LANES),
+        }
+    }
+
+    /// `lane remove`: drop the lane being edited.  Lane 1 holds the pattern
+    /// banks and always stays.
+    fn lane_remove(&mut self) {
+        let lane = self.seq_lane;
+        if lane == 0 {
+            self.status_msg = "Lane 1 is the main lane and can't be removed".to_string();
+            return;
+        }
+        let (removed, len) = {
+            let mut s = self.synth.lock().unwrap();
+            (s.remove_lane(lane), s.lanes.len())
+        };
+        if !removed { return; }
+        // Later lanes moved down one; their undo entries follow them.
+        self.undo.retain(|e| !matches!(e, Undo::Lane(l, _) if *l == lane));
+        for e in &mut self.undo {
+            if let Undo::Lane(l, _) = e { if *l > lane { *l -= 1; } }
+        }
+        self.seq_lane = lane.min(len - 1);
+        let steps = self.synth.lock().unwrap().lanes[self.seq_lane].num_steps;
+        if self.seq_cursor >= steps { self.seq_cursor = 0; }
+        self.status_msg = format!("Seq1 lane {} removed — editing lane {}/{}", lane + 1, self.seq_lane + 1, len);
+    }
+
+    /// `lane wave …` / `lane adsr …`: change the edited lane's own sound,
+    /// starting from synth 1's when it has none yet.
+    fn lane_patch_edit(&mut self, edit: impl FnOnce(&mut Patch)) {
+        let label = {
+            let mut s = self.synth.lock().unwrap();
+            let base = s.patch1();
+            let patch = s.lane_patches[self.seq_lane].get_or_insert(base);
+            edit(patch);
+            patch.label()
+        };
+        self.status_msg = format!("Lane {} sound: {}", self.seq_lane + 1, label);
+    }
+
+    /// `lane patch` shows the edited lane's sound; `lane patch off` makes it
+    /// follow synth 1 again.
+    fn lane_patch(&mut self, off: bool) {
+        let patch = {
+            let mut s = self.synth.lock().unwrap();
+            if off { s.lane_patches[self.seq_lane] = None; }
+            s.lane_patches[self.seq_lane]
+        };
+        self.status_msg = match patch {
+            Some(p) => format!("Lane {} sound: {}", self.seq_lane + 1, p.label()),
+            None    => format!("Lane {} sound: synth 1 (wave/envelope follow the synth)", self.seq_lane + 1),
+        };
+    }
+
     pub fn switch_seq1_bank(&mut self, new_bank: usize) {
         if new_bank == self.seq1_bank { return; }
         {
             let s = self.synth.lock().unwrap();
-            self.seq1_banks[self.seq1_bank] = SeqPattern {
-                steps:     s.sequencer.steps.clone(),
-                num_steps: s.sequencer.num_steps,
-            };
+            self.seq1_banks[self.seq1_bank] = SeqPattern::capture(&s.lanes[0]);
         }
         self.seq1_bank = new_bank;
-        {
-            let mut s = self.synth.lock().unwrap();
-            let p = &self.seq1_banks[new_bank];
-            s.sequencer.steps = p.steps.clone();
-            s.sequencer.num_steps = p.num_steps;
-        }
-        if self.seq_cursor >= self.seq1_banks[new_bank].num_steps {
+        self.seq1_banks[new_bank].restore(&mut self.synth.lock().unwrap().lanes[0]);
+        if self.seq_lane == 0 && self.seq_cursor >= self.seq1_banks[new_bank].num_steps {
             self.seq_cursor = 0;
         }
         self.status_msg = format!("Seq1 Bank: {}", new_bank + 1);
@@ -329,18 +2017,10 @@ impl App {
         if new_bank == self.seq2_bank { return; }
         {
             let s = self.synth.lock().unwrap();
-            self.seq2_banks[self.seq2_bank] = SeqPattern {
-                steps:     s.sequencer2.steps.clone(),
-                num_steps: s.sequencer2.num_steps,
-            };
-        }
-        self.seq2_bank = new_bank;
-        {
-            let mut s = self.synth.lock().unwrap();
-            let p = &self.seq2_banks[new_bank];
-            s.sequencer2.steps = p.steps.clone();
-            s.sequencer2.num_steps = p.num_steps;
+            self.seq2_banks[self.seq2_bank] = SeqPattern::capture(&s.sequencer2);
         }
+        self.seq2_bank = new_bank;
+        self.seq2_banks[new_bank].restore(&mut self.synth.lock().unwrap().sequencer2);
         if self.seq2_cursor >= self.seq2_banks[new_bank].num_steps {
             self.seq2_cursor = 0;
         }
@@ -351,33 +2031,90 @@ impl App {
         if new_bank == self.drum_bank { return; }
         {
             let s = self.synth.lock().unwrap();
-            let dm = &s.drum_machine;
-            self.drum_banks[self.drum_bank] = DrumPattern {
-                num_steps:   dm.num_steps,
-                swing:       dm.swing,
-                track_steps: dm.tracks.iter().map(|t| t.steps.clone()).collect(),
-            };
+            self.drum_banks[self.drum_bank] = DrumPattern::capture(&s.drum_machine);
         }
         self.drum_bank = new_bank;
-        {
-            let mut s = self.synth.lock().unwrap();
-            let p = &self.drum_banks[new_bank];
-            s.drum_machine.num_steps = p.num_steps;
-            s.drum_machine.swing = p.swing;
-            let n_tracks = s.drum_machine.tracks.len().min(p.track_steps.len());
-            for i in 0..n_tracks {
-                s.drum_machine.tracks[i].steps = p.track_steps[i].clone();
-                s.drum_machine.tracks[i].steps.resize(p.num_steps, 0);
-            }
-        }
+        self.drum_rec_undo = false;
+        self.drum_banks[new_bank].restore(&mut self.synth.lock().unwrap().drum_machine);
         if self.drum_step >= self.drum_banks[new_bank].num_steps {
             self.drum_step = 0;
         }
         self.status_msg = format!("Drum Bank: {}", new_bank + 1);
     }
 
-    pub fn refresh_active_notes(&mut self) {
-        self.active_notes = self.synth.lock().unwrap().active_notes();
+    /// Per-frame snapshot of audio-thread state the UI reacts to: the
+    /// sounding notes, voice counts, drum meters, the clip LED and the drum playhead, read
+    /// under a single lock.  Returns true if anything visible changed.
+    pub fn refresh_snapshot(&mut self) -> bool {
+        let (notes, playhead, voices, steals, meters, mix, clip_led) = {
+            let s = self.synth.lock().unwrap();
+            let dm = &s.drum_machine;
+            let bar = bar_steps(s.time_sig) as usize;
+            let playhead = dm.playing.then(|| (dm.current_step, dm.play_range().0, bar));
+            let meters: Vec<u8> = dm.tracks.iter().map(|t| meter_level(t.meter)).collect();
+            let mix = (s.mix_meter.peak, (20.0 * s.mix_meter.gain.max(1e-6).log10()).round() as i32);
+            (s.active_notes(), playhead, ([s.voices.len(), s.voices2.len()], s.max_voices), s.voice_steals, meters, mix,
+             s.clip_latch)
+        };
+        let (mix_peak, mix_cut_db) = mix;
+        let mix_level = meter_level(mix_peak / MIX_CLIP_LEVEL);
+        let changed = notes != self.active_notes || voices != (self.voice_count, self.max_voices)
+            || steals != self.voice_steals || meters != self.drum_meters
+            || (mix_level, mix_cut_db) != (self.mix_level, self.mix_cut_db)
+            || clip_led != self.clip_led;
+        self.clip_led = clip_led;
+        self.active_notes = notes;
+        self.drum_meters = meters;
+        (self.mix_level, self.mix_cut_db) = (mix_level, mix_cut_db);
+        if mix_peak > MIX_CLIP_LEVEL { self.clip_at = Some(Instant::now()); }
+        (self.voice_count, self.max_voices) = voices;
+        if steals != self.voice_steals {
+            self.voice_steals = steals;
+            self.steal_at = Some(Instant::now());
+        }
+
+        let step = playhead.map(|(step, _, _)| step);
+        if let Some((step, first, bar)) = playhead {
+            let downbeat = step >= first && (step - first).is_multiple_of(bar);
+            if downbeat && self.drum_playhead != Some(step) {
+                self.downbeat_at = Some(Instant::now());
+            }
+        }
+        self.drum_playhead = step;
+        changed
+    }
+
+    /// Brightness of the downbeat flash: 2 just after bar 1, 1 while it fades,
+    /// 0 when off or disabled.
+    pub fn beat_flash_level(&self) -> u8 {
+        if !self.beat_flash { return 0; }
+        match self.downbeat_at.map(|t| t.elapsed()) {
+            Some(age) if age < BEAT_FLASH / 2 => 2,
+            Some(age) if age < BEAT_FLASH     => 1,
+            _ => 0,
+        }
+    }
+
+    /// True shortly after a voice was stolen (the voices meter flashes).
+    pub fn steal_flash(&self) -> bool {
+        self.steal_at.is_some_and(|t| t.elapsed() < STEAL_FLASH)
+    }
+
+    /// True while the pre-tanh mix is over `MIX_CLIP_LEVEL`, and for
+    /// `CLIP_HOLD` after (the mix meter shows a clip warning).
+    pub fn clip_warning(&self) -> bool {
+        self.clip_at.is_some_and(|t| t.elapsed() < CLIP_HOLD)
+    }
+
+    /// True while the screen changes on its own: a sequencer is running or
+    /// audio (including effect tails) is still reaching the scope.
+    pub fn is_animating(&self) -> bool {
+        // An open MIDI input and running automation are polled every frame,
+        // so knob moves show (and lanes play) promptly.
+        if self.midi_in.is_some() || self.automation.state != AutoState::Stopped { return true; }
+        let s = self.synth.lock().unwrap();
+        s.lanes[0].playing || s.sequencer2.playing || s.drum_machine.playing
+            || !render::is_silent(&s.scope_buf)
     }
 
     // ── UI read helpers ───────────────────────────────────────────────────
@@ -399,7 +2136,7 @@ impl App {
     }
 
     pub fn seq_playing(&self) -> bool {
-        self.synth.lock().unwrap().sequencer.playing
+        self.synth.lock().unwrap().lanes[0].playing
     }
 
     pub fn seq2_playing(&self) -> bool {
@@ -414,14 +2151,22 @@ impl App {
 
     /// Cycle focus: Keyboard → SynthSeq → SynthSeq2 → Drums → Effects → Keyboard.
     pub fn toggle_mode(&mut self) {
-        self.release_all();
-        self.mode = match self.mode {
+        let next = match self.mode {
             AppMode::Play      => AppMode::SynthSeq,
             AppMode::SynthSeq  => AppMode::SynthSeq2,
             AppMode::SynthSeq2 => AppMode::Drums,
             AppMode::Drums     => AppMode::Effects,
             AppMode::Effects   => AppMode::Play,
         };
+        self.focus(next);
+    }
+
+    /// Move focus straight to `mode` (Alt+1–5), with the same side effects
+    /// as cycling to it.
+    pub fn focus(&mut self, mode: AppMode) {
+        self.release_all();
+        let paused = self.tab_pauses && self.pause_transport();
+        self.mode = mode;
         self.status_msg = match self.mode {
             AppMode::Play      => "Focus: Keyboard".to_string(),
             AppMode::SynthSeq  => "Focus: Synth Seq".to_string(),
@@ -429,70 +2174,169 @@ impl App {
             AppMode::Drums     => "Focus: Drums".to_string(),
             AppMode::Effects   => "Focus: Effects".to_string(),
         };
+        if paused { self.status_msg.push_str("  (transport paused)"); }
+    }
+
+    /// Stop every running sequencer and the drum machine.  Returns true if
+    /// anything was playing.
+    fn pause_transport(&mut self) -> bool {
+        let mut guard = self.synth.lock().unwrap();
+        let s = &mut *guard;
+        let was_playing = s.lanes[0].playing || s.sequencer2.playing || s.drum_machine.playing;
+        if s.lanes[0].playing { s.toggle_lanes(); }
+        if s.sequencer2.playing {
+            if let Some(note) = s.sequencer2.toggle_play() { s.note_off2(note); }
+        }
+        if s.drum_machine.playing { s.drum_machine.toggle_play(); }
+        was_playing
     }
 
     // ── Melodic sequencer 1 controls ──────────────────────────────────────
 
     pub fn seq_cursor_left(&mut self) {
-        let n = self.synth.lock().unwrap().sequencer.num_steps;
-        self.seq_cursor = if self.seq_cursor == 0 { n - 1 } else { self.seq_cursor - 1 };
+        let n = self.synth.lock().unwrap().lanes[self.seq_lane].num_steps;
+        self.seq_cursor = move_cursor(self.seq_cursor, n, false, self.cursor_wrap);
     }
 
     pub fn seq_cursor_right(&mut self) {
-        let n = self.synth.lock().unwrap().sequencer.num_steps;
-        self.seq_cursor = (self.seq_cursor + 1) % n;
+        let n = self.synth.lock().unwrap().lanes[self.seq_lane].num_steps;
+        self.seq_cursor = move_cursor(self.seq_cursor, n, true, self.cursor_wrap);
     }
 
     pub fn seq_set_note(&mut self, key: char) {
-        let Some(raw) = key_to_note(key, self.base_octave) else { return };
-        let note = self.scale_q.quantize(raw);
+        let Some(raw) = self.key_note(key) else { return };
+        self.seq_put_note(self.scale_q.quantize(raw));
+    }
+
+    fn seq_put_note(&mut self, note: u8) {
+        self.last_action = Some(Action::SeqNote(note));
         let cursor = self.seq_cursor;
         let n = {
             let mut s = self.synth.lock().unwrap();
-            s.sequencer.set_step(cursor, note);
-            s.sequencer.num_steps
+            s.lanes[self.seq_lane].set_step(cursor, note);
+            s.lanes[self.seq_lane].num_steps
         };
         self.status_msg = format!("Step {}: {}", cursor + 1, note_name(note));
-        self.seq_cursor = (cursor + 1) % n;
+        self.seq_cursor = (cursor + self.seq_advance) % n;
+    }
+
+    /// True while ↑/↓/Enter drive pitch entry (on, and a melodic sequencer focused).
+    pub fn pitch_entry_active(&self) -> bool {
+        self.pitch_entry && matches!(self.mode, AppMode::SynthSeq | AppMode::SynthSeq2)
+    }
+
+    /// Switch pitch entry on or off (`None` toggles).
+    pub fn set_pitch_entry(&mut self, on: Option<bool>) {
+        self.pitch_entry = on.unwrap_or(!self.pitch_entry);
+        self.status_msg = if self.pitch_entry {
+            format!("Pitch entry: ON  ([↑↓] pick {}, [Enter] place it, [PgUp/PgDn] BPM)",
+                note_name(self.scale_q.quantize(self.pending_note)))
+        } else {
+            "Pitch entry: OFF".to_string()
+        };
+    }
+
+    /// Move the pending note one scale degree (one semitone with the scale Off).
+    pub fn pending_note_step(&mut self, up: bool) {
+        let from = self.scale_q.quantize(self.pending_note);
+        self.pending_note = if up { self.scale_q.step_up(from) } else { self.scale_q.step_down(from) };
+        self.status_msg = format!("Pending note: {}", note_name(self.pending_note));
+    }
+
+    /// Enter the pending note at the focused sequencer's cursor, advancing
+    /// like a piano-key entry.
+    pub fn place_pending_note(&mut self) {
+        let note = self.scale_q.quantize(self.pending_note);
+        match self.mode {
+            AppMode::SynthSeq  => self.seq_put_note(note),
+            AppMode::SynthSeq2 => self.seq2_put_note(note),
+            _ => {}
+        }
     }
 
     pub fn seq_clear_step(&mut self) {
+        self.last_action = Some(Action::SeqClear);
         let cursor = self.seq_cursor;
-        self.synth.lock().unwrap().sequencer.clear_step(cursor);
+        self.synth.lock().unwrap().lanes[self.seq_lane].clear_step(cursor);
         self.status_msg = format!("Step {} cleared", cursor + 1);
     }
 
-    pub fn seq_toggle_play(&mut self) {
+    pub fn seq_nudge(&mut self, later: bool) {
+        self.last_action = Some(Action::SeqNudge(later));
+        let cursor = self.seq_cursor;
+        let delta = if later { NUDGE_STEP } else { -NUDGE_STEP };
+        let n = self.synth.lock().unwrap().lanes[self.seq_lane].nudge_step(cursor, delta);
+        self.status_msg = format!("Step {} nudge: {:+}%", cursor + 1, n);
+    }
+
+    /// Alt+↑/↓: raise / lower the chance the cursor step's note plays.
+    pub fn seq_prob(&mut self, up: bool) {
+        self.last_action = Some(Action::SeqProb(up));
+        let cursor = self.seq_cursor;
+        let p = self.synth.lock().unwrap().lanes[self.seq_lane].step_prob(cursor, up);
+        self.status_msg = format!("Step {} probability: {}%", cursor + 1, p);
+    }
+
+    /// `\`: solo the cursor step on loop, or return to normal playback.
+    pub fn seq_solo_toggle(&mut self) {
+        let cursor = self.seq_cursor;
+        let mut s = self.synth.lock().unwrap();
+        let range = if s.lanes[self.seq_lane].audition.is_some() { None } else { Some((cursor, cursor)) };
+        if let Some(note) = s.lanes[self.seq_lane].set_audition(range) { s.note_off(note); }
+        self.status_msg = solo_status("Seq", range);
+    }
+
+    /// `|`: grow the solo range to include the cursor step (starts one if none).
+    pub fn seq_solo_extend(&mut self) {
+        let cursor = self.seq_cursor;
         let mut s = self.synth.lock().unwrap();
-        if let Some(note) = s.sequencer.toggle_play() { s.note_off(note); }
-        self.status_msg = if s.sequencer.playing { "Seq: Playing".to_string() }
-                          else                   { "Seq: Paused".to_string() };
+        let range = extend_solo(s.lanes[self.seq_lane].audition, cursor);
+        if let Some(note) = s.lanes[self.seq_lane].set_audition(Some(range)) { s.note_off(note); }
+        self.status_msg = solo_status("Seq", Some(range));
+    }
+
+    /// `(` / `)`: set the loop start / end to the cursor step.
+    pub fn seq_loop_point(&mut self, is_end: bool) {
+        let cursor = self.seq_cursor;
+        let mut s = self.synth.lock().unwrap();
+        if let Some(note) = s.lanes[self.seq_lane].set_loop_point(cursor, is_end) { s.note_off(note); }
+        self.status_msg = loop_points_status("Seq", s.lanes[self.seq_lane].play_range(), s.lanes[self.seq_lane].num_steps);
+    }
+
+    pub fn seq_toggle_play(&mut self) {
+        let playing = self.synth.lock().unwrap().toggle_lanes();
+        self.status_msg = if playing { "Seq: Playing".to_string() }
+                          else       { "Seq: Paused".to_string() };
     }
 
     pub fn seq_cycle_steps(&mut self) {
         let mut s = self.synth.lock().unwrap();
-        s.sequencer.cycle_num_steps();
-        let n = s.sequencer.num_steps;
+        s.lanes[self.seq_lane].cycle_num_steps(self.seq_length_lock);
+        let n = s.lanes[self.seq_lane].num_steps;
         drop(s);
         if self.seq_cursor >= n { self.seq_cursor = 0; }
-        self.status_msg = format!("Seq steps: {}", n);
+        self.status_msg = format!("Seq steps: {}{}", n, length_lock_note(self.seq_length_lock));
     }
 
     // ── Melodic sequencer 2 controls ──────────────────────────────────────
 
     pub fn seq2_cursor_left(&mut self) {
         let n = self.synth.lock().unwrap().sequencer2.num_steps;
-        self.seq2_cursor = if self.seq2_cursor == 0 { n - 1 } else { self.seq2_cursor - 1 };
+        self.seq2_cursor = move_cursor(self.seq2_cursor, n, false, self.cursor_wrap);
     }
 
     pub fn seq2_cursor_right(&mut self) {
         let n = self.synth.lock().unwrap().sequencer2.num_steps;
-        self.seq2_cursor = (self.seq2_cursor + 1) % n;
+        self.seq2_cursor = move_cursor(self.seq2_cursor, n, true, self.cursor_wrap);
     }
 
     pub fn seq2_set_note(&mut self, key: char) {
-        let Some(raw) = key_to_note(key, self.base_octave) else { return };
-        let note = self.scale_q.quantize(raw);
+        let Some(raw) = self.key_note(key) else { return };
+        self.seq2_put_note(self.scale_q.quantize(raw));
+    }
+
+    fn seq2_put_note(&mut self, note: u8) {
+        self.last_action = Some(Action::Seq2Note(note));
         let cursor = self.seq2_cursor;
         let n = {
             let mut s = self.synth.lock().unwrap();
@@ -500,15 +2344,82 @@ impl App {
             s.sequencer2.num_steps
         };
         self.status_msg = format!("Seq2 step {}: {}", cursor + 1, note_name(note));
-        self.seq2_cursor = (cursor + 1) % n;
+        self.seq2_cursor = (cursor + self.seq_advance) % n;
     }
 
     pub fn seq2_clear_step(&mut self) {
+        self.last_action = Some(Action::Seq2Clear);
         let cursor = self.seq2_cursor;
         self.synth.lock().unwrap().sequencer2.clear_step(cursor);
         self.status_msg = format!("Seq2 step {} cleared", cursor + 1);
     }
 
+    pub fn seq2_nudge(&mut self, later: bool) {
+        self.last_action = Some(Action::Seq2Nudge(later));
+        let cursor = self.seq2_cursor;
+        let delta = if later { NUDGE_STEP } else { -NUDGE_STEP };
+        let n = self.synth.lock().unwrap().sequencer2.nudge_step(cursor, delta);
+        self.status_msg = format!("Seq2 step {} nudge: {:+}%", cursor + 1, n);
+    }
+
+    /// Alt+↑/↓: raise / lower the chance the Seq2 cursor step's note plays.
+    pub fn seq2_prob(&mut self, up: bool) {
+        self.last_action = Some(Action::Seq2Prob(up));
+        let cursor = self.seq2_cursor;
+        let p = self.synth.lock().unwrap().sequencer2.step_prob(cursor, up);
+        self.status_msg = format!("Seq2 step {} probability: {}%", cursor + 1, p);
+    }
+
+    /// Toggle the 303 accent on the Seq2 cursor step.
+    pub fn seq2_accent(&mut self) {
+        self.last_action = Some(Action::Seq2Accent);
+        let cursor = self.seq2_cursor;
+        let on = self.synth.lock().unwrap().sequencer2.toggle_accent(cursor);
+        self.status_msg = format!("Seq2 step {} accent: {}", cursor + 1, if on { "ON" } else { "OFF" });
+    }
+
+    /// Toggle the 303 slide on the Seq2 cursor step (glides into the next step).
+    pub fn seq2_slide(&mut self) {
+        self.last_action = Some(Action::Seq2Slide);
+        let cursor = self.seq2_cursor;
+        let on = self.synth.lock().unwrap().sequencer2.toggle_slide(cursor);
+        self.status_msg = format!("Seq2 step {} slide: {}", cursor + 1, if on { "ON" } else { "OFF" });
+    }
+
+    /// Show the Seq2 panel as the 303 bass lane (note / accent / slide rows)
+    /// or as the normal grid (`None` toggles).
+    pub fn set_bass_view(&mut self, on: Option<bool>) {
+        self.bass_view = on.unwrap_or(!self.bass_view);
+        self.status_msg = if self.bass_view {
+            "Seq2: 303 view  ([⇧↑] accent, [⇧↓] slide at the cursor)".to_string()
+        } else {
+            "Seq2: grid view".to_string()
+        };
+    }
+
+    pub fn seq2_solo_toggle(&mut self) {
+        let cursor = self.seq2_cursor;
+        let mut s = self.synth.lock().unwrap();
+        let range = if s.sequencer2.audition.is_some() { None } else { Some((cursor, cursor)) };
+        if let Some(note) = s.sequencer2.set_audition(range) { s.note_off2(note); }
+        self.status_msg = solo_status("Seq2", range);
+    }
+
+    pub fn seq2_solo_extend(&mut self) {
+        let cursor = self.seq2_cursor;
+        let mut s = self.synth.lock().unwrap();
+        let range = extend_solo(s.sequencer2.audition, cursor);
+        if let Some(note) = s.sequencer2.set_audition(Some(range)) { s.note_off2(note); }
+        self.status_msg = solo_status("Seq2", Some(range));
+    }
+
+    pub fn seq2_loop_point(&mut self, is_end: bool) {
+        let cursor = self.seq2_cursor;
+        let mut s = self.synth.lock().unwrap();
+        if let Some(note) = s.sequencer2.set_loop_point(cursor, is_end) { s.note_off2(note); }
+        self.status_msg = loop_points_status("Seq2", s.sequencer2.play_range(), s.sequencer2.num_steps);
+    }
+
     pub fn seq2_toggle_play(&mut self) {
         let mut s = self.synth.lock().unwrap();
         if let Some(note) = s.sequencer2.toggle_play() { s.note_off2(note); }
@@ -518,45 +2429,95 @@ impl App {
 
     pub fn seq2_cycle_steps(&mut self) {
         let mut s = self.synth.lock().unwrap();
-        s.sequencer2.cycle_num_steps();
+        s.sequencer2.cycle_num_steps(self.seq_length_lock);
         let n = s.sequencer2.num_steps;
         drop(s);
         if self.seq2_cursor >= n { self.seq2_cursor = 0; }
-        self.status_msg = format!("Seq2 steps: {}", n);
+        self.status_msg = format!("Seq2 steps: {}{}", n, length_lock_note(self.seq_length_lock));
     }
 
     // ── Drum machine controls ─────────────────────────────────────────────
 
     pub fn drum_track_up(&mut self) {
         let n = self.synth.lock().unwrap().drum_machine.tracks.len();
-        self.drum_track = if self.drum_track == 0 { n - 1 } else { self.drum_track - 1 };
+        self.drum_track = move_cursor(self.drum_track, n, false, self.cursor_wrap);
     }
 
     pub fn drum_track_down(&mut self) {
         let n = self.synth.lock().unwrap().drum_machine.tracks.len();
-        self.drum_track = (self.drum_track + 1) % n;
+        self.drum_track = move_cursor(self.drum_track, n, true, self.cursor_wrap);
     }
 
     pub fn drum_step_left(&mut self) {
         let n = self.synth.lock().unwrap().drum_machine.num_steps;
-        self.drum_step = if self.drum_step == 0 { n - 1 } else { self.drum_step - 1 };
+        self.drum_step = move_cursor(self.drum_step, n, false, self.cursor_wrap);
     }
 
     pub fn drum_step_right(&mut self) {
         let n = self.synth.lock().unwrap().drum_machine.num_steps;
-        self.drum_step = (self.drum_step + 1) % n;
+        self.drum_step = move_cursor(self.drum_step, n, true, self.cursor_wrap);
     }
 
     pub fn drum_toggle_step(&mut self) {
+        self.last_action = Some(Action::DrumToggle);
         let (track, step) = (self.drum_track, self.drum_step);
         self.synth.lock().unwrap().drum_machine.toggle_step(track, step);
     }
 
     pub fn drum_clear_step(&mut self) {
+        self.last_action = Some(Action::DrumClear);
         let (track, step) = (self.drum_track, self.drum_step);
         self.synth.lock().unwrap().drum_machine.clear_step(track, step);
     }
 
+    /// Load a WAV onto the selected drum track, or go back to the
+    /// synthesised voice (`None`).  The file is decoded before the lock.
+    fn drum_set_sample(&mut self, path: Option<&str>) {
+        let track = self.drum_track;
+        let loaded = match path.map(|p| sample::load_wav(std::path::Path::new(p))).transpose() {
+            Ok(s) => s,
+            Err(e) => { self.status_msg = format!("Sample: {:#}", e); return; }
+        };
+        let mut s = self.synth.lock().unwrap();
+        let Some(t) = s.drum_machine.tracks.get_mut(track) else { return };
+        let kind = t.kind.name().trim();
+        self.status_msg = match &loaded {
+            Some(smp) => format!("{}: sample {} ({:.2} s)", kind, smp.name, smp.secs()),
+            None      => format!("{}: synthesised voice", kind),
+        };
+        t.sample = loaded;
+    }
+
+    /// `fit <n>`: time-stretch the selected track's sample to `n` steps
+    /// (pitch kept), or back to its natural length (`None`).
+    fn drum_fit(&mut self, steps: Option<u16>) {
+        let track = self.drum_track;
+        let mut s = self.synth.lock().unwrap();
+        let bpm = s.bpm;
+        let Some(t) = s.drum_machine.tracks.get_mut(track) else { return };
+        t.fit_steps = steps;
+        let kind = t.kind.name().trim().to_string();
+        let has_sample = t.sample.is_some();
+        self.status_msg = match (steps, s.drum_machine.stretch_ratio(track, bpm)) {
+            (None, _)              => format!("{}: sample at natural length", kind),
+            (Some(n), Some(ratio)) => format!("{}: sample fitted to {} steps (×{:.2})", kind, n, ratio),
+            (Some(n), None) if !has_sample =>
+                format!("{}: fit {} steps  (applies to samples — load one with `sample <path>`)", kind, n),
+            (Some(n), None)        => format!("{}: fit {} steps", kind, n),
+        };
+    }
+
+    /// [r]: play the selected track's sample backwards (or forwards again).
+    pub fn drum_toggle_reverse(&mut self) {
+        let track = self.drum_track;
+        let mut s = self.synth.lock().unwrap();
+        let reverse = s.drum_machine.toggle_reverse(track);
+        let Some(t) = s.drum_machine.tracks.get(track) else { return };
+        self.status_msg = format!("{} reverse: {}{}", t.kind.name().trim(),
+            if reverse { "ON" } else { "OFF" },
+            if t.sample.is_none() { "  (applies to samples — load one with `sample <path>`)" } else { "" });
+    }
+
     pub fn drum_toggle_mute(&mut self) {
         let track = self.drum_track;
         self.synth.lock().unwrap().drum_machine.toggle_mute(track);
@@ -578,11 +2539,11 @@ impl App {
 
     pub fn drum_cycle_steps(&mut self) {
         let mut s = self.synth.lock().unwrap();
-        s.drum_machine.cycle_num_steps();
+        s.drum_machine.cycle_num_steps(self.drum_length_lock);
         let n = s.drum_machine.num_steps;
         drop(s);
         if self.drum_step >= n { self.drum_step = 0; }
-        self.status_msg = format!("Drum steps: {}", n);
+        self.status_msg = format!("Drum steps: {}{}", n, length_lock_note(self.drum_length_lock));
     }
 
     pub fn drum_vol_up(&mut self) {
@@ -603,7 +2564,33 @@ impl App {
         self.status_msg = format!("{} vol: {}%", kind.name(), (vol * 100.0).round() as u32);
     }
 
+    /// Move the selected track's pan by one 5% step (`right` = towards R).
+    pub fn drum_pan(&mut self, right: bool) {
+        let track = self.drum_track;
+        let mut s = self.synth.lock().unwrap();
+        s.drum_machine.track_pan(track, if right { 0.05 } else { -0.05 });
+        let t = &s.drum_machine.tracks[track];
+        self.status_msg = format!("{} pan: {}", t.kind.name(), pan_label(t.pan));
+    }
+
+    /// [./]: tune the selected track down/up a semitone.
+    pub fn drum_tune(&mut self, up: bool) {
+        let track = self.drum_track;
+        let mut s = self.synth.lock().unwrap();
+        let tune = s.drum_machine.tune_step(track, up);
+        let Some(t) = s.drum_machine.tracks.get(track) else { return };
+        self.status_msg = format!("{} tune: {:+} st", t.kind.name().trim(), tune);
+    }
+
+    /// Spread the kit across the stereo field, or back to centre.
+    pub fn drum_auto_pan(&mut self) {
+        let spread = self.synth.lock().unwrap().drum_machine.toggle_auto_pan();
+        self.status_msg = if spread { "Drum pan: auto spread".to_string() }
+                          else      { "Drum pan: all centre".to_string() };
+    }
+
     pub fn drum_prob_up(&mut self) {
+        self.last_action = Some(Action::DrumProb(true));
         let (track, step) = (self.drum_track, self.drum_step);
         let mut s = self.synth.lock().unwrap();
         s.drum_machine.step_prob_up(track, step);
@@ -612,63 +2599,432 @@ impl App {
         self.status_msg = format!("{} step {}: {}%", kind.name(), step + 1, prob);
     }
 
-    pub fn drum_prob_down(&mut self) {
-        let (track, step) = (self.drum_track, self.drum_step);
-        let mut s = self.synth.lock().unwrap();
-        s.drum_machine.step_prob_down(track, step);
-        let prob = s.drum_machine.tracks[track].steps[step];
-        let kind = s.drum_machine.tracks[track].kind;
-        self.status_msg = if prob == 0 {
-            format!("{} step {}: OFF", kind.name(), step + 1)
-        } else {
-            format!("{} step {}: {}%", kind.name(), step + 1, prob)
+    pub fn drum_prob_down(&mut self) {
+        self.last_action = Some(Action::DrumProb(false));
+        let (track, step) = (self.drum_track, self.drum_step);
+        let mut s = self.synth.lock().unwrap();
+        s.drum_machine.step_prob_down(track, step);
+        let prob = s.drum_machine.tracks[track].steps[step];
+        let kind = s.drum_machine.tracks[track].kind;
+        self.status_msg = if prob == 0 {
+            format!("{} step {}: OFF", kind.name(), step + 1)
+        } else {
+            format!("{} step {}: {}%", kind.name(), step + 1, prob)
+        };
+    }
+
+    pub fn drum_nudge(&mut self, later: bool) {
+        self.last_action = Some(Action::DrumNudge(later));
+        let (track, step) = (self.drum_track, self.drum_step);
+        let delta = if later { NUDGE_STEP } else { -NUDGE_STEP };
+        let mut s = self.synth.lock().unwrap();
+        let n = s.drum_machine.nudge_step(track, step, delta);
+        let kind = s.drum_machine.tracks[track].kind;
+        self.status_msg = format!("{} step {} nudge: {:+}%", kind.name(), step + 1, n);
+    }
+
+    /// Lengthen or shorten the selected step's decay.
+    pub fn drum_decay(&mut self, longer: bool) {
+        self.last_action = Some(Action::DrumDecay(longer));
+        let (track, step) = (self.drum_track, self.drum_step);
+        let mut s = self.synth.lock().unwrap();
+        let d = s.drum_machine.decay_step(track, step, longer);
+        let kind = s.drum_machine.tracks[track].kind;
+        self.status_msg = format!("{} step {} decay: {}%", kind.name(), step + 1, d);
+    }
+
+    pub fn drum_loop_point(&mut self, is_end: bool) {
+        let step = self.drum_step;
+        let mut s = self.synth.lock().unwrap();
+        s.drum_machine.set_loop_point(step, is_end);
+        self.status_msg = loop_points_status("Drum", s.drum_machine.play_range(), s.drum_machine.num_steps);
+    }
+
+    pub fn drum_swing_up(&mut self) {
+        let mut s = self.synth.lock().unwrap();
+        s.drum_machine.swing = (s.drum_machine.swing + 0.05).min(MAX_SWING);
+        self.status_msg = format!("Drum swing: {:.0}%", s.drum_machine.swing * 100.0);
+    }
+
+    pub fn drum_swing_down(&mut self) {
+        let mut s = self.synth.lock().unwrap();
+        s.drum_machine.swing = (s.drum_machine.swing - 0.05).max(0.0);
+        self.status_msg = format!("Drum swing: {:.0}%", s.drum_machine.swing * 100.0);
+    }
+
+    /// [<>] in SynthSeq/SynthSeq2 focus: melodic swing ∓/±5%, shared by every
+    /// synth 1 lane and synth 2.
+    pub fn seq_swing_step(&mut self, up: bool) {
+        let mut s = self.synth.lock().unwrap();
+        s.seq_swing = (s.seq_swing + if up { 0.05 } else { -0.05 }).clamp(0.0, MAX_SWING);
+        self.status_msg = format!("Seq swing: {:.0}%", s.seq_swing * 100.0);
+    }
+
+    /// `swing`: set the drum and/or melodic swing (`None` = leave it), or
+    /// just show both.
+    fn swing_set(&mut self, drums: Option<f32>, seq: Option<f32>) {
+        let mut s = self.synth.lock().unwrap();
+        if let Some(v) = drums { s.drum_machine.swing = v.clamp(0.0, MAX_SWING); }
+        if let Some(v) = seq   { s.seq_swing = v.clamp(0.0, MAX_SWING); }
+        self.status_msg = format!("Swing: drums {:.0}%  seq {:.0}%",
+            s.drum_machine.swing * 100.0, s.seq_swing * 100.0);
+    }
+
+    /// Step through Off → each available groove → Off.  The list is rescanned
+    /// every press so files dropped into `grooves/` show up immediately.
+    pub fn drum_cycle_groove(&mut self) {
+        let grooves = groove::available();
+        let current = self.synth.lock().unwrap().drum_machine.groove.as_ref()
+            .and_then(|g| grooves.iter().position(|x| x.name == g.name));
+        let next = match current {
+            None    => grooves.first(),
+            Some(i) => grooves.get(i + 1),
+        };
+        self.drum_set_groove(next.cloned());
+    }
+
+    /// Apply the groove named `name` (case-insensitive), or remove it.
+    pub fn drum_apply_groove(&mut self, name: Option<&str>) {
+        let Some(name) = name else { return self.drum_set_groove(None) };
+        match groove::available().into_iter().find(|g| g.name.eq_ignore_ascii_case(name)) {
+            Some(g) => self.drum_set_groove(Some(g)),
+            None    => self.status_msg = format!("No groove named \"{}\"  (try: groove)", name),
+        }
+    }
+
+    fn drum_set_groove(&mut self, groove: Option<Groove>) {
+        self.status_msg = match &groove {
+            Some(g) => format!("Groove: {}{}  ({} steps, stacks with swing/nudge)",
+                g.name, if g.factory { " (factory)" } else { "" }, g.timing.len()),
+            None    => "Groove: off".to_string(),
+        };
+        self.synth.lock().unwrap().drum_machine.groove = groove;
+    }
+
+    pub fn drum_euclidean(&mut self) {
+        let track = self.drum_track;
+        let (k, kind, n) = {
+            let s = self.synth.lock().unwrap();
+            let dm = &s.drum_machine;
+            let k = dm.tracks[track].steps.iter().filter(|&&p| p > 0).count();
+            let k = if k == 0 { 4 } else { k };
+            (k, dm.tracks[track].kind, dm.num_steps)
+        };
+        self.synth.lock().unwrap().drum_machine.euclidean_fill(track, k);
+        self.status_msg = format!("{}: E({},{})", kind.name(), k, n);
+    }
+
+    // ── Performance view / mute groups ────────────────────────────────────
+
+    pub fn toggle_perf_view(&mut self) {
+        self.perf_view = !self.perf_view;
+        self.status_msg = if self.perf_view { "Performance view: ON".to_string() }
+                          else              { "Performance view: OFF".to_string() };
+    }
+
+    /// Toggle a mute group.  If every track in the group is muted the group is
+    /// unmuted; otherwise all of its tracks are muted.
+    pub fn toggle_mute_group(&mut self, idx: usize) {
+        let Some(group) = self.mute_groups.get(idx) else { return };
+        let mut s = self.synth.lock().unwrap();
+        let tracks = &mut s.drum_machine.tracks;
+        let all_muted = group.tracks.iter()
+            .all(|&t| tracks.get(t).map(|t| t.muted).unwrap_or(true));
+        for &t in &group.tracks {
+            if let Some(t) = tracks.get_mut(t) { t.muted = !all_muted; }
+        }
+        self.status_msg = format!("{}: {}", group.name, if all_muted { "unmuted" } else { "muted" });
+    }
+
+    /// What the solo key acts on in the current focus: the drum track, the
+    /// synth 1 lane (the whole bus with a single lane), synth 2 or, from
+    /// the keyboard, synth 1.  `None` in effects focus.
+    fn solo_target(&self) -> Option<SoloTarget> {
+        match self.mode {
+            AppMode::Drums     => Some(SoloTarget::Drum(self.drum_track)),
+            AppMode::SynthSeq if self.synth.lock().unwrap().lanes.len() > 1 => Some(SoloTarget::Lane(self.seq_lane)),
+            AppMode::SynthSeq | AppMode::Play => Some(SoloTarget::Synth1),
+            AppMode::SynthSeq2 => Some(SoloTarget::Synth2),
+            AppMode::Effects   => None,
+        }
+    }
+
+    /// Alt+S: solo the focused element (adding to any other solos), or take
+    /// it out of the solo.
+    pub fn solo_toggle(&mut self) {
+        let Some(target) = self.solo_target() else {
+            self.status_msg = "Nothing to solo in effects focus".to_string();
+            return;
+        };
+        let mut s = self.synth.lock().unwrap();
+        let on = s.solo.toggle(target);
+        self.status_msg = match (on, s.solo.targets.len()) {
+            (true, 1)  => format!("Solo: {}", target.label()),
+            (true, n)  => format!("Solo: {} (+{} more)", target.label(), n - 1),
+            (false, 0) => format!("{} unsoloed — solo off", target.label()),
+            (false, _) => format!("{} unsoloed", target.label()),
+        };
+    }
+
+    /// Alt+Shift+S: clear every solo.
+    pub fn solo_clear(&mut self) {
+        let mut s = self.synth.lock().unwrap();
+        self.status_msg = if s.solo.is_active() { "Solos cleared".to_string() } else { "No solo".to_string() };
+        s.solo.clear();
+    }
+
+    /// Solo state of `target`, for the panels.
+    pub fn solo_state(&self, target: SoloTarget) -> SoloState {
+        self.synth.lock().unwrap().solo.state(target)
+    }
+
+    /// Per-group mute state for the performance view: `Some(true)` = all muted,
+    /// `Some(false)` = all live, `None` = partially muted.
+    pub fn mute_group_states(&self) -> Vec<Option<bool>> {
+        let s = self.synth.lock().unwrap();
+        let tracks = &s.drum_machine.tracks;
+        self.mute_groups.iter().map(|g| {
+            let muted = g.tracks.iter().filter(|&&t| tracks.get(t).map(|t| t.muted).unwrap_or(false)).count();
+            if muted == g.tracks.len()  { Some(true) }
+            else if muted == 0          { Some(false) }
+            else                        { None }
+        }).collect()
+    }
+
+    /// Preview a drum track by key: z=Kick x=Snare c=C-Hat v=O-Hat b=Clap
+    /// n=L.Tom m=M.Tom ,=H.Tom  — all fully polyphonic.
+    ///
+    /// Without the enhanced keyboard protocol a held key arrives as repeated
+    /// presses; with `roll_velocity` a repeat of the same key plays louder the
+    /// faster it comes (`roll_velocity_for`).  A first press, and every press
+    /// in enhanced mode, plays at full velocity.
+    pub fn drum_preview(&mut self, key: char, enhanced: bool) {
+        let idx: usize = match key {
+            'z' => 0, 'x' => 1, 'c' => 2, 'v' => 3,
+            'b' => 4, 'n' => 5, 'm' => 6, ',' => 7,
+            _ => return,
+        };
+        let now = Instant::now();
+        let velocity = match self.drum_last_hit {
+            Some((k, at)) if k == key && !enhanced && self.roll_velocity => roll_velocity_for(now - at),
+            _ => 1.0,
+        };
+        self.drum_last_hit = Some((key, now));
+        let recorded = {
+            let mut s = self.synth.lock().unwrap();
+            let s = &mut *s;
+            s.drum_machine.trigger_now(idx, s.bpm, velocity, &mut s.rng);
+            if self.drum_rec && s.drum_machine.playing {
+                let step = s.drum_machine.nearest_step(s.master_clock, samples_per_step(s.sample_rate, s.bpm));
+                let before = (!self.drum_rec_undo).then(|| DrumPattern::capture(&s.drum_machine));
+                s.drum_machine.record_hit(idx, step).then_some((step, before))
+            } else {
+                None
+            }
+        };
+        if let Some((step, before)) = recorded {
+            if let Some(before) = before {
+                self.push_undo(Undo::Drums(self.drum_bank, before));
+                self.drum_rec_undo = true;
+            }
+            let name = DrumKind::ALL.get(idx).map_or("?", |k| k.name().trim());
+            self.status_msg = format!("● REC {} → step {}", name, step + 1);
+        }
+    }
+
+    /// Arm or disarm drum recording (`w` in the Drums panel, `drumrec`).
+    pub fn drum_rec_toggle(&mut self, on: Option<bool>) {
+        self.drum_rec = on.unwrap_or(!self.drum_rec);
+        self.drum_rec_undo = false;
+        self.status_msg = if self.drum_rec {
+            "Drum record ON — preview keys (z x c v b n m ,) write hits to the nearest step while playing".to_string()
+        } else {
+            "Drum record OFF".to_string()
+        };
+    }
+
+    // ── MIDI input / learn ────────────────────────────────────────────────
+
+    /// `midiin <device>`: start reading controllers from a raw MIDI device.
+    fn midi_open(&mut self, path: &str) {
+        match MidiInput::open(path) {
+            Ok(input) => {
+                self.status_msg = format!("MIDI input: {}", input.path);
+                self.midi_in = Some(input);
+            }
+            Err(e) => self.status_msg = format!("MIDI input: {:#}", e),
+        }
+    }
+
+    /// `learn <param>`: bind the next controller moved to `param`.
+    fn learn(&mut self, param: Param) {
+        self.cc_learn = Some(param);
+        self.status_msg = format!("Learn {}: move a controller{}", param.label(),
+            if self.midi_in.is_none() { "  (no MIDI input — open one with `midiin <device>`)" } else { "" });
+    }
+
+    /// `unlearn <param>` / `unlearn all` (`None`).
+    fn unlearn(&mut self, param: Option<Param>) {
+        let before = self.cc_map.len();
+        self.cc_map.retain(|b| param.is_some_and(|p| b.param != p));
+        self.status_msg = match param {
+            Some(p) if self.cc_map.len() == before => format!("{}: not mapped", p.label()),
+            Some(p) => format!("{}: mapping removed", p.label()),
+            None    => format!("MIDI mappings cleared ({})", before),
+        };
+    }
+
+    /// Apply the control changes received since the last frame.  While a
+    /// learn is pending the first one binds to it (replacing any binding of
+    /// that parameter or that controller).  Returns true if anything arrived.
+    pub fn poll_midi(&mut self) -> bool {
+        let Some(input) = &self.midi_in else { return false };
+        let (ccs, ended) = input.poll();
+        if ended {
+            self.status_msg = format!("MIDI input closed: {}", input.path);
+            self.midi_in = None;
+        }
+        let Some(first) = ccs.first().copied() else { return ended };
+
+        let learned = self.cc_learn.take().map(|param| {
+            self.cc_map.retain(|b| b.param != param && (b.channel, b.cc) != (first.channel, first.cc));
+            self.cc_map.push(CcBinding { channel: first.channel, cc: first.cc, param, last: None });
+            self.status_msg = format!("Learned CC {} (ch {}) → {}", first.cc, first.channel + 1, param.label());
+        });
+
+        let mut s = self.synth.lock().unwrap();
+        let mut moved = None;
+        for cc in ccs {
+            for b in self.cc_map.iter_mut().filter(|b| (b.channel, b.cc) == (cc.channel, cc.cc)) {
+                b.param.set(&mut s, cc.value as f32 / 127.0);
+                b.last = Some(cc.value);
+                moved = Some(b.param);
+            }
+        }
+        if let (Some(p), None) = (moved, learned) {
+            self.status_msg = p.readout(&s);
+        }
+        true
+    }
+
+    // ── Mix automation ────────────────────────────────────────────────────
+
+    /// Advance the automation lanes to the master clock (once per frame).
+    pub fn tick_automation(&mut self) -> bool {
+        if self.automation.state == AutoState::Stopped { return false; }
+        let was = self.automation.state;
+        let changed = self.automation.tick(&mut self.synth.lock().unwrap());
+        if matches!(was, AutoState::Recording { .. }) && self.automation.state == AutoState::Playing {
+            self.status_msg = format!("Automation: recorded {} lane{} — playing",
+                self.automation.lanes().len(), if self.automation.lanes().len() == 1 { "" } else { "s" });
+        }
+        changed
+    }
+
+    /// `auto arm` / `auto rec`: record one pass of mix moves (from the next
+    /// loop start, or now).  A new recording is one drum pattern long.
+    fn automation_record(&mut self, now: bool) {
+        let s = self.synth.lock().unwrap();
+        self.automation.record(&s, s.drum_machine.num_steps, now);
+        self.auto_panel = true;
+        self.status_msg = format!("Automation: {} ({} steps) — move faders, knobs, mutes",
+            if now { "recording" } else { "armed, recording from the next loop" }, self.automation.steps);
+    }
+
+    fn automation_play(&mut self, play: bool) {
+        if !play {
+            self.automation.state = AutoState::Stopped;
+            self.status_msg = "Automation: stopped".to_string();
+        } else if self.automation.events.is_empty() {
+            self.status_msg = "Automation: nothing recorded  (auto arm / auto rec)".to_string();
+        } else {
+            self.automation.play_from_here(&self.synth.lock().unwrap());
+            self.status_msg = "Automation: playing".to_string();
+        }
+    }
+
+    // ── A/B snapshots ─────────────────────────────────────────────────────
+
+    fn capture_mix(&self) -> MixSnapshot {
+        let s = self.synth.lock().unwrap();
+        MixSnapshot {
+            volume:        s.volume,
+            volume2:       s.volume2,
+            track_volumes: s.drum_machine.tracks.iter().map(|t| t.volume).collect(),
+            track_muted:   s.drum_machine.tracks.iter().map(|t| t.muted).collect(),
+            fx:            FxState::capture(&s),
+            chains:        s.chains().iter()
+                .map(|c| RackFile::capture(c.rack.as_deref().unwrap_or(""), c))
+                .collect(),
+        }
+    }
+
+    fn apply_mix(&mut self, snap: &MixSnapshot) {
+        // Only rebuild chains that actually differ, so switching keeps effect
+        // tails running on unchanged chains.  Building happens outside the lock.
+        let (sr, changed): (f32, Vec<bool>) = {
+            let s = self.synth.lock().unwrap();
+            (s.sample_rate, s.chains().iter().zip(&snap.chains)
+                .map(|(c, r)| RackFile::capture("", c).effects != r.effects)
+                .collect())
         };
-    }
+        let rebuilt: Vec<Option<Vec<Box<dyn AudioEffect>>>> = snap.chains.iter().zip(changed)
+            .map(|(r, c)| if c { Some(r.build(sr)) } else { None })
+            .collect();
 
-    pub fn drum_swing_up(&mut self) {
         let mut s = self.synth.lock().unwrap();
-        s.drum_machine.swing = (s.drum_machine.swing + 0.05).min(0.50);
-        self.status_msg = format!("Swing: {:.0}%", s.drum_machine.swing * 100.0);
+        s.volume  = snap.volume;
+        s.volume2 = snap.volume2;
+        for (t, (&v, &m)) in s.drum_machine.tracks.iter_mut()
+            .zip(snap.track_volumes.iter().zip(&snap.track_muted))
+        {
+            t.volume = v;
+            t.muted  = m;
+        }
+        snap.fx.apply(&mut s);
+        for ((chain, r), fx) in s.chains_mut().into_iter().zip(&snap.chains).zip(rebuilt) {
+            if let Some(effects) = fx { chain.set_effects(effects); }
+            chain.rack = if r.name.is_empty() { None } else { Some(r.name.clone()) };
+        }
     }
 
-    pub fn drum_swing_down(&mut self) {
-        let mut s = self.synth.lock().unwrap();
-        s.drum_machine.swing = (s.drum_machine.swing - 0.05).max(0.0);
-        self.status_msg = format!("Swing: {:.0}%", s.drum_machine.swing * 100.0);
+    /// Store the current mix into slot A (0) or B (1) and make it the live slot.
+    pub fn ab_store(&mut self, slot: usize) {
+        self.ab_slots[slot] = Some(self.capture_mix());
+        self.ab_active = Some(slot);
+        self.status_msg = format!("Snapshot {} stored", ab_name(slot));
     }
 
-    pub fn drum_euclidean(&mut self) {
-        let track = self.drum_track;
-        let (k, kind, n) = {
-            let s = self.synth.lock().unwrap();
-            let dm = &s.drum_machine;
-            let k = dm.tracks[track].steps.iter().filter(|&&p| p > 0).count();
-            let k = if k == 0 { 4 } else { k };
-            (k, dm.tracks[track].kind, dm.num_steps)
+    /// Flip between A and B.  Edits made since the last switch are kept in the
+    /// slot being left, so each side can be tweaked independently.
+    pub fn ab_toggle(&mut self) {
+        let Some(from) = self.ab_active else {
+            self.status_msg = "A/B: store a snapshot first (Ctrl+A / Ctrl+B)".to_string();
+            return;
         };
-        self.synth.lock().unwrap().drum_machine.euclidean_fill(track, k);
-        self.status_msg = format!("{}: E({},{})", kind.name(), k, n);
+        let to = 1 - from;
+        let Some(target) = self.ab_slots[to].clone() else {
+            self.status_msg = format!("A/B: snapshot {} is empty (Ctrl+{})", ab_name(to), ab_name(to));
+            return;
+        };
+        self.ab_slots[from] = Some(self.capture_mix());
+        self.apply_mix(&target);
+        self.ab_active = Some(to);
+        self.status_msg = format!("A/B: {}", ab_name(to));
     }
 
-    /// Preview a drum track by key: z=Kick x=Snare c=C-Hat v=O-Hat b=Clap
-    /// n=L.Tom m=M.Tom ,=H.Tom  — all fully polyphonic.
-    pub fn drum_preview(&mut self, key: char) {
-        let idx: usize = match key {
-            'z' => 0, 'x' => 1, 'c' => 2, 'v' => 3,
-            'b' => 4, 'n' => 5, 'm' => 6, ',' => 7,
-            _ => return,
-        };
-        self.synth.lock().unwrap().drum_machine.trigger_now(idx);
+    pub fn ab_stored(&self, slot: usize) -> bool {
+        self.ab_slots[slot].is_some()
     }
 
     // ── Effects controls ──────────────────────────────────────────────────
 
     pub fn effects_sel_up(&mut self) {
-        self.effects_sel = if self.effects_sel == 0 { 5 } else { self.effects_sel - 1 };
+        self.effects_sel = if self.effects_sel == 0 { EFFECT_ROWS - 1 } else { self.effects_sel - 1 };
     }
 
     pub fn effects_sel_down(&mut self) {
-        self.effects_sel = (self.effects_sel + 1) % 6;
+        self.effects_sel = (self.effects_sel + 1) % EFFECT_ROWS;
     }
 
     /// Left/right cycles through params 0–5 (0-2=effect params, 3-5=send levels).
@@ -680,9 +3036,153 @@ impl App {
         self.effects_param = (self.effects_param + 1) % 6;
     }
 
-    /// Enter in Effects: always toggle on/off for the selected effect.
+    /// The insert chain edited by the selected Effects row, if it is a chain row.
+    fn effects_chain<'a>(&self, s: &'a mut Synth) -> Option<(&'a mut EffectChain, &'static str)> {
+        match self.effects_sel {
+            MASTER_FX_ROW => Some((&mut s.master_fx, "Master")),
+            DRUM_FX_ROW   => s.drum_machine.tracks.get_mut(self.fx_drum_track)
+                .map(|t| (&mut t.fx, t.kind.name().trim_end())),
+            SYNTH_FX_ROW if self.fx_synth_bus == 0 => Some((&mut s.fx,  "S1")),
+            SYNTH_FX_ROW  => Some((&mut s.fx2, "S2")),
+            _ => None,
+        }
+    }
+
+    /// `=` / `-` on a chain row: col 0 = insert type, col 1 = target, col 2 = rack.
+    fn chain_row_adjust(&mut self, forward: bool) {
+        match self.effects_param {
+            0 => {
+                self.insert_pick = if forward { self.insert_pick.next() } else { self.insert_pick.prev() };
+                self.status_msg = format!("Insert: {}", self.insert_pick.name());
+            }
+            1 if self.effects_sel == DRUM_FX_ROW => self.cycle_fx_drum_track(forward),
+            1 if self.effects_sel == SYNTH_FX_ROW => {
+                self.fx_synth_bus = 1 - self.fx_synth_bus;
+                self.status_msg = format!("Synth FX bus: S{}", self.fx_synth_bus + 1);
+            }
+            2 => {
+                // Rescan so racks saved from another session show up.
+                self.racks = rack::available();
+                let n = self.racks.len();
+                self.rack_pick = if forward { (self.rack_pick + 1) % n }
+                                 else       { (self.rack_pick + n - 1) % n };
+                let r = &self.racks[self.rack_pick];
+                self.status_msg = format!("Rack: {}{}  [Enter] to recall",
+                    r.name, if r.factory { " (factory)" } else { "" });
+            }
+            _ => {}
+        }
+    }
+
+    /// Enter on a chain row's Rack column: replace the chain with the selected rack.
+    fn effects_recall_rack(&mut self) {
+        let Some(rack) = self.racks.get(self.rack_pick).cloned() else { return };
+        let sr = self.synth.lock().unwrap().sample_rate;
+        let effects = rack.build(sr);
+        let msg = {
+            let mut s = self.synth.lock().unwrap();
+            match self.effects_chain(&mut s) {
+                Some((chain, label)) => {
+                    // Old effects are dropped here; the chain is small so this is brief.
+                    chain.set_effects(effects);
+                    chain.rack = Some(rack.name.clone());
+                    format!("{} FX: recalled rack \"{}\" ({} inserts)", label, rack.name, chain.len())
+                }
+                None => return,
+            }
+        };
+        self.status_msg = msg;
+    }
+
+    /// `w` on a chain row: prompt for a rack name to save the chain under.
+    pub fn effects_rack_save_prompt(&mut self) {
+        if !is_chain_row(self.effects_sel) { return; }
+        let current = {
+            let mut s = self.synth.lock().unwrap();
+            self.effects_chain(&mut s).and_then(|(c, _)| c.rack.clone())
+        };
+        self.input_mode = InputMode::RackSave;
+        self.input_buf  = current.unwrap_or_else(|| "my-rack".to_string());
+    }
+
+    fn save_rack(&mut self, name: &str) {
+        let file = {
+            let mut s = self.synth.lock().unwrap();
+            match self.effects_chain(&mut s) {
+                Some((chain, _)) => {
+                    chain.rack = Some(name.to_string());
+                    RackFile::capture(name, chain)
+                }
+                None => return,
+            }
+        };
+        self.status_msg = match rack::save(&file) {
+            Ok(path) => format!("Rack saved → {}", path.display()),
+            Err(e)   => format!("Rack save error: {:#}", e),
+        };
+        self.racks = rack::available();
+        if let Some(i) = self.racks.iter().position(|r| !r.factory && r.name == name) {
+            self.rack_pick = i;
+        }
+    }
+
+    /// Enter on a chain row: append an instance of `insert_pick`.
+    fn effects_add_insert(&mut self) {
+        let kind = self.insert_pick;
+        let sr = self.synth.lock().unwrap().sample_rate;
+        // Build outside the lock: reverb/delay allocate their buffers.
+        let fx = kind.build(sr);
+        let msg = {
+            let mut s = self.synth.lock().unwrap();
+            match self.effects_chain(&mut s) {
+                Some((chain, label)) if chain.len() >= MAX_INSERTS =>
+                    format!("{} FX: chain full ({} inserts)", label, MAX_INSERTS),
+                Some((chain, label)) => {
+                    chain.push(fx);
+                    format!("{} FX: added {}", label, kind.name())
+                }
+                None => return,
+            }
+        };
+        self.status_msg = msg;
+    }
+
+    /// Select the drum track whose chain the DRM-FX row edits.
+    fn cycle_fx_drum_track(&mut self, forward: bool) {
+        let (name, chain) = {
+            let s = self.synth.lock().unwrap();
+            let n = s.drum_machine.tracks.len();
+            self.fx_drum_track = if forward { (self.fx_drum_track + 1) % n }
+                                 else       { (self.fx_drum_track + n - 1) % n };
+            let t = &s.drum_machine.tracks[self.fx_drum_track];
+            (t.kind.name().trim_end(), t.fx.len())
+        };
+        self.status_msg = format!("Drum FX track: {} ({} inserts)", name, chain);
+    }
+
+    /// Backspace/Delete on a chain row: remove the last insert.
+    pub fn effects_remove_insert(&mut self) {
+        let msg = {
+            let mut s = self.synth.lock().unwrap();
+            match self.effects_chain(&mut s) {
+                Some((chain, label)) => match chain.pop() {
+                    Some(fx) => format!("{} FX: removed {}", label, fx.name()),
+                    None     => format!("{} FX: chain is empty", label),
+                },
+                None => return,
+            }
+        };
+        self.status_msg = msg;
+    }
+
+    /// Enter in Effects: toggle on/off for the selected effect.  On chain rows it
+    /// recalls the selected rack (Rack column) or appends `insert_pick`.
     pub fn effects_on_off(&mut self) {
         let sel = self.effects_sel;
+        if is_chain_row(sel) {
+            if self.effects_param == 2 { self.effects_recall_rack(); } else { self.effects_add_insert(); }
+            return;
+        }
         let msg = {
             let mut s = self.synth.lock().unwrap();
             match sel {
@@ -700,6 +3200,15 @@ impl App {
                 5 => { s.filter2.enabled = !s.filter2.enabled;
                        if s.filter2.enabled { s.filter2.reset_state(); }
                        format!("S2 Filter: {}", if s.filter2.enabled { "ON" } else { "OFF" }) }
+                6 => match self.effects_param {
+                    0 => { s.widener.width = 1.0;
+                           "Stereo Width: reset to 100%".to_string() }
+                    1 => { s.clipper.mode = ClipMode::Tanh;
+                           "Master Clip: reset to Tanh".to_string() }
+                    2 => { s.clipper.drive = 1.0;
+                           "Master Drive: reset to 1.0x".to_string() }
+                    _ => String::new()
+                },
                 _ => String::new()
             }
         };
@@ -738,6 +3247,11 @@ impl App {
     pub fn effects_param_inc(&mut self) {
         let (sel, param) = (self.effects_sel, self.effects_param);
 
+        if is_chain_row(sel) {
+            self.chain_row_adjust(true);
+            return;
+        }
+
         if param >= 3 {
             if sel >= 4 { return; } // Filter rows have no routing sends
             let ri = param - 3;
@@ -809,6 +3323,15 @@ impl App {
                         _ => { s.filter2.q = (s.filter2.q + 0.1).clamp(0.5, 10.0);
                                format!("S2 Q: {:.1}", s.filter2.q) }
                     },
+                    6 => match param {
+                        0 => { s.widener.width = (s.widener.width + 0.05).clamp(0.0, WIDENER_MAX_WIDTH);
+                               format!("Stereo Width: {:.0}%", s.widener.width * 100.0) }
+                        1 => { s.clipper.mode = s.clipper.mode.next();
+                               clip_status(s.clipper.mode) }
+                        2 => { s.clipper.drive = (s.clipper.drive + 0.1).clamp(CLIP_DRIVE_MIN, CLIP_DRIVE_MAX);
+                               format!("Master Drive: {:.1}x", s.clipper.drive) }
+                        _ => String::new()
+                    },
                     _ => String::new(),
                 }
             };
@@ -819,6 +3342,11 @@ impl App {
     pub fn effects_param_dec(&mut self) {
         let (sel, param) = (self.effects_sel, self.effects_param);
 
+        if is_chain_row(sel) {
+            self.chain_row_adjust(false);
+            return;
+        }
+
         if param >= 3 {
             if sel >= 4 { return; } // Filter rows have no routing sends
             let ri = param - 3;
@@ -890,6 +3418,15 @@ impl App {
                         _ => { s.filter2.q = (s.filter2.q - 0.1).clamp(0.5, 10.0);
                                format!("S2 Q: {:.1}", s.filter2.q) }
                     },
+                    6 => match param {
+                        0 => { s.widener.width = (s.widener.width - 0.05).clamp(0.0, WIDENER_MAX_WIDTH);
+                               format!("Stereo Width: {:.0}%", s.widener.width * 100.0) }
+                        1 => { s.clipper.mode = s.clipper.mode.prev();
+                               clip_status(s.clipper.mode) }
+                        2 => { s.clipper.drive = (s.clipper.drive - 0.1).clamp(CLIP_DRIVE_MIN, CLIP_DRIVE_MAX);
+                               format!("Master Drive: {:.1}x", s.clipper.drive) }
+                        _ => String::new()
+                    },
                     _ => String::new(),
                 }
             };
@@ -909,6 +3446,20 @@ impl App {
         if s.filter2.enabled    { ind.push_str("  ▶F2"); }
         if s.chord1 != ChordType::Off { ind.push_str("  ▶C1"); }
         if s.chord2 != ChordType::Off { ind.push_str("  ▶C2"); }
+        if s.freeze.active { ind.push_str("  ▶FREEZE"); }
+        if s.drone.on { ind.push_str(&format!("  ▶DRONE {}", note_name(s.drone.note))); }
+        if s.tuner.on { ind.push_str(&format!("  ▶TUNER {}", s.tuner.label())); }
+        if s.step_click.on { ind.push_str("  ▶CLICK"); }
+        if s.mix_meter.auto_gain { ind.push_str("  ▶AUTOGAIN"); }
+        if s.solo.is_active() {
+            let names: Vec<String> = s.solo.targets.iter().map(|t| t.label()).collect();
+            ind.push_str(&format!("  ▶SOLO {}", names.join("+")));
+        }
+        if s.synth_mode == SynthMode::Fm { ind.push_str(&format!("  ▶FM {}", fm_label(&s))); }
+        if let Some(r) = &s.tempo_ramp {
+            let arrow = if r.to >= r.from { "↗" } else { "↘" };
+            ind.push_str(&format!("  ▶RAMP {}{:.0} {:.0}%", arrow, r.to, r.progress() * 100.0));
+        }
         ind
     }
 
@@ -919,9 +3470,6 @@ impl App {
             match w { WaveType::Sine=>0, WaveType::Square=>1,
                       WaveType::Sawtooth=>2, WaveType::Triangle=>3 }
         }
-        fn filter_mode_idx(m: FilterMode) -> u8 {
-            match m { FilterMode::LowPass=>0, FilterMode::HighPass=>1, FilterMode::BandPass=>2 }
-        }
 
         // Copy App-level fields before taking the synth lock.
         let base_octave = self.base_octave;
@@ -931,113 +3479,83 @@ impl App {
         let scale_root = self.scale_q.root;
 
         // Step 1: Flush live state into current bank slots + read track metadata.
-        let (track_kinds, track_muted, track_volumes) = {
+        let (track_kinds, track_muted, track_volumes, track_pans, track_tunes, track_samples) = {
             let s = self.synth.lock().unwrap();
-            self.seq1_banks[self.seq1_bank] = SeqPattern {
-                steps:     s.sequencer.steps.clone(),
-                num_steps: s.sequencer.num_steps,
-            };
-            self.seq2_banks[self.seq2_bank] = SeqPattern {
-                steps:     s.sequencer2.steps.clone(),
-                num_steps: s.sequencer2.num_steps,
-            };
-            self.drum_banks[self.drum_bank] = DrumPattern {
-                num_steps:   s.drum_machine.num_steps,
-                swing:       s.drum_machine.swing,
-                track_steps: s.drum_machine.tracks.iter().map(|t| t.steps.clone()).collect(),
-            };
+            self.seq1_banks[self.seq1_bank] = SeqPattern::capture(&s.lanes[0]);
+            self.seq2_banks[self.seq2_bank] = SeqPattern::capture(&s.sequencer2);
+            self.drum_banks[self.drum_bank] = DrumPattern::capture(&s.drum_machine);
             let kinds: Vec<u8> = s.drum_machine.tracks.iter()
                 .map(|t| DrumKind::ALL.iter().position(|&k| k == t.kind).unwrap_or(0) as u8)
                 .collect();
             let muted:   Vec<bool> = s.drum_machine.tracks.iter().map(|t| t.muted).collect();
             let volumes: Vec<f32>  = s.drum_machine.tracks.iter().map(|t| t.volume).collect();
-            (kinds, muted, volumes)
+            let pans:    Vec<f32>  = s.drum_machine.tracks.iter().map(|t| t.pan).collect();
+            let tunes:   Vec<i8>   = s.drum_machine.tracks.iter().map(|t| t.tune).collect();
+            let samples: Vec<(Option<String>, bool, Option<u16>)> = s.drum_machine.tracks.iter()
+                .map(|t| (t.sample.as_ref().map(|smp| smp.path.clone()), t.reverse, t.fit_steps))
+                .collect();
+            (kinds, muted, volumes, pans, tunes, samples)
         };
 
         // Step 2: Serialize bank arrays (no lock needed — data is now in self.*_banks).
-        let seq1_banks_save: Vec<SeqSave> = self.seq1_banks.iter().map(|p| SeqSave {
-            num_steps: p.num_steps,
-            steps:     p.steps.clone(),
-        }).collect();
-        let seq2_banks_save: Vec<SeqSave> = self.seq2_banks.iter().map(|p| SeqSave {
-            num_steps: p.num_steps,
-            steps:     p.steps.clone(),
-        }).collect();
+        let seq1_banks_save: Vec<SeqSave> = self.seq1_banks.iter().map(SeqPattern::to_save).collect();
+        let seq2_banks_save: Vec<SeqSave> = self.seq2_banks.iter().map(SeqPattern::to_save).collect();
         let drum_banks_save: Vec<DrumsSave> = self.drum_banks.iter().map(|p| {
             let tracks = p.track_steps.iter().enumerate().map(|(i, steps)| TrackSave {
                 kind:   track_kinds.get(i).copied().unwrap_or(0),
                 steps:  steps.clone(),
                 muted:  track_muted.get(i).copied().unwrap_or(false),
                 volume: track_volumes.get(i).copied().unwrap_or(0.85),
+                pan:    track_pans.get(i).copied().unwrap_or(0.0),
+                tune:   track_tunes.get(i).copied().unwrap_or(0),
+                nudge:  p.track_nudge.get(i).cloned().unwrap_or_default(),
+                decay:  p.track_decay.get(i).cloned().unwrap_or_default(),
+                sample:  track_samples.get(i).and_then(|(p, ..)| p.clone()),
+                reverse: track_samples.get(i).is_some_and(|&(_, r, _)| r),
+                fit_steps: track_samples.get(i).and_then(|&(.., f)| f),
             }).collect();
-            DrumsSave { num_steps: p.num_steps, swing: p.swing, tracks }
+            DrumsSave {
+                num_steps:  p.num_steps,
+                swing:      p.swing,
+                loop_start: p.loop_pts.0,
+                loop_end:   Some(p.loop_pts.1),
+                direction:  p.direction.index(),
+                tracks,
+            }
         }).collect();
 
         let sf = {
             let s = self.synth.lock().unwrap();
 
-            let seq1 = SeqSave {
-                num_steps: s.sequencer.num_steps,
-                steps: s.sequencer.steps.clone(),
-            };
-            let seq2 = SeqSave {
-                num_steps: s.sequencer2.num_steps,
-                steps: s.sequencer2.steps.clone(),
-            };
+            let seq1 = SeqPattern::capture(&s.lanes[0]).to_save();
+            let seq1_lanes = s.lanes[1..].iter().map(|l| SeqPattern::capture(l).to_save()).collect();
+            let seq1_lane_patches = s.lane_patches.iter().map(|p| p.map(|p| PatchSave {
+                wave: wave_idx(p.wave), attack: p.attack, decay: p.decay, sustain: p.sustain, release: p.release,
+            })).collect();
+            let seq2 = SeqPattern::capture(&s.sequencer2).to_save();
 
             let drums = DrumsSave {
-                num_steps: s.drum_machine.num_steps,
-                swing:     s.drum_machine.swing,
+                num_steps:  s.drum_machine.num_steps,
+                swing:      s.drum_machine.swing,
+                loop_start: s.drum_machine.loop_start,
+                loop_end:   Some(s.drum_machine.loop_end),
+                direction:  s.drum_machine.direction.index(),
                 tracks: s.drum_machine.tracks.iter().map(|t| TrackSave {
                     kind:   DrumKind::ALL.iter().position(|&k| k == t.kind).unwrap_or(0) as u8,
                     steps:  t.steps.clone(),
                     muted:  t.muted,
                     volume: t.volume,
+                    pan:    t.pan,
+                    tune:   t.tune,
+                    nudge:  t.nudge.clone(),
+                    decay:  t.decay.clone(),
+                    sample:  t.sample.as_ref().map(|smp| smp.path.clone()),
+                    reverse: t.reverse,
+                    fit_steps: t.fit_steps,
                 }).collect(),
             };
 
-            let reverb = ReverbSave {
-                enabled:   s.reverb.enabled,
-                room_size: s.reverb.room_size,
-                damping:   s.reverb.damping,
-                mix:       s.reverb.mix,
-            };
-            let delay = DelaySave {
-                enabled:  s.delay.enabled,
-                time_ms:  s.delay.time_ms,
-                feedback: s.delay.feedback,
-                mix:      s.delay.mix,
-            };
-            let distortion = DistSave {
-                enabled: s.distortion.enabled,
-                drive:   s.distortion.drive,
-                tone:    s.distortion.tone,
-                level:   s.distortion.level,
-            };
-            let sidechain = SidechainSave {
-                enabled:    s.sidechain.enabled,
-                depth:      s.sidechain.depth,
-                release_ms: s.sidechain.release_ms,
-                duck_s1:    s.sidechain.duck_s1,
-                duck_s2:    s.sidechain.duck_s2,
-            };
-            let filter1 = FilterSave {
-                enabled: s.filter1.enabled,
-                mode:    filter_mode_idx(s.filter1.mode),
-                cutoff:  s.filter1.cutoff,
-                q:       s.filter1.q,
-            };
-            let filter2 = FilterSave {
-                enabled: s.filter2.enabled,
-                mode:    filter_mode_idx(s.filter2.mode),
-                cutoff:  s.filter2.cutoff,
-                q:       s.filter2.q,
-            };
-            let routing = RoutingSave {
-                s1_reverb: s.fx_routing.s1_reverb, s1_delay: s.fx_routing.s1_delay, s1_dist: s.fx_routing.s1_dist,
-                s2_reverb: s.fx_routing.s2_reverb, s2_delay: s.fx_routing.s2_delay, s2_dist: s.fx_routing.s2_dist,
-                dr_reverb: s.fx_routing.dr_reverb, dr_delay: s.fx_routing.dr_delay, dr_dist: s.fx_routing.dr_dist,
-            };
+            let fx = FxState::capture(&s);
 
             let chord1_idx = ChordType::ALL.iter()
                 .position(|&c| c == s.chord1).unwrap_or(0) as u8;
@@ -1046,30 +3564,58 @@ impl App {
 
             SaveFile {
                 bpm:        s.bpm,
+                seq_swing:  s.seq_swing,
                 base_octave,
+                key_layout: self.key_layout.index(),
+                note_fold:  self.note_fold,
+                note_priority: self.note_priority.index(),
+                time_sig:   [s.time_sig.0, s.time_sig.1],
                 scale:      scale_idx,
                 scale_root,
                 wave1:      wave_idx(s.wave_type),
                 wave2:      wave_idx(s.wave_type2),
                 volume:     s.volume,
                 volume2:    s.volume2,
-                seq1, seq2, drums,
-                reverb, delay, distortion, sidechain,
-                filter1, filter2, routing,
+                seq1, seq2, seq1_lanes, seq1_lane_patches, drums,
+                reverb:     fx.reverb,
+                delay:      fx.delay,
+                distortion: fx.distortion,
+                sidechain:  fx.sidechain,
+                filter1:    fx.filter1,
+                filter2:    fx.filter2,
+                routing:    fx.routing,
+                output:     fx.output,
                 chord1: chord1_idx,
                 chord2: chord2_idx,
+                seed:   s.rng.seed(),
+                retrigger: s.retrigger.index(),
+                attack_curve:  s.attack_curve.index(),
+                release_curve: s.release_curve.index(),
+                synth_mode: s.synth_mode.index(),
+                fm_ratio:   s.fm_ratio,
+                fm_index:   s.fm_index,
+                max_voices: s.max_voices,
+                wave_trims: s.wave_trims,
+                oversample: s.oversample(),
+                groove:     s.drum_machine.groove.clone(),
                 seq1_bank: self.seq1_bank,
                 seq2_bank: self.seq2_bank,
                 drum_bank: self.drum_bank,
                 seq1_banks: seq1_banks_save,
                 seq2_banks: seq2_banks_save,
                 drum_banks: drum_banks_save,
+                cc_map:     self.cc_map.iter()
+                    .map(|b| CcMapSave { channel: b.channel, cc: b.cc, param: b.param.id().to_string() })
+                    .collect(),
             }
         };
 
         match serde_json::to_string_pretty(&sf) {
             Ok(json) => match std::fs::write(path, &json) {
-                Ok(_)  => self.status_msg = format!("Saved → {}", path),
+                Ok(_)  => {
+                    self.status_msg = format!("Saved → {}", path);
+                    self.remember_project(path);
+                }
                 Err(e) => self.status_msg = format!("Save error: {}", e),
             },
             Err(e) => self.status_msg = format!("Serialize error: {}", e),
@@ -1077,21 +3623,81 @@ impl App {
     }
 
     pub fn load(&mut self, path: &str) {
-        let json = match std::fs::read_to_string(path) {
-            Ok(j)  => j,
-            Err(e) => { self.status_msg = format!("Load error: {}", e); return; }
+        match self.load_project(path) {
+            Ok(w)  => self.loaded(path, &w),
+            Err(e) => self.status_msg = format!("Load error: {}", e),
+        }
+    }
+
+    /// Startup: load `path` (`--project`), else the project remembered from
+    /// the last session, else `DEFAULT_PROJECT` — whichever exists.  With no
+    /// file the session starts from the defaults; a file that fails to load
+    /// is left alone and the session starts fresh with a warning.
+    pub fn load_startup(&mut self, path: Option<&str>) {
+        let remembered = std::fs::read_to_string(LAST_PROJECT_FILE).ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        let explicit = path.is_some();
+        let path = path.map(str::to_string).or(remembered).unwrap_or_else(|| DEFAULT_PROJECT.to_string());
+        if !std::path::Path::new(&path).exists() {
+            if explicit { self.status_msg = format!("{} not found — starting fresh", path); }
+            return;
+        }
+        match self.load_project(&path) {
+            Ok(w)  => self.loaded(&path, &w),
+            Err(e) => self.status_msg = format!("⚠ Could not load {}: {} — starting fresh", path, e),
+        }
+    }
+
+    fn loaded(&mut self, path: &str, warnings: &[String]) {
+        self.status_msg = if warnings.is_empty() {
+            format!("Loaded ← {}", path)
+        } else {
+            format!("Loaded ← {}  ({})", path, warnings.join("; "))
         };
-        let sf: SaveFile = match serde_json::from_str(&json) {
-            Ok(s)  => s,
-            Err(e) => { self.status_msg = format!("Load error: {}", e); return; }
+        self.remember_project(path);
+    }
+
+    /// Note `path` as the open project and remember it for the next launch.
+    fn remember_project(&mut self, path: &str) {
+        self.project = Some(path.to_string());
+        let _ = std::fs::write(LAST_PROJECT_FILE, path);
+    }
+
+    /// Read a project file into the synth and the app.  Shared by the Ctrl+L
+    /// prompt and the headless CLI modes; leaves `status_msg` alone.
+    /// Returns warnings for parts that could not be restored (e.g. a drum
+    /// sample file that is gone); the rest of the project still loads.
+    pub fn load_project(&mut self, path: &str) -> anyhow::Result<Vec<String>> {
+        let json = std::fs::read_to_string(path)?;
+        let sf: SaveFile = serde_json::from_str(&json)?;
+
+        // Drums — use active bank if available, else use drums field.  Its
+        // samples are decoded here, before the synth lock is taken.
+        let drums_src = if !sf.drum_banks.is_empty() {
+            let active = sf.drum_bank.min(sf.drum_banks.len() - 1);
+            &sf.drum_banks[active]
+        } else {
+            &sf.drums
         };
+        let mut warnings = Vec::new();
+        let samples: Vec<_> = drums_src.tracks.iter().map(|t| {
+            let p = t.sample.as_deref()?;
+            sample::load_wav(std::path::Path::new(p))
+                .map_err(|e| warnings.push(format!("{:#}", e)))
+                .ok()
+        }).collect();
 
         self.release_all();
+        self.undo.clear();
 
         {
             let mut s = self.synth.lock().unwrap();
 
             s.bpm = sf.bpm.clamp(30.0, 300.0);
+            s.seq_swing = sf.seq_swing.clamp(0.0, MAX_SWING);
+            s.tempo_ramp = None;
+            s.solo.clear();
 
             s.wave_type = match sf.wave1 {
                 1 => WaveType::Square, 2 => WaveType::Sawtooth,
@@ -1110,105 +3716,87 @@ impl App {
             s.chord2 = ChordType::ALL.get(sf.chord2 as usize).copied().unwrap_or(ChordType::Off);
 
             // Sequencer 1 — use active bank if available, else use seq1 field
-            let (n1, seq1_steps) = if !sf.seq1_banks.is_empty() {
-                let active = sf.seq1_bank.min(sf.seq1_banks.len() - 1);
-                let sb = &sf.seq1_banks[active];
-                (sb.num_steps.clamp(1, 32), sb.steps.clone())
+            let seq1_src = if !sf.seq1_banks.is_empty() {
+                &sf.seq1_banks[sf.seq1_bank.min(sf.seq1_banks.len() - 1)]
             } else {
-                (sf.seq1.num_steps.clamp(1, 32), sf.seq1.steps.clone())
+                &sf.seq1
             };
-            s.sequencer.num_steps = n1;
-            s.sequencer.steps = seq1_steps;
-            s.sequencer.steps.resize(n1, None);
+            SeqPattern::from_save(seq1_src).restore(&mut s.lanes[0]);
+            // Extra lanes join the main lane's transport.
+            let playing = s.lanes[0].playing;
+            while s.remove_lane(1) {}
+            for sv in sf.seq1_lanes.iter().take(MAX_LANES - 1) {
+                let mut lane = Sequencer::new(s.sample_rate);
+                SeqPattern::from_save(sv).restore(&mut lane);
+                lane.playing = playing;
+                s.lanes.push(lane);
+            }
+            s.lane_patches = (0..s.lanes.len()).map(|i| sf.seq1_lane_patches.get(i).copied().flatten().map(|p| Patch {
+                wave:    WaveType::ALL.get(p.wave as usize).copied().unwrap_or(WaveType::Sine),
+                attack:  p.attack.clamp(0.0, MAX_ENV_S),
+                decay:   p.decay.clamp(0.0, MAX_ENV_S),
+                sustain: p.sustain.clamp(0.0, 1.0),
+                release: p.release.clamp(0.0, MAX_ENV_S),
+            })).collect();
 
             // Sequencer 2 — use active bank if available, else use seq2 field
-            let (n2, seq2_steps) = if !sf.seq2_banks.is_empty() {
-                let active = sf.seq2_bank.min(sf.seq2_banks.len() - 1);
-                let sb = &sf.seq2_banks[active];
-                (sb.num_steps.clamp(1, 32), sb.steps.clone())
-            } else {
-                (sf.seq2.num_steps.clamp(1, 32), sf.seq2.steps.clone())
-            };
-            s.sequencer2.num_steps = n2;
-            s.sequencer2.steps = seq2_steps;
-            s.sequencer2.steps.resize(n2, None);
-
-            // Drums — use active bank if available, else use drums field
-            let drums_src = if !sf.drum_banks.is_empty() {
-                let active = sf.drum_bank.min(sf.drum_banks.len() - 1);
-                &sf.drum_banks[active]
+            let seq2_src = if !sf.seq2_banks.is_empty() {
+                &sf.seq2_banks[sf.seq2_bank.min(sf.seq2_banks.len() - 1)]
             } else {
-                &sf.drums
+                &sf.seq2
             };
-            let nd = drums_src.num_steps.clamp(1, 32);
-            s.drum_machine.num_steps = nd;
-            s.drum_machine.swing = drums_src.swing.clamp(0.0, 0.5);
-            let n_tracks = s.drum_machine.tracks.len().min(drums_src.tracks.len());
-            for i in 0..n_tracks {
-                let t = &drums_src.tracks[i];
-                s.drum_machine.tracks[i].steps = t.steps.clone();
-                s.drum_machine.tracks[i].steps.resize(nd, 0);
-                s.drum_machine.tracks[i].muted  = t.muted;
-                s.drum_machine.tracks[i].volume = t.volume.clamp(0.0, 1.0);
-            }
-
-            // Reverb
-            s.reverb.enabled   = sf.reverb.enabled;
-            s.reverb.room_size = sf.reverb.room_size.clamp(0.0, 1.0);
-            s.reverb.damping   = sf.reverb.damping.clamp(0.0, 1.0);
-            s.reverb.mix       = sf.reverb.mix.clamp(0.0, 1.0);
-
-            // Delay
-            s.delay.enabled  = sf.delay.enabled;
-            s.delay.time_ms  = sf.delay.time_ms.clamp(10.0, 1000.0);
-            s.delay.feedback = sf.delay.feedback.clamp(0.0, 0.95);
-            s.delay.mix      = sf.delay.mix.clamp(0.0, 1.0);
-
-            // Distortion
-            s.distortion.enabled = sf.distortion.enabled;
-            s.distortion.drive   = sf.distortion.drive.clamp(1.0, 10.0);
-            s.distortion.tone    = sf.distortion.tone.clamp(0.0, 1.0);
-            s.distortion.level   = sf.distortion.level.clamp(0.0, 1.0);
-
-            // Sidechain
-            s.sidechain.enabled    = sf.sidechain.enabled;
-            s.sidechain.depth      = sf.sidechain.depth.clamp(0.0, 1.0);
-            s.sidechain.release_ms = sf.sidechain.release_ms.clamp(10.0, 500.0);
-            s.sidechain.duck_s1    = sf.sidechain.duck_s1;
-            s.sidechain.duck_s2    = sf.sidechain.duck_s2;
-
-            // Filter 1
-            s.filter1.enabled = sf.filter1.enabled;
-            s.filter1.mode    = match sf.filter1.mode {
-                1 => FilterMode::HighPass, 2 => FilterMode::BandPass, _ => FilterMode::LowPass
-            };
-            s.filter1.cutoff = sf.filter1.cutoff.clamp(80.0, 18000.0);
-            s.filter1.q      = sf.filter1.q.clamp(0.5, 10.0);
-            if s.filter1.enabled { s.filter1.reset_state(); }
-
-            // Filter 2
-            s.filter2.enabled = sf.filter2.enabled;
-            s.filter2.mode    = match sf.filter2.mode {
-                1 => FilterMode::HighPass, 2 => FilterMode::BandPass, _ => FilterMode::LowPass
-            };
-            s.filter2.cutoff = sf.filter2.cutoff.clamp(80.0, 18000.0);
-            s.filter2.q      = sf.filter2.q.clamp(0.5, 10.0);
-            if s.filter2.enabled { s.filter2.reset_state(); }
-
-            // Routing
-            s.fx_routing.s1_reverb = sf.routing.s1_reverb.clamp(0.0, 1.0);
-            s.fx_routing.s1_delay  = sf.routing.s1_delay.clamp(0.0, 1.0);
-            s.fx_routing.s1_dist   = sf.routing.s1_dist.clamp(0.0, 1.0);
-            s.fx_routing.s2_reverb = sf.routing.s2_reverb.clamp(0.0, 1.0);
-            s.fx_routing.s2_delay  = sf.routing.s2_delay.clamp(0.0, 1.0);
-            s.fx_routing.s2_dist   = sf.routing.s2_dist.clamp(0.0, 1.0);
-            s.fx_routing.dr_reverb = sf.routing.dr_reverb.clamp(0.0, 1.0);
-            s.fx_routing.dr_delay  = sf.routing.dr_delay.clamp(0.0, 1.0);
-            s.fx_routing.dr_dist   = sf.routing.dr_dist.clamp(0.0, 1.0);
+            SeqPattern::from_save(seq2_src).restore(&mut s.sequencer2);
+
+            DrumPattern::from_save(drums_src).restore(&mut s.drum_machine);
+            for ((dt, t), smp) in s.drum_machine.tracks.iter_mut().zip(&drums_src.tracks).zip(samples) {
+                dt.muted   = t.muted;
+                dt.volume  = t.volume.clamp(0.0, 1.0);
+                dt.pan     = t.pan.clamp(-1.0, 1.0);
+                dt.tune    = t.tune.clamp(-MAX_TUNE, MAX_TUNE);
+                dt.sample  = smp;
+                dt.reverse = t.reverse;
+                dt.fit_steps = t.fit_steps.filter(|n| (1..=MAX_FIT_STEPS).contains(n));
+            }
+
+            FxState {
+                reverb:     sf.reverb,
+                delay:      sf.delay,
+                distortion: sf.distortion,
+                sidechain:  sf.sidechain,
+                filter1:    sf.filter1,
+                filter2:    sf.filter2,
+                routing:    sf.routing,
+                output:     sf.output,
+            }.apply(&mut s);
+
+            s.rng.reseed(sf.seed);
+            s.retrigger = Retrigger::from_index(sf.retrigger);
+            s.attack_curve  = EnvCurve::from_index(sf.attack_curve);
+            s.release_curve = EnvCurve::from_index(sf.release_curve);
+            s.synth_mode = SynthMode::from_index(sf.synth_mode);
+            s.fm_ratio   = sf.fm_ratio.clamp(*FM_RATIO_RANGE.start(), *FM_RATIO_RANGE.end());
+            s.fm_index   = sf.fm_index.clamp(0.0, MAX_FM_INDEX);
+            s.max_voices = sf.max_voices.clamp(*MAX_VOICES_RANGE.start(), *MAX_VOICES_RANGE.end());
+            s.wave_trims = sf.wave_trims.map(|g| g.clamp(0.0, MAX_WAVE_TRIM));
+            s.set_oversample(sf.oversample);
+            s.drum_machine.groove = sf.groove.and_then(Groove::sanitize);
         }
 
         // App-level fields
         self.base_octave   = sf.base_octave.clamp(0, 8);
+        self.cc_map = sf.cc_map.iter()
+            .filter_map(|m| Some(CcBinding {
+                channel: m.channel & 0x0F, cc: m.cc & 0x7F, param: Param::from_id(&m.param)?, last: None,
+            }))
+            .collect();
+        self.release_all();
+        self.key_layout    = KeyLayout::from_index(sf.key_layout);
+        self.note_fold     = sf.note_fold;
+        self.note_priority = NotePriority::from_index(sf.note_priority);
+        let sig = (sf.time_sig[0], sf.time_sig[1]);
+        let sig = if valid_time_sig(sig) { sig } else { COMMON_TIME_SIGS[0] };
+        self.synth.lock().unwrap().time_sig = sig;
+        self.grid_beat = time_sig_beat(sig);
         self.scale_q.scale = Scale::ALL.get(sf.scale as usize).copied().unwrap_or(Scale::Off);
         self.scale_q.root  = sf.scale_root % 12;
 
@@ -1217,43 +3805,23 @@ impl App {
         self.seq2_bank = sf.seq2_bank.min(3);
         self.drum_bank = sf.drum_bank.min(3);
 
-        // Populate seq1 bank slots
-        let n_seq1 = sf.seq1_banks.len().min(4);
-        for i in 0..n_seq1 {
-            let sb = &sf.seq1_banks[i];
-            self.seq1_banks[i] = SeqPattern {
-                steps:     sb.steps.clone(),
-                num_steps: sb.num_steps.clamp(1, 32),
-            };
+        // Populate bank slots
+        for (slot, sb) in self.seq1_banks.iter_mut().zip(&sf.seq1_banks) {
+            *slot = SeqPattern::from_save(sb);
         }
-
-        // Populate seq2 bank slots
-        let n_seq2 = sf.seq2_banks.len().min(4);
-        for i in 0..n_seq2 {
-            let sb = &sf.seq2_banks[i];
-            self.seq2_banks[i] = SeqPattern {
-                steps:     sb.steps.clone(),
-                num_steps: sb.num_steps.clamp(1, 32),
-            };
+        for (slot, sb) in self.seq2_banks.iter_mut().zip(&sf.seq2_banks) {
+            *slot = SeqPattern::from_save(sb);
         }
-
-        // Populate drum bank slots
-        let n_drum = sf.drum_banks.len().min(4);
-        for i in 0..n_drum {
-            let db = &sf.drum_banks[i];
-            self.drum_banks[i] = DrumPattern {
-                num_steps:   db.num_steps.clamp(1, 32),
-                swing:       db.swing.clamp(0.0, 0.5),
-                track_steps: db.tracks.iter().map(|t| t.steps.clone()).collect(),
-            };
+        for (slot, db) in self.drum_banks.iter_mut().zip(&sf.drum_banks) {
+            *slot = DrumPattern::from_save(db);
         }
 
         // Reset cursors
         self.seq_cursor  = 0;
+        self.seq_lane    = 0;
         self.seq2_cursor = 0;
         self.drum_step   = 0;
-
-        self.status_msg = format!("Loaded ← {}", path);
+        Ok(warnings)
     }
 
     /// Commit the current file-path input: call save or load, then reset input state.
@@ -1264,9 +3832,541 @@ impl App {
         self.input_buf.clear();
         if path.is_empty() { return; }
         match mode {
-            InputMode::Save => self.save(&path),
-            InputMode::Load => self.load(&path),
+            InputMode::Save     => self.save(&path),
+            InputMode::Load     => self.load(&path),
+            InputMode::RackSave => self.save_rack(&path),
+            InputMode::Command  => self.run_command(&path),
             InputMode::None => {}
         }
     }
+
+    /// Execute one command-palette line, reporting through `status_msg`.
+    pub fn run_command(&mut self, line: &str) {
+        let cmd = match command::parse(line) {
+            Ok(cmd) => cmd,
+            Err(msg) => { self.status_msg = msg; return; }
+        };
+        match cmd {
+            Command::Seed(None) => {
+                self.status_msg = format!("Seed: {}", self.synth.lock().unwrap().rng.seed());
+            }
+            Command::Seed(Some(n)) => {
+                self.synth.lock().unwrap().rng.reseed(n);
+                self.status_msg = format!("Seed set: {}", n);
+            }
+            Command::Retrigger(mode) => {
+                let mut s = self.synth.lock().unwrap();
+                s.retrigger = mode.unwrap_or(match s.retrigger {
+                    Retrigger::Replace => Retrigger::Reuse,
+                    Retrigger::Reuse   => Retrigger::Replace,
+                });
+                self.status_msg = format!("Retrigger: {}", s.retrigger.name());
+            }
+            Command::EnvCurve { attack, release } => {
+                let mut s = self.synth.lock().unwrap();
+                if let Some(c) = attack  { s.attack_curve = c; }
+                if let Some(c) = release { s.release_curve = c; }
+                self.status_msg = format!("Envelope curves: attack {}  release {}",
+                    s.attack_curve.name(), s.release_curve.name());
+            }
+            Command::AutoGain(on) => {
+                let mut s = self.synth.lock().unwrap();
+                let on = on.unwrap_or(!s.mix_meter.auto_gain);
+                s.mix_meter.set_auto_gain(on);
+                self.status_msg = if on {
+                    format!("Auto-gain: ON  (voice-count steps glide; loud mixes level down, up to {:.0} dB)", -AUTO_GAIN_MAX_CUT_DB)
+                } else {
+                    "Auto-gain: OFF".to_string()
+                };
+            }
+            Command::Fm(on) => {
+                let mut s = self.synth.lock().unwrap();
+                let on = on.unwrap_or(s.synth_mode != SynthMode::Fm);
+                s.synth_mode = if on { SynthMode::Fm } else { SynthMode::Subtractive };
+                self.status_msg = fm_status(&s);
+            }
+            Command::FmRatio(r) => {
+                let mut s = self.synth.lock().unwrap();
+                s.fm_ratio = r;
+                self.status_msg = fm_status(&s);
+            }
+            Command::FmIndex(i) => {
+                let mut s = self.synth.lock().unwrap();
+                s.fm_index = i;
+                self.status_msg = fm_status(&s);
+            }
+            Command::Spread(pct) => {
+                let mut s = self.synth.lock().unwrap();
+                if let Some(p) = pct { s.voice_spread = p as f32 / 100.0; }
+                self.status_msg = match s.voice_spread {
+                    0.0 => "Voice spread: 0%  (voices centred)".to_string(),
+                    v   => format!("Voice spread: {:.0}%  (each new voice panned by its slot)", v * 100.0),
+                };
+            }
+            Command::Voices(n) => {
+                // Held keys may move between the mono stack and plain voices.
+                if n.is_some() { self.release_all(); }
+                let mut s = self.synth.lock().unwrap();
+                if let Some(n) = n { s.max_voices = n; }
+                self.status_msg = format!("Max voices: {} per synth{}", s.max_voices,
+                    if s.max_voices == 1 { format!("  (mono, {} note priority)", self.note_priority.name()) }
+                    else { String::new() });
+            }
+            Command::Priority(p) => {
+                self.release_all();
+                self.note_priority = p.unwrap_or_else(|| self.note_priority.next());
+                let mono = self.synth.lock().unwrap().max_voices == 1;
+                self.status_msg = format!("Note priority: {}{}", self.note_priority.name(),
+                    if mono { "" } else { "  (applies at one voice — `voices 1`)" });
+            }
+            Command::Declick(ms) => {
+                let mut s = self.synth.lock().unwrap();
+                if let Some(ms) = ms { s.anti_click_ms = ms.clamp(0.0, MAX_ANTI_CLICK_MS); }
+                self.status_msg = format!("Anti-click fade: {:.1} ms", s.anti_click_ms);
+            }
+            Command::Trim(set) => {
+                let mut s = self.synth.lock().unwrap();
+                if let Some((wave, gain)) = set {
+                    s.wave_trims[wave as usize] = gain.clamp(0.0, MAX_WAVE_TRIM);
+                }
+                let trims: Vec<String> = WaveType::ALL.iter()
+                    .map(|&w| format!("{} {:.2}", w.name(), s.wave_trims[w as usize]))
+                    .collect();
+                self.status_msg = format!("Wave trim: {}", trims.join("  "));
+            }
+            Command::Oversample(factor) => {
+                let mut s = self.synth.lock().unwrap();
+                let factor = factor.unwrap_or_else(|| {
+                    let i = OVERSAMPLE_FACTORS.iter().position(|&f| f == s.oversample()).unwrap_or(0);
+                    OVERSAMPLE_FACTORS[(i + 1) % OVERSAMPLE_FACTORS.len()]
+                });
+                s.set_oversample(factor);
+                self.status_msg = format!("Saturation oversampling: {}x", s.oversample());
+            }
+            Command::Interp(mode) => {
+                let mut s = self.synth.lock().unwrap();
+                s.delay.interp = mode.unwrap_or_else(|| s.delay.interp.next());
+                self.status_msg = format!("Delay interpolation: {}", s.delay.interp.name());
+            }
+            Command::Limiter(on) => {
+                let mut s = self.synth.lock().unwrap();
+                let (enabled, lookahead) = (on.unwrap_or(!s.limiter.enabled), s.limiter.lookahead);
+                s.limiter.set(enabled, lookahead);
+                self.status_msg = limiter_status(&s);
+            }
+            Command::LimiterCeiling(db) => {
+                let mut s = self.synth.lock().unwrap();
+                s.limiter.ceiling_db = db;
+                let lookahead = s.limiter.lookahead;
+                s.limiter.set(true, lookahead);
+                self.status_msg = limiter_status(&s);
+            }
+            Command::Lookahead(on) => {
+                let mut s = self.synth.lock().unwrap();
+                let (enabled, lookahead) = (s.limiter.enabled, on.unwrap_or(!s.limiter.lookahead));
+                s.limiter.set(enabled, lookahead);
+                self.status_msg = limiter_status(&s);
+            }
+            Command::ClipLed { clear } => {
+                let mut s = self.synth.lock().unwrap();
+                self.status_msg = match (clear, s.clip_latch) {
+                    (true, _)      => "Clip LED cleared".to_string(),
+                    (false, true)  => "Clip LED: ON — the signal into the limiter went over 0 dBFS  (clip clear)".to_string(),
+                    (false, false) => "Clip LED: off — no over since it was last cleared".to_string(),
+                };
+                if clear { s.clip_latch = false; self.clip_led = false; }
+            }
+            Command::LowLatency(on) => self.set_low_latency(on),
+            Command::Compact(on) => self.set_compact(on),
+            Command::StepClick(on) => self.step_click_set(on),
+            Command::BassView(on) => self.set_bass_view(on),
+            Command::NoteFold(on) => self.set_note_fold(on),
+            Command::OctaveShift(on) => self.set_octave_retrigger(on),
+            Command::Swing { drums, seq } => self.swing_set(drums, seq),
+            Command::DevicePicker => self.open_device_picker(),
+            Command::Device(name) => self.switch_device(name),
+            Command::Drone(on) => self.drone_set(on),
+            Command::DroneNote(note) => self.drone_pick(note),
+            Command::Tuner(on)        => self.tuner_set(on),
+            Command::TunerPitch(p)    => self.tuner_pick(p),
+            Command::TunerRef(hz)     => self.tuner_ref(hz),
+            Command::Grooves => {
+                let names: Vec<String> = groove::available().into_iter().map(|g| g.name).collect();
+                self.status_msg = format!("Grooves: {}  (from {}/*.json)", names.join(", "), groove::GROOVE_DIR);
+            }
+            Command::Groove(name) => self.drum_apply_groove(name.as_deref()),
+            Command::Sample(path) => self.drum_set_sample(path.as_deref()),
+            Command::Fit(steps)   => self.drum_fit(steps),
+            Command::MidiIn(Some(path)) => self.midi_open(&path),
+            Command::MidiIn(None) => {
+                self.status_msg = match &self.midi_in {
+                    Some(m) => format!("MIDI input: {}  ({} mapping{})", m.path, self.cc_map.len(),
+                                       if self.cc_map.len() == 1 { "" } else { "s" }),
+                    None    => "MIDI input: off  (midiin <device>, e.g. /dev/snd/midiC1D0)".to_string(),
+                };
+            }
+            Command::MidiInOff => {
+                self.status_msg = match self.midi_in.take() {
+                    Some(m) => format!("MIDI input closed: {}", m.path),
+                    None    => "MIDI input: off".to_string(),
+                };
+            }
+            Command::Learn(param) => self.learn(param),
+            Command::LearnOff => {
+                self.cc_learn = None;
+                self.status_msg = "Learn cancelled".to_string();
+            }
+            Command::Mappings => {
+                self.cc_panel = !self.cc_panel;
+                self.status_msg = format!("MIDI mappings panel: {}", if self.cc_panel { "shown" } else { "hidden" });
+            }
+            Command::Unlearn(param) => self.unlearn(param),
+            Command::AutoLanes => {
+                self.auto_panel = !self.auto_panel;
+                self.status_msg = format!("Automation lanes: {}", if self.auto_panel { "shown" } else { "hidden" });
+            }
+            Command::AutoArm   => self.automation_record(false),
+            Command::AutoRec   => self.automation_record(true),
+            Command::AutoPlay  => self.automation_play(true),
+            Command::AutoStop  => self.automation_play(false),
+            Command::AutoClear => {
+                self.automation.clear();
+                self.status_msg = "Automation: lanes cleared".to_string();
+            }
+            Command::Ramp(None) => {
+                let s = self.synth.lock().unwrap();
+                self.status_msg = match &s.tempo_ramp {
+                    Some(r) => format!("Tempo ramp: {:.0} → {:.0} BPM over {} bars ({:.0}%, now {:.1})",
+                        r.from, r.to, r.bars(), r.progress() * 100.0, s.bpm),
+                    None    => format!("Tempo ramp: off  (BPM {:.0})", s.bpm),
+                };
+            }
+            Command::Ramp(Some((bpm, bars))) => {
+                let mut s = self.synth.lock().unwrap();
+                let (bpm, bars) = (bpm.clamp(30.0, 300.0), bars.clamp(1, MAX_RAMP_BARS));
+                s.tempo_ramp = Some(TempoRamp::new(s.bpm, bpm, bars, bar_steps(s.time_sig)));
+                self.status_msg = format!("Tempo ramp: {:.0} → {:.0} BPM over {} bar{}",
+                    s.bpm, bpm, bars, if bars == 1 { "" } else { "s" });
+            }
+            Command::RampStop => {
+                let mut s = self.synth.lock().unwrap();
+                s.tempo_ramp = None;
+                self.status_msg = format!("Tempo ramp stopped at {:.1} BPM", s.bpm);
+            }
+            Command::Roll(on) => {
+                self.roll_velocity = on.unwrap_or(!self.roll_velocity);
+                self.status_msg = format!("Roll velocity: {}", if self.roll_velocity {
+                    "ON  (faster drum-key repeats hit harder; fallback keyboard mode only)"
+                } else { "OFF" });
+            }
+            Command::DrumRec(on) => self.drum_rec_toggle(on),
+            Command::LengthLock { drums, seq, on } => {
+                let on = on.unwrap_or(!if drums { self.drum_length_lock } else { self.seq_length_lock });
+                if drums { self.drum_length_lock = on; }
+                if seq   { self.seq_length_lock = on; }
+                let state = |b: bool| if b { "ON" } else { "OFF" };
+                self.status_msg = format!("Length lock: drums {}  seq {}  (on: changing the step count stretches the pattern)",
+                    state(self.drum_length_lock), state(self.seq_length_lock));
+            }
+            Command::Double => self.seq_double(),
+            Command::Advance(n) => {
+                if let Some(n) = n { self.seq_advance = n; }
+                self.status_msg = match self.seq_advance {
+                    0 => "Step entry: cursor stays on the step".to_string(),
+                    1 => "Step entry: advance 1 step".to_string(),
+                    n => format!("Step entry: advance {} steps", n),
+                };
+            }
+            Command::Stop => self.soft_stop(),
+            Command::Mutate(pct) => {
+                if let Some(pct) = pct { self.mutate_pct = pct; }
+                self.mutate();
+            }
+            Command::Lane(n)    => self.lane_select(n),
+            Command::LaneAdd    => self.lane_add(),
+            Command::LaneRemove => self.lane_remove(),
+            Command::LaneWave(w) => self.lane_patch_edit(|p| p.wave = w),
+            Command::LaneAdsr([a, d, s, r]) => self.lane_patch_edit(|p| {
+                p.attack = a; p.decay = d; p.sustain = s; p.release = r;
+            }),
+            Command::LanePatch(off) => self.lane_patch(off),
+            Command::Flash(on) => {
+                self.beat_flash = on.unwrap_or(!self.beat_flash);
+                if !self.beat_flash { self.downbeat_at = None; }
+                self.status_msg = format!("Beat flash: {}", if self.beat_flash { "ON" } else { "OFF" });
+            }
+            Command::TimeSig(sig) => self.set_time_sig(sig),
+            Command::Grid(beat) => {
+                self.grid_beat = beat.unwrap_or_else(|| {
+                    let i = GRID_BEATS.iter().position(|&b| b == self.grid_beat).unwrap_or(0);
+                    GRID_BEATS[(i + 1) % GRID_BEATS.len()]
+                });
+                self.status_msg = format!("Grid: beats of {} steps", self.grid_beat);
+            }
+            Command::TabPause(pause) => {
+                self.tab_pauses = pause.unwrap_or(!self.tab_pauses);
+                self.status_msg = format!("Tab: {}",
+                    if self.tab_pauses { "changes focus and pauses transport" } else { "changes focus, transport keeps running" });
+            }
+            Command::PitchEntry(on) => self.set_pitch_entry(on),
+            Command::CursorWrap(wrap) => {
+                self.cursor_wrap = wrap.unwrap_or(!self.cursor_wrap);
+                self.status_msg = format!("Cursor: {}",
+                    if self.cursor_wrap { "wraps round at the pattern edges" } else { "stops at the pattern edges" });
+            }
+            Command::VelRamp(ramp) => self.velocity_ramp(ramp.map(Some)),
+            Command::VelRampOff    => self.velocity_ramp(Some(None)),
+            Command::Help => {
+                self.status_msg = format!("Commands: {}", command::COMMANDS.join(", "));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synth::EnvelopeStage;
+
+    /// An app on a fresh synth limited to one voice (mono keyboard play).
+    fn mono_app(priority: NotePriority) -> App {
+        let synth = Arc::new(Mutex::new(Synth::new(44_100.0)));
+        synth.lock().unwrap().max_voices = 1;
+        let mut app = App::new(synth);
+        app.note_priority = priority;
+        app
+    }
+
+    /// Notes the synth holds (started and not released).
+    fn held(app: &App) -> Vec<u8> {
+        let s = app.synth.lock().unwrap();
+        s.voices.iter().filter(|(_, v)| v.stage != EnvelopeStage::Release).map(|(&n, _)| n).collect()
+    }
+
+    fn note(app: &App, key: char) -> u8 {
+        app.scale_q.quantize(key_to_note(key, app.base_octave, app.key_layout).unwrap())
+    }
+
+    #[test]
+    fn bar_position_counts_bars_beats_and_steps() {
+        let app = App::new(Arc::new(Mutex::new(Synth::new(44_100.0))));
+        let at = |step: u64| {
+            let mut s = app.synth.lock().unwrap();
+            s.master_clock = crate::sequencer::step_start(step, samples_per_step(s.sample_rate, s.bpm));
+            drop(s);
+            app.bar_position()
+        };
+        assert_eq!(at(0), (1, 1, 1));
+        assert_eq!(at(5), (1, 2, 2));
+        assert_eq!(at(15), (1, 4, 4));
+        assert_eq!(at(16), (2, 1, 1));
+        assert_eq!(at(16 * 7 + 9), (8, 3, 2));
+    }
+
+    #[test]
+    fn time_sig_sets_the_bar_and_the_grid_beat() {
+        let mut app = App::new(Arc::new(Mutex::new(Synth::new(44_100.0))));
+        assert_eq!((app.synth.lock().unwrap().time_sig, app.grid_beat), ((4, 4), 4), "4/4 should stay the default");
+        for (sig, beat, bar) in [((3, 4), 4, 12), ((6, 8), 6, 12), ((5, 4), 4, 20), ((7, 8), 2, 14), ((4, 4), 4, 16)] {
+            app.set_time_sig(None);
+            assert_eq!((app.synth.lock().unwrap().time_sig, app.grid_beat, bar_steps(sig)), (sig, beat, bar));
+        }
+
+        // Step 13 of 3/4 is the second bar's first beat, on its second step.
+        app.set_time_sig(Some((3, 4)));
+        {
+            let mut s = app.synth.lock().unwrap();
+            s.master_clock = crate::sequencer::step_start(13, samples_per_step(s.sample_rate, s.bpm));
+        }
+        assert_eq!(app.bar_position(), (2, 1, 2));
+    }
+
+    #[test]
+    fn fold_brings_out_of_range_keys_back_by_octaves() {
+        let layout = KeyLayout::TwoOctave;
+        for octave in [-3, 9, 10] {
+            for key in NOTE_KEYS.chars().filter(|&k| layout.offset(k).is_some()) {
+                let raw = (octave + 1) * 12 + layout.offset(key).unwrap();
+                match key_to_note_folded(key, octave, layout, true) {
+                    Some((n, false)) => assert_eq!(n as i32, raw),
+                    Some((n, true)) => {
+                        assert!(!(0..=127).contains(&raw), "{} folded while in range", raw);
+                        assert_eq!((n as i32 - raw).rem_euclid(12), 0, "{} folded to another pitch class", raw);
+                        assert!(if raw > 127 { n >= 116 } else { n < 12 }, "{} folded to {}, not the nearest octave", raw, n);
+                    }
+                    None => panic!("{} not folded", raw),
+                }
+                assert_eq!(key_to_note(key, octave, layout).is_some(), (0..=127).contains(&raw));
+            }
+        }
+    }
+
+    #[test]
+    fn pick_follows_the_priority() {
+        let keys = [('a', 64), ('b', 60), ('c', 67), ('d', 62)];
+        assert_eq!(NotePriority::Last.pick(&keys), Some(62));
+        assert_eq!(NotePriority::Highest.pick(&keys), Some(67));
+        assert_eq!(NotePriority::Lowest.pick(&keys), Some(60));
+        assert_eq!(NotePriority::Last.pick(&[]), None);
+    }
+
+    #[test]
+    fn mono_keys_fall_back_on_release() {
+        // Press z, c, x (low, high, middle), then release them in reverse.
+        // Each row is what sounds after each press and release.
+        let cases = [
+            (NotePriority::Last,    ["z", "c", "x", "c", "z", ""]),
+            (NotePriority::Highest, ["z", "c", "c", "c", "z", ""]),
+            (NotePriority::Lowest,  ["z", "z", "z", "z", "z", ""]),
+        ];
+        for (priority, want) in cases {
+            let mut app = mono_app(priority);
+            assert!(note(&app, 'z') < note(&app, 'x') && note(&app, 'x') < note(&app, 'c'));
+            let mut got = Vec::new();
+            for k in ['z', 'c', 'x'] { app.key_press(k); got.push(held(&app)); }
+            for k in ['x', 'c', 'z'] { app.key_release(k); got.push(held(&app)); }
+            let want: Vec<Vec<u8>> = want.iter()
+                .map(|k| k.chars().map(|c| note(&app, c)).collect())
+                .collect();
+            assert_eq!(got, want, "{} priority", priority.name());
+        }
+    }
+
+    #[test]
+    fn octave_change_retriggers_held_keys() {
+        let mut app = App::new(Arc::new(Mutex::new(Synth::new(44_100.0))));
+        let sorted = |app: &App| { let mut h = held(app); h.sort_unstable(); h };
+        app.key_press('z');
+        app.key_press('e');
+        let before = sorted(&app);
+        assert_eq!(before, [note(&app, 'z'), note(&app, 'e')]);
+        app.octave_up();
+        assert_eq!(sorted(&app), [before[0] + 12, before[1] + 12]);
+        app.key_release('e');
+        assert_eq!(sorted(&app), [before[0] + 12], "releasing a key should stop its shifted note");
+
+        app.set_octave_retrigger(Some(false));
+        app.octave_down();
+        assert!(held(&app).is_empty(), "without retrigger an octave change releases held keys");
+
+        // Mono: the sounding key moves, and release still falls back to the other one.
+        let mut app = mono_app(NotePriority::Last);
+        app.key_press('z');
+        app.key_press('c');
+        let (z, c) = (note(&app, 'z'), note(&app, 'c'));
+        app.octave_up();
+        assert_eq!(held(&app), [c + 12]);
+        app.key_release('c');
+        assert_eq!(held(&app), [z + 12]);
+    }
+
+    #[test]
+    fn bpm_steps_accelerate_while_held() {
+        let mut app = App::new(Arc::new(Mutex::new(Synth::new(44_100.0))));
+        let bpm = |app: &App| app.synth.lock().unwrap().bpm;
+        let mut deltas = Vec::new();
+        for i in 0..40 {
+            // From 100 each time, so the 300 BPM limit never clips a step.
+            app.synth.lock().unwrap().bpm = 100.0;
+            app.bpm_step(true, false, i > 0);
+            deltas.push(bpm(&app) - 100.0);
+        }
+        assert_eq!(deltas[..8], [BPM_STEP; 8]);
+        assert_eq!(deltas[8..16], [2.0 * BPM_STEP; 8]);
+        assert_eq!(deltas[32..], [BPM_MAX_ACCEL as f32 * BPM_STEP; 8]);
+
+        // Single presses (and a held key with the fine modifier) stay small.
+        let mut app = App::new(Arc::new(Mutex::new(Synth::new(44_100.0))));
+        for _ in 0..3 {
+            app.bpm_last = None;
+            app.bpm_step(false, false, false);
+        }
+        assert_eq!(bpm(&app), 120.0 - 3.0 * BPM_STEP);
+        for i in 0..20 { app.bpm_step(true, true, i > 0); }
+        assert_eq!(bpm(&app), 120.0 - 3.0 * BPM_STEP + 20.0 * BPM_FINE_STEP);
+        assert!(app.status_msg.starts_with(&format!("BPM: {:.0}", bpm(&app))), "{}", app.status_msg);
+    }
+
+    #[test]
+    fn clip_led_latches_until_cleared_and_the_ceiling_holds() {
+        let mut app = App::new(Arc::new(Mutex::new(Synth::new(44_100.0))));
+        app.run_command("limiter -0.1");
+        app.run_command("limiter lookahead on");
+        let ceiling = 10f32.powf(-0.1 / 20.0);
+        let peak = {
+            let mut s = app.synth.lock().unwrap();
+            assert!(s.limiter.enabled && s.limiter.ceiling_db == -0.1);
+            s.note_on(48);
+            let quiet = crate::render::render(&mut s, 4410);
+            assert!(!s.clip_latch, "a single note latched the clip LED");
+            // A loud chord spread wide: the side pushes a channel past 0 dBFS.
+            (s.volume, s.voice_spread, s.widener.width) = (1.0, 1.0, 2.0);
+            for n in [36, 43, 52, 55, 60] { s.note_on(n); }
+            let loud = crate::render::render(&mut s, 22_050);
+            for n in [36, 43, 48, 52, 55, 60] { s.note_off(n); }
+            crate::render::render(&mut s, 44_100);
+            quiet.iter().chain(&loud).fold(0.0f32, |m, &(l, r)| m.max(l.abs()).max(r.abs()))
+        };
+        assert!(peak <= ceiling + 1e-6, "output peak {} over the {} ceiling", peak, ceiling);
+        app.run_command("clip");
+        assert!(app.synth.lock().unwrap().clip_latch, "the LED did not stay latched after the over");
+        assert!(app.status_msg.starts_with("Clip LED: ON"), "{}", app.status_msg);
+        app.run_command("clip clear");
+        assert!(!app.synth.lock().unwrap().clip_latch && !app.clip_led);
+    }
+
+    #[test]
+    fn cursor_wraps_or_stops_at_the_edges() {
+        assert_eq!(move_cursor(0, 16, false, true), 15);
+        assert_eq!(move_cursor(15, 16, true, true), 0);
+        assert_eq!(move_cursor(0, 16, false, false), 0);
+        assert_eq!(move_cursor(15, 16, true, false), 15);
+        assert_eq!((move_cursor(7, 16, false, false), move_cursor(7, 16, true, false)), (6, 8));
+        assert_eq!((move_cursor(0, 1, false, true), move_cursor(0, 1, true, false)), (0, 0));
+
+        let mut app = App::new(Arc::new(Mutex::new(Synth::new(44_100.0))));
+        assert!(app.cursor_wrap, "wrapping should stay the default");
+        app.seq_cursor_left();
+        app.drum_track_up();
+        let last_track = app.synth.lock().unwrap().drum_machine.tracks.len() - 1;
+        assert_eq!((app.seq_cursor, app.drum_track), (15, last_track));
+
+        app.cursor_wrap = false;
+        app.seq_cursor_right();
+        app.drum_track_down();
+        app.drum_step_left();
+        assert_eq!((app.seq_cursor, app.drum_track, app.drum_step), (15, last_track, 0));
+    }
+
+    #[test]
+    fn pitch_entry_steps_by_scale_degree_and_places_at_the_cursor() {
+        let mut app = App::new(Arc::new(Mutex::new(Synth::new(44_100.0))));
+        app.scale_q.scale = Scale::Major;
+        app.mode = AppMode::SynthSeq;
+        app.set_pitch_entry(Some(true));
+        assert!(app.pitch_entry_active());
+        for _ in 0..3 { app.pending_note_step(true); }
+        assert_eq!(app.pending_note, 65, "C4 up three degrees of C major is F4");
+        app.pending_note_step(false);
+        app.pending_note_step(false);
+        assert_eq!(app.pending_note, 62);
+
+        app.seq_advance = 2;
+        app.place_pending_note();
+        app.place_pending_note();
+        let steps = app.synth.lock().unwrap().lanes[0].steps[..4].to_vec();
+        assert_eq!(steps, [Some(62), None, Some(62), None]);
+        assert_eq!(app.seq_cursor, 4);
+
+        app.scale_q.scale = Scale::Off;
+        app.pending_note_step(true);
+        assert_eq!(app.pending_note, 63, "with the scale Off a step is a semitone");
+
+        app.mode = AppMode::SynthSeq2;
+        app.place_pending_note();
+        assert_eq!((app.synth.lock().unwrap().sequencer2.steps[0], app.seq2_cursor), (Some(63), 2));
+
+        app.mode = AppMode::Drums;
+        assert!(!app.pitch_entry_active(), "pitch entry only drives the melodic sequencers");
+    }
 }
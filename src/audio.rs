@@ -1,20 +1,52 @@
 use anyhow::{Context, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Stream, StreamConfig};
+use cpal::{BufferSize, Stream, StreamConfig, SupportedBufferSize};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 use crate::synth::Synth;
 
+/// Buffer size asked for in low-latency mode, in frames (~2.9 ms at 44.1 kHz).
+/// Clamped into the range the device reports.
+pub const LOW_LATENCY_FRAMES: u32 = 128;
+
 pub struct AudioEngine {
     _stream: Stream,
+    /// Name of the output device the stream plays on.
+    pub device: String,
+    /// Opened in low-latency mode (`LOW_LATENCY_FRAMES` requested).
+    pub low_latency: bool,
+    /// Fixed buffer size the stream runs with; `None` = the device default
+    /// (normal mode, or the device refused the requested size).
+    pub buffer_frames: Option<u32>,
+    pub sample_rate: f32,
+    /// Set by the stream's error callback when the device goes away.
+    lost: Arc<AtomicBool>,
+}
+
+/// Names of the output devices the host offers right now (re-enumerated
+/// on every call, so a device plugged in after launch shows up).
+pub fn output_devices() -> Vec<String> {
+    cpal::default_host().output_devices()
+        .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+        .unwrap_or_default()
 }
 
 impl AudioEngine {
-    pub fn new(synth: Arc<Mutex<Synth>>) -> Result<Self> {
+    /// Open the output device called `device` (`None` = the host default).
+    /// With `low_latency` a small fixed buffer is requested; if the device
+    /// can't honour it the stream is opened with its default buffer instead
+    /// (`buffer_frames` is `None`).
+    pub fn new(synth: Arc<Mutex<Synth>>, device: Option<&str>, low_latency: bool) -> Result<Self> {
         let host = cpal::default_host();
-        let device = host
-            .default_output_device()
-            .context("No output device found")?;
+        let device = match device {
+            None => host.default_output_device().context("No output device found")?,
+            Some(name) => host.output_devices()
+                .context("Cannot list output devices")?
+                .find(|d| d.name().is_ok_and(|n| n == name))
+                .with_context(|| format!("Output device not found: {}", name))?,
+        };
+        let name = device.name().unwrap_or_else(|_| "unknown".to_string());
 
         let config = device
             .default_output_config()
@@ -22,25 +54,58 @@ impl AudioEngine {
 
         let sample_rate = config.sample_rate().0 as f32;
         let channels = config.channels() as usize;
+        let format = config.sample_format();
 
-        // Update synth sample rate
-        {
-            let mut s = synth.lock().unwrap();
-            s.sample_rate = sample_rate;
-        }
-
-        let synth_clone = Arc::clone(&synth);
+        // Rebuild everything sized for the default rate at the device rate.
+        synth.lock().unwrap().set_sample_rate(sample_rate);
 
-        let stream = match config.sample_format() {
-            cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config.into(), synth_clone, channels)?,
-            cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config.into(), synth_clone, channels)?,
-            cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config.into(), synth_clone, channels)?,
-            fmt => anyhow::bail!("Unsupported sample format: {:?}", fmt),
+        let frames = match config.buffer_size() {
+            SupportedBufferSize::Range { min, max } => LOW_LATENCY_FRAMES.clamp(*min, *max),
+            SupportedBufferSize::Unknown            => LOW_LATENCY_FRAMES,
+        };
+        let stream_config: StreamConfig = config.into();
+        let lost = Arc::new(AtomicBool::new(false));
+        let open = |buffer_size| -> Result<Stream> {
+            let config = StreamConfig { buffer_size, ..stream_config.clone() };
+            let stream = build(&device, &config, format, Arc::clone(&synth), channels, Arc::clone(&lost))?;
+            stream.play().context("Failed to start audio stream")?;
+            Ok(stream)
+        };
+        // Some hosts only reject a fixed size when the stream starts, so a
+        // failure anywhere in `open` falls back to the default buffer.
+        let (stream, buffer_frames) = match low_latency.then(|| open(BufferSize::Fixed(frames))) {
+            Some(Ok(stream)) => (stream, Some(frames)),
+            _                => (open(BufferSize::Default)?, None),
         };
 
-        stream.play().context("Failed to start audio stream")?;
+        Ok(Self { _stream: stream, device: name, low_latency, buffer_frames, sample_rate, lost })
+    }
 
-        Ok(Self { _stream: stream })
+    /// True once the device has been removed (unplugged, or taken away by
+    /// the host); the stream no longer plays.
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Relaxed)
+    }
+
+    /// Buffer latency in milliseconds, when the buffer size is known.
+    pub fn latency_ms(&self) -> Option<f32> {
+        self.buffer_frames.map(|n| n as f32 * 1000.0 / self.sample_rate)
+    }
+}
+
+fn build(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    format: cpal::SampleFormat,
+    synth: Arc<Mutex<Synth>>,
+    channels: usize,
+    lost: Arc<AtomicBool>,
+) -> Result<Stream> {
+    match format {
+        cpal::SampleFormat::F32 => build_stream::<f32>(device, config, synth, channels, lost),
+        cpal::SampleFormat::I16 => build_stream::<i16>(device, config, synth, channels, lost),
+        cpal::SampleFormat::U16 => build_stream::<u16>(device, config, synth, channels, lost),
+        fmt => anyhow::bail!("Unsupported sample format: {:?}", fmt),
     }
 }
 
@@ -49,11 +114,17 @@ fn build_stream<T>(
     config: &StreamConfig,
     synth: Arc<Mutex<Synth>>,
     channels: usize,
+    lost: Arc<AtomicBool>,
 ) -> Result<Stream>
 where
     T: cpal::Sample + cpal::SizedSample + cpal::FromSample<f32>,
 {
-    let err_fn = |err| eprintln!("Audio stream error: {}", err);
+    // A removed device is reported to the UI (`App::poll_audio`) instead of
+    // printed over the screen.
+    let err_fn = move |err| match err {
+        cpal::StreamError::DeviceNotAvailable => lost.store(true, Ordering::Relaxed),
+        err => eprintln!("Audio stream error: {}", err),
+    };
 
     let stream = device.build_output_stream(
         config,
@@ -61,10 +132,17 @@ where
             let mut synth = synth.lock().unwrap();
             let frame_count = data.len() / channels;
             for frame in 0..frame_count {
-                let sample = synth.generate_sample();
-                let value = T::from_sample(sample);
-                for ch in 0..channels {
-                    data[frame * channels + ch] = value;
+                let (l, r) = synth.generate_frame();
+                let out = &mut data[frame * channels..(frame + 1) * channels];
+                match channels {
+                    1 => out[0] = T::from_sample((l + r) * 0.5),
+                    _ => {
+                        // Extra channels beyond the stereo pair get the mono sum.
+                        let mid = T::from_sample((l + r) * 0.5);
+                        out[0] = T::from_sample(l);
+                        out[1] = T::from_sample(r);
+                        for v in &mut out[2..] { *v = mid; }
+                    }
                 }
             }
         },
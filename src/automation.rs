@@ -0,0 +1,208 @@
+use crate::drums::DrumKind;
+use crate::param::Param;
+use crate::sequencer::samples_per_step;
+use crate::synth::Synth;
+
+/// Smallest change of a value the recorder notices.
+const CHANGE_EPS: f32 = 1e-4;
+
+// ── Mix automation ────────────────────────────────────────────────────────────
+//
+// Records mixer moves (any `Param`, drum track mutes) over a loop of the step
+// grid and plays them back as lanes.  Like MIDI input this runs on the UI
+// thread once per frame (`App::tick_automation`): the recorder compares every
+// target with the value it saw last frame — whatever changed it, a key, the
+// effects panel or a controller — so no edit site needs a hook.
+//
+// Event times come from the master clock, stored as a fractional grid
+// position (`clock / samples_per_step`, modulo the lane length) so a lane
+// keeps its shape when the tempo changes.  Playback applies the events the
+// position crossed since the last frame, so timing is frame-accurate.
+
+/// Something a lane can move.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    Param(Param),
+    /// Drum track mute (value 1.0 = muted).
+    Mute(usize),
+}
+
+impl Target {
+    fn all(tracks: usize) -> impl Iterator<Item = Target> {
+        Param::ALL.into_iter().map(Target::Param).chain((0..tracks).map(Target::Mute))
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Self::Param(p) => p.label().to_string(),
+            Self::Mute(i)  => format!("Mute {}", DrumKind::ALL.get(i).map_or("?", |k| k.name().trim())),
+        }
+    }
+
+    fn get(self, s: &Synth) -> f32 {
+        match self {
+            Self::Param(p) => p.get(s),
+            Self::Mute(i)  => s.drum_machine.tracks.get(i).map_or(0.0, |t| if t.muted { 1.0 } else { 0.0 }),
+        }
+    }
+
+    fn set(self, s: &mut Synth, v: f32) {
+        match self {
+            Self::Param(p) => p.set(s, v),
+            Self::Mute(i)  => if let Some(t) = s.drum_machine.tracks.get_mut(i) { t.muted = v >= 0.5; },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AutoEvent {
+    /// Grid position in the lane, 0.0..steps.
+    pub step:   f64,
+    pub target: Target,
+    pub value:  f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AutoState {
+    Stopped,
+    /// Waiting for the lane to wrap, then records one pass.
+    Armed,
+    /// Recording one pass that began at grid position `start`; `done` steps
+    /// of it so far.
+    Recording { start: f64, done: f64 },
+    Playing,
+}
+
+pub struct Automation {
+    pub state:  AutoState,
+    /// Lane length in grid steps (the drum pattern length when recording began).
+    pub steps:  usize,
+    /// Recorded events, sorted by `step`.
+    pub events: Vec<AutoEvent>,
+    /// Lane position at the last tick.
+    pub pos:    f64,
+    /// Every target's value at the last tick (change detection).
+    seen:       Vec<(Target, f32)>,
+    /// Targets moved during the current recording pass (their old events
+    /// are replaced; the others keep playing).
+    touched:    Vec<Target>,
+}
+
+impl Automation {
+    pub fn new() -> Self {
+        Self { state: AutoState::Stopped, steps: 16, events: Vec::new(), pos: 0.0, seen: Vec::new(), touched: Vec::new() }
+    }
+
+    /// Targets with recorded events, in the order they were first recorded.
+    pub fn lanes(&self) -> Vec<Target> {
+        let mut out: Vec<Target> = Vec::new();
+        for e in &self.events {
+            if !out.contains(&e.target) { out.push(e.target); }
+        }
+        out
+    }
+
+    /// Value of `target`'s lane at grid position `step` (the last event at or
+    /// before it, wrapping round to the end of the lane).
+    pub fn value_at(&self, target: Target, step: f64) -> Option<f32> {
+        let mut lane = self.events.iter().filter(|e| e.target == target);
+        let last = lane.clone().next_back()?;
+        Some(lane.rfind(|e| e.step <= step).unwrap_or(last).value)
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+        if self.state == AutoState::Playing { self.state = AutoState::Stopped; }
+    }
+
+    /// Record from the next lane wrap (`false`) or from here (`true`), for
+    /// one pass of `steps` (used when nothing has been recorded yet).
+    pub fn record(&mut self, s: &Synth, steps: usize, now: bool) {
+        if self.events.is_empty() { self.steps = steps.max(1); }
+        self.pos = self.position(s);
+        self.seen = Target::all(s.drum_machine.tracks.len()).map(|t| (t, t.get(s))).collect();
+        self.touched.clear();
+        self.state = if now { AutoState::Recording { start: self.pos, done: 0.0 } } else { AutoState::Armed };
+    }
+
+    /// Start lane playback from the current position.
+    pub fn play_from_here(&mut self, s: &Synth) {
+        self.pos = self.position(s);
+        self.state = AutoState::Playing;
+    }
+
+    fn position(&self, s: &Synth) -> f64 {
+        let sps = samples_per_step(s.sample_rate, s.bpm);
+        (s.master_clock as f64 / sps) % self.steps as f64
+    }
+
+    /// Advance to the current master-clock position: record changes, play
+    /// lanes, finish a recording pass.  Returns true if the lane display
+    /// changed.
+    pub fn tick(&mut self, s: &mut Synth) -> bool {
+        if self.state == AutoState::Stopped { return false; }
+        let (from, to) = (self.pos, self.position(s));
+        self.pos = to;
+        let len = self.steps as f64;
+        let advanced = (to - from).rem_euclid(len);
+        let wrapped = to < from;
+
+        match self.state {
+            AutoState::Armed if wrapped => {
+                self.state = AutoState::Recording { start: 0.0, done: to };
+                self.record_changes(s, 0.0, to);
+                true
+            }
+            AutoState::Recording { start, done } => {
+                self.play(s, from, to, true);
+                self.record_changes(s, start, to);
+                let done = done + advanced;
+                if done >= len {
+                    self.state = AutoState::Playing;
+                    self.touched.clear();
+                } else {
+                    self.state = AutoState::Recording { start, done };
+                }
+                true
+            }
+            AutoState::Playing => { self.play(s, from, to, false); advanced > 0.0 }
+            _ => false,
+        }
+    }
+
+    /// Apply the events in `(from, to]` (wrapping).  While recording, lanes
+    /// being re-recorded are skipped and what is applied is marked as seen,
+    /// so playback is not mistaken for a move.
+    fn play(&mut self, s: &mut Synth, from: f64, to: f64, recording: bool) {
+        let crossed = |step: f64| if from <= to { step > from && step <= to } else { step > from || step <= to };
+        for e in self.events.iter().filter(|e| crossed(e.step)) {
+            if recording && self.touched.contains(&e.target) { continue; }
+            e.target.set(s, e.value);
+            if let Some(seen) = self.seen.iter_mut().find(|(t, _)| *t == e.target) { seen.1 = e.target.get(s); }
+        }
+    }
+
+    /// Record every target that moved since the last tick at `step`, for a
+    /// pass that began at `start`.
+    fn record_changes(&mut self, s: &Synth, start: f64, step: f64) {
+        for i in 0..self.seen.len() {
+            let (target, old) = self.seen[i];
+            let v = target.get(s);
+            if (v - old).abs() <= CHANGE_EPS { continue; }
+            self.seen[i].1 = v;
+            if !self.touched.contains(&target) {
+                // First move this pass: drop the old lane, and keep the value
+                // from before the move so the lane starts from it.
+                self.touched.push(target);
+                self.events.retain(|e| e.target != target);
+                self.insert(AutoEvent { step: start, target, value: old });
+            }
+            self.insert(AutoEvent { step, target, value: v });
+        }
+    }
+
+    fn insert(&mut self, e: AutoEvent) {
+        let i = self.events.partition_point(|x| x.step <= e.step);
+        self.events.insert(i, e);
+    }
+}
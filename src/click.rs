@@ -0,0 +1,98 @@
+use std::f32::consts::TAU;
+
+/// Pitch of the click, in Hz.
+const CLICK_FREQ: f32 = 3000.0;
+/// Length of the click, in milliseconds.
+const CLICK_MS: f32 = 1.5;
+/// Decay time constant, in milliseconds.
+const CLICK_TAU_MS: f32 = 0.3;
+/// Output level of the click.
+const CLICK_LEVEL: f32 = 0.5;
+
+// ── Step click (timing debug) ─────────────────────────────────────────────────
+//
+// A very short blip fired by `Synth::generate_frame` on the sample a melodic
+// sequencer's `StepEvent` fires, for checking timing against external gear.
+// Not the musical metronome: no accents, no level control.  Like the tuner it
+// joins the output after the master chain, and full-scale on its first sample
+// so the onset is exactly the step.  Session-only, so headless renders (which
+// load a project into a fresh `Synth`) never contain it.
+
+pub struct StepClick {
+    pub on: bool,
+    /// Samples since the last trigger; `len` once the click has finished.
+    pos:    usize,
+    len:    usize,
+    decay:  f32,
+    sample_rate: f32,
+}
+
+impl StepClick {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut c = Self { on: false, pos: 0, len: 0, decay: 0.0, sample_rate };
+        c.set_sample_rate(sample_rate);
+        c.pos = c.len;
+        c
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        // A finished click must stay finished when the new rate makes it longer.
+        let idle = self.pos >= self.len;
+        self.sample_rate = sample_rate;
+        self.len   = (CLICK_MS * 0.001 * sample_rate).ceil() as usize;
+        self.decay = (-1.0 / (CLICK_TAU_MS * 0.001 * sample_rate)).exp();
+        self.pos   = if idle { self.len } else { self.pos.min(self.len) };
+    }
+
+    /// Start a click on this sample (ignored while off).
+    pub fn trigger(&mut self) {
+        if self.on { self.pos = 0; }
+    }
+
+    /// Cut a sounding click.
+    pub fn reset(&mut self) {
+        self.pos = self.len;
+    }
+
+    pub fn next_sample(&mut self) -> f32 {
+        if self.pos >= self.len { return 0.0; }
+        let t = self.pos as f32 / self.sample_rate;
+        let x = (TAU * CLICK_FREQ * t).cos() * self.decay.powi(self.pos as i32);
+        self.pos += 1;
+        x * CLICK_LEVEL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(c: &mut StepClick, n: usize) -> Vec<f32> {
+        (0..n).map(|_| c.next_sample()).collect()
+    }
+
+    #[test]
+    fn click_is_full_scale_on_the_step_and_then_stops() {
+        let mut c = StepClick::new(48_000.0);
+        c.trigger();
+        assert!(samples(&mut c, 100).iter().all(|&x| x == 0.0), "fired while off");
+        c.on = true;
+        c.trigger();
+        let out = samples(&mut c, 200);
+        assert_eq!(out[0], CLICK_LEVEL);
+        assert!(out[..c.len].iter().all(|x| x.abs() <= CLICK_LEVEL));
+        assert!(out[c.len..].iter().all(|&x| x == 0.0), "click ran past {} samples", c.len);
+    }
+
+    #[test]
+    fn rate_change_does_not_restart_a_finished_click() {
+        let mut c = StepClick::new(44_100.0);
+        c.on = true;
+        c.set_sample_rate(48_000.0);
+        assert!(samples(&mut c, 100).iter().all(|&x| x == 0.0), "idle click sounded after the rate change");
+        c.trigger();
+        samples(&mut c, 10);
+        c.set_sample_rate(96_000.0);
+        assert!(samples(&mut c, 10).iter().any(|&x| x != 0.0), "sounding click was cut");
+    }
+}
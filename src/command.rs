@@ -0,0 +1,584 @@
+use crate::app::{NotePriority, GRID_BEATS, MAX_SEQ_ADVANCE};
+use crate::drums::MAX_FIT_STEPS;
+use crate::effects::{Interp, LIMITER_CEILING_MAX_DB, LIMITER_CEILING_MIN_DB, OVERSAMPLE_FACTORS};
+use crate::param::Param;
+use crate::sequencer::{VelocityRamp, MAX_RAMP_LOOPS, MAX_SWING, MAX_VELOCITY};
+use crate::synth::{EnvCurve, Retrigger, WaveType, FM_RATIO_RANGE, MAX_FM_INDEX, MAX_ENV_S, MAX_LANES, MAX_VOICES_RANGE};
+use crate::tempo::{valid_time_sig, MAX_TIME_SIG_BEATS};
+use crate::tuner::{TunerPitch, A4_RANGE, HZ_RANGE};
+
+// ── Command palette ───────────────────────────────────────────────────────────
+//
+// Ctrl+P opens a one-line prompt; the text is parsed here and executed by
+// `App::run_command`.  Parsing is kept separate from `App` so new commands only
+// need a variant, a `parse` arm and a `COMMANDS` entry.
+
+/// A parsed command-palette line.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// `seed` shows the current RNG seed; `seed <n>` restarts it from `n`.
+    Seed(Option<u64>),
+    /// `retrig` toggles how a repeated note-on treats a sounding voice;
+    /// `retrig replace|reuse` sets it.
+    Retrigger(Option<Retrigger>),
+    /// `curve` shows the envelope attack/release shapes; `curve linear|exp`
+    /// sets both, `curve attack|release linear|exp` one of them.
+    EnvCurve { attack: Option<EnvCurve>, release: Option<EnvCurve> },
+    /// `autogain` toggles the mix auto-gain; `autogain on|off` sets it.
+    AutoGain(Option<bool>),
+    /// `fm` toggles between subtractive and 2-operator FM synthesis;
+    /// `fm on|off` sets it.
+    Fm(Option<bool>),
+    /// `fm ratio <r>`: modulator frequency as a multiple of the note.
+    FmRatio(f32),
+    /// `fm index <i>`: modulation depth (0 = plain carrier).
+    FmIndex(f32),
+    /// `voices` shows the polyphony limit per synth; `voices <n>` sets it.
+    Voices(Option<usize>),
+    /// `spread` shows the voice stereo spread; `spread <pct>` sets it (0 = centred).
+    Spread(Option<u8>),
+    /// `priority` cycles which held key sounds at one voice (last, highest,
+    /// lowest); `priority <name>` sets it.
+    Priority(Option<NotePriority>),
+    /// `declick` shows the minimum envelope ramp; `declick <ms>` sets it.
+    Declick(Option<f32>),
+    /// `trim` lists the per-waveform gain trims; `trim <wave> <gain>` sets one.
+    Trim(Option<(WaveType, f32)>),
+    /// `oversample` cycles 1×/2×/4× on the saturation stages; `oversample <n>` sets it.
+    Oversample(Option<usize>),
+    /// `interp` cycles the delay's read interpolation; `interp <mode>` sets it.
+    Interp(Option<Interp>),
+    /// `limiter` toggles the master limiter; `limiter on|off` sets it.
+    Limiter(Option<bool>),
+    /// `limiter <dB>` sets the limiter ceiling (and turns it on).
+    LimiterCeiling(f32),
+    /// `limiter lookahead [on|off]` toggles/sets the limiter's lookahead.
+    Lookahead(Option<bool>),
+    /// `clip` shows the output clip LED; `clip clear` resets it.
+    ClipLed { clear: bool },
+    /// `latency` toggles low-latency mode; `latency low|normal` sets it.
+    LowLatency(Option<bool>),
+    /// `device` opens the output device picker.
+    DevicePicker,
+    /// `device default|<name>` switches the output device (`None` = default).
+    Device(Option<String>),
+    /// `drone` toggles the drone; `drone on|off` sets it.
+    Drone(Option<bool>),
+    /// `drone <note>` (e.g. `A1`, `F#2`) picks the drone pitch and starts it;
+    /// `drone root` makes it follow the scale root again (`None`).
+    DroneNote(Option<u8>),
+    /// `groove` lists the available groove templates.
+    Grooves,
+    /// `groove <name>` lays a groove over the drum grid; `groove off` removes it (`None`).
+    Groove(Option<String>),
+    /// `ramp` shows the tempo ramp; `ramp <bpm> [<n>bars]` glides the master
+    /// tempo to `bpm` over `n` bars (default 4), then holds.
+    Ramp(Option<(f32, u32)>),
+    /// `ramp off` stops a ramp where it is.
+    RampStop,
+    /// `roll` toggles repeat-speed velocity for drum previews; `roll on|off` sets it.
+    Roll(Option<bool>),
+    /// `drumrec` toggles drum record arm; `drumrec on|off` sets it.
+    DrumRec(Option<bool>),
+    /// `lengthlock [on|off]` toggles/sets the length lock of the drums and
+    /// the melodic sequencers; `lengthlock drums|seq [on|off]` one of them.
+    LengthLock { drums: bool, seq: bool, on: Option<bool> },
+    /// `double` doubles the focused sequencer's pattern, copying it into the new half.
+    Double,
+    /// `advance` shows how far the cursor moves after entering a sequencer
+    /// note; `advance <n>` sets it, `advance off` (`Some(0)`) keeps it in place.
+    Advance(Option<usize>),
+    /// `stop` is the soft stop (F11): transport off, notes released, tails ring.
+    Stop,
+    /// `swing` shows the drum and melodic swing; `swing <pct>` sets both,
+    /// `swing drums|seq <pct>` one of them (fractions of a step).
+    Swing { drums: Option<f32>, seq: Option<f32> },
+    /// `mutate` makes small random changes to the focused pattern (Ctrl+E);
+    /// `mutate <pct>` first sets the chance per step (1–100).
+    Mutate(Option<u8>),
+    /// `lane` shows which synth 1 lane Seq1 edits; `lane <n>` (1-based,
+    /// stored 0-based) picks one.
+    Lane(Option<usize>),
+    /// `lane add` appends a synth 1 sequencer lane.
+    LaneAdd,
+    /// `lane remove` deletes the lane being edited.
+    LaneRemove,
+    /// `lane wave <wave>` gives the edited lane its own waveform.
+    LaneWave(WaveType),
+    /// `lane adsr <a> <d> <s> <r>` gives the edited lane its own envelope
+    /// (times in seconds, sustain 0–1).
+    LaneAdsr([f32; 4]),
+    /// `lane patch` shows the edited lane's sound; `lane patch off` (`true`)
+    /// drops it so the lane follows synth 1 again.
+    LanePatch(bool),
+    /// `flash` toggles the downbeat flash; `flash on|off` sets it.
+    Flash(Option<bool>),
+    /// `compact` toggles the compact layout; `compact on|off` sets it.
+    Compact(Option<bool>),
+    /// `stepclick` toggles the timing-debug click; `stepclick on|off` sets it.
+    StepClick(Option<bool>),
+    /// `bass` toggles the Seq2 panel's 303 view; `bass on|off` sets it.
+    BassView(Option<bool>),
+    /// `fold` toggles folding out-of-range piano keys back into range;
+    /// `fold on|off` sets it.
+    NoteFold(Option<bool>),
+    /// `grid` cycles the steps per highlighted beat; `grid 2|3|4|5|6` sets it.
+    Grid(Option<usize>),
+    /// `timesig <beats>/<4|8>` sets the time signature; bare cycles through
+    /// the common ones.
+    TimeSig(Option<(u8, u8)>),
+    /// `tab` toggles whether Tab (focus change) also pauses the transport;
+    /// `tab run|pause` sets it (`Some(true)` = pause).
+    TabPause(Option<bool>),
+    /// `cursor` toggles whether the sequencer and drum cursors wrap round
+    /// at the edges; `cursor wrap|clamp` sets it (`Some(true)` = wrap).
+    CursorWrap(Option<bool>),
+    /// `octshift` toggles whether an octave change re-triggers held piano
+    /// keys; `octshift retrigger|release` sets it (`Some(true)` = retrigger).
+    OctaveShift(Option<bool>),
+    /// `entry` toggles pitch entry (↑/↓ pick a note, Enter places it);
+    /// `entry on|off` sets it.
+    PitchEntry(Option<bool>),
+    /// `tuner` toggles the reference tone; `tuner on|off` sets it.
+    Tuner(Option<bool>),
+    /// `tuner <note>` (e.g. `A4`) or `tuner <hz>` (e.g. `432hz`) picks the
+    /// tone's pitch and starts it.
+    TunerPitch(TunerPitch),
+    /// `tuner ref` shows the A4 reference; `tuner ref <hz>` sets it.
+    TunerRef(Option<f32>),
+    /// `velramp` shows the focused sequencer's velocity ramp;
+    /// `velramp <start> <end> [<n>loops]` sets one (default 1 loop).
+    VelRamp(Option<VelocityRamp>),
+    /// `velramp off` goes back to flat, full velocity.
+    VelRampOff,
+    /// `sample <path>` loads a WAV onto the selected drum track;
+    /// `sample off` goes back to the synthesised voice (`None`).
+    Sample(Option<String>),
+    /// `fit <n>` time-stretches the selected drum track's sample to `n`
+    /// steps; `fit off` plays it at its natural length (`None`).
+    Fit(Option<u16>),
+    /// `midiin` shows the MIDI input; `midiin <device>` opens a raw MIDI
+    /// device (e.g. `/dev/snd/midiC1D0`) for controller input.
+    MidiIn(Option<String>),
+    /// `midiin off` closes it.
+    MidiInOff,
+    /// `learn <param>` binds the next controller moved to `param`.
+    Learn(Param),
+    /// `learn off` cancels a pending learn.
+    LearnOff,
+    /// `learn` alone shows/hides the MIDI mappings panel.
+    Mappings,
+    /// `unlearn <param>` removes its binding; `unlearn all` (`None`) clears them.
+    Unlearn(Option<Param>),
+    /// `auto` shows/hides the automation lanes panel.
+    AutoLanes,
+    /// `auto arm` records one pass of mix moves from the next loop start.
+    AutoArm,
+    /// `auto rec` records one pass starting now.
+    AutoRec,
+    /// `auto play` / `auto stop` start and stop lane playback.
+    AutoPlay,
+    AutoStop,
+    /// `auto clear` erases every lane.
+    AutoClear,
+    /// `help` lists the available commands.
+    Help,
+}
+
+/// Ramp length when `ramp <bpm>` gives no bar count.
+const DEFAULT_RAMP_BARS: u32 = 4;
+
+/// Usage strings shown by `help` and on parse errors.
+pub const COMMANDS: &[&str] = &["seed [n]", "retrig [replace|reuse]", "curve [attack|release] [linear|exp]", "fm [on|off|ratio <r>|index <i>]", "voices [n]", "spread [<pct>]", "priority [last|highest|lowest]", "drone [on|off|root|<note>]", "declick [ms]", "trim [wave gain]", "oversample [1|2|4]", "interp [linear|cubic|nearest]", "limiter [on|off|<dB>|lookahead [on|off]]", "clip [clear]", "autogain [on|off]", "latency [low|normal]", "device [default|<name>]", "groove [off|<name>]", "swing [drums|seq] [<pct>]", "ramp [off|<bpm> <n>bars]", "flash [on|off]", "compact [on|off]", "bass [on|off]", "fold [on|off]", "grid [2|3|4|5|6]", "timesig [<beats>/<4|8>]", "roll [on|off]", "drumrec [on|off]", "lengthlock [drums|seq] [on|off]", "advance [off|<n>]", "double", "mutate [<pct>]", "stop", "lane [add|remove|<n>]", "lane wave <wave>", "lane adsr <a> <d> <s> <r>", "lane patch [off]", "tab [run|pause]", "cursor [wrap|clamp]", "octshift [retrigger|release]", "entry [on|off]", "tuner [on|off|<note>|<hz>|ref <hz>]", "stepclick [on|off]", "velramp [off|<start> <end> [<n>loops]]", "sample [off|<path>]", "fit [off|<n>steps]", "midiin [off|<device>]", "learn [off|<param>]", "unlearn <param>|all", "auto [arm|rec|play|stop|clear]", "help"];
+
+/// Parse one palette line.  Errors are user-facing status messages.
+pub fn parse(line: &str) -> Result<Command, String> {
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else { return Err("Empty command".to_string()) };
+    let args: Vec<&str> = words.collect();
+    match (name.to_ascii_lowercase().as_str(), args.as_slice()) {
+        ("seed", [])  => Ok(Command::Seed(None)),
+        ("seed", [n]) => parse_seed(n).map(|n| Command::Seed(Some(n))),
+        ("retrig", [])          => Ok(Command::Retrigger(None)),
+        ("retrig", ["replace"]) => Ok(Command::Retrigger(Some(Retrigger::Replace))),
+        ("retrig", ["reuse"])   => Ok(Command::Retrigger(Some(Retrigger::Reuse))),
+        ("curve", [])             => Ok(Command::EnvCurve { attack: None, release: None }),
+        ("curve", [c])            => parse_curve(c).map(|c| Command::EnvCurve { attack: Some(c), release: Some(c) }),
+        ("curve", ["attack", c])  => parse_curve(c).map(|c| Command::EnvCurve { attack: Some(c), release: None }),
+        ("curve", ["release", c]) => parse_curve(c).map(|c| Command::EnvCurve { attack: None, release: Some(c) }),
+        ("autogain", [])      => Ok(Command::AutoGain(None)),
+        ("autogain", ["on"])  => Ok(Command::AutoGain(Some(true))),
+        ("autogain", ["off"]) => Ok(Command::AutoGain(Some(false))),
+        ("fm", [])      => Ok(Command::Fm(None)),
+        ("fm", ["on"])  => Ok(Command::Fm(Some(true))),
+        ("fm", ["off"]) => Ok(Command::Fm(Some(false))),
+        ("fm", ["ratio", r]) => r.parse::<f32>().ok().filter(|r| FM_RATIO_RANGE.contains(r))
+            .map(Command::FmRatio)
+            .ok_or_else(|| format!("Invalid ratio: {}  ({}–{})", r, FM_RATIO_RANGE.start(), FM_RATIO_RANGE.end())),
+        ("fm", ["index", i]) => i.parse::<f32>().ok().filter(|i| (0.0..=MAX_FM_INDEX).contains(i))
+            .map(Command::FmIndex)
+            .ok_or_else(|| format!("Invalid index: {}  (0–{})", i, MAX_FM_INDEX)),
+        ("voices", [])  => Ok(Command::Voices(None)),
+        ("voices", [n]) => n.parse::<usize>().ok().filter(|n| MAX_VOICES_RANGE.contains(n))
+            .map(|n| Command::Voices(Some(n)))
+            .ok_or_else(|| format!("Invalid voice count: {}  ({}–{})", n, MAX_VOICES_RANGE.start(), MAX_VOICES_RANGE.end())),
+        ("spread", [])    => Ok(Command::Spread(None)),
+        ("spread", [pct]) => pct.trim_end_matches('%').parse::<u8>().ok().filter(|p| *p <= 100)
+            .map(|p| Command::Spread(Some(p)))
+            .ok_or_else(|| format!("Invalid spread: {}  (0–100%)", pct)),
+        ("priority", [])  => Ok(Command::Priority(None)),
+        ("priority", [p]) => NotePriority::ALL.into_iter().find(|np| np.name().eq_ignore_ascii_case(p))
+            .map(|np| Command::Priority(Some(np)))
+            .ok_or_else(|| format!("Unknown note priority: {}  (last, highest, lowest)", p)),
+        ("declick", [])  => Ok(Command::Declick(None)),
+        ("declick", [ms]) => ms.parse::<f32>().ok().filter(|ms| ms.is_finite())
+            .map(|ms| Command::Declick(Some(ms)))
+            .ok_or_else(|| format!("Invalid time: {}", ms)),
+        ("trim", [])        => Ok(Command::Trim(None)),
+        ("trim", [w, g])    => {
+            let wave = parse_wave(w).ok_or_else(|| format!("Unknown wave: {}  (sine, square, saw, tri)", w))?;
+            let gain = g.parse::<f32>().ok().filter(|g| g.is_finite())
+                .ok_or_else(|| format!("Invalid gain: {}", g))?;
+            Ok(Command::Trim(Some((wave, gain))))
+        }
+        ("interp", [])     => Ok(Command::Interp(None)),
+        ("interp", [m])    => match m.to_ascii_lowercase().as_str() {
+            "linear"  | "lin" => Ok(Command::Interp(Some(Interp::Linear))),
+            "cubic"           => Ok(Command::Interp(Some(Interp::Cubic))),
+            "nearest" | "off" => Ok(Command::Interp(Some(Interp::Nearest))),
+            _ => Err(format!("Unknown interpolation: {}  (linear, cubic, nearest)", m)),
+        },
+        ("limiter", [])      => Ok(Command::Limiter(None)),
+        ("limiter", ["on"])  => Ok(Command::Limiter(Some(true))),
+        ("limiter", ["off"]) => Ok(Command::Limiter(Some(false))),
+        ("limiter", ["lookahead"])        => Ok(Command::Lookahead(None)),
+        ("limiter", ["lookahead", "on"])  => Ok(Command::Lookahead(Some(true))),
+        ("limiter", ["lookahead", "off"]) => Ok(Command::Lookahead(Some(false))),
+        ("clip", [])        => Ok(Command::ClipLed { clear: false }),
+        ("clip", ["clear"]) => Ok(Command::ClipLed { clear: true }),
+        ("device", [])          => Ok(Command::DevicePicker),
+        ("device", ["default"]) => Ok(Command::Device(None)),
+        ("device", [_, ..])     => Ok(Command::Device(Some(args.join(" ")))),
+        ("latency", [])         => Ok(Command::LowLatency(None)),
+        ("latency", ["low"])    => Ok(Command::LowLatency(Some(true))),
+        ("latency", ["normal"]) => Ok(Command::LowLatency(Some(false))),
+        ("limiter", [db]) => db.trim_end_matches("dB").trim_end_matches("db").parse::<f32>().ok()
+            .filter(|db| (LIMITER_CEILING_MIN_DB..=LIMITER_CEILING_MAX_DB).contains(db))
+            .map(Command::LimiterCeiling)
+            .ok_or_else(|| format!("Invalid ceiling: {}  ({} to {} dB)", db, LIMITER_CEILING_MIN_DB, LIMITER_CEILING_MAX_DB)),
+        ("oversample", [])  => Ok(Command::Oversample(None)),
+        ("oversample", [n]) => n.trim_end_matches('x').parse().ok()
+            .filter(|n| OVERSAMPLE_FACTORS.contains(n))
+            .map(|n| Command::Oversample(Some(n)))
+            .ok_or_else(|| "Oversample factor must be 1, 2 or 4".to_string()),
+        ("drone", [])       => Ok(Command::Drone(None)),
+        ("drone", ["on"])   => Ok(Command::Drone(Some(true))),
+        ("drone", ["off"])  => Ok(Command::Drone(Some(false))),
+        ("drone", ["root"]) => Ok(Command::DroneNote(None)),
+        ("drone", [n])      => parse_note(n).map(|n| Command::DroneNote(Some(n)))
+            .ok_or_else(|| format!("Invalid note: {}  (e.g. C2, F#1, Bb2)", n)),
+        ("groove", [])      => Ok(Command::Grooves),
+        ("groove", ["off"]) => Ok(Command::Groove(None)),
+        ("groove", name)    => Ok(Command::Groove(Some(name.join(" ")))),
+        ("ramp", [])      => Ok(Command::Ramp(None)),
+        ("ramp", ["off"]) => Ok(Command::RampStop),
+        ("ramp", [bpm])   => parse_bpm(bpm).map(|b| Command::Ramp(Some((b, DEFAULT_RAMP_BARS)))),
+        ("ramp", [bpm, bars]) => {
+            let bpm = parse_bpm(bpm)?;
+            let n = bars.trim_end_matches("bars").trim_end_matches("bar").trim_end_matches('b');
+            let bars = n.parse::<u32>().ok().filter(|&n| n > 0)
+                .ok_or_else(|| format!("Invalid bar count: {}  (e.g. 8bars)", bars))?;
+            Ok(Command::Ramp(Some((bpm, bars))))
+        }
+        ("roll", [])      => Ok(Command::Roll(None)),
+        ("roll", ["on"])  => Ok(Command::Roll(Some(true))),
+        ("roll", ["off"]) => Ok(Command::Roll(Some(false))),
+        ("drumrec", [])      => Ok(Command::DrumRec(None)),
+        ("drumrec", ["on"])  => Ok(Command::DrumRec(Some(true))),
+        ("drumrec", ["off"]) => Ok(Command::DrumRec(Some(false))),
+        ("lengthlock", rest) => parse_length_lock(rest),
+        ("flash", [])      => Ok(Command::Flash(None)),
+        ("flash", ["on"])  => Ok(Command::Flash(Some(true))),
+        ("flash", ["off"]) => Ok(Command::Flash(Some(false))),
+        ("compact", [])      => Ok(Command::Compact(None)),
+        ("compact", ["on"])  => Ok(Command::Compact(Some(true))),
+        ("compact", ["off"]) => Ok(Command::Compact(Some(false))),
+        ("stepclick", [])      => Ok(Command::StepClick(None)),
+        ("stepclick", ["on"])  => Ok(Command::StepClick(Some(true))),
+        ("stepclick", ["off"]) => Ok(Command::StepClick(Some(false))),
+        ("bass", [])      => Ok(Command::BassView(None)),
+        ("bass", ["on"])  => Ok(Command::BassView(Some(true))),
+        ("bass", ["off"]) => Ok(Command::BassView(Some(false))),
+        ("fold", [])      => Ok(Command::NoteFold(None)),
+        ("fold", ["on"])  => Ok(Command::NoteFold(Some(true))),
+        ("fold", ["off"]) => Ok(Command::NoteFold(Some(false))),
+        ("grid", [])  => Ok(Command::Grid(None)),
+        ("grid", [n]) => n.parse::<usize>().ok().filter(|n| GRID_BEATS.contains(n))
+            .map(|n| Command::Grid(Some(n)))
+            .ok_or_else(|| format!("Invalid grid: {}  (2–6 steps per beat)", n)),
+        ("timesig", [])  => Ok(Command::TimeSig(None)),
+        ("timesig", [s]) => s.split_once('/')
+            .and_then(|(b, u)| Some((b.parse::<u8>().ok()?, u.parse::<u8>().ok()?)))
+            .filter(|&sig| valid_time_sig(sig))
+            .map(|sig| Command::TimeSig(Some(sig)))
+            .ok_or_else(|| format!("Invalid time signature: {}  (1–{} beats of 4 or 8, e.g. 3/4, 6/8)", s, MAX_TIME_SIG_BEATS)),
+        ("tab", [])        => Ok(Command::TabPause(None)),
+        ("tab", ["run"])   => Ok(Command::TabPause(Some(false))),
+        ("tab", ["pause"]) => Ok(Command::TabPause(Some(true))),
+        ("cursor", [])        => Ok(Command::CursorWrap(None)),
+        ("cursor", ["wrap"])  => Ok(Command::CursorWrap(Some(true))),
+        ("cursor", ["clamp"]) => Ok(Command::CursorWrap(Some(false))),
+        ("octshift", [])            => Ok(Command::OctaveShift(None)),
+        ("octshift", ["retrigger"]) => Ok(Command::OctaveShift(Some(true))),
+        ("octshift", ["release"])   => Ok(Command::OctaveShift(Some(false))),
+        ("entry", [])      => Ok(Command::PitchEntry(None)),
+        ("entry", ["on"])  => Ok(Command::PitchEntry(Some(true))),
+        ("entry", ["off"]) => Ok(Command::PitchEntry(Some(false))),
+        ("tuner", [])      => Ok(Command::Tuner(None)),
+        ("tuner", ["on"])  => Ok(Command::Tuner(Some(true))),
+        ("tuner", ["off"]) => Ok(Command::Tuner(Some(false))),
+        ("tuner", ["ref"]) => Ok(Command::TunerRef(None)),
+        ("tuner", ["ref", hz]) => parse_hz(hz).filter(|f| A4_RANGE.contains(f))
+            .map(|f| Command::TunerRef(Some(f)))
+            .ok_or_else(|| format!("Invalid reference: {}  ({:.0}–{:.0} Hz)", hz, A4_RANGE.start(), A4_RANGE.end())),
+        ("tuner", [p]) => match parse_note(p) {
+            Some(n) => Ok(Command::TunerPitch(TunerPitch::Note(n))),
+            None => parse_hz(p).filter(|f| HZ_RANGE.contains(f))
+                .map(|f| Command::TunerPitch(TunerPitch::Hz(f)))
+                .ok_or_else(|| format!("Invalid pitch: {}  (e.g. A4, E2, 432hz)", p)),
+        },
+        ("velramp", [])      => Ok(Command::VelRamp(None)),
+        ("velramp", ["off"]) => Ok(Command::VelRampOff),
+        ("velramp", [a, b])  => Ok(Command::VelRamp(Some(VelocityRamp {
+            start: parse_velocity(a)?, end: parse_velocity(b)?, loops: 1,
+        }))),
+        ("velramp", [a, b, n]) => {
+            let (start, end) = (parse_velocity(a)?, parse_velocity(b)?);
+            let loops = n.trim_end_matches("loops").trim_end_matches("loop").parse::<u32>().ok()
+                .filter(|n| (1..=MAX_RAMP_LOOPS).contains(n))
+                .ok_or_else(|| format!("Invalid loop count: {}  (1–{}, e.g. 4loops)", n, MAX_RAMP_LOOPS))?;
+            Ok(Command::VelRamp(Some(VelocityRamp { start, end, loops })))
+        }
+        ("sample", ["off"]) => Ok(Command::Sample(None)),
+        ("sample", [_, ..]) => Ok(Command::Sample(Some(args.join(" ")))),
+        ("fit", ["off"]) => Ok(Command::Fit(None)),
+        ("fit", [n]) => n.trim_end_matches("steps").trim_end_matches("step").parse::<u16>().ok()
+            .filter(|n| (1..=MAX_FIT_STEPS).contains(n))
+            .map(|n| Command::Fit(Some(n)))
+            .ok_or_else(|| format!("Invalid step count: {}  (1–{}, e.g. 16steps)", n, MAX_FIT_STEPS)),
+        ("midiin", [])      => Ok(Command::MidiIn(None)),
+        ("midiin", ["off"]) => Ok(Command::MidiInOff),
+        ("midiin", [_, ..]) => Ok(Command::MidiIn(Some(args.join(" ")))),
+        ("learn", [])      => Ok(Command::Mappings),
+        ("learn", ["off"]) => Ok(Command::LearnOff),
+        ("learn", [p])     => parse_param(p).map(Command::Learn),
+        ("unlearn", ["all"]) => Ok(Command::Unlearn(None)),
+        ("unlearn", [p])     => parse_param(p).map(|p| Command::Unlearn(Some(p))),
+        ("auto", [])        => Ok(Command::AutoLanes),
+        ("auto", ["arm"])   => Ok(Command::AutoArm),
+        ("auto", ["rec"])   => Ok(Command::AutoRec),
+        ("auto", ["play"])  => Ok(Command::AutoPlay),
+        ("auto", ["stop"])  => Ok(Command::AutoStop),
+        ("auto", ["clear"]) => Ok(Command::AutoClear),
+        ("double", []) => Ok(Command::Double),
+        ("advance", [])      => Ok(Command::Advance(None)),
+        ("advance", ["off"]) => Ok(Command::Advance(Some(0))),
+        ("advance", [n])     => n.parse::<usize>().ok().filter(|n| *n <= MAX_SEQ_ADVANCE)
+            .map(|n| Command::Advance(Some(n)))
+            .ok_or_else(|| format!("Invalid advance: {}  (0–{})", n, MAX_SEQ_ADVANCE)),
+        ("stop", [])      => Ok(Command::Stop),
+        ("swing", [])              => Ok(Command::Swing { drums: None, seq: None }),
+        ("swing", [pct])           => parse_swing(pct).map(|v| Command::Swing { drums: Some(v), seq: Some(v) }),
+        ("swing", ["drums", pct])  => parse_swing(pct).map(|v| Command::Swing { drums: Some(v), seq: None }),
+        ("swing", ["seq", pct])    => parse_swing(pct).map(|v| Command::Swing { drums: None, seq: Some(v) }),
+        ("mutate", [])    => Ok(Command::Mutate(None)),
+        ("mutate", [pct]) => pct.trim_end_matches('%').parse::<u8>().ok().filter(|p| (1..=100).contains(p))
+            .map(|p| Command::Mutate(Some(p)))
+            .ok_or_else(|| format!("Invalid amount: {}  (1–100%)", pct)),
+        ("lane", [])         => Ok(Command::Lane(None)),
+        ("lane", ["add"])    => Ok(Command::LaneAdd),
+        ("lane", ["remove"]) => Ok(Command::LaneRemove),
+        ("lane", ["patch"])        => Ok(Command::LanePatch(false)),
+        ("lane", ["patch", "off"]) => Ok(Command::LanePatch(true)),
+        ("lane", ["wave", w])      => parse_wave(w).map(Command::LaneWave)
+            .ok_or_else(|| format!("Unknown wave: {}  (sine, square, saw, tri)", w)),
+        ("lane", ["adsr", a, d, s, r]) => {
+            let time = |t: &str| t.parse::<f32>().ok().filter(|t| (0.0..=MAX_ENV_S).contains(t))
+                .ok_or_else(|| format!("Invalid time: {}  (0–{}s)", t, MAX_ENV_S));
+            let sustain = s.parse::<f32>().ok().filter(|s| (0.0..=1.0).contains(s))
+                .ok_or_else(|| format!("Invalid sustain: {}  (0–1)", s))?;
+            Ok(Command::LaneAdsr([time(a)?, time(d)?, sustain, time(r)?]))
+        }
+        ("lane", [n])        => n.parse::<usize>().ok().filter(|n| (1..=MAX_LANES).contains(n))
+            .map(|n| Command::Lane(Some(n - 1)))
+            .ok_or_else(|| format!("Invalid lane: {}  (1–{})", n, MAX_LANES)),
+        ("help", [])  => Ok(Command::Help),
+        ("seed", _)   => Err("Usage: seed [n]".to_string()),
+        ("retrig", _) => Err("Usage: retrig [replace|reuse]".to_string()),
+        ("curve", _)  => Err("Usage: curve [attack|release] [linear|exp]".to_string()),
+        ("autogain", _) => Err("Usage: autogain [on|off]".to_string()),
+        ("fm", _)     => Err("Usage: fm [on|off|ratio <r>|index <i>]".to_string()),
+        ("voices", _) => Err("Usage: voices [n]".to_string()),
+        ("spread", _) => Err("Usage: spread [<pct>]".to_string()),
+        ("priority", _) => Err("Usage: priority [last|highest|lowest]".to_string()),
+        ("declick", _) => Err("Usage: declick [ms]".to_string()),
+        ("trim", _)   => Err("Usage: trim [wave gain]".to_string()),
+        ("oversample", _) => Err("Usage: oversample [1|2|4]".to_string()),
+        ("interp", _) => Err("Usage: interp [linear|cubic|nearest]".to_string()),
+        ("limiter", _) => Err("Usage: limiter [on|off|<dB>|lookahead [on|off]]".to_string()),
+        ("clip", _)    => Err("Usage: clip [clear]".to_string()),
+        ("latency", _) => Err("Usage: latency [low|normal]".to_string()),
+        ("drone", _)  => Err("Usage: drone [on|off|root|<note>]".to_string()),
+        ("ramp", _)   => Err("Usage: ramp [off|<bpm> <n>bars]".to_string()),
+        ("flash", _)  => Err("Usage: flash [on|off]".to_string()),
+        ("compact", _) => Err("Usage: compact [on|off]".to_string()),
+        ("stepclick", _) => Err("Usage: stepclick [on|off]".to_string()),
+        ("bass", _)   => Err("Usage: bass [on|off]".to_string()),
+        ("fold", _)   => Err("Usage: fold [on|off]".to_string()),
+        ("grid", _)   => Err("Usage: grid [2|3|4|5|6]".to_string()),
+        ("timesig", _) => Err("Usage: timesig [<beats>/<4|8>]".to_string()),
+        ("roll", _)   => Err("Usage: roll [on|off]".to_string()),
+        ("drumrec", _) => Err("Usage: drumrec [on|off]".to_string()),
+        ("advance", _) => Err("Usage: advance [off|<n>]".to_string()),
+        ("mutate", _)  => Err("Usage: mutate [<pct>]".to_string()),
+        ("swing", _)   => Err("Usage: swing [drums|seq] [<pct>]".to_string()),
+        ("stop", _)    => Err("Usage: stop".to_string()),
+        ("lane", _)    => Err("Usage: lane [add|remove|<n>] | lane wave <wave> | lane adsr <a> <d> <s> <r> | lane patch [off]".to_string()),
+        ("double", _) => Err("Usage: double".to_string()),
+        ("tab", _)    => Err("Usage: tab [run|pause]".to_string()),
+        ("cursor", _) => Err("Usage: cursor [wrap|clamp]".to_string()),
+        ("octshift", _) => Err("Usage: octshift [retrigger|release]".to_string()),
+        ("entry", _)  => Err("Usage: entry [on|off]".to_string()),
+        ("tuner", _)  => Err("Usage: tuner [on|off|<note>|<hz>|ref <hz>]".to_string()),
+        ("sample", _) => Err("Usage: sample [off|<path>]".to_string()),
+        ("fit", _)    => Err("Usage: fit [off|<n>steps]".to_string()),
+        ("learn", _)  => Err("Usage: learn [off|<param>]".to_string()),
+        ("unlearn", _) => Err("Usage: unlearn <param>|all".to_string()),
+        ("auto", _)   => Err("Usage: auto [arm|rec|play|stop|clear]".to_string()),
+        ("velramp", _) => Err("Usage: velramp [off|<start> <end> [<n>loops]]".to_string()),
+        _ => Err(format!("Unknown command: {}  (try: {})", name, COMMANDS.join(", "))),
+    }
+}
+
+/// Envelope curve name: `linear` / `lin` or `exp` / `exponential`.
+fn parse_curve(s: &str) -> Result<EnvCurve, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "linear" | "lin"      => Ok(EnvCurve::Linear),
+        "exp" | "exponential" => Ok(EnvCurve::Exponential),
+        _ => Err(format!("Invalid curve: {}  (linear or exp)", s)),
+    }
+}
+
+/// Swing in percent of a step (`0`–`50`, optional `%`) as a fraction.
+fn parse_swing(s: &str) -> Result<f32, String> {
+    s.trim_end_matches('%').parse::<f32>().ok()
+        .filter(|p| (0.0..=MAX_SWING * 100.0).contains(p))
+        .map(|p| p / 100.0)
+        .ok_or_else(|| format!("Invalid swing: {}  (0–50%)", s))
+}
+
+/// `[drums|seq] [on|off]` after `lengthlock`; no target means both.
+fn parse_length_lock(args: &[&str]) -> Result<Command, String> {
+    let (drums, seq, rest) = match args {
+        ["drums", rest @ ..] => (true, false, rest),
+        ["seq", rest @ ..]   => (false, true, rest),
+        rest                 => (true, true, rest),
+    };
+    let on = match rest {
+        []      => None,
+        ["on"]  => Some(true),
+        ["off"] => Some(false),
+        _       => return Err("Usage: lengthlock [drums|seq] [on|off]".to_string()),
+    };
+    Ok(Command::LengthLock { drums, seq, on })
+}
+
+/// Tempo in BPM, 30–300.
+fn parse_bpm(s: &str) -> Result<f32, String> {
+    s.trim_end_matches("bpm").parse::<f32>().ok()
+        .filter(|b| (30.0..=300.0).contains(b))
+        .ok_or_else(|| format!("Invalid BPM: {}  (30–300)", s))
+}
+
+/// Note velocity, 1–127.
+fn parse_velocity(s: &str) -> Result<u8, String> {
+    s.parse::<u8>().ok()
+        .filter(|v| (1..=MAX_VELOCITY).contains(v))
+        .ok_or_else(|| format!("Invalid velocity: {}  (1–{})", s, MAX_VELOCITY))
+}
+
+/// A controllable parameter by its `Param::id`.
+fn parse_param(s: &str) -> Result<Param, String> {
+    Param::from_id(s).ok_or_else(|| {
+        let ids: Vec<&str> = Param::ALL.iter().map(|p| p.id()).collect();
+        format!("Unknown parameter: {}  (one of {})", s, ids.join(", "))
+    })
+}
+
+/// Frequency in Hz, with or without a `hz` suffix.
+fn parse_hz(s: &str) -> Option<f32> {
+    let s = s.to_ascii_lowercase();
+    s.trim_end_matches("hz").parse::<f32>().ok().filter(|f| f.is_finite())
+}
+
+fn parse_wave(s: &str) -> Option<WaveType> {
+    match s.to_ascii_lowercase().as_str() {
+        "sine"                => Some(WaveType::Sine),
+        "square" | "sq"       => Some(WaveType::Square),
+        "saw" | "sawtooth"    => Some(WaveType::Sawtooth),
+        "tri" | "triangle"    => Some(WaveType::Triangle),
+        _ => None,
+    }
+}
+
+/// Note name with octave (`C4` = MIDI 60): letter, optional `#`/`b`, octave
+/// -1..9.
+fn parse_note(s: &str) -> Option<u8> {
+    let mut chars = s.chars();
+    let pc: i32 = match chars.next()?.to_ascii_uppercase() {
+        'C' => 0, 'D' => 2, 'E' => 4, 'F' => 5, 'G' => 7, 'A' => 9, 'B' => 11,
+        _ => return None,
+    };
+    let rest = chars.as_str();
+    let (acc, oct) = match rest.strip_prefix('#') {
+        Some(o) => (1, o),
+        None => match rest.strip_prefix('b') { Some(o) => (-1, o), None => (0, rest) },
+    };
+    let oct: i32 = oct.parse().ok()?;
+    let note = (oct + 1) * 12 + pc + acc;
+    (0..=127).contains(&note).then_some(note as u8)
+}
+
+/// Decimal, or hex with a `0x` prefix.
+fn parse_seed(s: &str) -> Result<u64, String> {
+    let parsed = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None      => s.parse(),
+    };
+    parsed.map_err(|_| format!("Invalid seed: {}", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swing_sets_both_or_one() {
+        assert_eq!(parse("swing"), Ok(Command::Swing { drums: None, seq: None }));
+        assert_eq!(parse("swing 25%"), Ok(Command::Swing { drums: Some(0.25), seq: Some(0.25) }));
+        assert_eq!(parse("swing drums 50"), Ok(Command::Swing { drums: Some(0.5), seq: None }));
+        assert_eq!(parse("swing seq 10"), Ok(Command::Swing { drums: None, seq: Some(0.1) }));
+        assert_eq!(parse("swing 51"), Err("Invalid swing: 51  (0–50%)".to_string()));
+        assert!(parse("swing bass 10").is_err());
+    }
+
+    #[test]
+    fn limiter_ceiling_and_clip_led() {
+        assert_eq!(parse("limiter -0.1"), Ok(Command::LimiterCeiling(-0.1)));
+        assert_eq!(parse("limiter -6dB"), Ok(Command::LimiterCeiling(-6.0)));
+        assert!(parse("limiter 0.5").is_err(), "a ceiling over 0 dBFS was accepted");
+        assert!(parse("limiter -13").is_err());
+        assert_eq!(parse("clip"), Ok(Command::ClipLed { clear: false }));
+        assert_eq!(parse("clip clear"), Ok(Command::ClipLed { clear: true }));
+    }
+}
@@ -1,5 +1,41 @@
 use std::f32::consts::PI;
-use crate::effects::EffectChain;
+use std::f64::consts::TAU;
+use std::sync::Arc;
+use crate::effects::{pan_gains, EffectChain};
+use crate::groove::Groove;
+use crate::rng::Rng;
+use crate::sample::Sample;
+use crate::solo::{Solo, SoloTarget};
+use crate::sequencer::{
+    due_step, loop_span, nudge_samples, remap_step, resize_loop, rotate, samples_per_step, set_loop_point,
+    step_at, step_start, swing_samples, Direction, MAX_NUDGE,
+};
+
+/// Length of the gain ramp applied when a track is muted or unmuted.
+const MUTE_FADE_MS: f32 = 5.0;
+/// Time for a track's level meter to fall back by 1/e after a hit.
+const METER_RELEASE_MS: f32 = 120.0;
+
+/// Per-step decay, in percent of the voice's natural length.
+pub const DEFAULT_DECAY: u8 = 100;
+pub const MIN_DECAY: u8 = 25;
+pub const MAX_DECAY: u8 = 250;
+const DECAY_STEP: u8 = 25;
+/// Fade applied when a short decay cuts a sample before its end.
+const SAMPLE_CUT_FADE_MS: f32 = 5.0;
+/// Longest grid length a sample can be fitted to, in steps.
+pub const MAX_FIT_STEPS: u16 = 64;
+/// Per-track tune range, in semitones either side of the voice's own pitch.
+pub const MAX_TUNE: i8 = 24;
+/// Time-stretch ratios beyond this range are clamped (grains get audibly
+/// smeared well before it).
+const MIN_STRETCH: f64 = 0.25;
+const MAX_STRETCH: f64 = 4.0;
+/// Grain length of the time-stretch, in milliseconds.  Two Hann-windowed
+/// grains overlap by half, so their windows always sum to 1.
+const GRAIN_MS: f32 = 40.0;
+/// Grain alignment compares (and steps through) every `ALIGN_STEP`th sample.
+const ALIGN_STEP: usize = 4;
 
 // ── Drum kind ─────────────────────────────────────────────────────────────────
 
@@ -40,6 +76,20 @@ impl DrumKind {
         }
     }
 
+    /// Pan position used by the auto-spread layout (audience view: hats and
+    /// high tom right, low tom left; kick/snare centred).
+    pub fn auto_pan(self) -> f32 {
+        match self {
+            Self::Kick | Self::Snare => 0.0,
+            Self::ClosedHat => 0.3,
+            Self::OpenHat   => 0.4,
+            Self::Clap      => -0.2,
+            Self::LowTom    => -0.5,
+            Self::MidTom    => -0.1,
+            Self::HighTom   => 0.35,
+        }
+    }
+
     /// Maximum duration (seconds) – the voice is dropped after this.
     fn duration(self) -> f32 {
         match self {
@@ -69,8 +119,8 @@ fn xorshift(state: &mut u32) -> f32 {
 // ── Single drum voice ─────────────────────────────────────────────────────────
 
 /// One triggered drum hit.  Generates samples until it naturally decays.
-/// Multiple voices of the same (or different) kind run in parallel inside
-/// `DrumMachine::voices`, giving full polyphony.
+/// Overlapping hits on a track run in parallel inside that track's
+/// `VoicePool`.
 struct DrumVoice {
     kind: DrumKind,
     sample_pos: u64,
@@ -81,19 +131,84 @@ struct DrumVoice {
     noise: u32,
     sample_rate: f32,
     volume: f32,
+    /// Amplitude-envelope time scale (1.0 = natural decay).  Pitch sweeps
+    /// and the clap's bursts keep their timing; only the fade is stretched.
+    stretch: f32,
+    /// Loaded sample played instead of the synthesiser, if any.
+    sample: Option<Arc<Sample>>,
+    /// Read the sample from its end towards its start.
+    reverse: bool,
+    /// Sample frames per output sample (file rate / engine rate).
+    rate: f64,
+    /// Output samples of fade before `dur_samples` when a decay below 100%
+    /// cuts the sample short (0 = play to the end).
+    cut_fade: u64,
+    /// Time-stretch ratio (output length / natural length, 1.0 = none).
+    ratio: f64,
+    /// Grain length in output samples, used while `ratio != 1.0`.
+    grain: u64,
+    /// Source start of the current and the previous grain (`stretched_at`).
+    grain_src: [f64; 2],
+    /// Playback speed from the track's tune (1.0 = as synthesised/recorded).
+    pitch: f32,
 }
 
 impl DrumVoice {
-    fn new(kind: DrumKind, sample_rate: f32, seed: u32, volume: f32) -> Self {
+    fn new(kind: DrumKind, sample_rate: f32, seed: u32, volume: f32, stretch: f32) -> Self {
         Self {
             kind,
             sample_pos: 0,
-            dur_samples: (kind.duration() * sample_rate).ceil() as u64,
+            dur_samples: (kind.duration() * stretch * sample_rate).ceil() as u64,
             phase: 0.0,
             noise: seed | 1, // xorshift must never be 0
             sample_rate,
             volume,
+            stretch,
+            sample: None,
+            reverse: false,
+            rate: 1.0,
+            cut_fade: 0,
+            ratio: 1.0,
+            grain: 2,
+            grain_src: [0.0, -1.0],
+            pitch: 1.0,
+        }
+    }
+
+    /// Shift the hit by `semitones`.  The synthesised voice runs faster or
+    /// slower as a whole, like a resampled recording: tone, sweeps and
+    /// envelope all scale, so a higher tune is also a shorter hit.  Call
+    /// before `with_sample`, which applies the same speed to a sample.
+    fn with_tune(mut self, semitones: i8) -> Self {
+        if semitones == 0 { return self; }
+        self.pitch = tune_ratio(semitones);
+        self.dur_samples = (self.dur_samples as f32 / self.pitch).ceil() as u64;
+        self
+    }
+
+    /// Play `sample` (forwards or reversed) instead of synthesising `kind`.
+    /// The length is the sample's own; a decay below 100% shortens it with a
+    /// short fade, a longer decay has nothing to stretch.
+    ///
+    /// `ratio` time-stretches the sample without changing its pitch (see
+    /// `stretched_at`); 1.0 plays it as recorded.  A tune resamples it.
+    fn with_sample(mut self, sample: Option<Arc<Sample>>, reverse: bool, ratio: f64) -> Self {
+        let Some(s) = sample else { return self };
+        self.rate = s.sample_rate as f64 / self.sample_rate as f64 * self.pitch as f64;
+        self.ratio = ratio;
+        self.grain = ((GRAIN_MS * 0.001 * self.sample_rate) as u64).max(2) & !1;
+        // Before the first hop the "previous" grain is a virtual one reading
+        // the same frames, so the attack is not faded in.
+        self.grain_src = [0.0, -((self.grain / 2) as f64)];
+        let natural = (s.frames.len() as f64 / self.rate * ratio).ceil() as u64;
+        self.dur_samples = natural;
+        if self.stretch < 1.0 {
+            self.dur_samples = (natural as f32 * self.stretch).ceil() as u64;
+            self.cut_fade = ((SAMPLE_CUT_FADE_MS * 0.001 * self.sample_rate) as u64).min(self.dur_samples);
         }
+        self.sample = Some(s);
+        self.reverse = reverse;
+        self
     }
 
     #[inline]
@@ -105,21 +220,38 @@ impl DrumVoice {
         if self.is_finished() {
             return 0.0;
         }
-        let t = self.sample_pos as f32 / self.sample_rate;
-        let raw = match self.kind {
-            DrumKind::Kick      => self.kick(t),
-            DrumKind::Snare     => self.snare(t),
-            DrumKind::ClosedHat => self.closed_hat(t),
-            DrumKind::OpenHat   => self.open_hat(t),
-            DrumKind::Clap      => self.clap(t),
-            DrumKind::LowTom    => self.tom(t, 110.0,  52.0, 0.55),
-            DrumKind::MidTom    => self.tom(t, 195.0,  90.0, 0.38),
-            DrumKind::HighTom   => self.tom(t, 275.0, 140.0, 0.26),
+        let t  = self.sample_pos as f32 / self.sample_rate * self.pitch;
+        let te = t / self.stretch;
+        let raw = match (&self.sample, self.kind) {
+            (Some(s), _) => {
+                let left = self.dur_samples - self.sample_pos;
+                let fade = if left < self.cut_fade { left as f32 / self.cut_fade as f32 } else { 1.0 };
+                let x = if self.ratio == 1.0 {
+                    sample_at(s, self.sample_pos as f64 * self.rate, self.reverse)
+                } else {
+                    stretched_at(s, self.sample_pos, &mut self.grain_src, self.rate, self.ratio, self.grain, self.reverse)
+                };
+                x * fade
+            }
+            (None, kind) => self.synth(kind, t, te),
         };
         self.sample_pos += 1;
         (raw * self.volume).clamp(-1.0, 1.0)
     }
 
+    fn synth(&mut self, kind: DrumKind, t: f32, te: f32) -> f32 {
+        match kind {
+            DrumKind::Kick      => self.kick(t, te),
+            DrumKind::Snare     => self.snare(te),
+            DrumKind::ClosedHat => self.closed_hat(te),
+            DrumKind::OpenHat   => self.open_hat(te),
+            DrumKind::Clap      => self.clap(t, te),
+            DrumKind::LowTom    => self.tom(t, te, 110.0,  52.0, 0.55),
+            DrumKind::MidTom    => self.tom(t, te, 195.0,  90.0, 0.38),
+            DrumKind::HighTom   => self.tom(t, te, 275.0, 140.0, 0.26),
+        }
+    }
+
     // ── Synthesis helpers ─────────────────────────────────────────────────
 
     #[inline]
@@ -130,7 +262,7 @@ impl DrumVoice {
     /// Advance the phase accumulator and return a sine value.
     #[inline]
     fn sine(&mut self, freq: f32) -> f32 {
-        self.phase += freq / self.sample_rate;
+        self.phase += freq * self.pitch / self.sample_rate;
         if self.phase >= 1.0 {
             self.phase -= 1.0;
         }
@@ -138,12 +270,14 @@ impl DrumVoice {
     }
 
     // ── Individual drum synthesisers ──────────────────────────────────────
+    //
+    // `t` is the time since the hit, `te` the envelope time (`t / stretch`).
 
-    fn kick(&mut self, t: f32) -> f32 {
+    fn kick(&mut self, t: f32, te: f32) -> f32 {
         // Exponential pitch sweep 150 → 50 Hz, fast transient click
         let freq = 50.0 + 100.0 * (-t * 32.0_f32).exp();
         let tone = self.sine(freq);
-        let amp  = (-t * 11.0_f32).exp();
+        let amp  = (-te * 11.0_f32).exp();
         let click = if t < 0.004 { self.noise() * 0.38 } else { 0.0 };
         (tone * 0.88 + click) * amp
     }
@@ -164,7 +298,7 @@ impl DrumVoice {
         self.noise() * (-t * 8.5_f32).exp()
     }
 
-    fn clap(&mut self, t: f32) -> f32 {
+    fn clap(&mut self, t: f32, te: f32) -> f32 {
         let noise = self.noise();
         let t_ms  = t * 1000.0;
         // Three staggered transient bursts that mimic a physical hand clap
@@ -175,19 +309,84 @@ impl DrumVoice {
                     else if t_ms < 21.0 { 0.62 }
                     else                { 0.00 };
         // Decaying body that starts after the transients
-        let body = if t > 0.024 { (-(t - 0.024) * 22.0_f32).exp() * 0.42 } else { 0.0 };
+        let body = if t > 0.024 { (-(te - 0.024 / self.stretch) * 22.0_f32).exp() * 0.42 } else { 0.0 };
         noise * (burst + body)
     }
 
-    fn tom(&mut self, t: f32, start_hz: f32, end_hz: f32, decay_s: f32) -> f32 {
+    fn tom(&mut self, t: f32, te: f32, start_hz: f32, end_hz: f32, decay_s: f32) -> f32 {
         let freq  = end_hz + (start_hz - end_hz) * (-t * 22.0_f32).exp();
         let tone  = self.sine(freq);
         let noise = self.noise();
-        let amp   = (-t / decay_s).exp();
+        let amp   = (-te / decay_s).exp();
         (tone * 0.80 + noise * 0.20) * amp
     }
 }
 
+/// Playback speed for a tune of `semitones`.
+fn tune_ratio(semitones: i8) -> f32 {
+    2.0_f32.powf(semitones as f32 / 12.0)
+}
+
+/// Linearly interpolated frame `pos` of `s`, counted from the end when
+/// `reverse` (silence past either end).
+fn sample_at(s: &Sample, pos: f64, reverse: bool) -> f32 {
+    let n = s.frames.len();
+    let get = |k: usize| if k < n { s.frames[if reverse { n - 1 - k } else { k }] } else { 0.0 };
+    let i = pos as usize;
+    let frac = (pos - i as f64) as f32;
+    let (a, b) = (get(i), get(i + 1));
+    a + (b - a) * frac
+}
+
+/// Output sample `n` of `s` time-stretched by `ratio` (WSOLA-style overlap-add).
+///
+/// Grains of `grain` output samples start every half grain and read the
+/// source at its normal speed (`rate`), so the pitch is kept; grain `k`
+/// starts near `k * hop / ratio` — the source position the stretched
+/// timeline has reached — so the length changes by `ratio`.  `src` holds
+/// the source start of the current and the previous grain.  Each new grain
+/// is shifted (`align`) to line up with where the previous one continues, so
+/// the cross-fade does not beat or smear the pitch.  The Hann windows of the
+/// two overlapping grains sum to 1.
+fn stretched_at(s: &Sample, n: u64, src: &mut [f64; 2], rate: f64, ratio: f64, grain: u64, reverse: bool) -> f32 {
+    let hop = grain / 2;
+    if n > 0 && n.is_multiple_of(hop) {
+        let want = src[0] + hop as f64;
+        let nominal = n as f64 / ratio;
+        src[1] = src[0];
+        src[0] = align(s, want, nominal, hop, rate, reverse);
+    }
+    let local = n % hop;
+    [(src[0], local), (src[1], local + hop)].into_iter().map(|(start, l)| {
+        let w = 0.5 - 0.5 * (TAU * l as f64 / grain as f64).cos();
+        w as f32 * sample_at(s, (start + l as f64) * rate, reverse)
+    }).sum()
+}
+
+/// Source start within half a `hop` of `nominal` whose next `hop` samples
+/// best match those from `want` (cross-correlation, coarse then fine, on
+/// every `ALIGN_STEP`th sample to keep the audio thread cheap).
+fn align(s: &Sample, want: f64, nominal: f64, hop: u64, rate: f64, reverse: bool) -> f64 {
+    let score = |cand: f64| -> f32 {
+        (0..hop).step_by(ALIGN_STEP)
+            .map(|j| sample_at(s, (want + j as f64) * rate, reverse) * sample_at(s, (cand + j as f64) * rate, reverse))
+            .sum()
+    };
+    let best = |from: f64, to: f64, step: f64| {
+        let mut best = (f32::MIN, nominal.max(0.0));
+        let mut c = from.max(0.0);
+        while c <= to {
+            let v = score(c);
+            if v > best.0 { best = (v, c); }
+            c += step;
+        }
+        best.1
+    };
+    let tol = (hop / 2) as f64;
+    let coarse = best(nominal - tol, nominal + tol, ALIGN_STEP as f64);
+    best(coarse - ALIGN_STEP as f64, coarse + ALIGN_STEP as f64, 1.0)
+}
+
 // ── Drum track ────────────────────────────────────────────────────────────────
 
 /// One row in the drum machine: a drum instrument, its step pattern,
@@ -197,24 +396,135 @@ pub struct DrumTrack {
     pub steps: Vec<u8>,
     pub muted: bool,
     pub volume: f32,
-    /// Per-track insert effects (e.g. compression, EQ). Empty = passthrough.
-    #[allow(dead_code)]
+    /// Stereo position, -1.0 (left) … 0.0 (centre) … 1.0 (right).
+    pub pan: f32,
+    /// Per-step timing offset in percent of a step, added on top of swing.
+    pub nudge: Vec<i8>,
+    /// Per-step decay in percent of the voice's natural length
+    /// (`DEFAULT_DECAY` = as synthesised).
+    pub decay: Vec<u8>,
+    /// Per-track insert effects, applied to this track's voices before the
+    /// drum bus sum. Empty = passthrough with no processing cost.
     pub fx: EffectChain,
+    /// WAV sample played instead of the synthesised voice (`None` = synth).
+    pub sample: Option<Arc<Sample>>,
+    /// Play the sample backwards (reverse-cymbal swells).  Has no effect on
+    /// a synthesised track.
+    pub reverse: bool,
+    /// Time-stretch the sample to this many grid steps at the current tempo,
+    /// keeping its pitch (`None` = natural length).
+    pub fit_steps: Option<u16>,
+    /// Pitch offset in semitones, `-MAX_TUNE..=MAX_TUNE` (0 = untuned).
+    pub tune: i8,
     /// Rotation offset incremented each time euclidean_fill is called (transient, not saved).
     pub euclid_rotation: usize,
+    /// Current mute-fade gain (0.0–1.0), ramped towards the `muted` target each sample.
+    fade_gain: f32,
+    /// Peak follower of the track's voice output (jumps on a hit, decays over
+    /// `METER_RELEASE_MS`), for the per-track meter.
+    pub meter: f32,
 }
 
 impl DrumTrack {
+    /// Output length / natural length that makes the sample last
+    /// `fit_steps` steps of `sps` samples, clamped to `MIN_STRETCH..=MAX_STRETCH`
+    /// (1.0 without a sample or fit length).  The natural length is the tuned
+    /// one, so a fitted sample keeps its length when retuned.
+    fn stretch_ratio(&self, sps: f64, sample_rate: f32) -> f64 {
+        let (Some(s), Some(steps)) = (&self.sample, self.fit_steps) else { return 1.0 };
+        let natural = s.frames.len() as f64 * sample_rate as f64 / s.sample_rate as f64
+            / tune_ratio(self.tune) as f64;
+        (steps as f64 * sps / natural).clamp(MIN_STRETCH, MAX_STRETCH)
+    }
+
     fn new(kind: DrumKind, num_steps: usize) -> Self {
         Self {
             kind,
             steps: vec![0u8; num_steps],
             muted: false,
             volume: 0.85,
+            pan: 0.0,
+            nudge: vec![0; num_steps],
+            decay: vec![DEFAULT_DECAY; num_steps],
             fx: EffectChain::new(),
+            sample: None,
+            reverse: false,
+            fit_steps: None,
+            tune: 0,
             euclid_rotation: 0,
+            fade_gain: 1.0,
+            meter: 0.0,
         }
     }
+
+    /// Move every hit of an `old`-step pattern to its place in a `new`-step
+    /// one (`remap_step`), carrying its decay.  Where hits collide the one
+    /// with the higher probability stays.
+    fn remap_steps(&mut self, old: usize, new: usize) {
+        let mut steps = vec![0; new];
+        let mut nudge = vec![0; new];
+        let mut decay = vec![DEFAULT_DECAY; new];
+        for (i, &hit) in self.steps.iter().enumerate().take(old) {
+            let (j, n) = remap_step(i, self.nudge.get(i).copied().unwrap_or(0), old, new);
+            if hit <= steps[j] { continue; }
+            steps[j] = hit;
+            nudge[j] = n;
+            decay[j] = self.decay.get(i).copied().unwrap_or(DEFAULT_DECAY);
+        }
+        (self.steps, self.nudge, self.decay) = (steps, nudge, decay);
+    }
+}
+
+// ── Voice pool ────────────────────────────────────────────────────────────────
+
+/// Simultaneous hits per drum track.  Enough for fast hat rolls to ring into
+/// each other; beyond this the oldest hit is replaced.
+const VOICES_PER_TRACK: usize = 4;
+
+/// Fixed set of voice slots for one track.  Triggering reuses a free slot
+/// (or the oldest one) so the audio thread never allocates for a hit.
+struct VoicePool {
+    slots: [Option<DrumVoice>; VOICES_PER_TRACK],
+}
+
+impl VoicePool {
+    fn new() -> Self {
+        Self { slots: std::array::from_fn(|_| None) }
+    }
+
+    /// Start `voice` in a free slot, replacing the oldest hit if all are busy.
+    fn start(&mut self, voice: DrumVoice) {
+        let slot = match self.slots.iter().position(Option::is_none) {
+            Some(i) => i,
+            None => (0..VOICES_PER_TRACK)
+                .max_by_key(|&i| self.slots[i].as_ref().map_or(0, |v| v.sample_pos))
+                .unwrap_or(0),
+        };
+        self.slots[slot] = Some(voice);
+    }
+
+    /// Sum of all sounding voices; finished voices free their slot.
+    fn next_sample(&mut self) -> f32 {
+        let mut sum = 0.0;
+        for slot in &mut self.slots {
+            if let Some(v) = slot {
+                sum += v.next_sample();
+                if v.is_finished() { *slot = None; }
+            }
+        }
+        sum
+    }
+
+    /// Cut every voice of `kind` (hi-hat choke).
+    fn stop_kind(&mut self, kind: DrumKind) {
+        for slot in &mut self.slots {
+            if slot.as_ref().is_some_and(|v| v.kind == kind) { *slot = None; }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.slots = std::array::from_fn(|_| None);
+    }
 }
 
 // ── Drum machine ──────────────────────────────────────────────────────────────
@@ -232,16 +542,19 @@ pub struct DrumMachine {
     pub current_step: usize,
     pub playing:      bool,
     pub swing:        f32,  // 0.0 = straight, ~0.33 = shuffle, 0.5 = maximum
+    /// Groove template layered over the grid (timing + velocity), if any.
+    pub groove:       Option<Groove>,
+    /// Playback wraps within `loop_start..=loop_end` (whole pattern by default).
+    pub loop_start:   usize,
+    pub loop_end:     usize,
+    /// Order the loop region is walked in (shared by all tracks).
+    pub direction:    Direction,
     /// Master insert effects applied to the summed drum bus output.
     pub fx: EffectChain,
 
     sample_rate: f32,
-    /// Polyphonic voice pool — all currently sounding drum hits.
-    voices: Vec<DrumVoice>,
-    /// Seed advanced before each trigger so every hit has a distinct noise flavour.
-    seed: u32,
-    /// Separate XOR-shift seed used only for probability rolls.
-    prob_seed: u32,
+    /// Sounding drum hits, one fixed-size pool per track (same order as `tracks`).
+    voices: Vec<VoicePool>,
     /// Set to true each sample that a kick fires; cleared by Synth::generate_sample.
     pub kick_triggered: bool,
 }
@@ -249,113 +562,211 @@ pub struct DrumMachine {
 impl DrumMachine {
     pub fn new(sample_rate: f32) -> Self {
         let num_steps = 16;
-        let tracks = DrumKind::ALL.iter().map(|&k| DrumTrack::new(k, num_steps)).collect();
+        let tracks: Vec<DrumTrack> = DrumKind::ALL.iter().map(|&k| DrumTrack::new(k, num_steps)).collect();
+        let voices = tracks.iter().map(|_| VoicePool::new()).collect();
         Self {
             tracks,
             num_steps,
             current_step: 0,
             playing: false,
             swing: 0.0,
+            groove: None,
+            loop_start: 0,
+            loop_end: num_steps - 1,
+            direction: Direction::Forward,
             fx: EffectChain::new(),
             sample_rate,
-            voices: Vec::with_capacity(32),
-            seed: 0xBEEF_CAFE,
-            prob_seed: 0xDEAD_BEEF,
+            voices,
             kick_triggered: false,
         }
     }
 
-    fn samples_per_step(&self, bpm: f32) -> u64 {
-        ((self.sample_rate * 60.0) / (bpm * 4.0)).round() as u64
+    /// Kill every sounding voice (effect state is reset by `Synth::panic`).
+    pub fn panic(&mut self) {
+        self.voices.iter_mut().for_each(VoicePool::clear);
     }
 
-    /// Generate the next audio sample.  Called once per sample from the audio
-    /// thread inside `Synth::generate_sample`, using the shared master clock.
-    pub fn generate_sample(&mut self, bpm: f32, clock: u64) -> f32 {
-        let sps = self.samples_per_step(bpm).max(1);
-        let step_idx = (clock / sps) as usize % self.num_steps;
-        let phase_in = clock % sps;
+    /// Switch to a new output rate.  Sounding hits were sized for the old
+    /// rate, so they are dropped; the insert chains keep their settings.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.panic();
+        self.fx.set_sample_rate(sample_rate);
+        for t in &mut self.tracks { t.fx.set_sample_rate(sample_rate); }
+    }
 
-        // Odd steps are delayed by swing fraction of one step width
-        let swing_offset = if step_idx % 2 == 1 {
-            (self.swing * sps as f32).round() as u64
-        } else {
-            0
-        };
+    /// Generate the next audio frame as `(left, right)`.  Called once per
+    /// sample from the audio thread inside `Synth::generate_frame`, using the
+    /// shared master clock.  Probability rolls and per-hit noise seeds are
+    /// drawn from `rng`.  Tracks `solo` silences fade out like a mute.
+    ///
+    /// Each track runs its own insert chain in mono and is then panned into
+    /// L/R (`pan_gains`); the bus `fx` and the headroom stage run on both
+    /// channels, so all-centre pans stay mono-identical.
+    pub fn generate_sample(&mut self, bpm: f32, clock: u64, rng: &mut Rng, solo: &Solo) -> (f32, f32) {
+        let sps = samples_per_step(self.sample_rate, bpm);
+        let (first, len) = self.play_range();
+        self.current_step = first + self.direction.position(step_at(clock, sps), len);
+        if self.playing {
+            self.fire_due(clock, sps, rng);
+        }
 
-        if self.playing && phase_in == swing_offset {
-            self.current_step = step_idx;
-            self.fire_step();
-        } else {
-            self.current_step = step_idx;
+        // Ramp each track's mute gain instead of hard-gating it, so muting
+        // mid-hit fades the ringing tail out over MUTE_FADE_MS.
+        let fade_step = 1.0 / (MUTE_FADE_MS * 0.001 * self.sample_rate).max(1.0);
+        for (i, t) in self.tracks.iter_mut().enumerate() {
+            let target = if t.muted || solo.mutes(SoloTarget::Drum(i)) { 0.0 } else { 1.0 };
+            if t.fade_gain < target {
+                t.fade_gain = (t.fade_gain + fade_step).min(target);
+            } else if t.fade_gain > target {
+                t.fade_gain = (t.fade_gain - fade_step).max(target);
+            }
         }
 
-        // Mix all active drum voices, apply per-track fx, then sum
-        let mut mix = 0.0f32;
-        for v in &mut self.voices {
-            mix += v.next_sample();
+        // Sum voices per track, run each track's insert chain, then mix.
+        // The mute fade is applied after the chain so effect tails are muted too.
+        let (mut mix_l, mut mix_r) = (0.0f32, 0.0f32);
+        let meter_c = (-1.0 / (METER_RELEASE_MS * 0.001 * self.sample_rate)).exp();
+        for (t, pool) in self.tracks.iter_mut().zip(&mut self.voices) {
+            let x = pool.next_sample();
+            t.meter = (x * t.fade_gain).abs().max(t.meter * meter_c);
+            let y = if t.fx.is_empty() { x } else { t.fx.process(x) } * t.fade_gain;
+            let (gl, gr) = pan_gains(t.pan);
+            mix_l += y * gl;
+            mix_r += y * gr;
         }
-        self.voices.retain(|v| !v.is_finished());
 
         // Master bus fx chain (empty = passthrough)
-        let out = self.fx.process(mix);
+        let (l, r) = self.fx.process_stereo(mix_l, mix_r);
 
         // Gentle headroom scaling + soft clip
-        (out * 0.22).tanh()
+        ((l * 0.22).tanh(), (r * 0.22).tanh())
+    }
+
+    /// Step (if any) that `track` triggers at `clock`.  Odd steps are delayed
+    /// by the swing fraction of one step width; the groove's timing and each
+    /// step's nudge add on top.
+    fn track_due(&self, track: usize, clock: u64, sps: f64) -> Option<usize> {
+        let t = &self.tracks[track];
+        let (first, len) = self.play_range();
+        let order = |k: u64| self.direction.position(k, len);
+        due_step(clock, sps, order, |p| {
+            let step = first + p;
+            self.grid_offset(step, sps) + nudge_samples(t.nudge.get(step).copied().unwrap_or(0), sps)
+        }).map(|k| first + order(k))
+    }
+
+    /// Timing offset of `step` shared by every track, in samples: swing on
+    /// odd steps plus the groove's timing.
+    fn grid_offset(&self, step: usize, sps: f64) -> i64 {
+        swing_samples(step, self.swing, sps) + nudge_samples(self.groove.as_ref().map_or(0, |g| g.offset(step)), sps)
     }
 
-    fn fire_step(&mut self) {
+    /// Step whose (swung) hit time is nearest to `clock`, in play order —
+    /// where a live hit at `clock` belongs in the pattern.
+    pub fn nearest_step(&self, clock: u64, sps: f64) -> usize {
+        let (first, len) = self.play_range();
+        let k = step_at(clock, sps);
+        (k.saturating_sub(1)..=k + 1)
+            .map(|k| {
+                let step = first + self.direction.position(k, len);
+                let at = step_start(k, sps) as i64 + self.grid_offset(step, sps);
+                (step, (at - clock as i64).abs())
+            })
+            .min_by_key(|&(_, dist)| dist)
+            .map_or(first, |(step, _)| step)
+    }
+
+    /// Arm `step` of `track` at full probability if it is empty; returns
+    /// true if the pattern changed.
+    pub fn record_hit(&mut self, track: usize, step: usize) -> bool {
+        match self.tracks.get_mut(track).and_then(|t| t.steps.get_mut(step)) {
+            Some(s) if *s == 0 => { *s = 100; true }
+            _ => false,
+        }
+    }
+
+    /// Sounding hits per track (same order as `tracks`), for the state dump.
+    pub fn active_voices(&self) -> Vec<usize> {
+        self.voices.iter().map(|p| p.slots.iter().filter(|s| s.is_some()).count()).collect()
+    }
+
+    /// First step and length of the loop region (whole pattern if invalid).
+    pub fn play_range(&self) -> (usize, usize) {
+        loop_span(self.loop_start, self.loop_end, self.num_steps).unwrap_or((0, self.num_steps))
+    }
+
+    /// Move the loop start (or end) to `step`.
+    pub fn set_loop_point(&mut self, step: usize, is_end: bool) {
+        set_loop_point(&mut self.loop_start, &mut self.loop_end, self.num_steps, step, is_end);
+    }
+
+    fn fire_due(&mut self, clock: u64, sps: f64, rng: &mut Rng) {
+        let step_on = |dm: &Self, ti: usize| {
+            let t = &dm.tracks[ti];
+            if t.muted { return None; }
+            let step = dm.track_due(ti, clock, sps)?;
+            let prob = t.steps.get(step).copied().unwrap_or(0);
+            (prob > 0).then_some((step, prob))
+        };
+
         // Hi-hat choke: kill any ringing open hat when a closed hat fires.
-        let closed_fires = self.tracks.iter().any(|t| {
-            t.kind == DrumKind::ClosedHat
-                && !t.muted
-                && t.steps.get(self.current_step).copied().unwrap_or(0) > 0
+        let closed_fires = (0..self.tracks.len()).any(|ti| {
+            self.tracks[ti].kind == DrumKind::ClosedHat && step_on(self, ti).is_some()
         });
         if closed_fires {
-            self.voices.retain(|v| v.kind != DrumKind::OpenHat);
+            self.choke_open_hats();
         }
 
-        for track in &mut self.tracks {
-            if track.muted { continue; }
-            let prob = track.steps.get(self.current_step).copied().unwrap_or(0);
-            if prob == 0 { continue; }
+        for ti in 0..self.tracks.len() {
+            let Some((step, prob)) = step_on(self, ti) else { continue };
 
             // Probability roll
-            if prob < 100 {
-                self.prob_seed ^= self.prob_seed << 13;
-                self.prob_seed ^= self.prob_seed >> 17;
-                self.prob_seed ^= self.prob_seed << 5;
-                let roll = (self.prob_seed % 100) as u8;
-                if roll >= prob { continue; }
-            }
+            if !rng.chance(prob) { continue; }
 
             // Unique noise seed per trigger for timbral variation
-            self.seed = self.seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
-            if track.kind == DrumKind::Kick {
+            let seed = rng.next_u32();
+            let t = &self.tracks[ti];
+            let (kind, mut volume) = (t.kind, t.volume);
+            if let Some(g) = &self.groove { volume *= g.gain(step); }
+            let decay = t.decay.get(step).copied().unwrap_or(DEFAULT_DECAY);
+            if kind == DrumKind::Kick {
                 self.kick_triggered = true;
             }
-            self.voices.push(DrumVoice::new(track.kind, self.sample_rate, self.seed, track.volume));
+            let stretch = decay as f32 / DEFAULT_DECAY as f32;
+            let voice = DrumVoice::new(kind, self.sample_rate, seed, volume, stretch)
+                .with_tune(t.tune)
+                .with_sample(t.sample.clone(), t.reverse, t.stretch_ratio(sps, self.sample_rate));
+            self.voices[ti].start(voice);
         }
     }
 
-    /// Immediately trigger a drum track (live preview / keyboard playing).
-    /// Fully polyphonic — does not stop any already-playing voices.
-    pub fn trigger_now(&mut self, track_idx: usize) {
+    fn choke_open_hats(&mut self) {
+        for pool in &mut self.voices { pool.stop_kind(DrumKind::OpenHat); }
+    }
+
+    /// Immediately trigger a drum track (live preview / keyboard playing) at
+    /// `velocity` (0.0–1.0, scales the track volume).  Earlier hits keep
+    /// ringing, up to `VOICES_PER_TRACK` per track.
+    pub fn trigger_now(&mut self, track_idx: usize, bpm: f32, velocity: f32, rng: &mut Rng) {
         let Some(track) = self.tracks.get(track_idx) else { return };
         if track.muted { return; }
+        let (kind, volume) = (track.kind, track.volume * velocity.clamp(0.0, 1.0));
+        let ratio = track.stretch_ratio(samples_per_step(self.sample_rate, bpm), self.sample_rate);
+        let voice = DrumVoice::new(kind, self.sample_rate, rng.next_u32(), volume, 1.0)
+            .with_tune(track.tune)
+            .with_sample(track.sample.clone(), track.reverse, ratio);
 
-        if track.kind == DrumKind::ClosedHat {
-            self.voices.retain(|v| v.kind != DrumKind::OpenHat);
+        if kind == DrumKind::ClosedHat {
+            self.choke_open_hats();
         }
-
-        self.seed = self.seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
-        self.voices.push(DrumVoice::new(track.kind, self.sample_rate, self.seed, track.volume));
+        self.voices[track_idx].start(voice);
     }
 
     pub fn toggle_play(&mut self) {
         self.playing = !self.playing;
         if !self.playing {
-            self.voices.clear();
+            self.panic();
         }
     }
 
@@ -375,6 +786,21 @@ impl DrumMachine {
         }
     }
 
+    /// Flip a track's sample direction.  Returns the new setting.
+    pub fn toggle_reverse(&mut self, track: usize) -> bool {
+        let Some(t) = self.tracks.get_mut(track) else { return false };
+        t.reverse = !t.reverse;
+        t.reverse
+    }
+
+    /// Time-stretch ratio of a track's sample at `bpm` (`None` when it has
+    /// no sample or no fit length).
+    pub fn stretch_ratio(&self, track: usize, bpm: f32) -> Option<f64> {
+        let t = self.tracks.get(track)?;
+        (t.sample.is_some() && t.fit_steps.is_some())
+            .then(|| t.stretch_ratio(samples_per_step(self.sample_rate, bpm), self.sample_rate))
+    }
+
     pub fn toggle_mute(&mut self, track: usize) {
         if let Some(t) = self.tracks.get_mut(track) {
             t.muted = !t.muted;
@@ -393,16 +819,48 @@ impl DrumMachine {
         }
     }
 
-    pub fn cycle_num_steps(&mut self) {
+    /// Raise or lower a track's tune by a semitone.  Returns the new tune.
+    pub fn tune_step(&mut self, track: usize, up: bool) -> i8 {
+        let Some(t) = self.tracks.get_mut(track) else { return 0 };
+        t.tune = (t.tune + if up { 1 } else { -1 }).clamp(-MAX_TUNE, MAX_TUNE);
+        t.tune
+    }
+
+    pub fn track_pan(&mut self, track: usize, delta: f32) {
+        if let Some(t) = self.tracks.get_mut(track) {
+            t.pan = ((t.pan + delta) * 20.0).round() / 20.0;
+            t.pan = t.pan.clamp(-1.0, 1.0);
+        }
+    }
+
+    /// Apply the auto-spread layout, or centre every track if it is already
+    /// applied.  Returns true if the spread is now active.
+    pub fn toggle_auto_pan(&mut self) -> bool {
+        let applied = self.tracks.iter().all(|t| t.pan == t.kind.auto_pan());
+        for t in &mut self.tracks {
+            t.pan = if applied { 0.0 } else { t.kind.auto_pan() };
+        }
+        !applied
+    }
+
+    /// Step to the next pattern length (8→16→24→32→8).  With `lock` the
+    /// hits keep their place in the bar (`DrumTrack::remap_steps`); without
+    /// it they keep their step indices and the end is cut or padded.
+    pub fn cycle_num_steps(&mut self, lock: bool) {
         let next = match self.num_steps {
             8  => 16,
             16 => 24,
             24 => 32,
             _  => 8,
         };
+        let old = self.num_steps;
+        resize_loop(&mut self.loop_start, &mut self.loop_end, old, next);
         self.num_steps = next;
         for t in &mut self.tracks {
+            if lock { t.remap_steps(old, next); }
             t.steps.resize(next, 0);
+            t.nudge.resize(next, 0);
+            t.decay.resize(next, DEFAULT_DECAY);
         }
         if self.current_step >= next {
             self.current_step = 0;
@@ -426,6 +884,53 @@ impl DrumMachine {
         }
     }
 
+    /// Shift a step's nudge by `delta` percent.  Returns the new value.
+    pub fn nudge_step(&mut self, track: usize, step: usize, delta: i8) -> i8 {
+        let Some(t) = self.tracks.get_mut(track) else { return 0 };
+        if t.nudge.len() < t.steps.len() { t.nudge.resize(t.steps.len(), 0); }
+        let Some(n) = t.nudge.get_mut(step) else { return 0 };
+        *n = n.saturating_add(delta).clamp(-MAX_NUDGE, MAX_NUDGE);
+        *n
+    }
+
+    /// Lengthen (or shorten) a step's decay by one `DECAY_STEP`, within
+    /// `MIN_DECAY..=MAX_DECAY`.  Returns the new value.
+    pub fn decay_step(&mut self, track: usize, step: usize, longer: bool) -> u8 {
+        let Some(t) = self.tracks.get_mut(track) else { return DEFAULT_DECAY };
+        if t.decay.len() < t.steps.len() { t.decay.resize(t.steps.len(), DEFAULT_DECAY); }
+        let Some(d) = t.decay.get_mut(step) else { return DEFAULT_DECAY };
+        *d = if longer { d.saturating_add(DECAY_STEP) } else { d.saturating_sub(DECAY_STEP) }
+            .clamp(MIN_DECAY, MAX_DECAY);
+        *d
+    }
+
+    /// Rotate every track's steps, nudges and decays one step, wrapping.
+    pub fn shift(&mut self, right: bool) {
+        let n = self.num_steps;
+        for t in &mut self.tracks {
+            rotate(&mut t.steps, n, right);
+            rotate(&mut t.nudge, n, right);
+            rotate(&mut t.decay, n, right);
+        }
+    }
+
+    /// Toggle each step of the tracks that have hits, with probability `pct`
+    /// percent, so a pattern drifts rather than gaining parts on empty
+    /// tracks.  Returns how many steps flipped.
+    pub fn mutate(&mut self, rng: &mut Rng, pct: u8) -> usize {
+        let n = self.num_steps;
+        let mut flipped = 0;
+        for t in &mut self.tracks {
+            if t.steps.iter().take(n).all(|&s| s == 0) { continue; }
+            for s in t.steps.iter_mut().take(n) {
+                if !rng.chance(pct) { continue; }
+                *s = if *s == 0 { 100 } else { 0 };
+                flipped += 1;
+            }
+        }
+        flipped
+    }
+
     pub fn euclidean_fill(&mut self, track: usize, k: usize) {
         let n = self.num_steps;
         if let Some(t) = self.tracks.get_mut(track) {
@@ -433,9 +938,9 @@ impl DrumMachine {
             // Generate canonical Euclidean (Bresenham) pattern.
             let mut pattern = vec![0u8; n];
             let mut bucket = 0usize;
-            for i in 0..n {
+            for cell in pattern.iter_mut() {
                 bucket += k;
-                if bucket >= n { bucket -= n; pattern[i] = 100; }
+                if bucket >= n { bucket -= n; *cell = 100; }
             }
             // Rotate by the per-track offset so successive presses produce different results.
             let rot = t.euclid_rotation % n;
@@ -444,3 +949,169 @@ impl DrumMachine {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::InsertKind;
+
+    const SR: f32 = 48_000.0;
+
+    /// Drive `dm` for `n` samples at 120 BPM from `clock`, returning the L/R frames.
+    fn run(dm: &mut DrumMachine, clock: u64, n: u64) -> Vec<(f32, f32)> {
+        let (mut rng, solo) = (Rng::new(1), Solo::default());
+        (clock..clock + n).map(|c| dm.generate_sample(120.0, c, &mut rng, &solo)).collect()
+    }
+
+    /// Track 0's fade gain over the next `n` samples.
+    fn gains(dm: &mut DrumMachine, n: u64) -> Vec<f32> {
+        (0..n).map(|c| { run(dm, c, 1); dm.tracks[0].fade_gain }).collect()
+    }
+
+    #[test]
+    fn mute_ramps_over_the_fade_length() {
+        let mut dm = DrumMachine::new(SR);
+        let len = (MUTE_FADE_MS * 0.001 * SR) as usize;
+        let max_step = 1.0 / len as f32 + 1e-6;
+
+        dm.toggle_mute(0);
+        let down = gains(&mut dm, len as u64 + 8);
+        let at = down.iter().position(|&g| g == 0.0).expect("mute never reached silence");
+        assert!((len - 1..=len).contains(&at), "fade out took {} samples, want {}", at + 1, len);
+        assert!(down.windows(2).all(|w| w[0] - w[1] <= max_step), "gain jumped");
+
+        dm.toggle_mute(0);
+        let up = gains(&mut dm, len as u64 + 8);
+        let at = up.iter().position(|&g| g == 1.0).expect("unmute never reached full level");
+        assert!((len - 1..=len).contains(&at), "fade in took {} samples, want {}", at + 1, len);
+        assert!(up.windows(2).all(|w| w[1] - w[0] <= max_step), "gain jumped");
+    }
+
+    #[test]
+    fn muting_a_ringing_hit_fades_its_tail() {
+        let mut dm = DrumMachine::new(SR);
+        dm.trigger_now(3, 120.0, 1.0, &mut Rng::new(1));            // open hat rings
+        run(&mut dm, 0, 200);
+        dm.toggle_mute(3);
+        let tail = run(&mut dm, 200, 2000);
+        let len = (MUTE_FADE_MS * 0.001 * SR) as usize;
+        assert!(tail[..len / 2].iter().any(|&(l, _)| l.abs() > 1e-3), "tail was cut, not faded");
+        assert!(tail[len + 1..].iter().all(|&(l, r)| l == 0.0 && r == 0.0), "muted track still audible");
+    }
+
+    #[test]
+    fn hat_roll_reuses_the_oldest_slot() {
+        let mut dm = DrumMachine::new(SR);
+        let mut rng = Rng::new(1);
+        for i in 0..VOICES_PER_TRACK as u64 + 3 {
+            dm.trigger_now(3, 120.0, 1.0, &mut rng);
+            run(&mut dm, i * 100, 100);
+            assert!(dm.active_voices()[3] <= VOICES_PER_TRACK, "pool grew past its slots");
+        }
+        // Seven hits 100 samples apart: only the last four are still ringing.
+        let mut ages: Vec<_> = dm.voices[3].slots.iter().flatten().map(|v| v.sample_pos).collect();
+        ages.sort_unstable();
+        assert_eq!(ages, [100, 200, 300, 400]);
+    }
+
+    #[test]
+    fn armed_step_fires_on_its_boundary() {
+        let mut dm = DrumMachine::new(SR);
+        dm.toggle_step(0, 4);
+        dm.playing = true;
+        let at = step_start(4, samples_per_step(SR, 120.0));
+        run(&mut dm, 0, at);
+        assert!(!dm.kick_triggered && dm.active_voices()[0] == 0, "kick fired early");
+        run(&mut dm, at, 1);
+        assert!(dm.kick_triggered && dm.active_voices()[0] == 1, "kick missed its step");
+
+        dm.toggle_step(0, 4);
+        assert_eq!(dm.tracks[0].steps[4], 0, "second toggle should clear the step");
+    }
+
+    /// A drum machine with kick hits on 0/4/8/12 and a hit on 14 nudged +20%
+    /// with a short decay.
+    fn four_on_the_floor() -> DrumMachine {
+        let mut dm = DrumMachine::new(SR);
+        for s in [0, 4, 8, 12, 14] { dm.toggle_step(0, s); }
+        dm.tracks[0].nudge[14] = 20;
+        dm.tracks[0].decay[14] = 50;
+        dm
+    }
+
+    /// Steps of track 0 that hold a hit, with their nudge and decay.
+    fn hits(dm: &DrumMachine) -> Vec<(usize, i8, u8)> {
+        let t = &dm.tracks[0];
+        (0..dm.num_steps).filter(|&s| t.steps[s] > 0).map(|s| (s, t.nudge[s], t.decay[s])).collect()
+    }
+
+    #[test]
+    fn length_change_without_lock_keeps_step_indices() {
+        let mut dm = four_on_the_floor();
+        dm.cycle_num_steps(false);
+        assert_eq!(dm.num_steps, 24);
+        assert_eq!(hits(&dm), [(0, 0, 100), (4, 0, 100), (8, 0, 100), (12, 0, 100), (14, 20, 50)]);
+        assert_eq!(dm.tracks[0].steps.len(), 24);
+    }
+
+    #[test]
+    fn length_lock_keeps_hits_at_their_place_in_the_bar() {
+        let mut dm = four_on_the_floor();
+        dm.cycle_num_steps(true);
+        assert_eq!(hits(&dm), [(0, 0, 100), (6, 0, 100), (12, 0, 100), (18, 0, 100), (21, 30, 50)]);
+
+        // 24 → 32 → 8: two hits land on step 0; the more probable one stays.
+        let mut dm = DrumMachine::new(SR);
+        dm.cycle_num_steps(false);
+        dm.tracks[0].steps[0] = 25;
+        dm.tracks[0].steps[1] = 75;
+        dm.cycle_num_steps(true);
+        dm.cycle_num_steps(true);
+        assert_eq!(dm.num_steps, 8);
+        assert_eq!(dm.tracks[0].steps[0], 75);
+        assert_eq!(dm.tracks[0].nudge[0], 33);
+    }
+
+    /// Peak of the left and right channels of a snare hit at `pan`, through
+    /// whatever bus inserts `dm` carries.
+    fn panned_snare(mut dm: DrumMachine, pan: f32) -> (f32, f32) {
+        dm.tracks[1].pan = pan;
+        dm.trigger_now(1, 120.0, 1.0, &mut Rng::new(1));
+        run(&mut dm, 0, 4800).iter()
+            .fold((0.0, 0.0), |(l, r), &(x, y)| (f32::max(l, x.abs()), f32::max(r, y.abs())))
+    }
+
+    #[test]
+    fn pan_moves_a_hit_between_the_channels() {
+        let (l, r) = panned_snare(DrumMachine::new(SR), 0.0);
+        assert!(l > 0.01 && l == r, "centred hit is not mono: {} / {}", l, r);
+        let (l, r) = panned_snare(DrumMachine::new(SR), 1.0);
+        assert!(l == 0.0 && r > 0.01, "hard right still in the left: {} / {}", l, r);
+        let (l, r) = panned_snare(DrumMachine::new(SR), -1.0);
+        assert!(r == 0.0 && l > 0.01, "hard left still in the right: {} / {}", l, r);
+    }
+
+    #[test]
+    fn a_bus_filter_keeps_a_hard_pan_out_of_the_other_channel() {
+        let filtered = || {
+            let mut dm = DrumMachine::new(SR);
+            let mut f = InsertKind::Filter.build(SR);
+            f.set_param("cutoff", 400.0);
+            dm.fx.push(f);
+            dm
+        };
+        let (l, r) = panned_snare(filtered(), 1.0);
+        assert!(l == 0.0 && r > 0.01, "hard right leaked left through the bus filter: {} / {}", l, r);
+        let (l, r) = panned_snare(filtered(), 0.0);
+        assert!(l > 0.01 && l == r, "filtered centred hit is not mono: {} / {}", l, r);
+    }
+
+    #[test]
+    fn auto_pan_toggles_the_spread_and_back() {
+        let mut dm = DrumMachine::new(SR);
+        assert!(dm.toggle_auto_pan());
+        assert!(dm.tracks.iter().all(|t| t.pan == t.kind.auto_pan()));
+        assert!(!dm.toggle_auto_pan());
+        assert!(dm.tracks.iter().all(|t| t.pan == 0.0));
+    }
+}
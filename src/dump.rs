@@ -0,0 +1,202 @@
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+
+use crate::drums::DrumKind;
+use crate::sequencer::{Direction, Sequencer};
+use crate::synth::{ChordType, EnvCurve, Patch, Retrigger, Synth, Voice, WaveType};
+
+// ── State dump ────────────────────────────────────────────────────────────────
+//
+// A hidden key writes everything that matters for a stuck-note or timing bug
+// report to a log file.  `SynthState::capture` copies the audio-side state
+// while the UI holds the synth lock (plain clones, no formatting); the
+// `Debug` text is built and written after the lock is released, so the audio
+// thread only ever waits for the copy.
+
+/// One melodic bus.
+#[derive(Debug)]
+#[allow(dead_code)] // read only through `Debug`
+pub struct BusState {
+    pub wave:    WaveType,
+    pub adsr:    (f32, f32, f32, f32),
+    pub volume:  f32,
+    pub chord:   ChordType,
+    /// Sounding voices by note, sorted.
+    pub voices:  Vec<(u8, Voice)>,
+    /// Replaced voices still fading out.
+    pub fading:  Vec<Voice>,
+    pub inserts: Vec<&'static str>,
+    /// The bus's step sequencers (synth 1 lanes; synth 2's one sequencer).
+    pub sequencers: Vec<Sequencer>,
+    /// Each sequencer's own sound (`None` = the bus's).
+    pub patches: Vec<Option<Patch>>,
+}
+
+#[derive(Debug)]
+#[allow(dead_code)] // read only through `Debug`
+pub struct DrumTrackState {
+    pub kind:    DrumKind,
+    pub steps:   Vec<u8>,
+    pub muted:   bool,
+    pub volume:  f32,
+    pub pan:     f32,
+    pub tune:    i8,
+    pub nudge:   Vec<i8>,
+    pub decay:   Vec<u8>,
+    pub inserts: Vec<&'static str>,
+    /// Drum hits currently sounding on this track.
+    pub voices:  usize,
+}
+
+#[derive(Debug)]
+#[allow(dead_code)] // read only through `Debug`
+pub struct DrumState {
+    pub playing:      bool,
+    pub num_steps:    usize,
+    pub current_step: usize,
+    pub swing:        f32,
+    pub groove:       Option<String>,
+    pub loop_pts:     (usize, usize),
+    pub direction:    Direction,
+    pub inserts:      Vec<&'static str>,
+    pub tracks:       Vec<DrumTrackState>,
+}
+
+/// Audio-side state, copied under the synth lock.
+#[derive(Debug)]
+#[allow(dead_code)] // read only through `Debug`
+pub struct SynthState {
+    pub sample_rate:   f32,
+    pub bpm:           f32,
+    pub seq_swing:     f32,
+    pub master_clock:  u64,
+    pub tempo_ramp:    Option<(f32, f32)>,
+    pub time_sig:      (u8, u8),
+    pub seed:          u64,
+    pub retrigger:     Retrigger,
+    pub env_curves:    (EnvCurve, EnvCurve),
+    pub fm:            Option<(f32, f32)>,
+    pub anti_click_ms: f32,
+    pub max_voices:    usize,
+    pub voice_steals:  u64,
+    pub wave_trims:    [f32; 4],
+    pub drone:         (bool, u8),
+    pub tuner:         Option<String>,
+    pub step_click:    bool,
+    pub freeze:        bool,
+    pub synth1:        BusState,
+    pub synth2:        BusState,
+    pub drums:         DrumState,
+    pub master_inserts: Vec<&'static str>,
+    pub sidechain_gain: f32,
+    pub clip_peak:     f32,
+    pub limiter_gain:  f32,
+    pub clip_latch:    bool,
+    pub auto_gain:     bool,
+    pub voice_spread:  f32,
+    pub mix_peak:      f32,
+    pub mix_gain:      f32,
+}
+
+fn sorted_voices(voices: &std::collections::HashMap<u8, Voice>) -> Vec<(u8, Voice)> {
+    let mut v: Vec<(u8, Voice)> = voices.iter().map(|(&n, v)| (n, v.clone())).collect();
+    v.sort_by_key(|&(n, _)| n);
+    v
+}
+
+impl SynthState {
+    pub fn capture(s: &Synth) -> Self {
+        let dm = &s.drum_machine;
+        let voices = dm.active_voices();
+        let tracks = dm.tracks.iter().enumerate().map(|(i, t)| DrumTrackState {
+            kind:    t.kind,
+            steps:   t.steps.clone(),
+            muted:   t.muted,
+            volume:  t.volume,
+            pan:     t.pan,
+            tune:    t.tune,
+            nudge:   t.nudge.clone(),
+            decay:   t.decay.clone(),
+            inserts: t.fx.names(),
+            voices:  voices.get(i).copied().unwrap_or(0),
+        }).collect();
+        Self {
+            sample_rate:   s.sample_rate,
+            bpm:           s.bpm,
+            seq_swing:     s.seq_swing,
+            master_clock:  s.master_clock,
+            tempo_ramp:    s.tempo_ramp.as_ref().map(|r| (r.from, r.to)),
+            time_sig:      s.time_sig,
+            seed:          s.rng.seed(),
+            retrigger:     s.retrigger,
+            env_curves:    (s.attack_curve, s.release_curve),
+            fm:            s.voice_globals().fm,
+            anti_click_ms: s.anti_click_ms,
+            max_voices:    s.max_voices,
+            voice_steals:  s.voice_steals,
+            wave_trims:    s.wave_trims,
+            drone:         (s.drone.on, s.drone.note),
+            tuner:         s.tuner.on.then(|| s.tuner.label()),
+            step_click:    s.step_click.on,
+            freeze:        s.freeze.active,
+            synth1: BusState {
+                wave:    s.wave_type,
+                adsr:    (s.attack, s.decay, s.sustain, s.release),
+                volume:  s.volume,
+                chord:   s.chord1,
+                voices:  sorted_voices(&s.voices),
+                fading:  s.fading.clone(),
+                inserts: s.fx.names(),
+                sequencers: s.lanes.clone(),
+                patches:    s.lane_patches.clone(),
+            },
+            synth2: BusState {
+                wave:    s.wave_type2,
+                adsr:    (s.attack2, s.decay2, s.sustain2, s.release2),
+                volume:  s.volume2,
+                chord:   s.chord2,
+                voices:  sorted_voices(&s.voices2),
+                fading:  s.fading2.clone(),
+                inserts: s.fx2.names(),
+                sequencers: vec![s.sequencer2.clone()],
+                patches:    vec![None],
+            },
+            drums: DrumState {
+                playing:      dm.playing,
+                num_steps:    dm.num_steps,
+                current_step: dm.current_step,
+                swing:        dm.swing,
+                groove:       dm.groove.as_ref().map(|g| g.name.clone()),
+                loop_pts:     (dm.loop_start, dm.loop_end),
+                direction:    dm.direction,
+                inserts:      dm.fx.names(),
+                tracks,
+            },
+            master_inserts: s.master_fx.names(),
+            sidechain_gain: s.sidechain.gain,
+            clip_peak:      s.clipper.peak,
+            limiter_gain:   s.limiter.gain,
+            clip_latch:     s.clip_latch,
+            auto_gain:      s.mix_meter.auto_gain,
+            voice_spread:   s.voice_spread,
+            mix_peak:       s.mix_meter.peak,
+            mix_gain:       s.mix_meter.gain,
+        }
+    }
+}
+
+/// Write the dump sections (`(title, value)` pairs, pretty-printed with
+/// `{:#?}`) to `tuibeat-state-<unix time>.log` in the working directory.
+pub fn write(sections: &[(&str, &dyn Debug)]) -> Result<PathBuf> {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let path = PathBuf::from(format!("tuibeat-state-{}.log", secs));
+    let mut text = format!("tuibeat {} state dump ({})\n", env!("CARGO_PKG_VERSION"), secs);
+    for (title, value) in sections {
+        text.push_str(&format!("\n── {} ──\n{:#?}\n", title, value));
+    }
+    std::fs::write(&path, text).with_context(|| format!("Cannot write {}", path.display()))?;
+    Ok(path)
+}
@@ -1,30 +1,64 @@
 use std::f32::consts::PI;
 
+use crate::smooth::Smoothed;
+
 /// Mono audio effect: one sample in, one sample out.
 #[allow(dead_code)]
 ///
 /// All implementations must be `Send` so they can live inside the audio thread
-/// (behind `Arc<Mutex<Synth>>`).  Stereo can be modelled as two independent
-/// mono effects or as a future specialisation — that's a later concern.
+/// (behind `Arc<Mutex<Synth>>`).  Stereo is two independent mono instances,
+/// one per channel (see [`EffectChain::process_stereo`]).
 pub trait AudioEffect: Send {
     fn process(&mut self, sample: f32) -> f32;
     fn name(&self) -> &'static str;
     /// Reset all internal state (clear delay lines, reset envelopes, etc.).
     fn reset(&mut self);
+    /// A second instance with the same parameters and state.
+    fn boxed_clone(&self) -> Box<dyn AudioEffect>;
+
+    /// Current user-facing parameters as `(name, value)` pairs.  Used to
+    /// serialise an effect generically (racks); empty = nothing to save.
+    fn params(&self) -> Vec<(&'static str, f32)> { Vec::new() }
+    /// Set one parameter by name, clamped to its valid range.  Unknown names
+    /// are ignored so older rack files keep loading.
+    fn set_param(&mut self, _name: &str, _value: f32) {}
+    /// Adapt to a new output sample rate: resize delay lines and recompute
+    /// rate-dependent coefficients, keeping the user parameters.  Effects
+    /// with no time constants keep the default no-op.
+    fn set_sample_rate(&mut self, _sample_rate: f32) {}
+}
+
+/// Stereo audio effect: one (left, right) frame in, one frame out.
+///
+/// Used on the master bus after the mono buses have been summed to stereo.
+/// Same `Send` requirement as [`AudioEffect`].
+pub trait StereoEffect: Send {
+    fn process(&mut self, left: f32, right: f32) -> (f32, f32);
+    #[allow(dead_code)]
+    fn name(&self) -> &'static str;
+    /// Reset all internal state.
+    #[allow(dead_code)]
+    fn reset(&mut self);
 }
 
-/// A serial chain of effects applied to a mono signal.
+/// A serial chain of effects applied to a mono signal, or to a stereo one
+/// with `process_stereo`.
 ///
 /// When the chain is empty the audio passes through completely unchanged,
 /// so there is zero CPU overhead until effects are actually inserted.
 pub struct EffectChain {
-    pub effects: Vec<Box<dyn AudioEffect>>,
+    effects: Vec<Box<dyn AudioEffect>>,
+    /// Right-channel twins of `effects` (same order and settings), kept in
+    /// step by every change to the chain.  The left channel is `effects`.
+    right: Vec<Box<dyn AudioEffect>>,
+    /// Name of the rack this chain was recalled from; cleared by `push`/`pop`.
+    pub rack: Option<String>,
 }
 
 #[allow(dead_code)]
 impl EffectChain {
     pub fn new() -> Self {
-        Self { effects: Vec::new() }
+        Self { effects: Vec::new(), right: Vec::new(), rack: None }
     }
 
     #[inline]
@@ -35,15 +69,171 @@ impl EffectChain {
         self.effects.iter_mut().fold(sample, |s, fx| fx.process(s))
     }
 
+    /// Process one `(left, right)` frame, each channel through its own
+    /// instance of every insert.  A centred signal (`left == right`) comes
+    /// out exactly as `process` would make it.
+    #[inline]
+    pub fn process_stereo(&mut self, left: f32, right: f32) -> (f32, f32) {
+        if self.effects.is_empty() {
+            return (left, right);
+        }
+        (self.effects.iter_mut().fold(left, |s, fx| fx.process(s)),
+         self.right.iter_mut().fold(right, |s, fx| fx.process(s)))
+    }
+
+    /// The inserts in processing order.
+    pub fn effects(&self) -> &[Box<dyn AudioEffect>] {
+        &self.effects
+    }
+
+    /// Replace every insert (a rack recall or snapshot restore).  The
+    /// right-channel twins are cloned from `effects`; `rack` is left as is.
+    pub fn set_effects(&mut self, effects: Vec<Box<dyn AudioEffect>>) {
+        self.right = effects.iter().map(|fx| fx.boxed_clone()).collect();
+        self.effects = effects;
+    }
+
     pub fn is_empty(&self) -> bool {
         self.effects.is_empty()
     }
 
     pub fn reset_all(&mut self) {
-        for fx in &mut self.effects {
+        for fx in self.effects.iter_mut().chain(&mut self.right) {
             fx.reset();
         }
     }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        for fx in self.effects.iter_mut().chain(&mut self.right) {
+            fx.set_sample_rate(sample_rate);
+        }
+    }
+
+    /// Append an effect to the end of the chain.
+    pub fn push(&mut self, fx: Box<dyn AudioEffect>) {
+        self.right.push(fx.boxed_clone());
+        self.effects.push(fx);
+        self.rack = None;
+    }
+
+    /// Remove and return the last effect in the chain.
+    pub fn pop(&mut self) -> Option<Box<dyn AudioEffect>> {
+        self.rack = None;
+        self.right.pop();
+        self.effects.pop()
+    }
+
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    /// Effect names in processing order (for display).
+    pub fn names(&self) -> Vec<&'static str> {
+        self.effects.iter().map(|fx| fx.name()).collect()
+    }
+}
+
+/// Maximum number of inserts the UI allows on one chain.
+pub const MAX_INSERTS: usize = 4;
+
+// ── Insert catalogue ──────────────────────────────────────────────────────────
+
+/// Effects that can be added to an `EffectChain` from the Effects panel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InsertKind { Filter, Distortion, AutoWah, NoiseGate, Reverb, Delay }
+
+impl InsertKind {
+    pub const ALL: [InsertKind; 6] = [
+        Self::Filter, Self::Distortion, Self::AutoWah, Self::NoiseGate, Self::Reverb, Self::Delay,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Filter     => "Filter",
+            Self::Distortion => "Distortion",
+            Self::AutoWah    => "AutoWah",
+            Self::NoiseGate  => "NoiseGate",
+            Self::Reverb     => "Reverb",
+            Self::Delay      => "Delay",
+        }
+    }
+
+    /// Look up a kind by the `AudioEffect::name()` of its instances.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|k| k.name() == name)
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&k| k == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(self) -> Self {
+        let i = Self::ALL.iter().position(|&k| k == self).unwrap_or(0);
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Build an enabled instance with default parameters.  Reverb and Delay are
+    /// wet-only send effects, so they are wrapped in `DryWet` to act as inserts.
+    pub fn build(self, sample_rate: f32) -> Box<dyn AudioEffect> {
+        match self {
+            Self::Filter => {
+                let mut f = BiquadFilter::new(sample_rate);
+                f.enabled = true;
+                Box::new(f)
+            }
+            Self::Distortion => {
+                let mut d = Distortion::new();
+                d.enabled = true;
+                Box::new(d)
+            }
+            Self::AutoWah   => Box::new(AutoWah::new(sample_rate)),
+            Self::NoiseGate => Box::new(NoiseGate::new(sample_rate)),
+            Self::Reverb => {
+                let mut r = Reverb::new(sample_rate);
+                r.enabled = true;
+                Box::new(DryWet::new(Box::new(r)))
+            }
+            Self::Delay => {
+                let mut d = Delay::new(sample_rate);
+                d.enabled = true;
+                Box::new(DryWet::new(Box::new(d)))
+            }
+        }
+    }
+}
+
+// ── Dry/wet wrapper ───────────────────────────────────────────────────────────
+
+/// Adapts a wet-only effect (Reverb, Delay) for use as a serial insert:
+/// the output is the dry input plus the wrapped effect's wet signal.
+pub struct DryWet {
+    pub inner: Box<dyn AudioEffect>,
+}
+
+impl DryWet {
+    pub fn new(inner: Box<dyn AudioEffect>) -> Self {
+        Self { inner }
+    }
+}
+
+impl AudioEffect for DryWet {
+    #[inline]
+    fn process(&mut self, sample: f32) -> f32 {
+        sample + self.inner.process(sample)
+    }
+
+    fn name(&self) -> &'static str { self.inner.name() }
+
+    fn reset(&mut self) { self.inner.reset(); }
+
+    fn params(&self) -> Vec<(&'static str, f32)> { self.inner.params() }
+
+    fn set_param(&mut self, name: &str, value: f32) { self.inner.set_param(name, value); }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) { self.inner.set_sample_rate(sample_rate); }
+
+    fn boxed_clone(&self) -> Box<dyn AudioEffect> { Box::new(DryWet::new(self.inner.boxed_clone())) }
 }
 
 impl Default for EffectChain {
@@ -54,6 +244,7 @@ impl Default for EffectChain {
 
 // ── Freeverb helpers (private) ────────────────────────────────────────────────
 
+#[derive(Clone)]
 struct CombFilter {
     buf: Vec<f32>,
     pos: usize,
@@ -82,6 +273,7 @@ impl CombFilter {
     fn set_damp(&mut self, v: f32) { self.damp1 = v; self.damp2 = 1.0 - v; }
 }
 
+#[derive(Clone)]
 struct AllpassFilter {
     buf: Vec<f32>,
     pos: usize,
@@ -102,8 +294,19 @@ impl AllpassFilter {
     }
 }
 
-// ── Reverb (Freeverb: 8 comb + 4 allpass, tuned for 44100 Hz) ────────────────
+// ── Reverb (Freeverb: 8 comb + 4 allpass, tuned at 44100 Hz, rate-scaled) ───
+
+/// Freeverb delay lengths in samples at `REVERB_TUNING_RATE`.
+const COMB_SIZES:    [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+const ALLPASS_SIZES: [usize; 4] = [556, 441, 341, 225];
+const REVERB_TUNING_RATE: f32 = 44100.0;
+
+/// Length of a delay line tuned to `n` samples, at `sample_rate`.
+fn reverb_len(n: usize, sample_rate: f32) -> usize {
+    ((n as f32 * sample_rate / REVERB_TUNING_RATE).round() as usize).max(1)
+}
 
+#[derive(Clone)]
 pub struct Reverb {
     pub enabled:   bool,
     pub room_size: f32,  // 0.0–1.0  (comb feedback = room_size*0.28+0.7)
@@ -114,19 +317,11 @@ pub struct Reverb {
 }
 
 impl Reverb {
-    pub fn new() -> Self {
+    pub fn new(sample_rate: f32) -> Self {
         let mut r = Self {
             enabled: false, room_size: 0.5, damping: 0.5, mix: 0.3,
-            combs: [
-                CombFilter::new(1116), CombFilter::new(1188),
-                CombFilter::new(1277), CombFilter::new(1356),
-                CombFilter::new(1422), CombFilter::new(1491),
-                CombFilter::new(1557), CombFilter::new(1617),
-            ],
-            allpasses: [
-                AllpassFilter::new(556), AllpassFilter::new(441),
-                AllpassFilter::new(341), AllpassFilter::new(225),
-            ],
+            combs:     COMB_SIZES.map(|n| CombFilter::new(reverb_len(n, sample_rate))),
+            allpasses: ALLPASS_SIZES.map(|n| AllpassFilter::new(reverb_len(n, sample_rate))),
         };
         let fb = r.room_size * 0.28 + 0.7;
         let dp = r.damping * 0.4;
@@ -150,30 +345,119 @@ impl AudioEffect for Reverb {
 
     fn name(&self) -> &'static str { "Reverb" }
 
+    fn boxed_clone(&self) -> Box<dyn AudioEffect> { Box::new(self.clone()) }
+
     fn reset(&mut self) {
         for c in &mut self.combs { c.buf.fill(0.0); c.pos = 0; c.damp_store = 0.0; }
         for ap in &mut self.allpasses { ap.buf.fill(0.0); ap.pos = 0; }
     }
+
+    fn params(&self) -> Vec<(&'static str, f32)> {
+        vec![("room_size", self.room_size), ("damping", self.damping), ("mix", self.mix)]
+    }
+
+    fn set_param(&mut self, name: &str, value: f32) {
+        match name {
+            "room_size" => self.room_size = value.clamp(0.0, 1.0),
+            "damping"   => self.damping   = value.clamp(0.0, 1.0),
+            "mix"       => self.mix       = value.clamp(0.0, 1.0),
+            _ => {}
+        }
+    }
+
+    /// Scale every delay line so the room sounds the same size at any rate.
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.combs     = COMB_SIZES.map(|n| CombFilter::new(reverb_len(n, sample_rate)));
+        self.allpasses = ALLPASS_SIZES.map(|n| AllpassFilter::new(reverb_len(n, sample_rate)));
+    }
 }
 
 // ── Delay (ring-buffer echo) ──────────────────────────────────────────────────
+//
+// The read position glides towards `time_ms` over DELAY_GLIDE_MS instead of
+// jumping, so changing the time bends the echoes' pitch like a tape delay.
+// While it moves the position is fractional, and `interp` decides how the
+// buffer is read between samples: `Nearest` steps from sample to sample
+// (audible zipper noise), `Linear` and `Cubic` (4-point Hermite) sweep
+// smoothly, cubic with less high-frequency dulling.
+
+/// Time constant of the read position's glide to a new `time_ms`.
+const DELAY_GLIDE_MS: f32 = 60.0;
+
+/// How `Delay` reads between buffer samples.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interp { Linear, Cubic, Nearest }
+
+impl Interp {
+    pub fn name(self) -> &'static str {
+        match self { Self::Linear => "Linear", Self::Cubic => "Cubic", Self::Nearest => "Nearest" }
+    }
+    pub fn next(self) -> Self {
+        match self { Self::Linear => Self::Cubic, Self::Cubic => Self::Nearest, Self::Nearest => Self::Linear }
+    }
+    /// Stable integer index (0=Linear 1=Cubic 2=Nearest), as stored in save files.
+    pub fn index(self) -> u8 {
+        match self { Self::Linear => 0, Self::Cubic => 1, Self::Nearest => 2 }
+    }
+    pub fn from_index(i: u8) -> Self {
+        match i { 1 => Self::Cubic, 2 => Self::Nearest, _ => Self::Linear }
+    }
+}
 
+#[derive(Clone)]
 pub struct Delay {
     pub enabled:  bool,
     pub time_ms:  f32,   // 10–1000 ms
     pub feedback: f32,   // 0.0–0.95
     pub mix:      f32,   // 0.0–1.0
+    pub interp:   Interp,
     buf:         Vec<f32>,
     write:       usize,
+    /// Current read distance behind `write`, in (fractional) samples.
+    delay_samp:  f32,
+    /// Per-sample glide coefficient of `delay_samp` towards `time_ms`.
+    glide:       f32,
     sample_rate: f32,
 }
 
 impl Delay {
     pub fn new(sample_rate: f32) -> Self {
-        Self {
-            enabled: false, time_ms: 250.0, feedback: 0.4, mix: 0.3,
-            buf: vec![0.0; sample_rate as usize],
-            write: 0, sample_rate,
+        let mut d = Self {
+            enabled: false, time_ms: 250.0, feedback: 0.4, mix: 0.3, interp: Interp::Linear,
+            buf: Vec::new(), write: 0, delay_samp: 0.0, glide: 0.0, sample_rate,
+        };
+        d.set_sample_rate(sample_rate);
+        d
+    }
+
+    /// Read distance for `time_ms`.  Kept two samples clear of the write
+    /// head so the cubic read's newest tap is already written.
+    fn target_samples(&self) -> f32 {
+        (self.time_ms / 1000.0 * self.sample_rate).clamp(2.0, (self.buf.len() - 3) as f32)
+    }
+
+    /// The sample written `back` samples ago.
+    fn tap(&self, back: usize) -> f32 {
+        let n = self.buf.len();
+        self.buf[(self.write + n - back) % n]
+    }
+
+    /// The signal `d` samples ago, interpolated per `interp`.
+    fn read(&self, d: f32) -> f32 {
+        let (i, t) = (d.floor() as usize, d.fract());
+        match self.interp {
+            Interp::Nearest => self.tap(d.round() as usize),
+            Interp::Linear  => {
+                let (a, b) = (self.tap(i), self.tap(i + 1));
+                a + (b - a) * t
+            }
+            Interp::Cubic => {
+                let (xm1, x0, x1, x2) = (self.tap(i - 1), self.tap(i), self.tap(i + 1), self.tap(i + 2));
+                let c1 = 0.5 * (x1 - xm1);
+                let c2 = xm1 - 2.5 * x0 + 2.0 * x1 - 0.5 * x2;
+                let c3 = 0.5 * (x2 - xm1) + 1.5 * (x0 - x1);
+                ((c3 * t + c2) * t + c1) * t + x0
+            }
         }
     }
 }
@@ -181,10 +465,8 @@ impl Delay {
 impl AudioEffect for Delay {
     fn process(&mut self, sample: f32) -> f32 {
         if !self.enabled { return 0.0; }
-        let delay_samp = ((self.time_ms / 1000.0 * self.sample_rate) as usize)
-            .clamp(1, self.buf.len() - 1);
-        let read = (self.write + self.buf.len() - delay_samp) % self.buf.len();
-        let delayed = self.buf[read];
+        self.delay_samp += (self.target_samples() - self.delay_samp) * self.glide;
+        let delayed = self.read(self.delay_samp);
         self.buf[self.write] = sample + delayed * self.feedback;
         self.write = (self.write + 1) % self.buf.len();
         delayed * self.mix
@@ -192,41 +474,182 @@ impl AudioEffect for Delay {
 
     fn name(&self) -> &'static str { "Delay" }
 
-    fn reset(&mut self) { self.buf.fill(0.0); self.write = 0; }
+    fn boxed_clone(&self) -> Box<dyn AudioEffect> { Box::new(self.clone()) }
+
+    fn reset(&mut self) {
+        self.buf.fill(0.0);
+        self.write = 0;
+        self.delay_samp = self.target_samples();
+    }
+
+    fn params(&self) -> Vec<(&'static str, f32)> {
+        vec![("time_ms", self.time_ms), ("feedback", self.feedback), ("mix", self.mix),
+             ("interp", self.interp.index() as f32)]
+    }
+
+    /// The ring buffer always holds one second.
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.buf = vec![0.0; sample_rate as usize];
+        self.write = 0;
+        self.sample_rate = sample_rate;
+        self.glide = 1.0 - (-1.0 / (DELAY_GLIDE_MS * 0.001 * sample_rate)).exp();
+        self.delay_samp = self.target_samples();
+    }
+
+    fn set_param(&mut self, name: &str, value: f32) {
+        match name {
+            "time_ms"  => self.time_ms  = value.clamp(10.0, 1000.0),
+            "feedback" => self.feedback = value.clamp(0.0, 0.95),
+            "mix"      => self.mix      = value.clamp(0.0, 1.0),
+            "interp"   => self.interp   = Interp::from_index(value as u8),
+            _ => {}
+        }
+    }
 }
 
 // ── Distortion (waveshaper) ───────────────────────────────────────────────────
 
+#[derive(Clone)]
 pub struct Distortion {
     pub enabled: bool,
     pub drive:   f32,   // 1.0–10.0  gain before clipping
     pub tone:    f32,   // 0.0–1.0   blend: 0=soft tanh, 1=hard clip
     pub level:   f32,   // 0.0–1.0   output level
+    /// Runs the clip curve at 1×/2×/4× the sample rate (see `Oversampler`).
+    pub os: Oversampler,
 }
 
 impl Distortion {
     pub fn new() -> Self {
-        Self { enabled: false, drive: 3.0, tone: 0.3, level: 0.7 }
+        Self { enabled: false, drive: 3.0, tone: 0.3, level: 0.7, os: Oversampler::new(1) }
     }
 }
 
 impl AudioEffect for Distortion {
     fn process(&mut self, sample: f32) -> f32 {
         if !self.enabled { return 0.0; }
-        let driven = sample * self.drive;
-        let soft   = driven.tanh();
-        let hard   = driven.clamp(-1.0, 1.0);
-        (soft * (1.0 - self.tone) + hard * self.tone) * self.level
+        let (drive, tone) = (self.drive, self.tone);
+        let shaped = self.os.process(sample * drive, |driven| {
+            let soft = driven.tanh();
+            let hard = driven.clamp(-1.0, 1.0);
+            soft * (1.0 - tone) + hard * tone
+        });
+        shaped * self.level
     }
 
     fn name(&self) -> &'static str { "Distortion" }
 
-    fn reset(&mut self) {}
+    fn boxed_clone(&self) -> Box<dyn AudioEffect> { Box::new(self.clone()) }
+
+    fn reset(&mut self) { self.os.reset(); }
+
+    fn params(&self) -> Vec<(&'static str, f32)> {
+        vec![("drive", self.drive), ("tone", self.tone), ("level", self.level)]
+    }
+
+    fn set_param(&mut self, name: &str, value: f32) {
+        match name {
+            "drive" => self.drive = value.clamp(1.0, 10.0),
+            "tone"  => self.tone  = value.clamp(0.0, 1.0),
+            "level" => self.level = value.clamp(0.0, 1.0),
+            _ => {}
+        }
+    }
+}
+
+// ── Oversampling ──────────────────────────────────────────────────────────────
+
+/// Oversampling factors offered for the saturation stages.
+pub const OVERSAMPLE_FACTORS: [usize; 3] = [1, 2, 4];
+/// Taps of each anti-imaging / anti-aliasing FIR (odd, so the delay is whole).
+const OS_TAPS: usize = 31;
+
+/// Direct-form FIR filter over a fixed ring buffer.
+#[derive(Clone)]
+struct Fir {
+    taps: Vec<f32>,
+    buf:  Vec<f32>,
+    pos:  usize,
+}
+
+impl Fir {
+    /// Windowed-sinc (Blackman) low-pass at `cutoff` cycles/sample (0–0.5),
+    /// unity DC gain.
+    fn lowpass(cutoff: f32) -> Self {
+        let mid = (OS_TAPS - 1) as f32 / 2.0;
+        let mut taps: Vec<f32> = (0..OS_TAPS).map(|i| {
+            let n = i as f32 - mid;
+            let sinc = if n == 0.0 { 2.0 * cutoff }
+                       else        { (2.0 * PI * cutoff * n).sin() / (PI * n) };
+            let w = 2.0 * PI * i as f32 / (OS_TAPS - 1) as f32;
+            sinc * (0.42 - 0.5 * w.cos() + 0.08 * (2.0 * w).cos())
+        }).collect();
+        let sum: f32 = taps.iter().sum();
+        for t in &mut taps { *t /= sum; }
+        Self { taps, buf: vec![0.0; OS_TAPS], pos: 0 }
+    }
+
+    #[inline]
+    fn push(&mut self, x: f32) -> f32 {
+        self.buf[self.pos] = x;
+        let n = self.buf.len();
+        let mut y = 0.0;
+        for (k, &t) in self.taps.iter().enumerate() {
+            y += t * self.buf[(self.pos + n - k) % n];
+        }
+        self.pos = (self.pos + 1) % n;
+        y
+    }
+
+    fn reset(&mut self) {
+        self.buf.fill(0.0);
+        self.pos = 0;
+    }
+}
+
+/// Runs a memoryless nonlinearity at `factor`× the sample rate to cut the
+/// aliasing that clipping otherwise folds back into the audible band:
+/// zero-stuff + FIR up, apply the curve, FIR + decimate down.  Factor 1 calls
+/// the curve directly at no cost.  Adds about `OS_TAPS / factor` samples of
+/// latency when enabled.
+#[derive(Clone)]
+pub struct Oversampler {
+    factor: usize,
+    up:     Fir,
+    down:   Fir,
+}
+
+impl Oversampler {
+    pub fn new(factor: usize) -> Self {
+        let factor = if OVERSAMPLE_FACTORS.contains(&factor) { factor } else { 1 };
+        let cutoff = 0.5 / factor as f32 * 0.9;
+        Self { factor, up: Fir::lowpass(cutoff), down: Fir::lowpass(cutoff) }
+    }
+
+    pub fn factor(&self) -> usize { self.factor }
+
+    #[inline]
+    pub fn process(&mut self, x: f32, curve: impl Fn(f32) -> f32) -> f32 {
+        if self.factor == 1 { return curve(x); }
+        let mut out = 0.0;
+        for i in 0..self.factor {
+            // Zero-stuffing divides the level by `factor`; scale back up.
+            let stuffed = if i == 0 { x * self.factor as f32 } else { 0.0 };
+            out = self.down.push(curve(self.up.push(stuffed)));
+        }
+        out
+    }
+
+    pub fn reset(&mut self) {
+        self.up.reset();
+        self.down.reset();
+    }
 }
 
 // ── Biquad filter (RBJ Audio EQ Cookbook) ────────────────────────────────────
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(clippy::enum_variant_names)]
 pub enum FilterMode { LowPass, HighPass, BandPass }
 
 impl FilterMode {
@@ -239,15 +662,32 @@ impl FilterMode {
     pub fn prev(self) -> Self {
         match self { Self::LowPass => Self::BandPass, Self::HighPass => Self::LowPass, Self::BandPass => Self::HighPass }
     }
+    /// Stable integer index (0=LP 1=HP 2=BP), as stored in save files.
+    pub fn index(self) -> u8 {
+        match self { Self::LowPass => 0, Self::HighPass => 1, Self::BandPass => 2 }
+    }
+    pub fn from_index(i: u8) -> Self {
+        match i { 1 => Self::HighPass, 2 => Self::BandPass, _ => Self::LowPass }
+    }
 }
 
-/// Two-pole biquad filter applied directly to a synth bus (not via EffectChain).
+/// Glide time of a filter's cutoff to a new panel value.
+const CUTOFF_RAMP_MS: f32 = 30.0;
+
+/// Two-pole biquad filter applied directly to a synth bus (not via EffectChain),
+/// and also available as a chain insert through its `AudioEffect` impl.
 /// When disabled, passes signal through unchanged at zero cost.
+#[derive(Clone)]
 pub struct BiquadFilter {
     pub enabled: bool,
     pub mode:    FilterMode,
     pub cutoff:  f32,   // Hz, 80.0–18 000.0
     pub q:       f32,   // 0.5–10.0
+    /// Multiplier on `cutoff` from modulation (the Synth 2 accent sweep);
+    /// 1.0 = none.  Kept apart so `cutoff` stays what the panel set.
+    pub cutoff_mod: f32,
+    /// `cutoff` as heard: glides after a panel move instead of stepping.
+    cutoff_s: Smoothed,
     sample_rate: f32,
     // Cached normalised coefficients
     b0: f32, b1: f32, b2: f32, a1: f32, a2: f32,
@@ -264,22 +704,38 @@ impl BiquadFilter {
             mode: FilterMode::LowPass,
             cutoff: 5000.0,
             q: 0.707,
+            cutoff_mod: 1.0,
+            cutoff_s: Smoothed::new(CUTOFF_RAMP_MS, sample_rate),
             sample_rate,
             b0: 0.0, b1: 0.0, b2: 0.0, a1: 0.0, a2: 0.0,
             x1: 0.0, x2: 0.0, y1: 0.0, y2: 0.0,
             last_cutoff: -1.0, last_q: -1.0, last_mode: FilterMode::LowPass,
         };
-        f.recompute();
+        f.recompute(f.cutoff * f.cutoff_mod);
         f
     }
 
+    /// Take `other`'s settings (not its state), so a second signal can be
+    /// filtered the same way.  Call after `other` has processed this sample:
+    /// the cutoff taken is where its glide has got to.  Switched on, it
+    /// starts from rest, as the panel starts `other`.
+    pub fn follow(&mut self, other: &BiquadFilter) {
+        if other.enabled && !self.enabled { self.reset_state(); }
+        self.enabled    = other.enabled;
+        self.mode       = other.mode;
+        self.cutoff     = other.cutoff_s.value();
+        self.q          = other.q;
+        self.cutoff_mod = other.cutoff_mod;
+        self.cutoff_s.snap(self.cutoff);
+    }
+
     /// Reset delay state (call when toggling on to avoid a transient pop).
     pub fn reset_state(&mut self) {
         self.x1 = 0.0; self.x2 = 0.0; self.y1 = 0.0; self.y2 = 0.0;
     }
 
-    fn recompute(&mut self) {
-        let w0    = 2.0 * PI * self.cutoff.min(self.sample_rate * 0.499) / self.sample_rate;
+    fn recompute(&mut self, cutoff: f32) {
+        let w0    = 2.0 * PI * cutoff.min(self.sample_rate * 0.499) / self.sample_rate;
         let cos_w = w0.cos();
         let sin_w = w0.sin();
         let alpha = sin_w / (2.0 * self.q);
@@ -294,16 +750,23 @@ impl BiquadFilter {
         self.a1 = -2.0 * cos_w / a0;
         self.a2 = (1.0 - alpha) / a0;
 
-        self.last_cutoff = self.cutoff;
+        self.last_cutoff = cutoff;
         self.last_q      = self.q;
         self.last_mode   = self.mode;
     }
 
     #[inline]
     pub fn process(&mut self, x: f32) -> f32 {
-        if !self.enabled { return x; }
-        if self.cutoff != self.last_cutoff || self.q != self.last_q || self.mode != self.last_mode {
-            self.recompute();
+        if !self.enabled {
+            // Re-enabled later, it starts at the panel value, not mid-glide.
+            self.cutoff_s.snap(self.cutoff);
+            return x;
+        }
+        // Only the panel value glides; the accent sweep is already smooth.
+        self.cutoff_s.set(self.cutoff);
+        let cutoff = self.cutoff_s.next() * self.cutoff_mod;
+        if cutoff != self.last_cutoff || self.q != self.last_q || self.mode != self.last_mode {
+            self.recompute(cutoff);
         }
         let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2
                              - self.a1 * self.y1 - self.a2 * self.y2;
@@ -312,3 +775,805 @@ impl BiquadFilter {
         y
     }
 }
+
+impl AudioEffect for BiquadFilter {
+    #[inline]
+    fn process(&mut self, sample: f32) -> f32 {
+        BiquadFilter::process(self, sample)
+    }
+
+    fn name(&self) -> &'static str { "Filter" }
+
+    fn boxed_clone(&self) -> Box<dyn AudioEffect> { Box::new(self.clone()) }
+
+    fn reset(&mut self) { self.reset_state(); }
+
+    fn params(&self) -> Vec<(&'static str, f32)> {
+        vec![("mode", self.mode.index() as f32), ("cutoff", self.cutoff), ("q", self.q)]
+    }
+
+    fn set_param(&mut self, name: &str, value: f32) {
+        match name {
+            "mode"   => self.mode   = FilterMode::from_index(value.round().max(0.0) as u8),
+            "cutoff" => self.cutoff = value.clamp(80.0, 18000.0),
+            "q"      => self.q      = value.clamp(0.5, 10.0),
+            _ => {}
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.cutoff_s.set_sample_rate(sample_rate);
+        self.recompute(self.cutoff * self.cutoff_mod);
+        self.reset_state();
+    }
+}
+
+// ── Auto-wah (envelope follower → resonant low-pass) ─────────────────────────
+
+/// Envelope-controlled resonant filter.  A peak follower tracks the input level
+/// and sweeps the cutoff up from `AUTOWAH_BASE_HZ` by as much as `range` octaves.
+/// Reuses `BiquadFilter` for the filter stage.
+#[derive(Clone)]
+pub struct AutoWah {
+    pub sensitivity: f32,   // 0.0–1.0   envelope gain before mapping to cutoff
+    pub range:       f32,   // 0.5–5.0   sweep range in octaves above the base cutoff
+    pub resonance:   f32,   // 0.5–10.0  filter Q
+    envelope:  f32,
+    attack_c:  f32,
+    release_c: f32,
+    filter:    BiquadFilter,
+}
+
+const AUTOWAH_BASE_HZ: f32 = 250.0;
+
+impl AutoWah {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut filter = BiquadFilter::new(sample_rate);
+        filter.enabled = true;
+        filter.cutoff  = AUTOWAH_BASE_HZ;
+        // The envelope already glides; a cutoff ramp would only blunt it.
+        filter.cutoff_s = Smoothed::new(0.0, sample_rate);
+        Self {
+            sensitivity: 0.6, range: 3.0, resonance: 4.0,
+            envelope:  0.0,
+            attack_c:  (-1.0 / (0.003 * sample_rate)).exp(),   // 3 ms attack
+            release_c: (-1.0 / (0.120 * sample_rate)).exp(),   // 120 ms release
+            filter,
+        }
+    }
+}
+
+impl AudioEffect for AutoWah {
+    fn process(&mut self, sample: f32) -> f32 {
+        let level = sample.abs();
+        let c = if level > self.envelope { self.attack_c } else { self.release_c };
+        self.envelope = level + c * (self.envelope - level);
+
+        let sweep  = (self.envelope * self.sensitivity * 4.0).clamp(0.0, 1.0);
+        let cutoff = (AUTOWAH_BASE_HZ * 2f32.powf(sweep * self.range)).clamp(80.0, 18000.0);
+        // Only touch the cutoff (and so trigger a coefficient recompute) when it
+        // has moved audibly — avoids recalculating trig functions every sample.
+        if (cutoff / self.filter.cutoff - 1.0).abs() > 0.005 {
+            self.filter.cutoff = cutoff;
+        }
+        self.filter.q = self.resonance.clamp(0.5, 10.0);
+        self.filter.process(sample)
+    }
+
+    fn name(&self) -> &'static str { "AutoWah" }
+
+    fn boxed_clone(&self) -> Box<dyn AudioEffect> { Box::new(self.clone()) }
+
+    fn reset(&mut self) {
+        self.envelope = 0.0;
+        self.filter.cutoff = AUTOWAH_BASE_HZ;
+        self.filter.reset_state();
+    }
+
+    fn params(&self) -> Vec<(&'static str, f32)> {
+        vec![("sensitivity", self.sensitivity), ("range", self.range), ("resonance", self.resonance)]
+    }
+
+    fn set_param(&mut self, name: &str, value: f32) {
+        match name {
+            "sensitivity" => self.sensitivity = value.clamp(0.0, 1.0),
+            "range"       => self.range       = value.clamp(0.5, 5.0),
+            "resonance"   => self.resonance   = value.clamp(0.5, 10.0),
+            _ => {}
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.attack_c  = (-1.0 / (0.003 * sample_rate)).exp();
+        self.release_c = (-1.0 / (0.120 * sample_rate)).exp();
+        AudioEffect::set_sample_rate(&mut self.filter, sample_rate);
+    }
+}
+
+// ── Noise gate ────────────────────────────────────────────────────────────────
+
+/// Envelope-follower gate: passes audio while the input level is above
+/// `threshold_db`, keeps the gate open for `hold_ms` after it drops, then closes.
+/// The gate gain ramps linearly over `attack_ms` / `release_ms` to avoid clicks.
+#[derive(Clone)]
+pub struct NoiseGate {
+    pub threshold_db: f32,  // -80.0–0.0 dBFS
+    pub attack_ms:    f32,  // 0.1–50.0
+    pub release_ms:   f32,  // 5.0–1000.0
+    pub hold_ms:      f32,  // 0.0–500.0
+    envelope:    f32,
+    gain:        f32,
+    hold_left:   u32,
+    sample_rate: f32,
+}
+
+impl NoiseGate {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            threshold_db: -45.0, attack_ms: 1.0, release_ms: 80.0, hold_ms: 30.0,
+            envelope: 0.0, gain: 0.0, hold_left: 0, sample_rate,
+        }
+    }
+}
+
+impl AudioEffect for NoiseGate {
+    fn process(&mut self, sample: f32) -> f32 {
+        // Peak follower: instant attack, ~10 ms release.
+        let level = sample.abs();
+        let rel_c = (-1.0 / (0.010 * self.sample_rate)).exp();
+        self.envelope = if level > self.envelope { level } else { level + rel_c * (self.envelope - level) };
+
+        let threshold = 10f32.powf(self.threshold_db / 20.0);
+        let open = if self.envelope >= threshold {
+            self.hold_left = (self.hold_ms * 0.001 * self.sample_rate) as u32;
+            true
+        } else if self.hold_left > 0 {
+            self.hold_left -= 1;
+            true
+        } else {
+            false
+        };
+
+        if open {
+            let step = 1.0 / (self.attack_ms.max(0.1) * 0.001 * self.sample_rate);
+            self.gain = (self.gain + step).min(1.0);
+        } else {
+            let step = 1.0 / (self.release_ms.max(1.0) * 0.001 * self.sample_rate);
+            self.gain = (self.gain - step).max(0.0);
+        }
+        sample * self.gain
+    }
+
+    fn name(&self) -> &'static str { "NoiseGate" }
+
+    fn boxed_clone(&self) -> Box<dyn AudioEffect> { Box::new(self.clone()) }
+
+    fn reset(&mut self) {
+        self.envelope  = 0.0;
+        self.gain      = 0.0;
+        self.hold_left = 0;
+    }
+
+    fn params(&self) -> Vec<(&'static str, f32)> {
+        vec![("threshold_db", self.threshold_db), ("attack_ms", self.attack_ms),
+             ("release_ms", self.release_ms), ("hold_ms", self.hold_ms)]
+    }
+
+    fn set_param(&mut self, name: &str, value: f32) {
+        match name {
+            "threshold_db" => self.threshold_db = value.clamp(-80.0, 0.0),
+            "attack_ms"    => self.attack_ms    = value.clamp(0.1, 50.0),
+            "release_ms"   => self.release_ms   = value.clamp(5.0, 1000.0),
+            "hold_ms"      => self.hold_ms      = value.clamp(0.0, 500.0),
+            _ => {}
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+    }
+}
+
+// ── Stereo widener (mid/side) ─────────────────────────────────────────────────
+
+/// Mid/side width control. The side signal is scaled by `width`
+/// (0.0 = mono, 1.0 = unchanged, up to 2.0 = wider).
+///
+/// Width is clamped to 0.0–2.0: beyond that the side component dominates and
+/// the image collapses into out-of-phase content that cancels on mono playback.
+pub struct StereoWidener {
+    pub width: f32,
+}
+
+pub const WIDENER_MAX_WIDTH: f32 = 2.0;
+
+/// Left and right gains of a mono source at `pan` (-1 = left … 1 = right):
+/// `1 - pan` and `1 + pan`, so the channels average back to the source and
+/// a centred one passes at exactly unity.
+#[inline]
+pub fn pan_gains(pan: f32) -> (f32, f32) {
+    let p = pan.clamp(-1.0, 1.0);
+    (1.0 - p, 1.0 + p)
+}
+
+impl StereoWidener {
+    pub fn new() -> Self {
+        Self { width: 1.0 }
+    }
+}
+
+impl StereoEffect for StereoWidener {
+    #[inline]
+    fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let width = self.width.clamp(0.0, WIDENER_MAX_WIDTH);
+        if (width - 1.0).abs() < 1e-6 {
+            return (left, right);
+        }
+        let mid  = (left + right) * 0.5;
+        let side = (left - right) * 0.5 * width;
+        (mid + side, mid - side)
+    }
+
+    fn name(&self) -> &'static str { "Widener" }
+
+    fn reset(&mut self) {}
+}
+
+// ── DC blocker ────────────────────────────────────────────────────────────────
+
+/// DC blocker corner frequency — well below anything musical.
+const DC_BLOCK_HZ: f32 = 10.0;
+
+/// One-pole high-pass (`y = x - x₁ + R·y₁`) per channel that removes the DC
+/// offset asymmetric waveforms leave behind, so it doesn't eat clip headroom.
+pub struct DcBlocker {
+    r: f32,
+    x1: (f32, f32),
+    y1: (f32, f32),
+}
+
+impl DcBlocker {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            r: (-2.0 * PI * DC_BLOCK_HZ / sample_rate).exp(),
+            x1: (0.0, 0.0),
+            y1: (0.0, 0.0),
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.r = (-2.0 * PI * DC_BLOCK_HZ / sample_rate).exp();
+    }
+}
+
+impl StereoEffect for DcBlocker {
+    #[inline]
+    fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let l = left  - self.x1.0 + self.r * self.y1.0;
+        let r = right - self.x1.1 + self.r * self.y1.1;
+        self.x1 = (left, right);
+        self.y1 = (l, r);
+        (l, r)
+    }
+
+    fn name(&self) -> &'static str { "DC Blocker" }
+
+    fn reset(&mut self) {
+        self.x1 = (0.0, 0.0);
+        self.y1 = (0.0, 0.0);
+    }
+}
+
+// ── Mix meter + auto-gain ─────────────────────────────────────────────────────
+//
+// Watches the dry master sum (synth 1 + synth 2 + drums, L/R) just before the
+// `tanh` that joins them.  Each melodic bus is divided by √(voice count), so
+// a chord swelling or a tail ending steps its level, and a dense mix then
+// pumps against the `tanh`.  The meter keeps the sum's peak for the status
+// bar and its clip warning (over `MIX_CLIP_LEVEL`).  With `auto_gain` the √n
+// divisor glides instead of stepping, and a slow RMS leveler pulls a loud mix
+// down towards `AUTO_GAIN_TARGET` (it never boosts), so loudness stays level
+// as voices come and go.  Off, the sum passes untouched.
+
+/// Pre-`tanh` level the clip warning fires at (`tanh` is already ~24%
+/// under a straight line there).
+pub const MIX_CLIP_LEVEL: f32 = 1.0;
+/// RMS the auto-gain pulls a loud mix down to, and its largest cut in dB.
+const AUTO_GAIN_TARGET: f32 = 0.35;
+pub const AUTO_GAIN_MAX_CUT_DB: f32 = -12.0;
+/// RMS window of the leveler, and glide of its gain and the √n divisors, in
+/// seconds.
+const AUTO_GAIN_WINDOW_S: f32 = 0.4;
+const AUTO_GAIN_GLIDE_S: f32 = 0.05;
+
+pub struct MixMeter {
+    pub auto_gain: bool,
+    /// Peak of the (levelled) sum: instant attack, `PEAK_FALL_S` fall.
+    pub peak: f32,
+    /// Gain the leveler applied to the last sample (1.0 = none).
+    pub gain: f32,
+    /// Gliding √(voice count) per melodic bus.
+    norm:    [f32; 2],
+    mean_sq: f32,
+    fall:    f32,
+    window:  f32,
+    glide:   f32,
+}
+
+impl MixMeter {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut m = Self {
+            auto_gain: false, peak: 0.0, gain: 1.0,
+            norm: [1.0; 2], mean_sq: 0.0, fall: 0.0, window: 0.0, glide: 0.0,
+        };
+        m.set_sample_rate(sample_rate);
+        m
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        let coef = |s: f32| 1.0 - (-1.0 / (s * sample_rate)).exp();
+        self.fall   = (-1.0 / (PEAK_FALL_S * sample_rate)).exp();
+        self.window = coef(AUTO_GAIN_WINDOW_S);
+        self.glide  = coef(AUTO_GAIN_GLIDE_S);
+    }
+
+    /// Switch the auto-gain; the leveler starts from unity either way (the
+    /// divisors are tracked while off, so they don't jump).
+    pub fn set_auto_gain(&mut self, on: bool) {
+        if on != self.auto_gain {
+            self.gain    = 1.0;
+            self.mean_sq = 0.0;
+        }
+        self.auto_gain = on;
+    }
+
+    /// Divisor for melodic bus `bus` playing `voices` voices: √n, gliding
+    /// towards it with auto-gain.
+    #[inline]
+    pub fn voice_norm(&mut self, bus: usize, voices: usize) -> f32 {
+        let target = (voices.max(1) as f32).sqrt();
+        if !self.auto_gain {
+            self.norm[bus] = target;
+            return target;
+        }
+        self.norm[bus] += (target - self.norm[bus]) * self.glide;
+        self.norm[bus]
+    }
+
+    /// Level (with auto-gain) and meter one `(left, right)` frame of the
+    /// pre-`tanh` sum.  The RMS is taken over both channels and both take
+    /// the same gain, so panned parts keep their place in the image.
+    #[inline]
+    pub fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let (l, r) = if self.auto_gain {
+            let power = (left * left + right * right) * 0.5;
+            self.mean_sq += (power - self.mean_sq) * self.window;
+            let rms = self.mean_sq.sqrt();
+            let floor = 10f32.powf(AUTO_GAIN_MAX_CUT_DB / 20.0);
+            let target = if rms > AUTO_GAIN_TARGET { (AUTO_GAIN_TARGET / rms).max(floor) } else { 1.0 };
+            self.gain += (target - self.gain) * self.glide;
+            (left * self.gain, right * self.gain)
+        } else {
+            (left, right)
+        };
+        let pk = l.abs().max(r.abs());
+        self.peak = if pk > self.peak { pk } else { self.peak * self.fall };
+        (l, r)
+    }
+
+    pub fn reset(&mut self) {
+        self.peak    = 0.0;
+        self.gain    = 1.0;
+        self.norm    = [1.0; 2];
+        self.mean_sq = 0.0;
+    }
+}
+
+// ── Master limiter ────────────────────────────────────────────────────────────
+//
+// Peak limiter ahead of the clipper.  The gain computer works on the louder
+// channel: the gain each sample needs to stay under the ceiling is smoothed
+// (fast attack, slow release) and applied to both channels, so the image
+// doesn't shift.  Without lookahead a transient passes while the attack
+// catches up; with it the signal is delayed by `LIMITER_LOOKAHEAD_MS`, the
+// gain follows the smallest need over the delayed window, and a final clamp
+// on the outgoing sample guarantees the ceiling.
+
+/// Lookahead delay (and added output latency), in milliseconds.
+pub const LIMITER_LOOKAHEAD_MS: f32 = 3.0;
+/// Ceiling range, in dBFS, and the default.
+pub const LIMITER_CEILING_MIN_DB: f32 = -12.0;
+pub const LIMITER_CEILING_MAX_DB: f32 = 0.0;
+pub const LIMITER_DEFAULT_CEILING_DB: f32 = -0.3;
+/// Gain smoothing time constants: the attack settles within the lookahead.
+const LIMITER_ATTACK_MS: f32 = LIMITER_LOOKAHEAD_MS / 4.0;
+const LIMITER_RELEASE_MS: f32 = 80.0;
+
+pub struct Limiter {
+    pub enabled:    bool,
+    pub ceiling_db: f32,
+    pub lookahead:  bool,
+    /// Gain applied to the last sample (1.0 = no reduction), for the meter.
+    pub gain:       f32,
+    /// Lookahead ring: delayed frames and the gain each one needs.
+    delay:   Vec<(f32, f32)>,
+    need:    Vec<f32>,
+    pos:     usize,
+    attack:  f32,
+    release: f32,
+}
+
+impl Limiter {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut lim = Self {
+            enabled: false, ceiling_db: LIMITER_DEFAULT_CEILING_DB, lookahead: false, gain: 1.0,
+            delay: Vec::new(), need: Vec::new(), pos: 0, attack: 0.0, release: 0.0,
+        };
+        lim.set_sample_rate(sample_rate);
+        lim
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        let n = ((LIMITER_LOOKAHEAD_MS * 0.001 * sample_rate).round() as usize).max(1);
+        self.delay = vec![(0.0, 0.0); n];
+        self.need  = vec![1.0; n];
+        self.pos   = 0;
+        let coef = |ms: f32| 1.0 - (-1.0 / (ms * 0.001 * sample_rate)).exp();
+        self.attack  = coef(LIMITER_ATTACK_MS);
+        self.release = coef(LIMITER_RELEASE_MS);
+    }
+
+    /// Switch the limiter or its lookahead; the ring is cleared so stale
+    /// audio from before the switch is not replayed.
+    pub fn set(&mut self, enabled: bool, lookahead: bool) {
+        if (enabled, lookahead) != (self.enabled, self.lookahead) { self.reset(); }
+        self.enabled   = enabled;
+        self.lookahead = lookahead;
+    }
+
+    /// Gain that brings a peak of `pk` down to `ceiling` (1.0 if under it).
+    #[inline]
+    fn needed(pk: f32, ceiling: f32) -> f32 {
+        if pk > ceiling { ceiling / pk } else { 1.0 }
+    }
+}
+
+impl StereoEffect for Limiter {
+    #[inline]
+    fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        if !self.enabled { return (left, right); }
+        let ceiling = 10f32.powf(self.ceiling_db.clamp(LIMITER_CEILING_MIN_DB, LIMITER_CEILING_MAX_DB) / 20.0);
+        let need = Self::needed(left.abs().max(right.abs()), ceiling);
+        let (l, r, target) = if self.lookahead {
+            let out = self.delay[self.pos];
+            self.delay[self.pos] = (left, right);
+            self.need[self.pos] = need;
+            self.pos = (self.pos + 1) % self.delay.len();
+            (out.0, out.1, self.need.iter().fold(1.0f32, |a, &b| a.min(b)))
+        } else {
+            (left, right, need)
+        };
+        let coef = if target < self.gain { self.attack } else { self.release };
+        self.gain += (target - self.gain) * coef;
+        let g = if self.lookahead { self.gain.min(Self::needed(l.abs().max(r.abs()), ceiling)) } else { self.gain };
+        (l * g, r * g)
+    }
+
+    fn name(&self) -> &'static str { "Limiter" }
+
+    fn reset(&mut self) {
+        self.delay.fill((0.0, 0.0));
+        self.need.fill(1.0);
+        self.gain = 1.0;
+    }
+}
+
+// ── Master clipper ────────────────────────────────────────────────────────────
+
+/// Final saturation stage on the master output.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClipMode { Tanh, Hard, Off }
+
+impl ClipMode {
+    pub fn name(self) -> &'static str {
+        match self { Self::Tanh => "Tanh", Self::Hard => "Hard", Self::Off => "Off" }
+    }
+    pub fn next(self) -> Self {
+        match self { Self::Tanh => Self::Hard, Self::Hard => Self::Off, Self::Off => Self::Tanh }
+    }
+    pub fn prev(self) -> Self {
+        match self { Self::Tanh => Self::Off, Self::Hard => Self::Tanh, Self::Off => Self::Hard }
+    }
+    /// Stable integer index (0=Tanh 1=Hard 2=Off), as stored in save files.
+    pub fn index(self) -> u8 {
+        match self { Self::Tanh => 0, Self::Hard => 1, Self::Off => 2 }
+    }
+    pub fn from_index(i: u8) -> Self {
+        match i { 1 => Self::Hard, 2 => Self::Off, _ => Self::Tanh }
+    }
+}
+
+pub const CLIP_DRIVE_MIN: f32 = 0.5;
+pub const CLIP_DRIVE_MAX: f32 = 4.0;
+/// Peak meter fall time constant, in seconds.
+const PEAK_FALL_S: f32 = 0.3;
+
+/// Drive gain followed by the selected clip curve.  Also meters the driven
+/// (pre-clip) peak, so the UI can show when the clipper engages — or, with
+/// `ClipMode::Off`, when the output goes over full scale.
+pub struct MasterClipper {
+    pub mode:  ClipMode,
+    /// Gain applied before the clip curve (1.0 = unity).
+    pub drive: f32,
+    /// Pre-clip peak of either channel: instant attack, ~300 ms fall.
+    pub peak:  f32,
+    /// Per-channel oversampling around the clip curve.
+    pub os:    (Oversampler, Oversampler),
+    fall: f32,
+}
+
+impl MasterClipper {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            mode:  ClipMode::Tanh,
+            drive: 1.0,
+            peak:  0.0,
+            os:    (Oversampler::new(1), Oversampler::new(1)),
+            fall:  (-1.0 / (PEAK_FALL_S * sample_rate)).exp(),
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.fall = (-1.0 / (PEAK_FALL_S * sample_rate)).exp();
+    }
+}
+
+impl StereoEffect for MasterClipper {
+    #[inline]
+    fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let drive = self.drive.clamp(CLIP_DRIVE_MIN, CLIP_DRIVE_MAX);
+        let (l, r) = (left * drive, right * drive);
+        let pk = l.abs().max(r.abs());
+        self.peak = if pk > self.peak { pk } else { self.peak * self.fall };
+        let curve: fn(f32) -> f32 = match self.mode {
+            ClipMode::Tanh => f32::tanh,
+            ClipMode::Hard => |x: f32| x.clamp(-1.0, 1.0),
+            ClipMode::Off  => return (l, r),
+        };
+        (self.os.0.process(l, curve), self.os.1.process(r, curve))
+    }
+
+    fn name(&self) -> &'static str { "Clipper" }
+
+    fn reset(&mut self) {
+        self.peak = 0.0;
+        self.os.0.reset();
+        self.os.1.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SR: f32 = 48_000.0;
+
+    fn sine(hz: f32, i: usize) -> f32 {
+        (2.0 * PI * hz * i as f32 / SR).sin()
+    }
+
+    #[test]
+    fn dc_blocker_removes_offset_and_passes_audio() {
+        let mut dc = DcBlocker::new(SR);
+        let out: Vec<f32> = (0..SR as usize).map(|i| dc.process(0.5 + 0.5 * sine(440.0, i), 0.5).0).collect();
+        let tail = &out[out.len() / 2..];
+        let mean = tail.iter().sum::<f32>() / tail.len() as f32;
+        let peak = tail.iter().fold(0.0f32, |m, x| m.max(x.abs()));
+        assert!(mean.abs() < 1e-3, "DC left: {}", mean);
+        assert!((peak - 0.5).abs() < 0.01, "440 Hz came out at {}", peak);
+        assert!(out.iter().all(|x| x.abs() <= 1.0), "output left the input range");
+        assert!(dc.process(0.5, 0.5).1.abs() < 1e-3, "constant right channel not blocked");
+    }
+
+    /// Amplitude of the `hz` component of `buf` (single-bin DFT).
+    fn level_at(buf: &[f32], hz: f32) -> f32 {
+        let (mut re, mut im) = (0.0f32, 0.0f32);
+        for (i, &x) in buf.iter().enumerate() {
+            let w = 2.0 * PI * hz * i as f32 / SR;
+            re += x * w.cos();
+            im += x * w.sin();
+        }
+        2.0 * (re * re + im * im).sqrt() / buf.len() as f32
+    }
+
+    /// Hard-clip a 9 kHz sine through an oversampler of `factor`.
+    fn clipped_sine(factor: usize) -> Vec<f32> {
+        let mut os = Oversampler::new(factor);
+        let n = SR as usize / 10;
+        let out: Vec<f32> = (0..n + 480).map(|i| os.process(4.0 * sine(9_000.0, i), |x| x.clamp(-1.0, 1.0))).collect();
+        out[480..].to_vec()
+    }
+
+    #[test]
+    fn oversampling_cuts_clip_aliasing() {
+        // The 5th harmonic (45 kHz) folds back to 3 kHz at the base rate.
+        let alias = [1, 2, 4].map(|f| level_at(&clipped_sine(f), 3_000.0));
+        let fund  = [1, 2, 4].map(|f| level_at(&clipped_sine(f), 9_000.0));
+        assert!(alias[1] < alias[0] / 4.0 && alias[2] < alias[1], "alias levels {:?}", alias);
+        assert!(fund.iter().all(|&f| (f / fund[0] - 1.0).abs() < 0.05), "fundamental levels {:?}", fund);
+    }
+
+    #[test]
+    fn oversampled_clipper_is_bounded_and_silent_for_silence() {
+        for f in OVERSAMPLE_FACTORS {
+            let mut c = MasterClipper::new(SR);
+            c.mode = ClipMode::Hard;
+            c.os = (Oversampler::new(f), Oversampler::new(f));
+            assert!((0..500).all(|_| c.process(0.0, 0.0) == (0.0, 0.0)), "{}x: silence in, sound out", f);
+            let peak = (0..4800).map(|i| c.process(4.0 * sine(9_000.0, i), -4.0 * sine(5_000.0, i)))
+                .fold(0.0f32, |m, (l, r)| m.max(l.abs()).max(r.abs()));
+            assert!(peak < 1.25, "{}x: clipped output peaked at {}", f, peak);
+        }
+    }
+
+    /// Index of the first echo of an impulse through `d`.
+    fn echo_at(d: &mut Delay) -> Option<usize> {
+        d.enabled = true;
+        (0..SR as usize).map(|i| d.process(if i == 0 { 1.0 } else { 0.0 })).position(|y| y != 0.0)
+    }
+
+    /// Samples from an impulse to the first reverb return through `fx`: the
+    /// shortest comb's length.
+    fn first_return(fx: &mut dyn AudioEffect) -> Option<usize> {
+        (0..SR as usize).map(|i| fx.process(if i == 0 { 1.0 } else { 0.0 }))
+            .skip(1).position(|y| y != 0.0).map(|i| i + 1)
+    }
+
+    #[test]
+    fn reverb_is_tuned_to_the_rate_it_is_built_at() {
+        let at_48k = reverb_len(COMB_SIZES[0], SR);
+        assert_eq!(at_48k, 1215);
+        assert_eq!(first_return(&mut *InsertKind::Reverb.build(SR)), Some(at_48k), "reverb insert built at 44.1 kHz");
+        let built = |sr| { let mut r = Reverb::new(sr); r.enabled = true; r };
+        assert_eq!(first_return(&mut built(SR)), Some(at_48k));
+        assert_eq!(first_return(&mut built(44_100.0)), Some(COMB_SIZES[0]));
+        let mut moved = built(44_100.0);
+        moved.set_sample_rate(SR);
+        assert_eq!(first_return(&mut moved), Some(at_48k));
+    }
+
+    #[test]
+    fn delay_time_follows_a_rate_change() {
+        let mut d = Delay::new(44_100.0);
+        d.set_sample_rate(SR);
+        assert_eq!(echo_at(&mut d), Some((0.25 * SR) as usize));
+        d.set_param("time_ms", 1000.0);
+        d.reset();
+        assert!(echo_at(&mut d).is_some_and(|i| i > (0.99 * SR) as usize), "1 s echo no longer fits the buffer");
+    }
+
+    /// Summed second difference of a 220 Hz sine through a delay gliding
+    /// from 200 to 260 ms (lower = smoother).
+    fn glide_roughness(interp: Interp) -> f32 {
+        let mut d = Delay::new(SR);
+        (d.enabled, d.interp, d.feedback, d.mix, d.time_ms) = (true, interp, 0.0, 1.0, 200.0);
+        d.reset();
+        let n = SR as usize / 2;
+        for i in 0..n { d.process(sine(220.0, i)); }
+        d.time_ms = 260.0;
+        let out: Vec<f32> = (n..n + SR as usize / 5).map(|i| d.process(sine(220.0, i))).collect();
+        assert!(out.iter().all(|y| y.abs() <= 1.0 + 1e-3), "{:?} read overshot", interp);
+        out.windows(3).map(|w| (w[0] - 2.0 * w[1] + w[2]).abs()).sum()
+    }
+
+    #[test]
+    fn interpolated_delay_glides_without_zipper() {
+        let nearest = glide_roughness(Interp::Nearest);
+        for interp in [Interp::Linear, Interp::Cubic] {
+            let r = glide_roughness(interp);
+            assert!(r < nearest / 10.0, "{:?}: roughness {} vs nearest {}", interp, r, nearest);
+        }
+    }
+
+    #[test]
+    fn delay_is_silent_for_silence() {
+        for interp in [Interp::Nearest, Interp::Linear, Interp::Cubic] {
+            let mut d = Delay::new(SR);
+            (d.enabled, d.interp, d.feedback) = (true, interp, 0.95);
+            assert!((0..SR as usize).all(|_| d.process(0.0) == 0.0), "{:?}", interp);
+        }
+    }
+
+    /// Output peak of `lim` for a quiet sine with a 2.0 spike in the middle.
+    fn limited_peak(lim: &mut Limiter) -> f32 {
+        (0..4800)
+            .map(|i| { let x = if i == 2400 { 2.0 } else { 0.3 * sine(440.0, i) }; lim.process(x, -x) })
+            .fold(0.0f32, |m, (l, r)| m.max(l.abs()).max(r.abs()))
+    }
+
+    #[test]
+    fn lookahead_limiter_holds_the_ceiling_on_a_transient() {
+        let mut lim = Limiter::new(SR);
+        lim.ceiling_db = -0.3;
+        let ceiling = 10f32.powf(-0.3 / 20.0);
+        lim.set(true, false);
+        assert!(limited_peak(&mut lim) > ceiling, "plain limiter caught a one-sample spike");
+        lim.set(true, true);
+        let peak = limited_peak(&mut lim);
+        assert!(peak <= ceiling + 1e-6, "lookahead let {} through a {} ceiling", peak, ceiling);
+        lim.set(false, false);
+        assert_eq!(limited_peak(&mut lim), 2.0, "disabled limiter is not a passthrough");
+    }
+
+    #[test]
+    fn limiter_is_silent_for_silence_and_transparent_below_the_ceiling() {
+        let mut lim = Limiter::new(SR);
+        lim.set(true, true);
+        assert!((0..1000).all(|_| lim.process(0.0, 0.0) == (0.0, 0.0)));
+        let quiet: Vec<f32> = (0..1000).map(|i| lim.process(0.5 * sine(440.0, i), 0.0).0).collect();
+        let delay = (LIMITER_LOOKAHEAD_MS * 0.001 * SR).round() as usize;
+        assert!(quiet[delay..].iter().enumerate().all(|(i, &y)| (y - 0.5 * sine(440.0, i)).abs() < 1e-6));
+        assert_eq!(lim.gain, 1.0);
+    }
+
+    #[test]
+    fn stereo_chain_matches_mono_at_the_centre_and_keeps_the_channels_apart() {
+        let chain = || {
+            let mut c = EffectChain::new();
+            c.push(InsertKind::Distortion.build(SR));
+            c.push(InsertKind::Reverb.build(SR));
+            c
+        };
+        let (mut mono, mut stereo) = (chain(), chain());
+        assert!((0..4800).all(|i| {
+            let x = 0.5 * sine(220.0, i);
+            let y = mono.process(x);
+            stereo.process_stereo(x, x) == (y, y)
+        }), "a centred frame differs from the mono chain");
+
+        // Each channel has its own reverb tail: nothing fed right, nothing out.
+        let mut stereo = chain();
+        assert!((0..4800).all(|i| stereo.process_stereo(0.5 * sine(220.0, i), 0.0).1 == 0.0));
+        stereo.pop();
+        assert_eq!((stereo.len(), stereo.right.len()), (1, 1));
+        stereo.set_effects(vec![InsertKind::Delay.build(SR)]);
+        assert_eq!((stereo.names(), stereo.right.len()), (vec!["Delay"], 1));
+    }
+
+    #[test]
+    fn dc_blocker_is_silent_for_silence() {
+        let mut dc = DcBlocker::new(SR);
+        assert!((0..1000).all(|_| dc.process(0.0, 0.0) == (0.0, 0.0)));
+    }
+
+    #[test]
+    fn mix_meter_off_passes_the_sum_and_flags_overs() {
+        let mut m = MixMeter::new(SR);
+        assert_eq!((m.voice_norm(0, 1), m.voice_norm(0, 4), m.voice_norm(1, 0)), (1.0, 2.0, 1.0));
+        let out: Vec<f32> = (0..4800).map(|i| { let x = 1.5 * sine(100.0, i); m.process(x, x).0 }).collect();
+        assert!(out.iter().enumerate().all(|(i, &y)| y == 1.5 * sine(100.0, i)), "meter off changed the sum");
+        assert!(m.peak > MIX_CLIP_LEVEL && m.gain == 1.0, "peak {} gain {}", m.peak, m.gain);
+    }
+
+    #[test]
+    fn auto_gain_levels_a_loud_mix_and_leaves_a_quiet_one() {
+        let mut m = MixMeter::new(SR);
+        m.set_auto_gain(true);
+        // The divisor glides to √4 rather than stepping.
+        let first = m.voice_norm(0, 4);
+        assert!(first > 1.0 && first < 1.01, "divisor stepped to {}", first);
+        for _ in 0..SR as usize { m.voice_norm(0, 4); }
+        assert!((m.voice_norm(0, 4) - 2.0).abs() < 1e-3);
+
+        // 1.5 peak = 1.06 RMS, pulled down to the 0.35 target.
+        let loud: Vec<f32> = (0..3 * SR as usize).map(|i| { let x = 1.5 * sine(100.0, i); m.process(x, x).0 }).collect();
+        let peak = loud[2 * SR as usize..].iter().fold(0.0f32, |p, y| p.max(y.abs()));
+        assert!((peak - AUTO_GAIN_TARGET * 2f32.sqrt()).abs() < 0.02, "levelled peak {}", peak);
+        assert!(m.peak < MIX_CLIP_LEVEL, "clip warning still on at {}", m.peak);
+
+        let mut m = MixMeter::new(SR);
+        m.set_auto_gain(true);
+        assert!((0..SR as usize).all(|i| { let x = 0.3 * sine(100.0, i); m.process(x, x) == (x, x) }), "auto-gain boosted or cut a quiet mix");
+    }
+}
@@ -0,0 +1,78 @@
+// ── Freeze (grain pad) ────────────────────────────────────────────────────────
+//
+// Captures the most recent master output from the scope ring buffer and loops
+// it as a sustained texture.  One grain, looped with a crossfade between its
+// tail and head so the seam doesn't click.
+
+/// Samples blended across the loop seam.
+const SEAM_FADE: usize = 128;
+/// Time to fade the pad in/out when toggled, in milliseconds.
+const TOGGLE_FADE_MS: f32 = 20.0;
+
+pub struct Freeze {
+    /// True while the pad is sounding (or fading in).
+    pub active: bool,
+    /// Output level of the pad, 0.0–1.0.
+    pub level: f32,
+    grain: Vec<f32>,
+    pos: usize,
+    /// Current toggle fade gain, ramped towards 0/1.
+    gain: f32,
+    fade_step: f32,
+}
+
+impl Freeze {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            active: false,
+            level: 0.5,
+            grain: Vec::new(),
+            pos: 0,
+            gain: 0.0,
+            fade_step: 1.0 / (TOGGLE_FADE_MS * 0.001 * sample_rate).max(1.0),
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.fade_step = 1.0 / (TOGGLE_FADE_MS * 0.001 * sample_rate).max(1.0);
+    }
+
+    /// Copy the ring buffer (oldest sample first; `write_pos` is the next
+    /// slot to be written) as the new grain and restart its loop.
+    pub fn capture(&mut self, ring: &[f32], write_pos: usize) {
+        let n = ring.len();
+        self.grain.clear();
+        self.grain.extend((0..n).map(|i| ring[(write_pos + i) % n]));
+        self.pos = 0;
+    }
+
+    /// Stop immediately, without the fade-out.
+    pub fn reset(&mut self) {
+        self.active = false;
+        self.gain = 0.0;
+    }
+
+    /// Next pad sample.  The loop plays `grain[..len - SEAM_FADE]`; its first
+    /// `SEAM_FADE` samples are blended with the tail that follows the loop
+    /// end, so wrapping around is continuous.
+    pub fn next_sample(&mut self) -> f32 {
+        let target = if self.active { 1.0 } else { 0.0 };
+        if self.gain < target {
+            self.gain = (self.gain + self.fade_step).min(target);
+        } else if self.gain > target {
+            self.gain = (self.gain - self.fade_step).max(target);
+        }
+        if self.gain == 0.0 || self.grain.len() <= SEAM_FADE * 2 { return 0.0; }
+
+        let loop_len = self.grain.len() - SEAM_FADE;
+        let p = self.pos;
+        let x = if p < SEAM_FADE {
+            let t = p as f32 / SEAM_FADE as f32;
+            self.grain[p] * t + self.grain[p + loop_len] * (1.0 - t)
+        } else {
+            self.grain[p]
+        };
+        self.pos = (p + 1) % loop_len;
+        x * self.gain * self.level
+    }
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+use crate::sequencer::MAX_NUDGE;
+
+/// Directory (relative to the working directory) scanned for user grooves.
+pub const GROOVE_DIR: &str = "grooves";
+
+/// Longest groove template, in steps (matches the longest drum pattern).
+pub const MAX_GROOVE_STEPS: usize = 32;
+
+// ── Groove template ───────────────────────────────────────────────────────────
+//
+// MPC-style feel: a per-step timing offset and velocity scale laid over the
+// straight drum grid.  The template repeats every `timing.len()` steps and
+// stacks with swing and per-step nudge (offsets add, clamped by `due_step`).
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Groove {
+    pub name: String,
+    /// Timing offset per step, in percent of one step (like `DrumTrack::nudge`).
+    pub timing: Vec<i8>,
+    /// Velocity per step, in percent of the track volume (100 = as programmed).
+    /// Shorter than `timing` (or empty) means 100 for the missing steps.
+    #[serde(default)]
+    pub velocity: Vec<u8>,
+    /// True for the built-in grooves (never written to disk).
+    #[serde(skip)]
+    pub factory: bool,
+}
+
+impl Groove {
+    /// Timing offset for pattern step `step`, in percent of a step.
+    pub fn offset(&self, step: usize) -> i8 {
+        if self.timing.is_empty() { return 0; }
+        self.timing[step % self.timing.len()]
+    }
+
+    /// Velocity scale for pattern step `step` (1.0 = unchanged).
+    pub fn gain(&self, step: usize) -> f32 {
+        if self.timing.is_empty() { return 1.0; }
+        let v = self.velocity.get(step % self.timing.len()).copied().unwrap_or(100);
+        v as f32 / 100.0
+    }
+
+    /// Clamp a loaded template (file or save) to the ranges the drum machine
+    /// accepts.  Returns `None` if nothing usable is left.
+    pub fn sanitize(mut self) -> Option<Self> {
+        self.timing.truncate(MAX_GROOVE_STEPS);
+        self.velocity.truncate(self.timing.len());
+        for t in &mut self.timing   { *t = (*t).clamp(-MAX_NUDGE, MAX_NUDGE); }
+        for v in &mut self.velocity { *v = (*v).min(200); }
+        (!self.timing.is_empty()).then_some(self)
+    }
+}
+
+fn factory_groove(name: &str, timing: &[i8], velocity: &[u8]) -> Groove {
+    Groove { name: name.to_string(), timing: timing.to_vec(), velocity: velocity.to_vec(), factory: true }
+}
+
+/// Built-in grooves, always listed first.
+pub fn factory() -> Vec<Groove> {
+    vec![
+        // 58% 16th swing with soft off-beats.
+        factory_groove("MPC 58",
+            &[0, 16, 0, 16, 0, 16, 0, 16, 0, 16, 0, 16, 0, 16, 0, 16],
+            &[100, 62, 82, 58, 94, 64, 80, 56, 100, 62, 82, 58, 94, 64, 80, 56]),
+        // Straight downbeats, backbeats and off-16ths dragged behind the grid.
+        factory_groove("Lazy",
+            &[0, 6, 2, 8, 10, 6, 2, 8, 0, 6, 2, 8, 10, 6, 2, 8],
+            &[100, 55, 75, 60, 96, 55, 72, 62, 100, 55, 75, 60, 96, 55, 72, 62]),
+    ]
+}
+
+/// Factory grooves followed by every readable `grooves/*.json`, sorted by name.
+pub fn available() -> Vec<Groove> {
+    let mut user: Vec<Groove> = std::fs::read_dir(GROOVE_DIR)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|x| x == "json"))
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .filter_map(|j| serde_json::from_str::<Groove>(&j).ok())
+        .filter_map(Groove::sanitize)
+        .collect();
+    user.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut grooves = factory();
+    grooves.extend(user);
+    grooves
+}
@@ -1,19 +1,36 @@
 mod app;
 mod audio;
+mod automation;
+mod click;
+mod command;
 mod drums;
+mod dump;
 mod effects;
+mod freeze;
+mod groove;
+mod midi;
+mod midi_in;
+mod param;
+mod rack;
+mod render;
+mod rng;
+mod sample;
 mod save;
 mod scale;
 mod sequencer;
+mod smooth;
+mod solo;
 mod synth;
+mod tempo;
+mod tuner;
 mod ui;
 
-use anyhow::Result;
-use app::{App, AppMode, InputMode};
+use anyhow::{anyhow, bail, Context, Result};
+use app::{App, AppMode, InputMode, DEFAULT_PROJECT};
 use audio::AudioEngine;
 use crossterm::{
     event::{
-        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind,
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
         KeyboardEnhancementFlags, KeyModifiers, PopKeyboardEnhancementFlags,
         PushKeyboardEnhancementFlags,
     },
@@ -24,10 +41,196 @@ use crossterm::{
     },
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::{io, sync::{Arc, Mutex}, time::Duration};
+use std::{io::{self, Write}, path::Path, sync::{Arc, Mutex}, time::{Duration, Instant}};
 use synth::Synth;
 
+/// Target redraw rate when `--fps` is not given (one frame ≈ 16 ms).
+const DEFAULT_FPS: u32 = 60;
+/// Input poll timeout while nothing is animating.  Still short enough for the
+/// non-enhanced key-release fallback.
+const IDLE_POLL: Duration = Duration::from_millis(100);
+/// Most input events applied before a redraw is forced.
+const MAX_EVENTS_PER_FRAME: usize = 64;
+
+/// Sample rate for headless renders (no device to ask).
+const RENDER_SAMPLE_RATE: f32 = 44100.0;
+/// Bars rendered or exported when `--bars` is not given.
+const DEFAULT_RENDER_BARS: u32 = 8;
+/// Exit status for a bad command line (runtime failures exit with 1).
+const EXIT_USAGE: i32 = 2;
+
+const USAGE: &str = "usage: tuibeat [--fps <1-240>] [--project <project.json> | --fresh]\n       \
+                     tuibeat --render <project.json> [--out <file.wav>] [--bars <n>] [--loop]\n       \
+                     tuibeat --midi <project.json> [--out <file.mid>] [--bars <n>]\n       \
+                     tuibeat --stems <project.json> [--out <dir>] [--bars <n>] [--loop]\n       \
+                     tuibeat --help";
+
+/// What the binary was asked to do.
+enum Mode {
+    /// Interactive TUI (the default).  `project` is loaded at startup in place
+    /// of the remembered one; `fresh` skips the startup load.
+    Tui { fps: u32, project: Option<String>, fresh: bool },
+    /// Load a project, write a WAV file and exit without touching the terminal.
+    /// `looped` crossfades an extra bar into the start for a seamless loop.
+    Render { project: String, out: String, bars: u32, looped: bool },
+    /// Load a project, write a MIDI file and exit without touching the terminal.
+    Midi { project: String, out: String, bars: u32 },
+    /// Load a project, write one WAV per drum track plus the synth bus into a directory.
+    Stems { project: String, out: String, bars: u32, looped: bool },
+    /// Hidden: load a project, render it offline and print its fingerprint
+    /// (`render::fingerprint`) for golden-output checks.
+    Checksum { project: String, bars: u32 },
+}
+
+/// Parse the command line (without the program name): `--fps <n>` (1–240) and `--project <file>` or
+/// `--fresh` for the TUI, or one headless
+/// mode (`--render` / `--midi` / `--stems <project> [--out <path>] [--bars <n>]`,
+/// plus `--loop` for the WAV modes).  Every valued flag also accepts `--flag=value`.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Mode> {
+    let mut args = args.into_iter();
+    let (mut fps, mut render, mut midi, mut stems, mut checksum) = (None, None, None, None, None);
+    let (mut out, mut bars, mut looped) = (None, None, false);
+    let (mut startup, mut fresh) = (None, false);
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((f, v)) => (f.to_string(), Some(v.to_string())),
+            None         => (arg.clone(), None),
+        };
+        let mut value = || inline.clone().or_else(|| args.next())
+            .ok_or_else(|| anyhow!("{} needs a value", flag));
+        match flag.as_str() {
+            "--fps"    => fps    = Some(value()?),
+            "--render" => render = Some(value()?),
+            "--midi"   => midi   = Some(value()?),
+            "--stems"  => stems  = Some(value()?),
+            "--checksum" => checksum = Some(value()?),
+            "--out"    => out    = Some(value()?),
+            "--bars"   => bars   = Some(value()?),
+            "--project" => startup = Some(value()?),
+            "--loop" if inline.is_none() => looped = true,
+            "--fresh" if inline.is_none() => fresh = true,
+            "-h" | "--help" => { println!("{}", USAGE); std::process::exit(0); }
+            _ => bail!("Unknown argument: {}\n{}", arg, USAGE),
+        }
+    }
+
+    let mut chosen: Vec<(&str, String)> = [("--render", render), ("--midi", midi), ("--stems", stems), ("--checksum", checksum)]
+        .into_iter()
+        .filter_map(|(flag, project)| Some((flag, project?)))
+        .collect();
+    if chosen.len() > 1 {
+        let flags: Vec<&str> = chosen.iter().map(|&(f, _)| f).collect();
+        bail!("{} can't be combined", flags.join(" and "));
+    }
+    let Some((flag, project)) = chosen.pop() else {
+        if out.is_some() || bars.is_some() || looped {
+            bail!("--out, --bars and --loop need --render, --midi or --stems\n{}", USAGE);
+        }
+        let fps = match fps {
+            Some(v) => v.parse().map_err(|_| anyhow!("Invalid --fps value: {}", v))?,
+            None    => DEFAULT_FPS,
+        };
+        if !(1..=240).contains(&fps) { bail!("--fps must be between 1 and 240"); }
+        if fresh && startup.is_some() { bail!("--project and --fresh can't be combined"); }
+        return Ok(Mode::Tui { fps, project: startup, fresh });
+    };
+    if fps.is_some() { bail!("--fps has no effect in headless modes"); }
+    if startup.is_some() || fresh { bail!("--project and --fresh only apply to the TUI"); }
+    if looped && matches!(flag, "--midi" | "--checksum") { bail!("--loop only applies to --render and --stems"); }
+    if out.is_some() && flag == "--checksum" { bail!("--checksum prints to stdout; --out has no effect"); }
+    let bars = match bars {
+        Some(v) => v.parse().ok().filter(|&n| (1..=999).contains(&n))
+            .ok_or_else(|| anyhow!("Invalid --bars value: {}  (1-999)", v))?,
+        None    => DEFAULT_RENDER_BARS,
+    };
+    let out = out.unwrap_or_else(|| {
+        let path = Path::new(&project);
+        match flag {
+            "--render" => path.with_extension("wav").to_string_lossy().into_owned(),
+            "--midi"   => path.with_extension("mid").to_string_lossy().into_owned(),
+            _          => format!("{}-stems", path.with_extension("").display()),
+        }
+    });
+    Ok(match flag {
+        "--render" => Mode::Render { project, out, bars, looped },
+        "--midi"   => Mode::Midi   { project, out, bars },
+        "--checksum" => Mode::Checksum { project, bars },
+        _          => Mode::Stems  { project, out, bars, looped },
+    })
+}
+
+/// Synth with `project` loaded through the same path as Ctrl+L, for the
+/// headless modes (no raw mode, no audio device).
+fn load_headless(project: &str) -> Result<Synth> {
+    let synth = Arc::new(Mutex::new(Synth::new(RENDER_SAMPLE_RATE)));
+    let warnings = App::new(Arc::clone(&synth)).load_project(project)
+        .with_context(|| format!("Cannot load {}", project))?;
+    for w in warnings { eprintln!("warning: {}", w); }
+    // The temporary App is gone, so this is the last handle.
+    let synth = Arc::try_unwrap(synth).map_err(|_| anyhow!("Synth still shared after load"))?;
+    Ok(synth.into_inner().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Headless `--render`: progress on stdout.
+fn run_render(project: &str, out: &str, bars: u32, looped: bool) -> Result<()> {
+    let mut s = load_headless(project)?;
+    println!("Rendering {} ({} bars at {:.0} BPM) → {}", project, bars, s.bpm, out);
+    render::rewind_and_play(&mut s);
+    let frames = render::render_to_wav(&mut s, bars, Path::new(out), looped, |bar, bars| {
+        print!("\r  bar {}/{}", bar, bars);
+        let _ = io::stdout().flush();
+    })?;
+    println!("\nWrote {} ({:.1} s)", out, frames as f32 / s.sample_rate);
+    Ok(())
+}
+
+/// Headless `--midi`.
+fn run_midi(project: &str, out: &str, bars: u32) -> Result<()> {
+    let s = load_headless(project)?;
+    let notes = midi::export_midi(&s, bars, Path::new(out))?;
+    println!("Wrote {} ({} bars at {:.0} BPM, {} notes)", out, bars, s.bpm, notes);
+    Ok(())
+}
+
+/// Headless `--checksum`: one line, easy to diff or paste into the golden test.
+fn run_checksum(project: &str, bars: u32) -> Result<()> {
+    let fp = render::fingerprint(&mut load_headless(project)?, bars);
+    println!("frames={} peak={:.6} rms={:.6} checksum={:#018x}", fp.frames, fp.peak, fp.rms, fp.checksum);
+    Ok(())
+}
+
+/// Headless `--stems`: the project is reloaded for every stem so each one
+/// starts from the same state.
+fn run_stems(project: &str, out: &str, bars: u32, looped: bool) -> Result<()> {
+    println!("Rendering stems of {} ({} bars) → {}/", project, bars, out);
+    let files = render::render_stems(|| load_headless(project), bars, Path::new(out), looped, |stem, bar, bars| {
+        print!("\r  {:<12} bar {}/{}", stem, bar, bars);
+        let _ = io::stdout().flush();
+    })?;
+    println!("\nWrote {} stems to {}", files.len(), out);
+    Ok(())
+}
+
 fn main() -> Result<()> {
+    let mode = parse_args(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(EXIT_USAGE);
+    });
+    let headless = match mode {
+        Mode::Tui { fps, project, fresh } => return run_tui(fps, project.as_deref(), fresh),
+        Mode::Render { project, out, bars, looped } => run_render(&project, &out, bars, looped),
+        Mode::Midi   { project, out, bars } => run_midi(&project, &out, bars),
+        Mode::Stems  { project, out, bars, looped } => run_stems(&project, &out, bars, looped),
+        Mode::Checksum { project, bars } => run_checksum(&project, bars),
+    };
+    if let Err(e) = headless {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn run_tui(fps: u32, project: Option<&str>, fresh: bool) -> Result<()> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
 
@@ -43,7 +246,7 @@ fn main() -> Result<()> {
 
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
-    let result = run(&mut terminal, enhanced);
+    let result = run(&mut terminal, enhanced, fps, project, fresh);
 
     disable_raw_mode()?;
     if enhanced {
@@ -57,238 +260,503 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, enhanced: bool) -> Result<()> {
-    let synth  = Arc::new(Mutex::new(Synth::new(44100.0)));
-    let _audio = AudioEngine::new(Arc::clone(&synth))?;
+fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, enhanced: bool, fps: u32,
+       project: Option<&str>, fresh: bool) -> Result<()> {
+    let synth = Arc::new(Mutex::new(Synth::new(44100.0)));
+    let audio = AudioEngine::new(Arc::clone(&synth), None, false)?;
     let mut app = App::new(Arc::clone(&synth));
+    app.audio = Some(audio);
+    app.fallback_notice = !enhanced;
+    // After the audio engine, so inserts are built at the device rate.
+    if !fresh { app.load_startup(project); }
+
+    let frame = Duration::from_secs_f64(1.0 / fps as f64);
+    let mut last_draw: Option<Instant> = None;
+    let mut dirty = true;
 
-    loop {
+    'main: loop {
         if !enhanced { app.tick_fallback_release(); }
-        app.refresh_active_notes();
-        terminal.draw(|f| ui::draw(f, &app, enhanced))?;
-
-        if event::poll(Duration::from_millis(16))? {
-            match event::read()? {
-                Event::Key(key) => {
-                    // ── Key release (enhanced mode only) ──────────────────
-                    if key.kind == KeyEventKind::Release {
-                        if app.mode == AppMode::Play {
-                            if let KeyCode::Char(c) = key.code { app.key_release(c); }
-                        }
-                        continue;
-                    }
 
-                    // ── Key repeat ────────────────────────────────────────
-                    if key.kind == KeyEventKind::Repeat {
-                        match key.code {
-                            // Global BPM + scale + chord
-                            KeyCode::PageUp   => app.bpm_up(),
-                            KeyCode::PageDown => app.bpm_down(),
-                            KeyCode::F(6)     => app.cycle_scale(),
-                            KeyCode::F(7)     => app.cycle_scale_root(),
-                            KeyCode::F(8) => {
-                                if app.mode == AppMode::SynthSeq2 { app.cycle_chord2(); }
-                                else { app.cycle_chord1(); }
-                            }
-                            KeyCode::F(9) => match app.mode {
-                                AppMode::SynthSeq  => { let b = (app.seq1_bank + 1) % 4; app.switch_seq1_bank(b); }
-                                AppMode::SynthSeq2 => { let b = (app.seq2_bank + 1) % 4; app.switch_seq2_bank(b); }
-                                AppMode::Drums     => { let b = (app.drum_bank + 1) % 4; app.switch_drum_bank(b); }
-                                _ => {}
-                            },
-
-                            // Effects focus: navigation + param adjust (no Space repeat)
-                            KeyCode::Up    if app.mode == AppMode::Effects => app.effects_sel_up(),
-                            KeyCode::Down  if app.mode == AppMode::Effects => app.effects_sel_down(),
-                            KeyCode::Left  if app.mode == AppMode::Effects => app.effects_param_left(),
-                            KeyCode::Right if app.mode == AppMode::Effects => app.effects_param_right(),
-                            KeyCode::Char('=') if app.mode == AppMode::Effects => app.effects_param_inc(),
-                            KeyCode::Char('-') if app.mode == AppMode::Effects => app.effects_param_dec(),
-
-                            // Drums focus: navigation + drum vol repeat
-                            KeyCode::Up    if app.mode == AppMode::Drums => app.drum_track_up(),
-                            KeyCode::Down  if app.mode == AppMode::Drums => app.drum_track_down(),
-                            KeyCode::Left  if app.mode == AppMode::Drums => app.drum_step_left(),
-                            KeyCode::Right if app.mode == AppMode::Drums => app.drum_step_right(),
-                            KeyCode::Char('=') if app.mode == AppMode::Drums => app.drum_vol_up(),
-                            KeyCode::Char('-') if app.mode == AppMode::Drums => app.drum_vol_down(),
-                            KeyCode::Char('p') if app.mode == AppMode::Drums => app.drum_prob_up(),
-                            KeyCode::Char('[') if app.mode == AppMode::Drums => app.drum_prob_down(),
-                            KeyCode::Char('<') if app.mode == AppMode::Drums => app.drum_swing_down(),
-                            KeyCode::Char('>') if app.mode == AppMode::Drums => app.drum_swing_up(),
-
-                            // SynthSeq2 focus: cursor + BPM + volume + octave
-                            KeyCode::Enter if app.mode == AppMode::SynthSeq2 => app.seq2_toggle_play(),
-                            KeyCode::Left  if app.mode == AppMode::SynthSeq2 => app.seq2_cursor_left(),
-                            KeyCode::Right if app.mode == AppMode::SynthSeq2 => app.seq2_cursor_right(),
-                            KeyCode::Up    if app.mode == AppMode::SynthSeq2 => app.bpm_up(),
-                            KeyCode::Down  if app.mode == AppMode::SynthSeq2 => app.bpm_down(),
-                            KeyCode::Char('=') if app.mode == AppMode::SynthSeq2 => app.synth2_vol_up(),
-                            KeyCode::Char('-') if app.mode == AppMode::SynthSeq2 => app.synth2_vol_down(),
-                            KeyCode::Char('[') if app.mode == AppMode::SynthSeq2 => app.octave_down(),
-                            KeyCode::Char('{') if app.mode == AppMode::SynthSeq2 => app.octave_up(),
-
-                            // SynthSeq focus: cursor + BPM + volume + octave
-                            KeyCode::Enter if app.mode == AppMode::SynthSeq => app.seq_toggle_play(),
-                            KeyCode::Left  if app.mode == AppMode::SynthSeq => app.seq_cursor_left(),
-                            KeyCode::Right if app.mode == AppMode::SynthSeq => app.seq_cursor_right(),
-                            KeyCode::Up    if app.mode == AppMode::SynthSeq => app.bpm_up(),
-                            KeyCode::Down  if app.mode == AppMode::SynthSeq => app.bpm_down(),
-                            KeyCode::Char('=') if app.mode == AppMode::SynthSeq => app.volume_up(),
-                            KeyCode::Char('-') if app.mode == AppMode::SynthSeq => app.volume_down(),
-                            KeyCode::Char('[') if app.mode == AppMode::SynthSeq => app.octave_down(),
-                            KeyCode::Char('{') if app.mode == AppMode::SynthSeq => app.octave_up(),
-
-                            // Keyboard focus: octave + volume
-                            KeyCode::Left  => app.octave_down(),
-                            KeyCode::Right => app.octave_up(),
-                            KeyCode::Up    => app.volume_up(),
-                            KeyCode::Down  => app.volume_down(),
-
-                            _ => {
-                                if let KeyCode::Char(c) = key.code {
-                                    if app.mode == AppMode::Play { app.key_press_fallback(c); }
-                                }
-                            }
-                        }
-                        continue;
+        // Redraw only when something changed: input arrived, the held notes
+        // changed, or playback/audio is animating the panels.
+        let animating = app.is_animating();
+        let until_frame = last_draw.map_or(Duration::ZERO, |t| frame.saturating_sub(t.elapsed()));
+        let timeout = if dirty || animating { until_frame } else { IDLE_POLL };
+
+        // Wait for input, then drain what is already queued (in arrival order,
+        // so press/release pairs stay paired) so key presses are applied
+        // before the frame that shows them.  The cap keeps a burst from
+        // starving the redraw; the rest is picked up next frame.
+        if event::poll(timeout)? {
+            for _ in 0..MAX_EVENTS_PER_FRAME {
+                if !handle_event(&mut app, event::read()?, enhanced) { break 'main; }
+                if !event::poll(Duration::ZERO)? { break; }
+            }
+            dirty = true;
+        }
+        if app.should_quit { break; }
+
+        if app.poll_audio() { dirty = true; }
+        if app.poll_midi() { dirty = true; }
+        if app.tick_automation() { dirty = true; }
+        if app.refresh_snapshot() { dirty = true; }
+        let frame_due = last_draw.is_none_or(|t| t.elapsed() >= frame);
+        if (dirty || animating) && frame_due {
+            terminal.draw(|f| ui::draw(f, &app, enhanced))?;
+            last_draw = Some(Instant::now());
+            dirty = false;
+        }
+    }
+
+    app.release_all();
+    Ok(())
+}
+
+/// Fine BPM steps: PageUp/PageDown with Shift, or Alt where the terminal
+/// keeps Shift+PageUp for its scrollback.
+fn bpm_fine(key: &KeyEvent) -> bool {
+    key.modifiers.intersects(KeyModifiers::SHIFT | KeyModifiers::ALT)
+}
+
+/// Apply one terminal event to `app`.  Returns `false` when the user quits.
+fn handle_event(app: &mut App, ev: Event, enhanced: bool) -> bool {
+    match ev {
+        Event::Key(key) => {
+            // ── Key release (enhanced mode only) ──────────────────
+            if key.kind == KeyEventKind::Release {
+                if app.mode == AppMode::Play {
+                    if let KeyCode::Char(c) = key.code { app.key_release(c); }
+                }
+                return true;
+            }
+
+            // ── Key repeat ────────────────────────────────────────
+            if key.kind == KeyEventKind::Repeat {
+                match key.code {
+                    // Global BPM + scale + chord
+                    KeyCode::PageUp   => app.bpm_step(true, bpm_fine(&key), true),
+                    KeyCode::PageDown => app.bpm_step(false, bpm_fine(&key), true),
+                    // Solo keys don't repeat (nor play the piano key)
+                    KeyCode::Char('s' | 'S') if key.modifiers.contains(KeyModifiers::ALT) => {}
+                    KeyCode::F(6)     => app.cycle_scale(),
+                    KeyCode::F(7)     => app.cycle_scale_root(),
+                    KeyCode::F(8) => {
+                        if app.mode == AppMode::SynthSeq2 { app.cycle_chord2(); }
+                        else { app.cycle_chord1(); }
                     }
+                    KeyCode::F(9) => match app.mode {
+                        AppMode::SynthSeq  => { let b = (app.seq1_bank + 1) % 4; app.switch_seq1_bank(b); }
+                        AppMode::SynthSeq2 => { let b = (app.seq2_bank + 1) % 4; app.switch_seq2_bank(b); }
+                        AppMode::Drums     => { let b = (app.drum_bank + 1) % 4; app.switch_drum_bank(b); }
+                        _ => {}
+                    },
+
+                    // Effects focus: navigation + param adjust (no Space repeat)
+                    KeyCode::Up    if app.mode == AppMode::Effects => app.effects_sel_up(),
+                    KeyCode::Down  if app.mode == AppMode::Effects => app.effects_sel_down(),
+                    KeyCode::Left  if app.mode == AppMode::Effects => app.effects_param_left(),
+                    KeyCode::Right if app.mode == AppMode::Effects => app.effects_param_right(),
+                    KeyCode::Char('=') if app.mode == AppMode::Effects => app.effects_param_inc(),
+                    KeyCode::Char('-') if app.mode == AppMode::Effects => app.effects_param_dec(),
+
+                    // Drums focus: navigation + drum vol repeat
+                    KeyCode::Up    if app.mode == AppMode::Drums => app.drum_track_up(),
+                    KeyCode::Down  if app.mode == AppMode::Drums => app.drum_track_down(),
+                    KeyCode::Left  if app.mode == AppMode::Drums && key.modifiers.contains(KeyModifiers::CONTROL) => app.drum_shift_left(),
+                    KeyCode::Right if app.mode == AppMode::Drums && key.modifiers.contains(KeyModifiers::CONTROL) => app.drum_shift_right(),
+                    KeyCode::Left  if app.mode == AppMode::Drums && key.modifiers.contains(KeyModifiers::SHIFT) => app.drum_nudge(false),
+                    KeyCode::Right if app.mode == AppMode::Drums && key.modifiers.contains(KeyModifiers::SHIFT) => app.drum_nudge(true),
+                    KeyCode::Left  if app.mode == AppMode::Drums => app.drum_step_left(),
+                    KeyCode::Right if app.mode == AppMode::Drums => app.drum_step_right(),
+                    KeyCode::Char('=') if app.mode == AppMode::Drums => app.drum_vol_up(),
+                    KeyCode::Char('-') if app.mode == AppMode::Drums => app.drum_vol_down(),
+                    KeyCode::Char('p') if app.mode == AppMode::Drums => app.drum_prob_up(),
+                    KeyCode::Char('[') if app.mode == AppMode::Drums => app.drum_prob_down(),
+                    KeyCode::Char('<') if app.mode == AppMode::Drums => app.drum_swing_down(),
+                    KeyCode::Char('>') if app.mode == AppMode::Drums => app.drum_swing_up(),
+                    KeyCode::Char('{') if app.mode == AppMode::Drums => app.drum_pan(false),
+                    KeyCode::Char('}') if app.mode == AppMode::Drums => app.drum_pan(true),
+                    KeyCode::Char(';') if app.mode == AppMode::Drums => app.drum_decay(false),
+                    KeyCode::Char('\'') if app.mode == AppMode::Drums => app.drum_decay(true),
+                    KeyCode::Char('.') if app.mode == AppMode::Drums => app.drum_tune(false),
+                    KeyCode::Char('/') if app.mode == AppMode::Drums => app.drum_tune(true),
+
+                    // Pitch entry: the pending note climbs; Enter doesn't repeat
+                    KeyCode::Up    if app.pitch_entry_active() && !key.modifiers.contains(KeyModifiers::SHIFT) => app.pending_note_step(true),
+                    KeyCode::Down  if app.pitch_entry_active() && !key.modifiers.contains(KeyModifiers::SHIFT) => app.pending_note_step(false),
+                    KeyCode::Enter if app.pitch_entry_active() => {}
+
+                    // SynthSeq2 focus: cursor + BPM + volume + octave
+                    KeyCode::Enter if app.mode == AppMode::SynthSeq2 => app.seq2_toggle_play(),
+                    KeyCode::Left  if app.mode == AppMode::SynthSeq2 && key.modifiers.contains(KeyModifiers::CONTROL) => app.seq2_shift_left(),
+                    KeyCode::Right if app.mode == AppMode::SynthSeq2 && key.modifiers.contains(KeyModifiers::CONTROL) => app.seq2_shift_right(),
+                    KeyCode::Left  if app.mode == AppMode::SynthSeq2 && key.modifiers.contains(KeyModifiers::SHIFT) => app.seq2_nudge(false),
+                    KeyCode::Right if app.mode == AppMode::SynthSeq2 && key.modifiers.contains(KeyModifiers::SHIFT) => app.seq2_nudge(true),
+                    KeyCode::Left  if app.mode == AppMode::SynthSeq2 => app.seq2_cursor_left(),
+                    KeyCode::Right if app.mode == AppMode::SynthSeq2 => app.seq2_cursor_right(),
+                    KeyCode::Up    if app.mode == AppMode::SynthSeq2 && key.modifiers.contains(KeyModifiers::ALT) => app.seq2_prob(true),
+                    KeyCode::Down  if app.mode == AppMode::SynthSeq2 && key.modifiers.contains(KeyModifiers::ALT) => app.seq2_prob(false),
+                    // Accent / slide toggles don't repeat
+                    KeyCode::Up | KeyCode::Down if app.mode == AppMode::SynthSeq2 && key.modifiers.contains(KeyModifiers::SHIFT) => {}
+                    KeyCode::Up    if app.mode == AppMode::SynthSeq2 => app.bpm_step(true, false, true),
+                    KeyCode::Down  if app.mode == AppMode::SynthSeq2 => app.bpm_step(false, false, true),
+                    KeyCode::Char('=') if app.mode == AppMode::SynthSeq2 => app.synth2_vol_up(),
+                    KeyCode::Char('-') if app.mode == AppMode::SynthSeq2 => app.synth2_vol_down(),
+                    KeyCode::Char('[') if app.mode == AppMode::SynthSeq2 => app.octave_down(),
+                    KeyCode::Char('{') if app.mode == AppMode::SynthSeq2 => app.octave_up(),
+                    KeyCode::Char('<') if app.mode == AppMode::SynthSeq2 => app.seq_swing_step(false),
+                    KeyCode::Char('>') if app.mode == AppMode::SynthSeq2 => app.seq_swing_step(true),
+
+                    // SynthSeq focus: cursor + BPM + volume + octave
+                    KeyCode::Enter if app.mode == AppMode::SynthSeq => app.seq_toggle_play(),
+                    KeyCode::Left  if app.mode == AppMode::SynthSeq && key.modifiers.contains(KeyModifiers::CONTROL) => app.seq_shift_left(),
+                    KeyCode::Right if app.mode == AppMode::SynthSeq && key.modifiers.contains(KeyModifiers::CONTROL) => app.seq_shift_right(),
+                    KeyCode::Left  if app.mode == AppMode::SynthSeq && key.modifiers.contains(KeyModifiers::SHIFT) => app.seq_nudge(false),
+                    KeyCode::Right if app.mode == AppMode::SynthSeq && key.modifiers.contains(KeyModifiers::SHIFT) => app.seq_nudge(true),
+                    KeyCode::Left  if app.mode == AppMode::SynthSeq => app.seq_cursor_left(),
+                    KeyCode::Right if app.mode == AppMode::SynthSeq => app.seq_cursor_right(),
+                    KeyCode::Up    if app.mode == AppMode::SynthSeq && key.modifiers.contains(KeyModifiers::ALT) => app.seq_prob(true),
+                    KeyCode::Down  if app.mode == AppMode::SynthSeq && key.modifiers.contains(KeyModifiers::ALT) => app.seq_prob(false),
+                    KeyCode::Up    if app.mode == AppMode::SynthSeq => app.bpm_step(true, false, true),
+                    KeyCode::Down  if app.mode == AppMode::SynthSeq => app.bpm_step(false, false, true),
+                    KeyCode::Char('=') if app.mode == AppMode::SynthSeq => app.volume_up(),
+                    KeyCode::Char('-') if app.mode == AppMode::SynthSeq => app.volume_down(),
+                    KeyCode::Char('[') if app.mode == AppMode::SynthSeq => app.octave_down(),
+                    KeyCode::Char('{') if app.mode == AppMode::SynthSeq => app.octave_up(),
+                    KeyCode::Char('<') if app.mode == AppMode::SynthSeq => app.seq_swing_step(false),
+                    KeyCode::Char('>') if app.mode == AppMode::SynthSeq => app.seq_swing_step(true),
+
+                    // Keyboard focus: octave + volume
+                    KeyCode::Left  => app.octave_down(),
+                    KeyCode::Right => app.octave_up(),
+                    KeyCode::Up    => app.volume_up(),
+                    KeyCode::Down  => app.volume_down(),
 
-                    // ── Input mode: intercept all keys for file-path prompt ──
-                    if app.input_mode != InputMode::None {
-                        match key.code {
-                            KeyCode::Esc => {
-                                app.input_mode = InputMode::None;
-                                app.input_buf.clear();
-                                app.status_msg = "Cancelled".to_string();
-                            }
-                            KeyCode::Enter     => app.commit_input(),
-                            KeyCode::Backspace => { app.input_buf.pop(); }
-                            KeyCode::Char(c)   => app.input_buf.push(c),
-                            _ => {}
+                    _ => {
+                        if let KeyCode::Char(c) = key.code {
+                            if app.mode == AppMode::Play && !app.perf_view { app.key_repeat(c); }
                         }
-                        continue;
                     }
+                }
+                return true;
+            }
 
-                    // ── Key press ─────────────────────────────────────────
-                    match key.code {
-                        // Global quit
-                        KeyCode::Esc => break,
-                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
-                        // Save / Load
-                        KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            app.input_mode = InputMode::Save;
-                            app.input_buf  = "rusttuisynth.json".to_string();
-                        }
-                        KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            app.input_mode = InputMode::Load;
-                            app.input_buf  = "rusttuisynth.json".to_string();
-                        }
+            // ── Input mode: intercept all keys for file-path prompt ──
+            if app.input_mode != InputMode::None {
+                match key.code {
+                    KeyCode::Esc => {
+                        app.input_mode = InputMode::None;
+                        app.input_buf.clear();
+                        app.status_msg = "Cancelled".to_string();
+                    }
+                    KeyCode::Enter     => app.commit_input(),
+                    KeyCode::Backspace => { app.input_buf.pop(); }
+                    KeyCode::Char(c)   => app.input_buf.push(c),
+                    _ => {}
+                }
+                return true;
+            }
 
-                        // Global: cycle focus, waveform, drum play, BPM, scale, chord, bank
-                        KeyCode::Tab          => app.toggle_mode(),
-                        KeyCode::F(2)         => app.toggle_mode(),
-                        KeyCode::F(1)         => app.cycle_wave(),
-                        KeyCode::F(3)         => app.drum_toggle_play(),
-                        KeyCode::F(6)         => app.cycle_scale(),
-                        KeyCode::F(7)         => app.cycle_scale_root(),
-                        KeyCode::F(8) => {
-                            if app.mode == AppMode::SynthSeq2 { app.cycle_chord2(); }
-                            else { app.cycle_chord1(); }
-                        }
-                        KeyCode::F(9) => match app.mode {
-                            AppMode::SynthSeq  => { let b = (app.seq1_bank + 1) % 4; app.switch_seq1_bank(b); }
-                            AppMode::SynthSeq2 => { let b = (app.seq2_bank + 1) % 4; app.switch_seq2_bank(b); }
-                            AppMode::Drums     => { let b = (app.drum_bank + 1) % 4; app.switch_drum_bank(b); }
-                            _ => {}
-                        },
-                        KeyCode::PageUp       => app.bpm_up(),
-                        KeyCode::PageDown     => app.bpm_down(),
-
-                        // ── Effects focus ─────────────────────────────────
-                        KeyCode::Up    if app.mode == AppMode::Effects => app.effects_sel_up(),
-                        KeyCode::Down  if app.mode == AppMode::Effects => app.effects_sel_down(),
-                        KeyCode::Left  if app.mode == AppMode::Effects => app.effects_param_left(),
-                        KeyCode::Right if app.mode == AppMode::Effects => app.effects_param_right(),
-                        KeyCode::Char('=') if app.mode == AppMode::Effects => app.effects_param_inc(),
-                        KeyCode::Char('-') if app.mode == AppMode::Effects => app.effects_param_dec(),
-                        KeyCode::Enter     if app.mode == AppMode::Effects => app.effects_on_off(),
-                        KeyCode::Char(' ') if app.mode == AppMode::Effects => app.effects_route_toggle(),
-
-                        // ── Drums focus ───────────────────────────────────
-                        KeyCode::Up    if app.mode == AppMode::Drums => app.drum_track_up(),
-                        KeyCode::Down  if app.mode == AppMode::Drums => app.drum_track_down(),
-                        KeyCode::Left  if app.mode == AppMode::Drums => app.drum_step_left(),
-                        KeyCode::Right if app.mode == AppMode::Drums => app.drum_step_right(),
-                        KeyCode::Enter if app.mode == AppMode::Drums => app.drum_toggle_play(),
-                        KeyCode::Backspace | KeyCode::Delete if app.mode == AppMode::Drums => app.drum_clear_step(),
-                        KeyCode::Char(' ')  if app.mode == AppMode::Drums => app.drum_toggle_step(),
-                        KeyCode::Char(']')  if app.mode == AppMode::Drums => app.drum_cycle_steps(),
-                        KeyCode::Char('\\') if app.mode == AppMode::Drums => app.drum_toggle_mute(),
-                        KeyCode::Char('=')  if app.mode == AppMode::Drums => app.drum_vol_up(),
-                        KeyCode::Char('-')  if app.mode == AppMode::Drums => app.drum_vol_down(),
-                        KeyCode::Char('p')  if app.mode == AppMode::Drums => app.drum_prob_up(),
-                        KeyCode::Char('[')  if app.mode == AppMode::Drums => app.drum_prob_down(),
-                        KeyCode::Char('e')  if app.mode == AppMode::Drums => app.drum_euclidean(),
-                        KeyCode::Char('<')  if app.mode == AppMode::Drums => app.drum_swing_down(),
-                        KeyCode::Char('>')  if app.mode == AppMode::Drums => app.drum_swing_up(),
-
-                        // ── SynthSeq2 focus ───────────────────────────────
-                        KeyCode::Left  if app.mode == AppMode::SynthSeq2 => app.seq2_cursor_left(),
-                        KeyCode::Right if app.mode == AppMode::SynthSeq2 => app.seq2_cursor_right(),
-                        KeyCode::Up    if app.mode == AppMode::SynthSeq2 => app.bpm_up(),
-                        KeyCode::Down  if app.mode == AppMode::SynthSeq2 => app.bpm_down(),
-                        KeyCode::Char(' ') if app.mode == AppMode::SynthSeq2 => app.seq2_toggle_play(),
-                        KeyCode::Enter     if app.mode == AppMode::SynthSeq2 => app.seq2_toggle_play(),
-                        KeyCode::Backspace | KeyCode::Delete if app.mode == AppMode::SynthSeq2 => app.seq2_clear_step(),
-                        KeyCode::Char(']') if app.mode == AppMode::SynthSeq2 => app.seq2_cycle_steps(),
-                        KeyCode::F(5)      if app.mode == AppMode::SynthSeq2 => app.cycle_wave2(),
-                        KeyCode::Char('=') if app.mode == AppMode::SynthSeq2 => app.synth2_vol_up(),
-                        KeyCode::Char('-') if app.mode == AppMode::SynthSeq2 => app.synth2_vol_down(),
-                        KeyCode::Char('[') if app.mode == AppMode::SynthSeq2 => app.octave_down(),
-                        KeyCode::Char('{') if app.mode == AppMode::SynthSeq2 => app.octave_up(),
-
-                        // ── SynthSeq focus ────────────────────────────────
-                        KeyCode::Left  if app.mode == AppMode::SynthSeq => app.seq_cursor_left(),
-                        KeyCode::Right if app.mode == AppMode::SynthSeq => app.seq_cursor_right(),
-                        KeyCode::Up    if app.mode == AppMode::SynthSeq => app.bpm_up(),
-                        KeyCode::Down  if app.mode == AppMode::SynthSeq => app.bpm_down(),
-                        KeyCode::Char(' ') if app.mode == AppMode::SynthSeq => app.seq_toggle_play(),
-                        KeyCode::Enter     if app.mode == AppMode::SynthSeq => app.seq_toggle_play(),
-                        KeyCode::Backspace | KeyCode::Delete if app.mode == AppMode::SynthSeq => app.seq_clear_step(),
-                        KeyCode::Char(']') if app.mode == AppMode::SynthSeq => app.seq_cycle_steps(),
-                        KeyCode::Char('=') if app.mode == AppMode::SynthSeq => app.volume_up(),
-                        KeyCode::Char('-') if app.mode == AppMode::SynthSeq => app.volume_down(),
-                        KeyCode::Char('[') if app.mode == AppMode::SynthSeq => app.octave_down(),
-                        KeyCode::Char('{') if app.mode == AppMode::SynthSeq => app.octave_up(),
-
-                        // ── Keyboard focus ────────────────────────────────
-                        KeyCode::Left  => app.octave_down(),
-                        KeyCode::Right => app.octave_up(),
-                        KeyCode::Up    => app.volume_up(),
-                        KeyCode::Down  => app.volume_down(),
-
-                        // ── Piano / drum preview / sequencer note keys ────
-                        KeyCode::Char(c) => match app.mode {
-                            AppMode::Play      => {
-                                if enhanced { app.key_press(c); } else { app.key_press_fallback(c); }
-                            }
-                            AppMode::SynthSeq  => app.seq_set_note(c),
-                            AppMode::SynthSeq2 => app.seq2_set_note(c),
-                            AppMode::Drums     => app.drum_preview(c),
-                            AppMode::Effects   => {}
-                        },
+            // ── Device picker: owns the keyboard while open ──
+            if app.device_picker.is_some() {
+                match key.code {
+                    KeyCode::Up    => app.device_picker_move(-1),
+                    KeyCode::Down  => app.device_picker_move(1),
+                    KeyCode::Enter => app.device_picker_pick(),
+                    KeyCode::Char('r') => app.open_device_picker(),
+                    KeyCode::Esc   => {
+                        app.device_picker = None;
+                        app.status_msg = "Cancelled".to_string();
+                    }
+                    _ => {}
+                }
+                return true;
+            }
 
-                        _ => {}
+            // ── Performance view: number keys toggle mute groups ──
+            if app.perf_view {
+                match key.code {
+                    KeyCode::Char(c @ '1'..='9') => {
+                        app.toggle_mute_group(c as usize - '1' as usize);
+                        return true;
                     }
+                    KeyCode::Esc => { app.toggle_perf_view(); return true; }
+                    _ => {}
+                }
+            }
+
+            // ── Key press ─────────────────────────────────────────
+            match key.code {
+                // Global quit
+                KeyCode::Esc => return false,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+                // Save / Load
+                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    app.input_mode = InputMode::Save;
+                    app.input_buf  = app.project.clone().unwrap_or_else(|| DEFAULT_PROJECT.to_string());
+                }
+                KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    app.input_mode = InputMode::Load;
+                    app.input_buf  = app.project.clone().unwrap_or_else(|| DEFAULT_PROJECT.to_string());
+                }
+                KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    app.input_mode = InputMode::Command;
+                    app.input_buf.clear();
+                }
+                // A/B mix snapshots
+                KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) => app.ab_store(0),
+                KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => app.ab_store(1),
+                KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => app.freeze_toggle(),
+                KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => app.toggle_scale_run(),
+                KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => app.drone_set(None),
+                KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => app.undo(),
+                KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => app.cycle_direction(),
+                KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => app.tuner_set(None),
+                KeyCode::Char('k') if key.modifiers.contains(KeyModifiers::CONTROL) => app.cycle_key_layout(),
+                KeyCode::Char('g') if key.modifiers.contains(KeyModifiers::CONTROL) => app.toggle_chord_keys(),
+                KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => app.mutate(),
+                KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => app.repeat_last(),
+                KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => app.dismiss_fallback_notice(),
+                KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => app.set_low_latency(None),
+                KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => app.set_compact(None),
+                // Hidden: debug state dump for bug reports (not in the help bar)
+                KeyCode::Char('x') if key.modifiers.contains(KeyModifiers::CONTROL) => app.dump_state(),
+                KeyCode::F(10) => app.ab_toggle(),
+                // Direct focus (before the piano keys claim the digits)
+                KeyCode::Char(c @ '1'..='5') if key.modifiers.contains(KeyModifiers::ALT) => app.focus(match c {
+                    '1' => AppMode::Play,
+                    '2' => AppMode::SynthSeq,
+                    '3' => AppMode::SynthSeq2,
+                    '4' => AppMode::Drums,
+                    _   => AppMode::Effects,
+                }),
+                // Channel solo (before the piano keys too)
+                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::ALT) => app.solo_toggle(),
+                KeyCode::Char('S') if key.modifiers.contains(KeyModifiers::ALT) => app.solo_clear(),
+
+                // Global: cycle focus, waveform, drum play, BPM, scale, chord, bank
+                KeyCode::Tab          => app.toggle_mode(),
+                KeyCode::F(2)         => app.toggle_mode(),
+                KeyCode::F(1)         => app.cycle_wave(),
+                KeyCode::F(3)         => app.drum_toggle_play(),
+                KeyCode::F(4)         => app.toggle_perf_view(),
+                KeyCode::F(11)        => app.soft_stop(),
+                KeyCode::F(12)        => app.panic(),
+                KeyCode::F(6)         => app.cycle_scale(),
+                KeyCode::F(7)         => app.cycle_scale_root(),
+                KeyCode::F(8) => {
+                    if app.mode == AppMode::SynthSeq2 { app.cycle_chord2(); }
+                    else { app.cycle_chord1(); }
                 }
-                Event::FocusLost => { app.release_all(); }
+                KeyCode::F(9) => match app.mode {
+                    AppMode::SynthSeq  => { let b = (app.seq1_bank + 1) % 4; app.switch_seq1_bank(b); }
+                    AppMode::SynthSeq2 => { let b = (app.seq2_bank + 1) % 4; app.switch_seq2_bank(b); }
+                    AppMode::Drums     => { let b = (app.drum_bank + 1) % 4; app.switch_drum_bank(b); }
+                    _ => {}
+                },
+                KeyCode::PageUp       => app.bpm_step(true, bpm_fine(&key), false),
+                KeyCode::PageDown     => app.bpm_step(false, bpm_fine(&key), false),
+
+                // ── Effects focus ─────────────────────────────────
+                KeyCode::Up    if app.mode == AppMode::Effects => app.effects_sel_up(),
+                KeyCode::Down  if app.mode == AppMode::Effects => app.effects_sel_down(),
+                KeyCode::Left  if app.mode == AppMode::Effects => app.effects_param_left(),
+                KeyCode::Right if app.mode == AppMode::Effects => app.effects_param_right(),
+                KeyCode::Char('=') if app.mode == AppMode::Effects => app.effects_param_inc(),
+                KeyCode::Char('-') if app.mode == AppMode::Effects => app.effects_param_dec(),
+                KeyCode::Enter     if app.mode == AppMode::Effects => app.effects_on_off(),
+                KeyCode::Char(' ') if app.mode == AppMode::Effects => app.effects_route_toggle(),
+                KeyCode::Backspace | KeyCode::Delete if app.mode == AppMode::Effects => app.effects_remove_insert(),
+                KeyCode::Char('w') if app.mode == AppMode::Effects => app.effects_rack_save_prompt(),
+
+                // ── Drums focus ───────────────────────────────────
+                KeyCode::Up    if app.mode == AppMode::Drums => app.drum_track_up(),
+                KeyCode::Down  if app.mode == AppMode::Drums => app.drum_track_down(),
+                KeyCode::Left  if app.mode == AppMode::Drums && key.modifiers.contains(KeyModifiers::CONTROL) => app.drum_shift_left(),
+                KeyCode::Right if app.mode == AppMode::Drums && key.modifiers.contains(KeyModifiers::CONTROL) => app.drum_shift_right(),
+                KeyCode::Left  if app.mode == AppMode::Drums && key.modifiers.contains(KeyModifiers::SHIFT) => app.drum_nudge(false),
+                KeyCode::Right if app.mode == AppMode::Drums && key.modifiers.contains(KeyModifiers::SHIFT) => app.drum_nudge(true),
+                KeyCode::Left  if app.mode == AppMode::Drums => app.drum_step_left(),
+                KeyCode::Right if app.mode == AppMode::Drums => app.drum_step_right(),
+                KeyCode::Enter if app.mode == AppMode::Drums => app.drum_toggle_play(),
+                KeyCode::Backspace | KeyCode::Delete if app.mode == AppMode::Drums => app.drum_clear_step(),
+                KeyCode::Char(' ')  if app.mode == AppMode::Drums => app.drum_toggle_step(),
+                KeyCode::Char(']')  if app.mode == AppMode::Drums => app.drum_cycle_steps(),
+                KeyCode::Char('(')  if app.mode == AppMode::Drums => app.drum_loop_point(false),
+                KeyCode::Char(')')  if app.mode == AppMode::Drums => app.drum_loop_point(true),
+                KeyCode::Char('\\') if app.mode == AppMode::Drums => app.drum_toggle_mute(),
+                KeyCode::Char('=')  if app.mode == AppMode::Drums => app.drum_vol_up(),
+                KeyCode::Char('-')  if app.mode == AppMode::Drums => app.drum_vol_down(),
+                KeyCode::Char('p')  if app.mode == AppMode::Drums => app.drum_prob_up(),
+                KeyCode::Char('[')  if app.mode == AppMode::Drums => app.drum_prob_down(),
+                KeyCode::Char('e')  if app.mode == AppMode::Drums => app.drum_euclidean(),
+                KeyCode::Char('<')  if app.mode == AppMode::Drums => app.drum_swing_down(),
+                KeyCode::Char('>')  if app.mode == AppMode::Drums => app.drum_swing_up(),
+                KeyCode::Char('{')  if app.mode == AppMode::Drums => app.drum_pan(false),
+                KeyCode::Char('}')  if app.mode == AppMode::Drums => app.drum_pan(true),
+                KeyCode::Char(';')  if app.mode == AppMode::Drums => app.drum_decay(false),
+                KeyCode::Char('\'') if app.mode == AppMode::Drums => app.drum_decay(true),
+                KeyCode::Char('.')  if app.mode == AppMode::Drums => app.drum_tune(false),
+                KeyCode::Char('/')  if app.mode == AppMode::Drums => app.drum_tune(true),
+                KeyCode::Char('a')  if app.mode == AppMode::Drums => app.drum_auto_pan(),
+                KeyCode::Char('g')  if app.mode == AppMode::Drums => app.drum_cycle_groove(),
+                KeyCode::Char('r')  if app.mode == AppMode::Drums => app.drum_toggle_reverse(),
+                KeyCode::Char('w')  if app.mode == AppMode::Drums => app.drum_rec_toggle(None),
+
+                // ── Pitch entry (SynthSeq / SynthSeq2 focus) ──────
+                KeyCode::Insert if matches!(app.mode, AppMode::SynthSeq | AppMode::SynthSeq2) => app.set_pitch_entry(None),
+                KeyCode::Up    if app.pitch_entry_active() && !key.modifiers.contains(KeyModifiers::SHIFT) => app.pending_note_step(true),
+                KeyCode::Down  if app.pitch_entry_active() && !key.modifiers.contains(KeyModifiers::SHIFT) => app.pending_note_step(false),
+                KeyCode::Enter if app.pitch_entry_active() => app.place_pending_note(),
+
+                // ── SynthSeq2 focus ───────────────────────────────
+                KeyCode::Left  if app.mode == AppMode::SynthSeq2 && key.modifiers.contains(KeyModifiers::CONTROL) => app.seq2_shift_left(),
+                KeyCode::Right if app.mode == AppMode::SynthSeq2 && key.modifiers.contains(KeyModifiers::CONTROL) => app.seq2_shift_right(),
+                KeyCode::Left  if app.mode == AppMode::SynthSeq2 && key.modifiers.contains(KeyModifiers::SHIFT) => app.seq2_nudge(false),
+                KeyCode::Right if app.mode == AppMode::SynthSeq2 && key.modifiers.contains(KeyModifiers::SHIFT) => app.seq2_nudge(true),
+                KeyCode::Left  if app.mode == AppMode::SynthSeq2 => app.seq2_cursor_left(),
+                KeyCode::Right if app.mode == AppMode::SynthSeq2 => app.seq2_cursor_right(),
+                KeyCode::Up    if app.mode == AppMode::SynthSeq2 && key.modifiers.contains(KeyModifiers::ALT) => app.seq2_prob(true),
+                KeyCode::Down  if app.mode == AppMode::SynthSeq2 && key.modifiers.contains(KeyModifiers::ALT) => app.seq2_prob(false),
+                KeyCode::Up    if app.mode == AppMode::SynthSeq2 && key.modifiers.contains(KeyModifiers::SHIFT) => app.seq2_accent(),
+                KeyCode::Down  if app.mode == AppMode::SynthSeq2 && key.modifiers.contains(KeyModifiers::SHIFT) => app.seq2_slide(),
+                KeyCode::Up    if app.mode == AppMode::SynthSeq2 => app.bpm_step(true, false, false),
+                KeyCode::Down  if app.mode == AppMode::SynthSeq2 => app.bpm_step(false, false, false),
+                KeyCode::Char(' ') if app.mode == AppMode::SynthSeq2 => app.seq2_toggle_play(),
+                KeyCode::Enter     if app.mode == AppMode::SynthSeq2 => app.seq2_toggle_play(),
+                KeyCode::Backspace | KeyCode::Delete if app.mode == AppMode::SynthSeq2 => app.seq2_clear_step(),
+                KeyCode::Char(']') if app.mode == AppMode::SynthSeq2 => app.seq2_cycle_steps(),
+                KeyCode::Char('(')  if app.mode == AppMode::SynthSeq2 => app.seq2_loop_point(false),
+                KeyCode::Char(')')  if app.mode == AppMode::SynthSeq2 => app.seq2_loop_point(true),
+                KeyCode::Char('\\') if app.mode == AppMode::SynthSeq2 => app.seq2_solo_toggle(),
+                KeyCode::Char('|')  if app.mode == AppMode::SynthSeq2 => app.seq2_solo_extend(),
+                KeyCode::F(5)      if app.mode == AppMode::SynthSeq2 => app.cycle_wave2(),
+                KeyCode::Char('=') if app.mode == AppMode::SynthSeq2 => app.synth2_vol_up(),
+                KeyCode::Char('-') if app.mode == AppMode::SynthSeq2 => app.synth2_vol_down(),
+                KeyCode::Char('[') if app.mode == AppMode::SynthSeq2 => app.octave_down(),
+                KeyCode::Char('{') if app.mode == AppMode::SynthSeq2 => app.octave_up(),
+                KeyCode::Char('<') if app.mode == AppMode::SynthSeq2 => app.seq_swing_step(false),
+                KeyCode::Char('>') if app.mode == AppMode::SynthSeq2 => app.seq_swing_step(true),
+                KeyCode::Char('\'') if app.mode == AppMode::SynthSeq2 => app.set_bass_view(None),
+
+                // ── SynthSeq focus ────────────────────────────────
+                KeyCode::Left  if app.mode == AppMode::SynthSeq && key.modifiers.contains(KeyModifiers::CONTROL) => app.seq_shift_left(),
+                KeyCode::Right if app.mode == AppMode::SynthSeq && key.modifiers.contains(KeyModifiers::CONTROL) => app.seq_shift_right(),
+                KeyCode::Left  if app.mode == AppMode::SynthSeq && key.modifiers.contains(KeyModifiers::SHIFT) => app.seq_nudge(false),
+                KeyCode::Right if app.mode == AppMode::SynthSeq && key.modifiers.contains(KeyModifiers::SHIFT) => app.seq_nudge(true),
+                KeyCode::Left  if app.mode == AppMode::SynthSeq => app.seq_cursor_left(),
+                KeyCode::Right if app.mode == AppMode::SynthSeq => app.seq_cursor_right(),
+                KeyCode::Up    if app.mode == AppMode::SynthSeq && key.modifiers.contains(KeyModifiers::ALT) => app.seq_prob(true),
+                KeyCode::Down  if app.mode == AppMode::SynthSeq && key.modifiers.contains(KeyModifiers::ALT) => app.seq_prob(false),
+                KeyCode::Up    if app.mode == AppMode::SynthSeq => app.bpm_step(true, false, false),
+                KeyCode::Down  if app.mode == AppMode::SynthSeq => app.bpm_step(false, false, false),
+                KeyCode::Char(' ') if app.mode == AppMode::SynthSeq => app.seq_toggle_play(),
+                KeyCode::Enter     if app.mode == AppMode::SynthSeq => app.seq_toggle_play(),
+                KeyCode::Backspace | KeyCode::Delete if app.mode == AppMode::SynthSeq => app.seq_clear_step(),
+                KeyCode::Char(']') if app.mode == AppMode::SynthSeq => app.seq_cycle_steps(),
+                KeyCode::Char('(')  if app.mode == AppMode::SynthSeq => app.seq_loop_point(false),
+                KeyCode::Char(')')  if app.mode == AppMode::SynthSeq => app.seq_loop_point(true),
+                KeyCode::Char('\\') if app.mode == AppMode::SynthSeq => app.seq_solo_toggle(),
+                KeyCode::Char('|')  if app.mode == AppMode::SynthSeq => app.seq_solo_extend(),
+                KeyCode::Char('=') if app.mode == AppMode::SynthSeq => app.volume_up(),
+                KeyCode::Char('-') if app.mode == AppMode::SynthSeq => app.volume_down(),
+                KeyCode::Char('[') if app.mode == AppMode::SynthSeq => app.octave_down(),
+                KeyCode::Char('{') if app.mode == AppMode::SynthSeq => app.octave_up(),
+                KeyCode::Char('<') if app.mode == AppMode::SynthSeq => app.seq_swing_step(false),
+                KeyCode::Char('>') if app.mode == AppMode::SynthSeq => app.seq_swing_step(true),
+
+                // ── Keyboard focus ────────────────────────────────
+                KeyCode::Left  => app.octave_down(),
+                KeyCode::Right => app.octave_up(),
+                KeyCode::Up    => app.volume_up(),
+                KeyCode::Down  => app.volume_down(),
+
+                // ── Piano / drum preview / sequencer note keys ────
+                KeyCode::Char(c) => match app.mode {
+                    AppMode::Play      => {
+                        if enhanced { app.key_press(c); } else { app.key_press_fallback(c); }
+                    }
+                    AppMode::SynthSeq  => app.seq_set_note(c),
+                    AppMode::SynthSeq2 => app.seq2_set_note(c),
+                    AppMode::Drums     => app.drum_preview(c, enhanced),
+                    AppMode::Effects   => {}
+                },
+
                 _ => {}
             }
         }
-        if app.should_quit { break; }
+        Event::FocusLost => { app.release_all(); }
+        _ => {}
     }
+    true
+}
 
-    app.release_all();
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The engine's output for a fixed project must not drift.  If a change
+    /// is meant to alter the sound, re-record the values printed by
+    /// `tuibeat --checksum tests/fixtures/golden.json --bars 2`.  Transcendental
+    /// functions come from the platform's libm, so another OS may need its own
+    /// values.
+    #[test]
+    fn golden_render_is_stable() {
+        let project = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/golden.json");
+        let fp = render::fingerprint(&mut load_headless(project).unwrap(), 2);
+        assert_eq!(fp.frames, 176_400);
+        assert!((fp.peak - 0.644126).abs() < 1e-5, "peak {}", fp.peak);
+        assert!((fp.rms - 0.213809).abs() < 1e-5, "rms {}", fp.rms);
+        assert_eq!(fp.checksum, 0x2440ae992fba8c3d, "render changed: {:?}", fp);
+    }
+
+    fn parse(line: &str) -> Result<Mode> {
+        parse_args(line.split_whitespace().map(String::from))
+    }
+
+    fn parse_err(line: &str) -> String {
+        match parse(line) {
+            Ok(_)  => panic!("{:?} should not parse", line),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn tui_flags() {
+        assert!(matches!(parse("").unwrap(), Mode::Tui { fps: DEFAULT_FPS, project: None, fresh: false }));
+        assert!(matches!(parse("--fps 30").unwrap(), Mode::Tui { fps: 30, .. }));
+        assert!(matches!(parse("--fps=240 --fresh").unwrap(), Mode::Tui { fps: 240, fresh: true, .. }));
+        let Mode::Tui { project, .. } = parse("--project=a.json").unwrap() else { panic!("not the TUI") };
+        assert_eq!(project.as_deref(), Some("a.json"));
+    }
+
+    #[test]
+    fn headless_flags_and_default_outputs() {
+        let Mode::Render { project, out, bars, looped } = parse("--render song.json").unwrap() else { panic!("not --render") };
+        assert_eq!((project.as_str(), out.as_str(), bars, looped), ("song.json", "song.wav", DEFAULT_RENDER_BARS, false));
+        let Mode::Render { out, bars, looped, .. } = parse("--render song.json --out x.wav --bars=4 --loop").unwrap() else { panic!("not --render") };
+        assert_eq!((out.as_str(), bars, looped), ("x.wav", 4, true));
+        let Mode::Midi { out, .. } = parse("--midi dir/song.json").unwrap() else { panic!("not --midi") };
+        assert_eq!(out, "dir/song.mid");
+        let Mode::Stems { out, .. } = parse("--stems song.json").unwrap() else { panic!("not --stems") };
+        assert_eq!(out, "song-stems");
+        assert!(matches!(parse("--checksum song.json --bars 2").unwrap(), Mode::Checksum { bars: 2, .. }));
+    }
+
+    #[test]
+    fn bad_arguments_are_rejected() {
+        assert!(parse_err("--bogus").starts_with("Unknown argument: --bogus"));
+        assert!(parse_err("--fresh=1").starts_with("Unknown argument"));
+        assert_eq!(parse_err("--render"), "--render needs a value");
+        assert_eq!(parse_err("--fps 0"), "--fps must be between 1 and 240");
+        assert_eq!(parse_err("--fps fast"), "Invalid --fps value: fast");
+        assert_eq!(parse_err("--bars 0 --render a.json"), "Invalid --bars value: 0  (1-999)");
+        assert_eq!(parse_err("--render a.json --midi b.json"), "--render and --midi can't be combined");
+        assert!(parse_err("--out x.wav").starts_with("--out, --bars and --loop need"));
+        assert_eq!(parse_err("--midi a.json --loop"), "--loop only applies to --render and --stems");
+        assert_eq!(parse_err("--render a.json --fps 30"), "--fps has no effect in headless modes");
+        assert_eq!(parse_err("--project a.json --fresh"), "--project and --fresh can't be combined");
+        assert_eq!(parse_err("--render a.json --fresh"), "--project and --fresh only apply to the TUI");
+        assert_eq!(parse_err("--checksum a.json --out x"), "--checksum prints to stdout; --out has no effect");
+    }
 }
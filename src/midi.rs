@@ -0,0 +1,212 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::drums::{DrumKind, DrumMachine};
+use crate::rng::Rng;
+use crate::sequencer::{Sequencer, FULL_PROB};
+use crate::synth::{ChordType, Synth};
+use crate::tempo::bar_steps;
+
+// ── MIDI export ───────────────────────────────────────────────────────────────
+//
+// Writes the loaded patterns as a Standard MIDI File (format 1): a tempo
+// track, one track per melodic sequencer and the drums on channel 10 with GM
+// note numbers.  Built by walking the pattern data over the grid (play range,
+// direction, nudge, swing, groove), not by listening to the audio, so the
+// notes land exactly where the sequencers would fire them.
+
+/// Ticks per quarter note.
+pub const PPQ: u16 = 96;
+/// Ticks per 16th-note grid step.
+const TICKS_PER_STEP: i64 = PPQ as i64 / 4;
+/// Melodic note velocity for a sequence without a velocity ramp.
+const NOTE_VELOCITY: u8 = 100;
+/// Velocity of an accented Synth 2 step.
+const ACCENT_VELOCITY: u8 = 127;
+/// How far a sliding note overlaps the next one, so a mono synth plays the
+/// pair legato (glides).
+const SLIDE_OVERLAP_TICKS: i64 = TICKS_PER_STEP / 4;
+/// Drum hits are written as this many ticks long (drum voices are one-shots).
+const DRUM_NOTE_TICKS: i64 = TICKS_PER_STEP / 2;
+/// General MIDI drum channel (10, zero-based).
+const DRUM_CHANNEL: u8 = 9;
+
+/// General MIDI percussion note for a drum voice.
+fn gm_note(kind: DrumKind) -> u8 {
+    match kind {
+        DrumKind::Kick      => 36,
+        DrumKind::Snare     => 38,
+        DrumKind::Clap      => 39,
+        DrumKind::ClosedHat => 42,
+        DrumKind::OpenHat   => 46,
+        DrumKind::LowTom    => 41,
+        DrumKind::MidTom    => 47,
+        DrumKind::HighTom   => 50,
+    }
+}
+
+/// Convert a timing offset in percent of a step into ticks.
+fn offset_ticks(pct: i8) -> i64 {
+    (pct as i64 * TICKS_PER_STEP) / 100
+}
+
+/// One track's events as `(tick, status, data1, data2)`.
+struct Track {
+    name:   String,
+    events: Vec<(i64, u8, u8, u8)>,
+}
+
+impl Track {
+    fn new(name: &str) -> Self { Self { name: name.to_string(), events: Vec::new() } }
+
+    fn note(&mut self, channel: u8, note: u8, velocity: u8, on: i64, off: i64) {
+        let on = on.max(0);
+        self.events.push((on, 0x90 | channel, note, velocity));
+        self.events.push((off.max(on + 1), 0x80 | channel, note, 0));
+    }
+
+    /// `MTrk` chunk body: name meta, events in time order (note-offs first
+    /// on a shared tick, so a repeated note retriggers cleanly), end of track.
+    fn encode(mut self) -> Vec<u8> {
+        self.events.sort_by_key(|&(t, status, ..)| (t, status & 0xF0 == 0x90));
+        let mut out = Vec::new();
+        write_vlq(&mut out, 0);
+        out.extend([0xFF, 0x03]);
+        write_vlq(&mut out, self.name.len() as u32);
+        out.extend(self.name.as_bytes());
+        let mut now = 0;
+        for (t, status, a, b) in self.events {
+            write_vlq(&mut out, (t - now) as u32);
+            out.extend([status, a, b]);
+            now = t;
+        }
+        out.extend([0x00, 0xFF, 0x2F, 0x00]);
+        out
+    }
+}
+
+fn write_vlq(out: &mut Vec<u8>, mut v: u32) {
+    let mut buf = [0u8; 5];
+    let mut i = buf.len() - 1;
+    buf[i] = (v & 0x7F) as u8;
+    v >>= 7;
+    while v > 0 {
+        i -= 1;
+        buf[i] = (v & 0x7F) as u8 | 0x80;
+        v >>= 7;
+    }
+    out.extend(&buf[i..]);
+}
+
+/// A melodic sequencer over `steps` grid steps.  Each note (plus its chord
+/// tones) is held until the next step fires, as in playback; odd steps are
+/// delayed by `swing` of a step.  Step probability is rolled with `rng`.
+/// With `bass` (Synth 2) accented steps play at `ACCENT_VELOCITY` and a
+/// slide overlaps the next note.
+#[allow(clippy::too_many_arguments)]
+fn seq_track(name: &str, seq: &Sequencer, chord: ChordType, channel: u8, steps: u64, swing: f32, bass: bool,
+             rng: &mut Rng) -> Track {
+    let mut track = Track::new(name);
+    let (first, len) = seq.play_range();
+    let swing = (swing as f64 * TICKS_PER_STEP as f64).round() as i64;
+    let offset = |pos: usize| {
+        offset_ticks(seq.nudge.get(pos).copied().unwrap_or(0)) + if pos % 2 == 1 { swing } else { 0 }
+    };
+    for k in 0..steps {
+        let pos  = first + seq.direction.position(k, len);
+        let next = first + seq.direction.position(k + 1, len);
+        let Some(note) = seq.steps.get(pos).copied().flatten() else { continue };
+        if !rng.chance(seq.prob.get(pos).copied().unwrap_or(FULL_PROB)) { continue; }
+        let flag = |v: &[bool], i: usize| bass && v.get(i).copied().unwrap_or(false);
+        // Not to the same pitch: the overlap would cut the next note short.
+        let slide = flag(&seq.slide, pos) && seq.steps.get(next).copied().flatten().is_some_and(|n| n != note);
+        let on  = k as i64 * TICKS_PER_STEP + offset(pos);
+        let off = (k as i64 + 1) * TICKS_PER_STEP + offset(next) + if slide { SLIDE_OVERLAP_TICKS } else { 0 };
+        let velocity = if flag(&seq.accent, pos) { ACCENT_VELOCITY }
+                       else { seq.velocity_ramp.map_or(NOTE_VELOCITY, |r| r.velocity(k, len)) };
+        track.note(channel, note, velocity, on, off);
+        for &iv in chord.intervals() {
+            track.note(channel, (note as i32 + iv).clamp(0, 127) as u8, velocity, on, off);
+        }
+    }
+    track
+}
+
+/// Every unmuted drum track over `steps` grid steps.  Probability is rolled
+/// with `rng`, and the velocity follows the track volume and the groove.
+fn drum_track(dm: &DrumMachine, steps: u64, rng: &mut Rng) -> Track {
+    let mut track = Track::new("Drums");
+    let (first, len) = dm.play_range();
+    let swing = (dm.swing as f64 * TICKS_PER_STEP as f64).round() as i64;
+    for k in 0..steps {
+        let pos = first + dm.direction.position(k, len);
+        for t in dm.tracks.iter().filter(|t| !t.muted) {
+            let prob = t.steps.get(pos).copied().unwrap_or(0);
+            if prob == 0 || !rng.chance(prob) { continue; }
+            let mut on = k as i64 * TICKS_PER_STEP
+                + offset_ticks(t.nudge.get(pos).copied().unwrap_or(0));
+            if pos % 2 == 1 { on += swing; }
+            let mut gain = t.volume;
+            if let Some(g) = &dm.groove {
+                on += offset_ticks(g.offset(pos));
+                gain *= g.gain(pos);
+            }
+            let velocity = (gain * 127.0).round().clamp(1.0, 127.0) as u8;
+            track.note(DRUM_CHANNEL, gm_note(t.kind), velocity, on, on + DRUM_NOTE_TICKS);
+        }
+    }
+    track
+}
+
+/// Write `bars` bars of the synth's patterns to a MIDI file at `path`.
+/// Returns the number of note events written.  Step probability is rolled
+/// from the project seed, so the same project always exports the same file.
+pub fn export_midi(synth: &Synth, bars: u32, path: &Path) -> Result<usize> {
+    let steps = bars as u64 * bar_steps(synth.time_sig);
+    let mut rng = Rng::new(synth.rng.seed());
+
+    let usec_per_quarter = (60_000_000.0 / synth.bpm as f64).round() as u32;
+    // One track per synth 1 lane, all on its channel.
+    let mut tracks: Vec<Track> = synth.lanes.iter().enumerate().map(|(i, lane)| {
+        let name = if i == 0 { "Synth 1".to_string() } else { format!("Synth 1 lane {}", i + 1) };
+        seq_track(&name, lane, synth.chord1, 0, steps, synth.seq_swing, false, &mut rng)
+    }).collect();
+    tracks.push(seq_track("Synth 2", &synth.sequencer2, synth.chord2, 1, steps, synth.seq_swing, true, &mut rng));
+    tracks.push(drum_track(&synth.drum_machine, steps, &mut rng));
+    let notes = tracks.iter().map(|t| t.events.len() / 2).sum();
+
+    let mut file = Vec::new();
+    file.extend(b"MThd");
+    file.extend(6u32.to_be_bytes());
+    file.extend(1u16.to_be_bytes());                       // format 1
+    file.extend((tracks.len() as u16 + 1).to_be_bytes());
+    file.extend(PPQ.to_be_bytes());
+
+    let mut chunk = |body: Vec<u8>| {
+        file.extend(b"MTrk");
+        file.extend((body.len() as u32).to_be_bytes());
+        file.extend(body);
+    };
+    chunk(tempo_chunk(usec_per_quarter, synth.time_sig, steps as i64 * TICKS_PER_STEP));
+    for t in tracks { chunk(t.encode()); }
+
+    std::fs::write(path, file).with_context(|| format!("Cannot write {}", path.display()))?;
+    Ok(notes)
+}
+
+/// Tempo track: name, time signature `(beats, unit)` and tempo, ending at
+/// `end` so every track covers the full export length.
+fn tempo_chunk(usec_per_quarter: u32, (beats, unit): (u8, u8), end: i64) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_vlq(&mut out, 0);
+    out.extend([0xFF, 0x03, 5]);
+    out.extend(b"Tempo");
+    // The denominator is stored as a power of two.
+    out.extend([0x00, 0xFF, 0x58, 0x04, beats, unit.trailing_zeros() as u8, 24, 8]);
+    out.extend([0x00, 0xFF, 0x51, 0x03]);
+    out.extend(&usec_per_quarter.to_be_bytes()[1..]);
+    write_vlq(&mut out, end as u32);
+    out.extend([0xFF, 0x2F, 0x00]);
+    out
+}
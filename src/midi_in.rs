@@ -0,0 +1,113 @@
+use std::io::Read;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use anyhow::{Context, Result};
+
+use crate::param::Param;
+
+// ── MIDI input ────────────────────────────────────────────────────────────────
+//
+// Reads a raw MIDI byte stream from a device node (`/dev/snd/midiC1D0`,
+// `/dev/midi1`, …) or a FIFO on a background thread and forwards control
+// changes to the UI over a channel.  Nothing here touches the synth: the UI
+// drains the channel once per frame (`App::poll_midi`) and applies bound
+// parameters through the shared state like a key press would.
+//
+// The reader blocks in `read`, so after `midiin off` it lingers until the
+// next byte (or EOF) arrives, notices the receiver is gone and exits.
+
+/// CC messages held between frames before the reader drops them.
+const QUEUE_LEN: usize = 1024;
+
+/// One control-change message.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ControlChange {
+    /// 0-based MIDI channel.
+    pub channel: u8,
+    pub cc:      u8,
+    pub value:   u8,
+}
+
+/// A CC bound to a parameter by MIDI learn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CcBinding {
+    pub channel: u8,
+    pub cc:      u8,
+    pub param:   Param,
+    /// Last value received, for the mappings panel.
+    pub last:    Option<u8>,
+}
+
+pub struct MidiInput {
+    /// Device path as given.
+    pub path: String,
+    rx: Receiver<ControlChange>,
+}
+
+impl MidiInput {
+    pub fn open(path: &str) -> Result<Self> {
+        let mut file = std::fs::File::open(path).with_context(|| format!("Cannot open {}", path))?;
+        let (tx, rx) = mpsc::sync_channel(QUEUE_LEN);
+        std::thread::Builder::new()
+            .name("midi-in".to_string())
+            .spawn(move || {
+                let mut parser = Parser::default();
+                let mut buf = [0u8; 256];
+                while let Ok(n) = file.read(&mut buf) {
+                    if n == 0 { break; }
+                    for &b in &buf[..n] {
+                        let Some(cc) = parser.feed(b) else { continue };
+                        match tx.try_send(cc) {
+                            Ok(()) | Err(mpsc::TrySendError::Full(_)) => {}
+                            Err(mpsc::TrySendError::Disconnected(_)) => return,
+                        }
+                    }
+                }
+            })
+            .context("Cannot start the MIDI input thread")?;
+        Ok(Self { path: path.to_string(), rx })
+    }
+
+    /// Control changes received since the last call, oldest first, and
+    /// whether the stream has ended (device unplugged, FIFO closed).
+    pub fn poll(&self) -> (Vec<ControlChange>, bool) {
+        let mut out = Vec::new();
+        loop {
+            match self.rx.try_recv() {
+                Ok(cc) => out.push(cc),
+                Err(TryRecvError::Empty)        => return (out, false),
+                Err(TryRecvError::Disconnected) => return (out, true),
+            }
+        }
+    }
+}
+
+/// Byte-at-a-time MIDI parser that keeps running status and yields CCs only.
+#[derive(Default)]
+struct Parser {
+    status: Option<u8>,
+    data:   Vec<u8>,
+}
+
+impl Parser {
+    fn feed(&mut self, b: u8) -> Option<ControlChange> {
+        match b {
+            // Real-time bytes may appear anywhere and leave running status alone.
+            0xF8..=0xFF => None,
+            // System common / SysEx: ignored, and they cancel running status.
+            0xF0..=0xF7 => { self.status = None; self.data.clear(); None }
+            0x80..=0xEF => { self.status = Some(b); self.data.clear(); None }
+            _ => {
+                let status = self.status?;
+                self.data.push(b);
+                let len = if matches!(status & 0xF0, 0xC0 | 0xD0) { 1 } else { 2 };
+                if self.data.len() < len { return None; }
+                let msg = (status & 0xF0 == 0xB0).then(|| ControlChange {
+                    channel: status & 0x0F, cc: self.data[0], value: self.data[1],
+                });
+                self.data.clear();
+                msg
+            }
+        }
+    }
+}
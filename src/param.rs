@@ -0,0 +1,149 @@
+use crate::effects::WIDENER_MAX_WIDTH;
+use crate::synth::{Synth, MAX_FM_INDEX};
+
+/// Filter cutoff range shared with the effects panel, in Hz.
+const CUTOFF_MIN: f32 = 80.0;
+const CUTOFF_MAX: f32 = 18000.0;
+
+// ── Controllable parameters ───────────────────────────────────────────────────
+//
+// Continuous mix/effect parameters an external controller can drive.  A value
+// is set from a normalised 0.0..=1.0 position (a CC value / 127), mapped to
+// the same range the effects panel clamps to; cutoff is log-scaled like its
+// bar, so a knob sweep sounds even.  `get` reads the position back, which is
+// what the automation recorder watches.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Param {
+    Volume1,
+    Volume2,
+    Cutoff1,
+    Cutoff2,
+    Res1,
+    Res2,
+    ReverbMix,
+    DelayMix,
+    DelayFeedback,
+    DistDrive,
+    SidechainDepth,
+    Width,
+    FmIndex,
+    VoiceSpread,
+}
+
+impl Param {
+    pub const ALL: [Param; 14] = [
+        Param::Volume1, Param::Volume2, Param::Cutoff1, Param::Cutoff2, Param::Res1, Param::Res2,
+        Param::ReverbMix, Param::DelayMix, Param::DelayFeedback, Param::DistDrive,
+        Param::SidechainDepth, Param::Width, Param::FmIndex, Param::VoiceSpread,
+    ];
+
+    /// Name typed in the palette and stored in the project file.
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Volume1        => "vol1",
+            Self::Volume2        => "vol2",
+            Self::Cutoff1        => "cutoff1",
+            Self::Cutoff2        => "cutoff2",
+            Self::Res1           => "res1",
+            Self::Res2           => "res2",
+            Self::ReverbMix      => "reverb",
+            Self::DelayMix       => "delay",
+            Self::DelayFeedback  => "feedback",
+            Self::DistDrive      => "drive",
+            Self::SidechainDepth => "sidechain",
+            Self::Width          => "width",
+            Self::FmIndex        => "fmindex",
+            Self::VoiceSpread    => "spread",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.id() == id)
+    }
+
+    /// Display name, as in the effects panel status messages.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Volume1        => "S1 Volume",
+            Self::Volume2        => "S2 Volume",
+            Self::Cutoff1        => "S1 Cutoff",
+            Self::Cutoff2        => "S2 Cutoff",
+            Self::Res1           => "S1 Q",
+            Self::Res2           => "S2 Q",
+            Self::ReverbMix      => "Reverb Mix",
+            Self::DelayMix       => "Delay Mix",
+            Self::DelayFeedback  => "Delay Feed",
+            Self::DistDrive      => "Dist Drive",
+            Self::SidechainDepth => "SC Depth",
+            Self::Width          => "Stereo Width",
+            Self::FmIndex        => "FM Index",
+            Self::VoiceSpread    => "Voice Spread",
+        }
+    }
+
+    /// Set the parameter from a 0.0..=1.0 position.
+    pub fn set(self, s: &mut Synth, x: f32) {
+        let x = x.clamp(0.0, 1.0);
+        let lin = |lo: f32, hi: f32| lo + (hi - lo) * x;
+        match self {
+            Self::Volume1        => s.volume = x,
+            Self::Volume2        => s.volume2 = x,
+            Self::Cutoff1        => s.filter1.cutoff = CUTOFF_MIN * (CUTOFF_MAX / CUTOFF_MIN).powf(x),
+            Self::Cutoff2        => s.filter2.cutoff = CUTOFF_MIN * (CUTOFF_MAX / CUTOFF_MIN).powf(x),
+            Self::Res1           => s.filter1.q = lin(0.5, 10.0),
+            Self::Res2           => s.filter2.q = lin(0.5, 10.0),
+            Self::ReverbMix      => s.reverb.mix = x,
+            Self::DelayMix       => s.delay.mix = x,
+            Self::DelayFeedback  => s.delay.feedback = lin(0.0, 0.95),
+            Self::DistDrive      => s.distortion.drive = lin(1.0, 10.0),
+            Self::SidechainDepth => s.sidechain.depth = x,
+            Self::Width          => s.widener.width = lin(0.0, WIDENER_MAX_WIDTH),
+            Self::FmIndex        => s.fm_index = lin(0.0, MAX_FM_INDEX),
+            Self::VoiceSpread    => s.voice_spread = lin(0.0, 1.0),
+        }
+    }
+
+    /// Current position, 0.0..=1.0 (the inverse of `set`).
+    pub fn get(self, s: &Synth) -> f32 {
+        let inv = |v: f32, lo: f32, hi: f32| ((v - lo) / (hi - lo)).clamp(0.0, 1.0);
+        let cut = |c: f32| ((c / CUTOFF_MIN).ln() / (CUTOFF_MAX / CUTOFF_MIN).ln()).clamp(0.0, 1.0);
+        match self {
+            Self::Volume1        => s.volume,
+            Self::Volume2        => s.volume2,
+            Self::Cutoff1        => cut(s.filter1.cutoff),
+            Self::Cutoff2        => cut(s.filter2.cutoff),
+            Self::Res1           => inv(s.filter1.q, 0.5, 10.0),
+            Self::Res2           => inv(s.filter2.q, 0.5, 10.0),
+            Self::ReverbMix      => s.reverb.mix,
+            Self::DelayMix       => s.delay.mix,
+            Self::DelayFeedback  => inv(s.delay.feedback, 0.0, 0.95),
+            Self::DistDrive      => inv(s.distortion.drive, 1.0, 10.0),
+            Self::SidechainDepth => s.sidechain.depth,
+            Self::Width          => inv(s.widener.width, 0.0, WIDENER_MAX_WIDTH),
+            Self::FmIndex        => inv(s.fm_index, 0.0, MAX_FM_INDEX),
+            Self::VoiceSpread    => inv(s.voice_spread, 0.0, 1.0),
+        }
+    }
+
+    /// Current value as shown in the status line, e.g. `S1 Cutoff: 1200Hz`.
+    pub fn readout(self, s: &Synth) -> String {
+        let pct = |v: f32| format!("{}: {:.0}%", self.label(), v * 100.0);
+        match self {
+            Self::Volume1        => pct(s.volume),
+            Self::Volume2        => pct(s.volume2),
+            Self::Cutoff1        => format!("{}: {:.0}Hz", self.label(), s.filter1.cutoff),
+            Self::Cutoff2        => format!("{}: {:.0}Hz", self.label(), s.filter2.cutoff),
+            Self::Res1           => format!("{}: {:.1}", self.label(), s.filter1.q),
+            Self::Res2           => format!("{}: {:.1}", self.label(), s.filter2.q),
+            Self::ReverbMix      => pct(s.reverb.mix),
+            Self::DelayMix       => pct(s.delay.mix),
+            Self::DelayFeedback  => pct(s.delay.feedback),
+            Self::DistDrive      => format!("{}: {:.1}x", self.label(), s.distortion.drive),
+            Self::SidechainDepth => pct(s.sidechain.depth),
+            Self::Width          => pct(s.widener.width),
+            Self::FmIndex        => format!("{}: {:.1}", self.label(), s.fm_index),
+            Self::VoiceSpread    => pct(s.voice_spread),
+        }
+    }
+}
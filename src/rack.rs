@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::effects::{AudioEffect, EffectChain, InsertKind, MAX_INSERTS};
+
+/// Directory (relative to the working directory) scanned for user racks.
+pub const RACK_DIR: &str = "racks";
+
+// ── Rack file format ──────────────────────────────────────────────────────────
+
+/// A saved insert chain: effect types in processing order plus their params.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RackFile {
+    pub name:    String,
+    pub effects: Vec<RackEffect>,
+    /// True for the built-in racks (never written to disk).
+    #[serde(skip)]
+    pub factory: bool,
+}
+
+/// One effect in a rack.  `kind` is the effect's `AudioEffect::name()`.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct RackEffect {
+    pub kind: String,
+    #[serde(default)]
+    pub params: BTreeMap<String, f32>,
+}
+
+impl RackFile {
+    /// Snapshot a live chain into a rack.
+    pub fn capture(name: &str, chain: &EffectChain) -> Self {
+        let effects = chain.effects().iter().map(|fx| RackEffect {
+            kind:   fx.name().to_string(),
+            params: fx.params().into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+        }).collect();
+        Self { name: name.to_string(), effects, factory: false }
+    }
+
+    /// Instantiate the rack's effects.  Unknown effect kinds are skipped and
+    /// the chain is capped at `MAX_INSERTS`.
+    pub fn build(&self, sample_rate: f32) -> Vec<Box<dyn AudioEffect>> {
+        self.effects.iter()
+            .filter_map(|re| {
+                let mut fx = InsertKind::from_name(&re.kind)?.build(sample_rate);
+                for (k, &v) in &re.params { fx.set_param(k, v); }
+                Some(fx)
+            })
+            .take(MAX_INSERTS)
+            .collect()
+    }
+}
+
+fn factory_rack(name: &str, effects: &[(&str, &[(&str, f32)])]) -> RackFile {
+    RackFile {
+        name: name.to_string(),
+        effects: effects.iter().map(|(kind, params)| RackEffect {
+            kind:   kind.to_string(),
+            params: params.iter().map(|&(k, v)| (k.to_string(), v)).collect(),
+        }).collect(),
+        factory: true,
+    }
+}
+
+/// Built-in racks, always listed first.
+pub fn factory() -> Vec<RackFile> {
+    vec![
+        factory_rack("Ambient", &[
+            ("Filter", &[("mode", 1.0), ("cutoff", 150.0), ("q", 0.707)]),
+            ("Delay",  &[("time_ms", 375.0), ("feedback", 0.45), ("mix", 0.3)]),
+            ("Reverb", &[("room_size", 0.9), ("damping", 0.3), ("mix", 0.5)]),
+        ]),
+        factory_rack("Lo-fi", &[
+            ("Filter",     &[("mode", 0.0), ("cutoff", 3200.0), ("q", 1.2)]),
+            ("Distortion", &[("drive", 3.5), ("tone", 0.6), ("level", 0.55)]),
+            ("NoiseGate",  &[("threshold_db", -50.0), ("attack_ms", 1.0),
+                             ("release_ms", 60.0), ("hold_ms", 20.0)]),
+        ]),
+    ]
+}
+
+/// Factory racks followed by every readable `racks/*.json`, sorted by name.
+pub fn available() -> Vec<RackFile> {
+    let mut user: Vec<RackFile> = std::fs::read_dir(RACK_DIR)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|x| x == "json"))
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .filter_map(|j| serde_json::from_str::<RackFile>(&j).ok())
+        .collect();
+    user.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut racks = factory();
+    racks.extend(user);
+    racks
+}
+
+/// Write a rack to `racks/<name>.json`, creating the directory if needed.
+pub fn save(rack: &RackFile) -> Result<PathBuf> {
+    let file: String = rack.name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    std::fs::create_dir_all(RACK_DIR).context("Cannot create rack directory")?;
+    let path = PathBuf::from(RACK_DIR).join(format!("{}.json", file));
+    let json = serde_json::to_string_pretty(rack)?;
+    std::fs::write(&path, json).with_context(|| format!("Cannot write {}", path.display()))?;
+    Ok(path)
+}
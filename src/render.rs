@@ -0,0 +1,362 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::sequencer::samples_per_step;
+use crate::synth::Synth;
+use crate::tempo::bar_steps;
+
+// ── Offline rendering ─────────────────────────────────────────────────────────
+//
+// Drives `Synth` without an audio device, for checking what the engine
+// produces.  Nothing here touches cpal; the caller owns the `Synth`.
+
+/// Render `frames` stereo frames from `synth`, advancing its master clock.
+pub fn render(synth: &mut Synth, frames: usize) -> Vec<(f32, f32)> {
+    (0..frames).map(|_| synth.generate_frame()).collect()
+}
+
+/// Render `frames` frames folded to mono (`(l + r) / 2`), like the scope.
+#[cfg(test)]
+pub fn render_mono(synth: &mut Synth, frames: usize) -> Vec<f32> {
+    (0..frames).map(|_| { let (l, r) = synth.generate_frame(); (l + r) * 0.5 }).collect()
+}
+
+/// Number of frames covering `ms` milliseconds at `sample_rate`.
+#[cfg(test)]
+pub fn frames_for_ms(sample_rate: f32, ms: f32) -> usize {
+    (sample_rate * ms * 0.001).ceil() as usize
+}
+
+/// Frames covering `bars` bars of time signature `sig` at `bpm`
+/// (`bar_steps(sig)` grid steps each).
+pub fn frames_for_bars(sample_rate: f32, bpm: f32, sig: (u8, u8), bars: u32) -> usize {
+    (samples_per_step(sample_rate, bpm) * bar_steps(sig) as f64 * bars as f64).ceil() as usize
+}
+
+/// Rewind the master clock to the start of bar 1 and start every transport,
+/// so an offline render begins on the downbeat.  Empty patterns stay silent.
+pub fn rewind_and_play(synth: &mut Synth) {
+    synth.master_clock = 0;
+    // No device is starting, so the first downbeat keeps its attack.
+    synth.frames_out = synth.startup_fade_frames();
+    for lane in &mut synth.lanes { lane.playing = true; }
+    synth.sequencer2.playing   = true;
+    synth.drum_machine.playing = true;
+}
+
+// ── WAV export ────────────────────────────────────────────────────────────────
+
+/// Write `frames` as a 16-bit PCM stereo WAV file.  Samples are clamped to
+/// ±1.0 (the master clipper already keeps them there).
+pub fn write_wav(path: &Path, sample_rate: u32, frames: &[(f32, f32)]) -> Result<()> {
+    let file = File::create(path).with_context(|| format!("Cannot create {}", path.display()))?;
+    let mut w = BufWriter::new(file);
+    let data_len = (frames.len() * 4) as u32;
+    w.write_all(b"RIFF")?;
+    w.write_all(&(36 + data_len).to_le_bytes())?;
+    w.write_all(b"WAVEfmt ")?;
+    w.write_all(&16u32.to_le_bytes())?;               // fmt chunk size
+    w.write_all(&1u16.to_le_bytes())?;                // PCM
+    w.write_all(&2u16.to_le_bytes())?;                // channels
+    w.write_all(&sample_rate.to_le_bytes())?;
+    w.write_all(&(sample_rate * 4).to_le_bytes())?;   // byte rate
+    w.write_all(&4u16.to_le_bytes())?;                // block align
+    w.write_all(&16u16.to_le_bytes())?;               // bits per sample
+    w.write_all(b"data")?;
+    w.write_all(&data_len.to_le_bytes())?;
+    for &(l, r) in frames {
+        w.write_all(&pcm16(l).to_le_bytes())?;
+        w.write_all(&pcm16(r).to_le_bytes())?;
+    }
+    w.flush().with_context(|| format!("Cannot write {}", path.display()))
+}
+
+/// A sample as written to the WAV file.
+fn pcm16(x: f32) -> i16 {
+    (x.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16
+}
+
+/// Render `bars` bars of `synth` from its current state into a WAV file at
+/// `path`.  With `looped`, one extra bar is rendered and folded over the
+/// start by `crossfade_tail`, so the file loops without a click.
+/// `progress(bar, total)` is called after each rendered bar.  Returns the
+/// number of frames written.
+pub fn render_to_wav(
+    synth: &mut Synth, bars: u32, path: &Path, looped: bool, mut progress: impl FnMut(u32, u32),
+) -> Result<usize> {
+    let (sr, bpm, sig) = (synth.sample_rate, synth.bpm, synth.time_sig);
+    let total = if looped { bars + 1 } else { bars };
+    let mut frames = Vec::with_capacity(frames_for_bars(sr, bpm, sig, total));
+    for bar in 1..=total {
+        let n = frames_for_bars(sr, bpm, sig, bar) - frames_for_bars(sr, bpm, sig, bar - 1);
+        frames.extend(render(synth, n));
+        progress(bar, total);
+    }
+    if looped { crossfade_tail(&mut frames, frames_for_bars(sr, bpm, sig, bars)); }
+    write_wav(path, sr as u32, &frames)?;
+    Ok(frames.len())
+}
+
+/// Cut `frames` to `len` and blend what followed the cut over the start,
+/// fading linearly from the tail to the original head.  The first frame is
+/// then the continuation of the last one, and effect tails and held notes
+/// ring across the wrap instead of stopping dead.
+pub fn crossfade_tail(frames: &mut Vec<(f32, f32)>, len: usize) {
+    if frames.len() <= len { return; }
+    let tail = frames.split_off(len);
+    let n = tail.len().min(len);
+    for (i, &(tl, tr)) in tail.iter().take(n).enumerate() {
+        let g = i as f32 / n as f32;
+        let (l, r) = &mut frames[i];
+        *l = *l * g + tl * (1.0 - g);
+        *r = *r * g + tr * (1.0 - g);
+    }
+}
+
+// ── Stems ─────────────────────────────────────────────────────────────────────
+//
+// One offline render per source, with everything else silenced.  Silenced
+// drum tracks keep firing at zero volume instead of being muted, so the
+// probability rolls, hi-hat choke and kick sidechain match the full mix.
+// Each stem still runs through the master chain (sends, master FX, clipper).
+
+/// One source of a stems export.
+#[derive(Clone, Copy)]
+pub enum Stem {
+    /// A drum track, by index.
+    Drum(usize),
+    /// Both melodic buses and the drone, without the drums.
+    Synth,
+}
+
+impl Stem {
+    /// Every stem worth rendering: unmuted drum tracks with at least one
+    /// active step, then the synth bus.
+    pub fn all(synth: &Synth) -> Vec<Stem> {
+        let tracks = &synth.drum_machine.tracks;
+        let mut stems: Vec<Stem> = (0..tracks.len())
+            .filter(|&i| !tracks[i].muted && tracks[i].steps.iter().any(|&p| p > 0))
+            .map(Stem::Drum)
+            .collect();
+        stems.push(Stem::Synth);
+        stems
+    }
+
+    /// Display name, e.g. `C-Hat` or `Synth`.
+    pub fn name(self, synth: &Synth) -> String {
+        match self {
+            Stem::Drum(i) => synth.drum_machine.tracks[i].kind.name().trim().to_string(),
+            Stem::Synth   => "Synth".to_string(),
+        }
+    }
+
+    /// File name inside the stems directory, e.g. `03-c-hat.wav` or `synth.wav`.
+    pub fn file_name(self, synth: &Synth) -> String {
+        let slug = self.name(synth).to_lowercase().replace('.', "-");
+        match self {
+            Stem::Drum(i) => format!("{:02}-{}.wav", i + 1, slug),
+            Stem::Synth   => format!("{}.wav", slug),
+        }
+    }
+
+    /// Silence every other source.  Call after `rewind_and_play`.
+    fn isolate(self, synth: &mut Synth) {
+        let keep = match self { Stem::Drum(i) => Some(i), Stem::Synth => None };
+        for (i, t) in synth.drum_machine.tracks.iter_mut().enumerate() {
+            if Some(i) != keep { t.volume = 0.0; }
+        }
+        if keep.is_some() {
+            for lane in &mut synth.lanes { lane.playing = false; }
+            synth.sequencer2.playing = false;
+            synth.drone.on = false;
+        }
+    }
+}
+
+/// Render `bars` bars of every `Stem` into `dir` (created if missing), one
+/// WAV file each (`looped` as for `render_to_wav`).  `make` builds a freshly loaded synth per stem, so every
+/// render starts from the same state and seed.  `progress(stem, bar, bars)`
+/// is called after each bar.  Returns the files written.
+pub fn render_stems(
+    mut make: impl FnMut() -> Result<Synth>, bars: u32, dir: &Path, looped: bool,
+    mut progress: impl FnMut(&str, u32, u32),
+) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Cannot create {}", dir.display()))?;
+    let stems = Stem::all(&make()?);
+    let mut files = Vec::with_capacity(stems.len());
+    for stem in stems {
+        let mut synth = make()?;
+        let (name, path) = (stem.name(&synth), dir.join(stem.file_name(&synth)));
+        rewind_and_play(&mut synth);
+        stem.isolate(&mut synth);
+        render_to_wav(&mut synth, bars, &path, looped, |bar, bars| progress(&name, bar, bars))?;
+        files.push(path);
+    }
+    Ok(files)
+}
+
+// ── Buffer measurements ───────────────────────────────────────────────────────
+
+/// Largest absolute sample value (0.0 for an empty buffer).
+pub fn peak(buf: &[f32]) -> f32 {
+    buf.iter().fold(0.0f32, |m, &x| m.max(x.abs()))
+}
+
+/// Root-mean-square level (0.0 for an empty buffer).
+pub fn rms(buf: &[f32]) -> f32 {
+    if buf.is_empty() { return 0.0; }
+    (buf.iter().map(|&x| x * x).sum::<f32>() / buf.len() as f32).sqrt()
+}
+
+/// Silence threshold used by `is_silent` (-80 dBFS).
+pub const SILENCE_PEAK: f32 = 1e-4;
+
+/// True when no sample exceeds `SILENCE_PEAK`.
+pub fn is_silent(buf: &[f32]) -> bool {
+    peak(buf) <= SILENCE_PEAK
+}
+
+/// Index of the first sample whose magnitude exceeds `SILENCE_PEAK`, i.e.
+/// where a triggered sound becomes audible.
+#[cfg(test)]
+pub fn first_onset(buf: &[f32]) -> Option<usize> {
+    buf.iter().position(|&x| x.abs() > SILENCE_PEAK)
+}
+
+// ── Golden-output fingerprint ─────────────────────────────────────────────────
+//
+// A short summary of a render from bar 1 for regression checks without an
+// audio device: the hidden `--checksum` flag prints it, and the test in
+// `main.rs` compares it with the recorded one for `tests/fixtures/golden.json`.
+
+#[derive(Debug, PartialEq)]
+pub struct Fingerprint {
+    pub frames:   usize,
+    /// Peak and RMS of the mono fold, for a readable idea of what changed.
+    pub peak:     f32,
+    pub rms:      f32,
+    /// FNV-1a over the 16-bit samples `write_wav` would write, so float
+    /// noise below the file's resolution does not change it.
+    pub checksum: u64,
+}
+
+/// Render `bars` bars from the start (`rewind_and_play`) and summarise them.
+pub fn fingerprint(synth: &mut Synth, bars: u32) -> Fingerprint {
+    rewind_and_play(synth);
+    let frames = render(synth, frames_for_bars(synth.sample_rate, synth.bpm, synth.time_sig, bars));
+    let mono: Vec<f32> = frames.iter().map(|&(l, r)| (l + r) * 0.5).collect();
+    let mut checksum: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in frames.iter().flat_map(|&(l, r)| [pcm16(l), pcm16(r)]).flat_map(i16::to_le_bytes) {
+        checksum = (checksum ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    Fingerprint { frames: frames.len(), peak: peak(&mono), rms: rms(&mono), checksum }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequencer::step_start;
+
+    const SR: f32 = 44_100.0;
+
+    /// A fresh synth with the startup fade already over.
+    fn synth() -> Synth {
+        let mut s = Synth::new(SR);
+        s.frames_out = s.startup_fade_frames();
+        s
+    }
+
+    #[test]
+    fn idle_synth_is_silent() {
+        assert!(is_silent(&render_mono(&mut synth(), frames_for_ms(SR, 200.0))));
+    }
+
+    #[test]
+    fn note_on_makes_sound() {
+        let mut s = synth();
+        s.note_on(60);
+        let buf = render_mono(&mut s, frames_for_ms(SR, 100.0));
+        let onset = first_onset(&buf).expect("note_on stayed silent");
+        assert!(onset < frames_for_ms(SR, 2.0), "onset after {} frames", onset);
+        assert!(rms(&buf[frames_for_ms(SR, 20.0)..]) > 0.05, "rms {}", rms(&buf));
+        assert!(peak(&buf) <= 1.0);
+    }
+
+    #[test]
+    fn note_off_decays_to_silence_within_the_release() {
+        let mut s = synth();
+        s.note_on(60);
+        render_mono(&mut s, frames_for_ms(SR, 100.0));
+        s.note_off(60);
+        let release = frames_for_ms(SR, s.release * 1000.0);
+        let tail = render_mono(&mut s, release + frames_for_ms(SR, 20.0));
+        assert!(!is_silent(&tail[..release / 2]), "note cut instead of releasing");
+        assert!(is_silent(&tail[release + frames_for_ms(SR, 10.0)..]), "still sounding after the release");
+        assert!(s.voices.is_empty());
+    }
+
+    #[test]
+    fn sequencer_step_sounds_on_its_boundary() {
+        let mut s = synth();
+        s.lanes[0].steps[4] = Some(60);
+        rewind_and_play(&mut s);
+        let sps = samples_per_step(SR, s.bpm);
+        let buf = render_mono(&mut s, (16.0 * sps) as usize);
+        let at = step_start(4, sps) as usize;
+        let onset = first_onset(&buf).expect("step never sounded");
+        assert!((at..at + 8).contains(&onset), "step 4 at sample {}, sounded at {}", at, onset);
+    }
+
+    /// `n` frames of a 50 Hz sine, opposite phase on the right.
+    fn sine_frames(n: usize) -> Vec<(f32, f32)> {
+        (0..n).map(|i| {
+            let x = (std::f32::consts::TAU * 50.0 * i as f32 / SR).sin();
+            (x, -x)
+        }).collect()
+    }
+
+    #[test]
+    fn crossfade_tail_is_continuous_across_the_wrap() {
+        let len = 1000;
+        let orig = sine_frames(1500);
+        let mut looped = orig.clone();
+        crossfade_tail(&mut looped, len);
+        assert_eq!(looped.len(), len);
+        // The loop starts where the cut-off tail started …
+        assert_eq!(looped[0], orig[len]);
+        // … so wrapping from the last frame to the first is one normal step.
+        let max_step = SR.recip() * std::f32::consts::TAU * 50.0 * 1.01;
+        let wrap = (looped[0].0 - looped[len - 1].0).abs();
+        assert!(wrap <= max_step, "jump of {} at the wrap", wrap);
+        assert!(looped.windows(2).all(|w| (w[1].0 - w[0].0).abs() <= 2.0 * max_step), "crossfade jumped");
+        // Past the 500-frame tail the head is untouched.
+        assert_eq!(&looped[500..], &orig[500..len]);
+    }
+
+    #[test]
+    fn crossfade_tail_leaves_a_short_render_alone() {
+        let orig = sine_frames(800);
+        let mut frames = orig.clone();
+        crossfade_tail(&mut frames, 800);
+        assert_eq!(frames, orig);
+    }
+
+    #[test]
+    fn bars_follow_the_time_signature() {
+        // 120 BPM at 44.1 kHz: 5512.5 frames a step.
+        assert_eq!(frames_for_bars(SR, 120.0, (4, 4), 1), 88_200);
+        assert_eq!(frames_for_bars(SR, 120.0, (3, 4), 1), 66_150);
+        assert_eq!(frames_for_bars(SR, 120.0, (7, 8), 1), 77_175);
+        assert_eq!(frames_for_bars(SR, 120.0, (7, 8), 2), 154_350);
+        assert_eq!(frames_for_bars(SR, 133.0, (3, 4), 64), step_start(64 * 12, samples_per_step(SR, 133.0)) as usize);
+
+        let mut s = synth();
+        s.time_sig = (3, 4);
+        assert_eq!(fingerprint(&mut s, 2).frames, 132_300);
+        s.time_sig = (7, 8);
+        assert_eq!(fingerprint(&mut s, 2).frames, 154_350);
+    }
+}
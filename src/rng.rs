@@ -0,0 +1,57 @@
+// ── Seedable RNG ──────────────────────────────────────────────────────────────
+
+/// Seed used at startup, so a fresh session is reproducible too.
+pub const DEFAULT_SEED: u64 = 0x5EED;
+
+/// Small xorshift64* generator shared by every probabilistic feature.
+///
+/// One instance lives on `Synth`; anything that rolls dice (step probability,
+/// per-hit noise flavour, …) draws from it, so reseeding makes playback and
+/// offline renders repeat exactly.
+pub struct Rng {
+    seed:  u64,
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Self { seed, state: 0 };
+        rng.reseed(seed);
+        rng
+    }
+
+    /// Seed the generator was last (re)started from.
+    pub fn seed(&self) -> u64 { self.seed }
+
+    /// Restart the sequence from `seed`.
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        // SplitMix64 scramble so small seeds still give well-mixed state.
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        self.state = if z == 0 { 0x2545_F491_4F6C_DD1D } else { z }; // xorshift must never be 0
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Uniform integer in `0..n` (`n` must be non-zero).
+    pub fn below(&mut self, n: u32) -> u32 {
+        ((self.next_u32() as u64 * n as u64) >> 32) as u32
+    }
+
+    /// True with probability `pct` percent (0 = never, ≥100 = always).
+    pub fn chance(&mut self, pct: u8) -> bool {
+        pct >= 100 || self.below(100) < pct as u32
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+
+/// Longest sample accepted, in seconds (a drum hit or a loop of a few bars).
+pub const MAX_SAMPLE_SECS: f32 = 30.0;
+
+// ── Drum samples ──────────────────────────────────────────────────────────────
+//
+// A WAV file loaded onto a drum track replaces that track's synthesised voice.
+// Files are decoded on the UI thread (load/save, palette) into mono f32 and
+// shared with the audio thread as `Arc<Sample>`, so a trigger never touches
+// the disk or allocates.
+
+pub struct Sample {
+    /// File name shown in the drum panel.
+    pub name: String,
+    /// Path as given when loaded; what the session file stores.
+    pub path: String,
+    /// Mono frames (channels averaged), -1.0..=1.0.
+    pub frames: Vec<f32>,
+    pub sample_rate: f32,
+}
+
+impl Sample {
+    /// Length in seconds at the file's own rate.
+    pub fn secs(&self) -> f32 {
+        self.frames.len() as f32 / self.sample_rate
+    }
+}
+
+fn u16_at(b: &[u8], i: usize) -> u16 { u16::from_le_bytes([b[i], b[i + 1]]) }
+fn u32_at(b: &[u8], i: usize) -> u32 { u32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]) }
+
+/// Decode one sample of `bits` bits (`float` for IEEE) to -1.0..=1.0.
+fn decode(b: &[u8], bits: u16, float: bool) -> f32 {
+    match (bits, float) {
+        (8, false)  => (b[0] as f32 - 128.0) / 128.0,
+        (16, false) => i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+        (24, false) => (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0,
+        (32, false) => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0,
+        (32, true)  => f32::from_le_bytes([b[0], b[1], b[2], b[3]]).clamp(-1.0, 1.0),
+        _ => 0.0,
+    }
+}
+
+/// Load a PCM (8/16/24/32-bit) or 32-bit float WAV file, mixed down to mono.
+pub fn load_wav(path: &Path) -> Result<Arc<Sample>> {
+    let bytes = std::fs::read(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        bail!("{} is not a WAV file", path.display());
+    }
+
+    let mut fmt: Option<(u16, u16, u32, u16)> = None;
+    let mut data: Option<&[u8]> = None;
+    let mut i = 12;
+    while i + 8 <= bytes.len() {
+        let id  = &bytes[i..i + 4];
+        let len = u32_at(&bytes, i + 4) as usize;
+        let body = &bytes[i + 8..(i + 8 + len).min(bytes.len())];
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let mut format = u16_at(body, 0);
+                // WAVE_FORMAT_EXTENSIBLE: the real format is in the sub-format GUID.
+                if format == 0xFFFE && body.len() >= 26 { format = u16_at(body, 24); }
+                fmt = Some((format, u16_at(body, 2), u32_at(body, 4), u16_at(body, 14)));
+            }
+            b"data" => data = Some(body),
+            _ => {}
+        }
+        i += 8 + len + (len & 1);
+    }
+
+    let (format, channels, rate, bits) = fmt.with_context(|| format!("{}: no fmt chunk", path.display()))?;
+    let data = data.with_context(|| format!("{}: no data chunk", path.display()))?;
+    let float = match (format, bits) {
+        (1, 8 | 16 | 24 | 32) => false,
+        (3, 32)               => true,
+        _ => bail!("{}: unsupported WAV format ({} bits, format {})", path.display(), bits, format),
+    };
+    if channels == 0 || rate == 0 { bail!("{}: invalid WAV header", path.display()); }
+
+    let width = bits as usize / 8;
+    let frame_len = width * channels as usize;
+    let max_frames = (MAX_SAMPLE_SECS * rate as f32) as usize;
+    let frames: Vec<f32> = data.chunks_exact(frame_len)
+        .take(max_frames)
+        .map(|f| f.chunks_exact(width).map(|s| decode(s, bits, float)).sum::<f32>() / channels as f32)
+        .collect();
+    if frames.is_empty() { bail!("{}: no audio", path.display()); }
+
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    Ok(Arc::new(Sample {
+        name,
+        path: path.to_string_lossy().into_owned(),
+        frames,
+        sample_rate: rate as f32,
+    }))
+}
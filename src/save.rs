@@ -4,7 +4,17 @@ use serde::{Deserialize, Serialize};
 pub struct SaveFile {
     // Global
     pub bpm: f32,
+    // Swing of the melodic sequencers (the drum swing is in `drums`)
+    #[serde(default)] pub seq_swing: f32,
     pub base_octave: i32,
+    // Time signature [beats, note value] (bar length and grid beats)
+    #[serde(default = "default_time_sig")] pub time_sig: [u8; 2],
+    // Computer-keyboard layout (KeyLayout::index; 0 = two-octave)
+    #[serde(default)] pub key_layout: u8,
+    // Fold piano keys past MIDI 0–127 back by octaves (false = drop them)
+    #[serde(default)] pub note_fold: bool,
+    // Mono-mode note priority (NotePriority::index; 0 = last)
+    #[serde(default)] pub note_priority: u8,
     pub scale: u8,        // index into Scale::ALL
     pub scale_root: u8,
     // Synths
@@ -15,9 +25,32 @@ pub struct SaveFile {
     // Chord mode (index into ChordType::ALL)
     #[serde(default)] pub chord1: u8,
     #[serde(default)] pub chord2: u8,
+    // RNG seed for probabilistic features
+    #[serde(default = "default_seed")] pub seed: u64,
+    // Repeated note-on behaviour (Retrigger::index; 0 = replace)
+    #[serde(default)] pub retrigger: u8,
+    // Envelope attack / release shapes (EnvCurve::index; 0 = linear)
+    #[serde(default)] pub attack_curve: u8,
+    #[serde(default)] pub release_curve: u8,
+    // Tone generation (SynthMode::index; 0 = subtractive) and the FM operator
+    #[serde(default)] pub synth_mode: u8,
+    #[serde(default = "default_fm_ratio")] pub fm_ratio: f32,
+    #[serde(default = "default_fm_index")] pub fm_index: f32,
+    // Polyphony limit per melodic synth
+    #[serde(default = "default_max_voices")] pub max_voices: usize,
+    // Per-waveform gain trims (Sine, Square, Sawtooth, Triangle)
+    #[serde(default = "default_wave_trims")] pub wave_trims: [f32; 4],
+    // Saturation oversampling factor (1, 2 or 4)
+    #[serde(default = "default_oversample")] pub oversample: usize,
+    // Drum groove template, stored by value so the save doesn't need the file
+    #[serde(default)] pub groove: Option<crate::groove::Groove>,
     // Sequencers (live state — active bank)
     pub seq1: SeqSave,
     pub seq2: SeqSave,
+    // Synth 1 lanes after the first (not part of the pattern banks)
+    #[serde(default)] pub seq1_lanes: Vec<SeqSave>,
+    // Sound of each synth 1 lane, lane 1 first (none = synth 1's own)
+    #[serde(default)] pub seq1_lane_patches: Vec<Option<PatchSave>>,
     // Drums (live state — active bank)
     pub drums: DrumsSave,
     // Effects
@@ -28,6 +61,7 @@ pub struct SaveFile {
     pub filter1: FilterSave,
     pub filter2: FilterSave,
     pub routing: RoutingSave,
+    #[serde(default)] pub output: OutputSave,
     // Pattern banks
     #[serde(default)] pub seq1_bank:  usize,
     #[serde(default)] pub seq2_bank:  usize,
@@ -35,33 +69,104 @@ pub struct SaveFile {
     #[serde(default)] pub seq1_banks: Vec<SeqSave>,
     #[serde(default)] pub seq2_banks: Vec<SeqSave>,
     #[serde(default)] pub drum_banks: Vec<DrumsSave>,
+    // MIDI-learn bindings (CC → parameter)
+    #[serde(default)] pub cc_map: Vec<CcMapSave>,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct SeqSave { pub num_steps: usize, pub steps: Vec<Option<u8>> }
+fn default_seed() -> u64 { crate::rng::DEFAULT_SEED }
+fn default_wave_trims() -> [f32; 4] { crate::synth::DEFAULT_WAVE_TRIMS }
+fn default_oversample() -> usize { 1 }
+fn default_max_voices() -> usize { crate::synth::DEFAULT_MAX_VOICES }
+fn default_time_sig() -> [u8; 2] { [4, 4] }
+fn default_fm_ratio() -> f32 { crate::synth::DEFAULT_FM_RATIO }
+fn default_fm_index() -> f32 { crate::synth::DEFAULT_FM_INDEX }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CcMapSave {
+    pub channel: u8,
+    pub cc:      u8,
+    // Param::id
+    pub param:   String,
+}
 
 #[derive(Serialize, Deserialize)]
-pub struct DrumsSave { pub num_steps: usize, pub swing: f32, pub tracks: Vec<TrackSave> }
+pub struct SeqSave {
+    pub num_steps: usize,
+    pub steps:     Vec<Option<u8>>,
+    #[serde(default)] pub nudge: Vec<i8>,
+    // Loop points; a missing `loop_end` means the whole pattern
+    #[serde(default)] pub loop_start: usize,
+    #[serde(default)] pub loop_end:   Option<usize>,
+    // Play direction (Direction::index; 0 = forward)
+    #[serde(default)] pub direction:  u8,
+    // Velocity automation (none = every step at full velocity)
+    #[serde(default)] pub velocity_ramp: Option<crate::sequencer::VelocityRamp>,
+    // Per-step 303 accent / slide flags (played by Synth 2 only)
+    #[serde(default)] pub accent: Vec<bool>,
+    #[serde(default)] pub slide:  Vec<bool>,
+    // Per-step note probability in percent; missing steps play always
+    #[serde(default)] pub prob:   Vec<u8>,
+}
+
+/// A lane's own waveform and envelope (`synth::Patch`).
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct PatchSave {
+    pub wave:    u8,    // 0=Sine 1=Square 2=Saw 3=Tri
+    pub attack:  f32,
+    pub decay:   f32,
+    pub sustain: f32,
+    pub release: f32,
+}
 
 #[derive(Serialize, Deserialize)]
-pub struct TrackSave { pub kind: u8, pub steps: Vec<u8>, pub muted: bool, pub volume: f32 }
+pub struct DrumsSave {
+    pub num_steps: usize,
+    pub swing:     f32,
+    #[serde(default)] pub loop_start: usize,
+    #[serde(default)] pub loop_end:   Option<usize>,
+    #[serde(default)] pub direction:  u8,
+    pub tracks:    Vec<TrackSave>,
+}
 
 #[derive(Serialize, Deserialize)]
+pub struct TrackSave {
+    pub kind:   u8,
+    pub steps:  Vec<u8>,
+    pub muted:  bool,
+    pub volume: f32,
+    #[serde(default)] pub pan:   f32,
+    // Pitch offset in semitones
+    #[serde(default)] pub tune:  i8,
+    #[serde(default)] pub nudge: Vec<i8>,
+    // Per-step decay in percent (missing = 100)
+    #[serde(default)] pub decay: Vec<u8>,
+    // WAV sample path (none = synthesised voice) and whether it plays backwards
+    #[serde(default)] pub sample:  Option<String>,
+    #[serde(default)] pub reverse: bool,
+    // Steps the sample is time-stretched to (none = natural length)
+    #[serde(default)] pub fit_steps: Option<u16>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ReverbSave { pub enabled: bool, pub room_size: f32, pub damping: f32, pub mix: f32 }
 
-#[derive(Serialize, Deserialize)]
-pub struct DelaySave { pub enabled: bool, pub time_ms: f32, pub feedback: f32, pub mix: f32 }
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DelaySave {
+    pub enabled: bool, pub time_ms: f32, pub feedback: f32, pub mix: f32,
+    /// `Interp::index()`; 0 = linear.
+    #[serde(default)] pub interp: u8,
+}
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DistSave { pub enabled: bool, pub drive: f32, pub tone: f32, pub level: f32 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SidechainSave {
     pub enabled: bool, pub depth: f32, pub release_ms: f32,
     pub duck_s1: bool, pub duck_s2: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FilterSave {
     pub enabled: bool,
     pub mode: u8,     // 0=LP 1=HP 2=BP
@@ -69,9 +174,34 @@ pub struct FilterSave {
     pub q: f32,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RoutingSave {
     pub s1_reverb: f32, pub s1_delay: f32, pub s1_dist: f32,
     pub s2_reverb: f32, pub s2_delay: f32, pub s2_dist: f32,
     pub dr_reverb: f32, pub dr_delay: f32, pub dr_dist: f32,
 }
+
+/// Master output stage (mix auto-gain + stereo widener + limiter + clipper).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OutputSave {
+    pub width: f32,
+    /// `ClipMode::index()`; 0 = tanh.
+    #[serde(default)] pub clip: u8,
+    #[serde(default = "default_drive")] pub drive: f32,
+    #[serde(default)] pub limiter: bool,
+    #[serde(default = "default_limiter_ceiling")] pub limiter_ceiling: f32,
+    #[serde(default)] pub lookahead: bool,
+    #[serde(default)] pub auto_gain: bool,
+    /// `Synth::voice_spread`, 0.0–1.0.
+    #[serde(default)] pub voice_spread: f32,
+}
+
+impl Default for OutputSave {
+    fn default() -> Self {
+        Self { width: 1.0, clip: 0, drive: 1.0, limiter: false, limiter_ceiling: default_limiter_ceiling(), lookahead: false,
+               auto_gain: false, voice_spread: 0.0 }
+    }
+}
+
+fn default_drive() -> f32 { 1.0 }
+fn default_limiter_ceiling() -> f32 { crate::effects::LIMITER_DEFAULT_CEILING_DB }
@@ -1,3 +1,6 @@
+/// Pitch-class names, C = 0.
+const PITCH_NAMES: [&str; 12] = ["C","C#","D","D#","E","F","F#","G","G#","A","A#","B"];
+
 // ── Scale definitions ─────────────────────────────────────────────────────────
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -73,6 +76,7 @@ impl Scale {
 
 // ── Quantizer ─────────────────────────────────────────────────────────────────
 
+#[derive(Debug)]
 pub struct ScaleQuantizer {
     pub scale: Scale,
     pub root:  u8,   // 0 = C, 1 = C#, … 11 = B
@@ -115,11 +119,123 @@ impl ScaleQuantizer {
         (note + best_offset).clamp(0, 127) as u8
     }
 
+    /// True if `note` lies in the selected scale (every note when Off).
+    pub fn contains(&self, note: u8) -> bool {
+        self.scale.intervals().contains(&((note as i32 - self.root as i32).rem_euclid(12) as u8))
+    }
+
+    /// Next note above `note` that lies in the scale (the next semitone when
+    /// the scale is Off).  Stays at `note` at the top of the MIDI range.
+    pub fn step_up(&self, note: u8) -> u8 {
+        (note + 1..=127).find(|&n| self.contains(n)).unwrap_or(note)
+    }
+
+    /// Next note below `note` that lies in the scale (mirror of `step_up`).
+    pub fn step_down(&self, note: u8) -> u8 {
+        (0..note).rev().find(|&n| self.contains(n)).unwrap_or(note)
+    }
+
+    /// Diatonic triad on `note`'s scale degree: the note (snapped into the
+    /// scale) plus the scale tones two and four degrees above it.  With the
+    /// scale Off, the major scale on `root` is used.
+    pub fn triad(&self, note: u8) -> [u8; 3] {
+        let scale = if self.active() { self.scale } else { Scale::Major };
+        let q = ScaleQuantizer { scale, root: self.root };
+        let root  = q.quantize(note);
+        let third = q.step_up(q.step_up(root));
+        let fifth = q.step_up(q.step_up(third));
+        [root, third, fifth]
+    }
+
     pub fn root_name(&self) -> &'static str {
-        ["C","C#","D","D#","E","F","F#","G","G#","A","A#","B"][self.root as usize]
+        PITCH_NAMES[self.root as usize]
     }
 
     pub fn cycle_root(&mut self) {
         self.root = (self.root + 1) % 12;
     }
 }
+
+// ── Chord detection ───────────────────────────────────────────────────────────
+//
+// Names the chord a set of held notes spells, for the status bar.  Only the
+// pitch classes count (octaves and doublings don't), and they must match a
+// template exactly.  A chord whose root isn't the lowest note is an
+// inversion and is written as a slash chord over that bass note.
+
+/// Chord templates: semitones above the root (root included) and the suffix
+/// after the root name.  Earlier entries win when one set of pitch classes
+/// spells several chords from roots other than the bass (C6 = Am7/C).
+const CHORD_TEMPLATES: [(&[u8], &str); 17] = [
+    (&[0, 4, 7],        ""),
+    (&[0, 3, 7],        "m"),
+    (&[0, 3, 6],        "dim"),
+    (&[0, 4, 8],        "+"),
+    (&[0, 2, 7],        "sus2"),
+    (&[0, 5, 7],        "sus4"),
+    (&[0, 4, 7, 10],    "7"),
+    (&[0, 4, 7, 11],    "maj7"),
+    (&[0, 3, 7, 10],    "m7"),
+    (&[0, 3, 6, 10],    "m7b5"),
+    (&[0, 3, 6, 9],     "dim7"),
+    (&[0, 3, 7, 11],    "mMaj7"),
+    (&[0, 4, 7, 9],     "6"),
+    (&[0, 3, 7, 9],     "m6"),
+    (&[0, 2, 4, 7],     "add9"),
+    (&[0, 2, 4, 7, 10], "9"),
+    (&[0, 7],           "5"),
+];
+
+/// Name of the chord `notes` (MIDI notes, any order) spell, e.g. "Cmaj7" or
+/// "Am7/E", or None for fewer than two pitch classes or no template match.
+/// A root on the bass note is preferred, so symmetric chords (dim7, +) and
+/// shared spellings are named from the bottom up.
+pub fn detect_chord(notes: &[u8]) -> Option<String> {
+    let bass = *notes.iter().min()? % 12;
+    let set = notes.iter().fold(0u16, |m, &n| m | 1 << (n % 12));
+    if set.count_ones() < 2 { return None; }
+    let mask = |root: u8, ivs: &[u8]| ivs.iter().fold(0u16, |m, &i| m | 1 << ((root + i) % 12));
+    // Try the bass first, then the other held pitch classes upwards from it.
+    let (root, suffix) = (0..12).map(|k| (bass + k) % 12)
+        .filter(|&r| set & 1 << r != 0)
+        .find_map(|r| CHORD_TEMPLATES.iter().find(|(ivs, _)| mask(r, ivs) == set).map(|&(_, s)| (r, s)))?;
+    let slash = if root == bass { String::new() } else { format!("/{}", PITCH_NAMES[bass as usize]) };
+    Some(format!("{}{}{}", PITCH_NAMES[root as usize], suffix, slash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_chord_names_root_position_chords() {
+        assert_eq!(detect_chord(&[60, 64, 67, 71]).as_deref(), Some("Cmaj7"));
+        assert_eq!(detect_chord(&[71, 48, 64, 67, 60, 76]).as_deref(), Some("Cmaj7"), "order and doublings count");
+        assert_eq!(detect_chord(&[57, 60, 64]).as_deref(), Some("Am"));
+        assert_eq!(detect_chord(&[60, 64, 67, 69]).as_deref(), Some("C6"), "the bass root wins over Am7/C");
+        assert_eq!(detect_chord(&[48, 55]).as_deref(), Some("C5"));
+    }
+
+    #[test]
+    fn detect_chord_writes_inversions_over_the_bass() {
+        assert_eq!(detect_chord(&[64, 67, 72]).as_deref(), Some("C/E"));
+        assert_eq!(detect_chord(&[52, 57, 60, 67]).as_deref(), Some("Am7/E"));
+        assert_eq!(detect_chord(&[55, 60, 64, 70]).as_deref(), Some("C7/G"));
+    }
+
+    #[test]
+    fn detect_chord_names_symmetric_chords_from_the_bass() {
+        assert_eq!(detect_chord(&[60, 63, 66, 69]).as_deref(), Some("Cdim7"));
+        assert_eq!(detect_chord(&[63, 66, 69, 72]).as_deref(), Some("D#dim7"));
+        assert_eq!(detect_chord(&[64, 68, 72]).as_deref(), Some("E+"));
+    }
+
+    #[test]
+    fn detect_chord_gives_none_without_a_match() {
+        assert_eq!(detect_chord(&[]), None);
+        assert_eq!(detect_chord(&[60]), None);
+        assert_eq!(detect_chord(&[48, 60, 72]), None, "octaves are one pitch class");
+        assert_eq!(detect_chord(&[60, 61, 62]), None);
+        assert_eq!(detect_chord(&[60, 61, 62, 63, 64]), None);
+    }
+}
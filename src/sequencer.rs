@@ -1,20 +1,316 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rng::Rng;
+use crate::scale::ScaleQuantizer;
+
+/// Largest per-step timing nudge, in percent of one step, in either direction.
+pub const MAX_NUDGE: i8 = 50;
+
+/// Step note probability: always (the default) and the edit increment, in percent.
+pub const FULL_PROB: u8 = 100;
+pub const PROB_STEP: u8 = 25;
+
+/// Length of one 16th-note step in samples.  Deliberately fractional:
+/// rounding it would make the loop drift from the ideal tempo over time.
+pub fn samples_per_step(sample_rate: f32, bpm: f32) -> f64 {
+    (sample_rate as f64 * 60.0 / (bpm as f64 * 4.0)).max(1.0)
+}
+
+/// First sample of grid step `k` (counted from clock 0).  Steps start on
+/// `ceil(k * sps)`, so step lengths alternate between floor/ceil of `sps`
+/// and the error never accumulates.
+pub fn step_start(k: u64, sps: f64) -> u64 {
+    (k as f64 * sps).ceil() as u64
+}
+
+/// Absolute grid step containing `clock`.
+pub fn step_at(clock: u64, sps: f64) -> u64 {
+    let mut k = (clock as f64 / sps).floor() as u64;
+    // Guard against float error right at a boundary.
+    if step_start(k, sps) > clock { k -= 1; }
+    if step_start(k + 1, sps) <= clock { k += 1; }
+    k
+}
+
+/// Master clock value that keeps playback at the same grid step and the same
+/// fraction through it after the step length changes from `old_sps` to
+/// `new_sps`.  Without this a tempo change jumps the playhead, because step
+/// positions are derived from the absolute clock.
+pub fn rescale_clock(clock: u64, old_sps: f64, new_sps: f64) -> u64 {
+    let k     = step_at(clock, old_sps);
+    let start = step_start(k, old_sps);
+    let len   = (step_start(k + 1, old_sps) - start).max(1);
+    let frac  = (clock - start) as f64 / len as f64;
+    let new_start = step_start(k, new_sps);
+    let new_len   = step_start(k + 1, new_sps) - new_start;
+    new_start + ((frac * new_len as f64).floor() as u64).min(new_len.saturating_sub(1))
+}
+
+/// Largest swing, as a fraction of a step (0.5 = odd steps land halfway).
+pub const MAX_SWING: f32 = 0.5;
+
+/// Swing delay of pattern step `step` in samples: odd steps are pushed late
+/// by `swing` (0.0 ..= `MAX_SWING`) of a step.
+pub fn swing_samples(step: usize, swing: f32, sps: f64) -> i64 {
+    if step % 2 == 1 { (swing as f64 * sps).round() as i64 } else { 0 }
+}
+
+/// Convert a nudge in percent of a step into a sample offset.
+pub fn nudge_samples(nudge: i8, sps: f64) -> i64 {
+    (nudge as f64 * sps / 100.0).round() as i64
+}
+
+// ── Velocity ramp ─────────────────────────────────────────────────────────────
+
+/// Full note velocity (MIDI scale); what every step plays without a ramp.
+pub const MAX_VELOCITY: u8 = 127;
+/// Longest velocity ramp, in passes of the play range.
+pub const MAX_RAMP_LOOPS: u32 = 64;
+
+/// Longest pattern a melodic sequencer holds (the top of the `]` cycle).
+pub const MAX_SEQ_STEPS: usize = 32;
+
+/// Crescendo (or fade) automation for a melodic sequence: velocity moves
+/// linearly from `start` to `end` over `loops` passes of the play range, then
+/// jumps back to `start`.  Like the play direction it is a function of the
+/// grid step, so it never drifts from the clock.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VelocityRamp {
+    pub start: u8,
+    pub end:   u8,
+    /// Ramp length in passes of the play range (1 = across the steps of one pass).
+    pub loops: u32,
+}
+
+impl VelocityRamp {
+    /// Velocity for grid step `k` of a play range `len` steps long.
+    pub fn velocity(&self, k: u64, len: usize) -> u8 {
+        let span = (len as u64 * self.loops.max(1) as u64).max(1);
+        let t = if span > 1 { (k % span) as f32 / (span - 1) as f32 } else { 1.0 };
+        let (a, b) = (self.start as f32, self.end as f32);
+        (a + (b - a) * t).round() as u8
+    }
+
+    /// Clamp a loaded ramp to the accepted ranges.
+    pub fn sanitize(self) -> Self {
+        Self {
+            start: self.start.clamp(1, MAX_VELOCITY),
+            end:   self.end.clamp(1, MAX_VELOCITY),
+            loops: self.loops.clamp(1, MAX_RAMP_LOOPS),
+        }
+    }
+
+    /// Short readout such as `40→127 /4 loops`.
+    pub fn label(&self) -> String {
+        if self.loops == 1 {
+            format!("{}→{}", self.start, self.end)
+        } else {
+            format!("{}→{} /{} loops", self.start, self.end, self.loops)
+        }
+    }
+}
+
+// ── Play direction ────────────────────────────────────────────────────────────
+
+/// Order in which a sequencer walks its play range.  Playback stays locked to
+/// the master clock: grid step `k` always plays `position(k, len)`, so
+/// switching direction mid-bar never drifts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Forward,
+    Backward,
+    /// Forward then backward; the end steps play once per turn, not twice.
+    PingPong,
+    /// A fresh pseudo-random step each grid step (repeatable per clock).
+    Random,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::Forward, Direction::Backward, Direction::PingPong, Direction::Random];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Direction::Forward  => "Forward",
+            Direction::Backward => "Backward",
+            Direction::PingPong => "Ping-pong",
+            Direction::Random   => "Random",
+        }
+    }
+
+    /// Short panel label.
+    pub fn label(self) -> &'static str {
+        match self {
+            Direction::Forward  => "→",
+            Direction::Backward => "←",
+            Direction::PingPong => "↔",
+            Direction::Random   => "?",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() as usize + 1) % Self::ALL.len()]
+    }
+
+    pub fn index(self) -> u8 {
+        Self::ALL.iter().position(|&d| d == self).unwrap_or(0) as u8
+    }
+
+    pub fn from_index(i: u8) -> Self {
+        Self::ALL.get(i as usize).copied().unwrap_or(Direction::Forward)
+    }
+
+    /// Position (`0..len`) within the play range played at grid step `k`.
+    pub fn position(self, k: u64, len: usize) -> usize {
+        let len = len.max(1) as u64;
+        let p = match self {
+            Direction::Forward  => k % len,
+            Direction::Backward => len - 1 - k % len,
+            Direction::PingPong if len == 1 => 0,
+            Direction::PingPong => {
+                let m = k % (2 * len - 2);
+                if m < len { m } else { 2 * len - 2 - m }
+            }
+            Direction::Random => {
+                // splitmix64 finaliser: cheap, stateless, well spread
+                let mut z = k.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+                (z ^ (z >> 31)) % len
+            }
+        };
+        p as usize
+    }
+}
+
+/// Which grid step, if any, triggers at `clock`.
+///
+/// `order(k)` maps grid step `k` to the step it plays (see
+/// `Direction::position`) and `offset(step)` is that step's trigger time in
+/// samples relative to its grid position.  Offsets are clamped to `-len/2 ..= len-1` (`len` being the
+/// current step's length), so a step can fire late inside its own window or
+/// early in the second half of the previous one.  A late step is pulled in
+/// just ahead of an early successor so the two never collide.  With all
+/// offsets 0 this fires at every step boundary.  Returns the grid step `k`;
+/// the step it plays is `order(k)`.
+pub fn due_step(
+    clock: u64, sps: f64, order: impl Fn(u64) -> usize, offset: impl Fn(usize) -> i64,
+) -> Option<u64> {
+    let k     = step_at(clock, sps);
+    let start = step_start(k, sps);
+    let len   = (step_start(k + 1, sps) - start) as i64;
+    let phase = (clock - start) as i64;
+    let clamp = |o: i64| o.clamp(-(len / 2), len - 1);
+
+    let next_off = clamp(offset(order(k + 1)));
+    if next_off < 0 && phase == len + next_off { return Some(k + 1); }
+
+    let mut off = clamp(offset(order(k)));
+    if next_off < 0 { off = off.min(len + next_off - 1); }
+    (off >= 0 && phase == off).then_some(k)
+}
+
+/// Rotate the first `n` entries of `v` one place, wrapping: `right` moves
+/// step 0 to step 1 and the last step to step 0.
+pub fn rotate<T>(v: &mut [T], n: usize, right: bool) {
+    let n = n.min(v.len());
+    let v = &mut v[..n];
+    if v.is_empty() { return; }
+    if right { v.rotate_right(1) } else { v.rotate_left(1) }
+}
+
+/// Flip `flags[step]`, growing `flags` to `len` first.  Returns the new value.
+fn toggle_flag(flags: &mut Vec<bool>, len: usize, step: usize) -> bool {
+    if flags.len() < len { flags.resize(len, false); }
+    let Some(f) = flags.get_mut(step) else { return false };
+    *f = !*f;
+    *f
+}
+
+/// `(first, len)` of the loop `start..=end`, or `None` if it doesn't fit in
+/// `num_steps`.
+pub fn loop_span(start: usize, end: usize, num_steps: usize) -> Option<(usize, usize)> {
+    (start <= end && end < num_steps).then(|| (start, end - start + 1))
+}
+
+/// Move loop point `start` (or `end`) to `step`, dropping the other point
+/// back to the pattern edge if the two would cross.
+pub fn set_loop_point(start: &mut usize, end: &mut usize, num_steps: usize, step: usize, is_end: bool) {
+    let step = step.min(num_steps.saturating_sub(1));
+    if is_end {
+        *end = step;
+        if *start > step { *start = 0; }
+    } else {
+        *start = step;
+        if *end < step { *end = num_steps - 1; }
+    }
+}
+
+/// Keep loop points valid after the step count changed from `old` to `new`.
+/// A loop that covered the whole pattern keeps covering it.
+pub fn resize_loop(start: &mut usize, end: &mut usize, old: usize, new: usize) {
+    if (*start == 0 && *end + 1 >= old) || *end >= new { *end = new - 1; }
+    if *start > *end { *start = 0; }
+}
+
+/// Where step `i` (with its `nudge`) of an `old`-step pattern lands when the
+/// pattern is stretched to `new` steps at the same place in the bar: the
+/// nearest step, wrapping, with the rest of the distance as its new nudge.
+pub fn remap_step(i: usize, nudge: i8, old: usize, new: usize) -> (usize, i8) {
+    let pos = (i as f64 + nudge as f64 / 100.0) * new as f64 / old as f64;
+    let j = pos.round();
+    let nudge = ((pos - j) * 100.0).round() as i8;
+    ((j as i64).rem_euclid(new as i64) as usize, nudge.clamp(-MAX_NUDGE, MAX_NUDGE))
+}
+
 /// An event fired when the sequencer crosses a step boundary.
 pub struct StepEvent {
     pub note_off: Option<u8>,
     pub note_on:  Option<u8>,
+    /// Velocity for `note_on` (`MAX_VELOCITY` without a ramp).
+    pub velocity: u8,
+    /// The step is accented.
+    pub accent:   bool,
+    /// The previous step slides into this one: both have notes and the
+    /// previous step's slide flag is set, so `note_off` bends into `note_on`
+    /// instead of releasing.
+    pub slide:    bool,
 }
 
 /// Sample-accurate melodic step sequencer.
 ///
 /// BPM is **not** stored here — it is passed to `tick()` every sample from
 /// `Synth::bpm` so the melodic and drum sequencers always share one master clock.
+#[derive(Clone, Debug)]
 pub struct Sequencer {
     pub steps:        Vec<Option<u8>>,
     pub num_steps:    usize,
     pub current_step: usize,
     pub playing:      bool,
+    /// Per-step timing offset in percent of a step (`-MAX_NUDGE..=MAX_NUDGE`).
+    pub nudge:        Vec<i8>,
+    /// Playback wraps within `loop_start..=loop_end`; the rest of the grid
+    /// stays editable.  Defaults to the whole pattern.
+    pub loop_start:   usize,
+    pub loop_end:     usize,
+    /// Order the play range is walked in.
+    pub direction:    Direction,
+    /// Temporary wrap range `(first, last)`, inclusive, for auditioning a few
+    /// steps on their own.  Overrides the loop points while set.
+    pub audition:     Option<(usize, usize)>,
+    /// Velocity automation; `None` plays every step at full velocity.
+    pub velocity_ramp: Option<VelocityRamp>,
+    /// Per-step accent and slide flags (303-style, played by Synth 2 only).
+    /// A slide on step N glides its note into step N+1's.
+    pub accent:       Vec<bool>,
+    pub slide:        Vec<bool>,
+    /// Per-step chance in percent that the note plays (`FULL_PROB` = always,
+    /// 0 = never), rolled on the shared `Rng` only below `FULL_PROB`.
+    pub prob:         Vec<u8>,
 
     sample_rate: f32,
+    /// No note from the last step is sounding (it lost its roll, or playback
+    /// has only just started), so there is nothing to release.
+    nothing_held: bool,
 }
 
 impl Sequencer {
@@ -24,66 +320,542 @@ impl Sequencer {
             num_steps:    16,
             current_step: 0,
             playing:      false,
+            nudge:        vec![0; 16],
+            loop_start:   0,
+            loop_end:     15,
+            direction:    Direction::Forward,
+            audition:     None,
+            velocity_ramp: None,
+            accent:       vec![false; 16],
+            slide:        vec![false; 16],
+            prob:         vec![FULL_PROB; 16],
             sample_rate,
+            nothing_held: true,
         }
     }
 
-    fn samples_per_step(&self, bpm: f32) -> u64 {
-        ((self.sample_rate * 60.0) / (bpm * 4.0)).round() as u64
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
     }
 
-    /// Called once per audio sample with the shared master clock.
-    /// Returns `Some(StepEvent)` on step boundaries.
-    pub fn tick(&mut self, bpm: f32, clock: u64) -> Option<StepEvent> {
+    /// Called once per audio sample with the shared master clock and the
+    /// melodic swing (`Synth::seq_swing`).  Returns `Some(StepEvent)` when a
+    /// step triggers (its boundary plus swing and nudge).  A note below
+    /// `FULL_PROB` plays only if its roll on `rng` succeeds; the event still
+    /// fires (without a `note_on`) so the previous note is released.  A
+    /// `muted` sequencer (out of the solo) keeps time and rolls, but starts
+    /// nothing, so it only ever releases notes it started.
+    pub fn tick(&mut self, bpm: f32, clock: u64, swing: f32, muted: bool, rng: &mut Rng) -> Option<StepEvent> {
         if !self.playing { return None; }
 
-        let sps = self.samples_per_step(bpm).max(1);
-        let step_idx = (clock / sps) as usize % self.num_steps;
-        let phase_in = clock % sps;
+        let sps = samples_per_step(self.sample_rate, bpm);
+        let (first, len) = self.play_range();
+        let dir = self.direction;
+        let order = move |k: u64| dir.position(k, len);
+        self.current_step = first + order(step_at(clock, sps));
 
-        self.current_step = step_idx;
+        let nudge = &self.nudge;
+        let k = due_step(clock, sps, order, |p| {
+            swing_samples(first + p, swing, sps) + nudge_samples(nudge.get(first + p).copied().unwrap_or(0), sps)
+        })?;
+        let step = first + order(k);
+        // The step played before this one, whose note is released.
+        let prev = first + order(k.checked_sub(1).unwrap_or(len as u64 - 1));
+        let note_off = if self.nothing_held { None } else { self.steps.get(prev).copied().flatten() };
+        let note_on  = self.steps.get(step).copied().flatten()
+            .filter(|_| rng.chance(self.prob.get(step).copied().unwrap_or(FULL_PROB)))
+            .filter(|_| !muted);
+        self.nothing_held = note_on.is_none();
+        Some(StepEvent {
+            note_off,
+            note_on,
+            velocity: self.velocity(k, len),
+            accent:   self.accent.get(step).copied().unwrap_or(false),
+            slide:    note_off.is_some() && note_on.is_some() && self.slide.get(prev).copied().unwrap_or(false),
+        })
+    }
 
-        if phase_in == 0 {
-            let prev = if step_idx == 0 { self.num_steps - 1 } else { step_idx - 1 };
-            Some(StepEvent {
-                note_off: self.steps[prev],
-                note_on:  self.steps[step_idx],
-            })
-        } else {
-            None
-        }
+    /// Velocity of grid step `k` in a play range of `len` steps.
+    pub fn velocity(&self, k: u64, len: usize) -> u8 {
+        self.velocity_ramp.map_or(MAX_VELOCITY, |r| r.velocity(k, len))
+    }
+
+    /// First step and length of the region playback wraps in: the audition
+    /// range if set, else the loop points.  Anything invalid (e.g. after the
+    /// step count shrank) falls back to the whole sequence.
+    pub fn play_range(&self) -> (usize, usize) {
+        self.audition.and_then(|(a, b)| loop_span(a, b, self.num_steps))
+            .or_else(|| loop_span(self.loop_start, self.loop_end, self.num_steps))
+            .unwrap_or((0, self.num_steps))
+    }
+
+    /// Note currently held by playback, if any (none if its roll failed).
+    fn held_note(&self) -> Option<u8> {
+        if self.playing && !self.nothing_held { self.steps.get(self.current_step).copied().flatten() } else { None }
+    }
+
+    /// Replace the audition range.  Returns the note currently held (for
+    /// note-off), since the step that would have released it may be skipped.
+    pub fn set_audition(&mut self, range: Option<(usize, usize)>) -> Option<u8> {
+        self.audition = range;
+        self.held_note()
+    }
+
+    /// Move the loop start (or end) to `step`.  Returns the held note, as
+    /// for `set_audition`.
+    pub fn set_loop_point(&mut self, step: usize, is_end: bool) -> Option<u8> {
+        set_loop_point(&mut self.loop_start, &mut self.loop_end, self.num_steps, step, is_end);
+        self.held_note()
+    }
+
+    /// Change the play direction.  Returns the held note, as for
+    /// `set_audition`: the next step event releases whatever step preceded it
+    /// in the *new* order.
+    pub fn set_direction(&mut self, direction: Direction) -> Option<u8> {
+        self.direction = direction;
+        self.held_note()
     }
 
     /// Toggle play/pause.  Returns the note currently held (for note-off).
     pub fn toggle_play(&mut self) -> Option<u8> {
-        self.playing = !self.playing;
-        if self.playing {
-            None
-        } else {
-            self.steps.get(self.current_step).copied().flatten()
-        }
+        let note = self.held_note();
+        self.playing      = !self.playing;
+        self.nothing_held = true;
+        note
     }
 
-    #[allow(dead_code)]
     pub fn stop(&mut self) -> Option<u8> {
-        let note = if self.playing { self.steps.get(self.current_step).copied().flatten() } else { None };
+        let note = self.held_note();
         self.playing      = false;
         self.current_step = 0;
+        self.nothing_held = true;
         note
     }
 
-    pub fn cycle_num_steps(&mut self) {
-        let next = match self.num_steps { 8 => 16, 16 => 24, 24 => 32, _ => 8 };
+    /// Step to the next pattern length (8→16→24→32→8).  With `lock` the
+    /// notes keep their place in the bar (`remap_steps`); without it they
+    /// keep their step indices and the end is cut or padded with rests.
+    pub fn cycle_num_steps(&mut self, lock: bool) {
+        let next = match self.num_steps { 8 => 16, 16 => 24, 24 => MAX_SEQ_STEPS, _ => 8 };
+        let old = self.num_steps;
+        resize_loop(&mut self.loop_start, &mut self.loop_end, old, next);
         self.num_steps = next;
+        if lock { self.remap_steps(old, next); }
         self.steps.resize(next, None);
+        self.nudge.resize(next, 0);
+        self.accent.resize(next, false);
+        self.slide.resize(next, false);
+        self.prob.resize(next, FULL_PROB);
         if self.current_step >= next { self.current_step = 0; }
     }
 
+    /// Move every note of an `old`-step pattern to its place in a `new`-step
+    /// one (`remap_step`), with its flags and probability.  Where notes collide
+    /// the earlier one stays.
+    fn remap_steps(&mut self, old: usize, new: usize) {
+        let mut steps  = vec![None; new];
+        let mut nudge  = vec![0; new];
+        let mut accent = vec![false; new];
+        let mut slide  = vec![false; new];
+        let mut prob   = vec![FULL_PROB; new];
+        for (i, &note) in self.steps.iter().enumerate().take(old) {
+            if note.is_none() { continue; }
+            let (j, n) = remap_step(i, self.nudge.get(i).copied().unwrap_or(0), old, new);
+            if steps[j].is_some() { continue; }
+            steps[j]  = note;
+            nudge[j]  = n;
+            accent[j] = self.accent.get(i).copied().unwrap_or(false);
+            slide[j]  = self.slide.get(i).copied().unwrap_or(false);
+            prob[j]   = self.prob.get(i).copied().unwrap_or(FULL_PROB);
+        }
+        (self.steps, self.nudge, self.accent, self.slide, self.prob) = (steps, nudge, accent, slide, prob);
+    }
+
+    /// Double the pattern length with a copy of the notes, nudges, flags and probabilities
+    /// in the new half.  Returns false, leaving the pattern alone, past `MAX_SEQ_STEPS`.
+    pub fn double(&mut self) -> bool {
+        let (old, next) = (self.num_steps, self.num_steps * 2);
+        if next > MAX_SEQ_STEPS { return false; }
+        self.steps.resize(old, None);
+        self.nudge.resize(old, 0);
+        self.accent.resize(old, false);
+        self.slide.resize(old, false);
+        self.prob.resize(old, FULL_PROB);
+        self.steps.extend_from_within(..);
+        self.nudge.extend_from_within(..);
+        self.accent.extend_from_within(..);
+        self.slide.extend_from_within(..);
+        self.prob.extend_from_within(..);
+        resize_loop(&mut self.loop_start, &mut self.loop_end, old, next);
+        self.num_steps = next;
+        true
+    }
+
+    /// Move each note, with probability `pct` percent, one step of `scale`
+    /// up or down (rests stay rests).  Returns how many notes moved.
+    pub fn mutate(&mut self, rng: &mut Rng, pct: u8, scale: &ScaleQuantizer) -> usize {
+        let mut moved = 0;
+        for note in self.steps.iter_mut().take(self.num_steps).flatten() {
+            if !rng.chance(pct) { continue; }
+            let to = if rng.below(2) == 0 { scale.step_up(*note) } else { scale.step_down(*note) };
+            if to != *note { *note = to; moved += 1; }
+        }
+        moved
+    }
+
     pub fn set_step(&mut self, step: usize, note: u8) {
         if step < self.steps.len() { self.steps[step] = Some(note); }
     }
 
+    /// Clear a step; the next note put there plays at `FULL_PROB`.
     pub fn clear_step(&mut self, step: usize) {
         if step < self.steps.len() { self.steps[step] = None; }
+        if let Some(p) = self.prob.get_mut(step) { *p = FULL_PROB; }
+    }
+
+    /// Raise or lower a step's note probability by `PROB_STEP` (0 ..=
+    /// `FULL_PROB`).  Returns the new value.
+    pub fn step_prob(&mut self, step: usize, up: bool) -> u8 {
+        if self.prob.len() < self.steps.len() { self.prob.resize(self.steps.len(), FULL_PROB); }
+        let Some(p) = self.prob.get_mut(step) else { return FULL_PROB };
+        *p = if up { (*p + PROB_STEP).min(FULL_PROB) } else { p.saturating_sub(PROB_STEP) };
+        *p
+    }
+
+    /// Shift a step's nudge by `delta` percent.  Returns the new value.
+    pub fn nudge_step(&mut self, step: usize, delta: i8) -> i8 {
+        if self.nudge.len() < self.steps.len() { self.nudge.resize(self.steps.len(), 0); }
+        let Some(n) = self.nudge.get_mut(step) else { return 0 };
+        *n = n.saturating_add(delta).clamp(-MAX_NUDGE, MAX_NUDGE);
+        *n
+    }
+
+    /// Flip a step's accent flag.  Returns the new value.
+    pub fn toggle_accent(&mut self, step: usize) -> bool {
+        toggle_flag(&mut self.accent, self.steps.len(), step)
+    }
+
+    /// Flip a step's slide flag.  Returns the new value.
+    pub fn toggle_slide(&mut self, step: usize) -> bool {
+        toggle_flag(&mut self.slide, self.steps.len(), step)
+    }
+
+    /// Rotate the whole pattern (notes, nudges, flags and probabilities) one step, wrapping.
+    /// Loop points stay where they are.
+    pub fn shift(&mut self, right: bool) {
+        rotate(&mut self.steps, self.num_steps, right);
+        rotate(&mut self.nudge, self.num_steps, right);
+        rotate(&mut self.accent, self.num_steps, right);
+        rotate(&mut self.slide, self.num_steps, right);
+        rotate(&mut self.prob, self.num_steps, right);
+    }
+}
+
+// ── Deterministic driving ─────────────────────────────────────────────────────
+//
+// The audio thread ticks a sequencer once per sample from `Synth`; these run
+// the same loop over a known stretch of master clock without a `Synth` or an
+// audio device, and check when steps fired against the ideal grid — for
+// pinning down timing (step 0 on sample 0, no drift over long runs).
+
+#[cfg(test)]
+impl Sequencer {
+    /// Tick once for every clock value in `clock` and collect the steps that
+    /// fired, with the clock value each fired on.
+    pub fn run(&mut self, bpm: f32, swing: f32, clock: std::ops::Range<u64>, rng: &mut Rng) -> Vec<(u64, StepEvent)> {
+        clock.filter_map(|c| self.tick(bpm, c, swing, false, rng).map(|e| (c, e))).collect()
+    }
+}
+
+/// Check that `times[n]` is the first sample of grid step `first + n` at
+/// `bpm` / `sample_rate` (no swing or nudge).  The error names the first
+/// step that is off.
+#[cfg(test)]
+pub fn check_step_times(times: &[u64], sample_rate: f32, bpm: f32, first: u64) -> Result<(), String> {
+    let sps = samples_per_step(sample_rate, bpm);
+    for (n, &t) in times.iter().enumerate() {
+        let want = step_start(first + n as u64, sps);
+        if t != want {
+            return Err(format!("step {} fired at sample {}, expected {} ({} BPM, {} Hz)",
+                               first + n as u64, t, want, bpm, sample_rate));
+        }
+    }
+    Ok(())
+}
+
+/// Largest distance, in samples, of `times[n]` from the exact (fractional)
+/// start of grid step `first + n`.  Below 1.0 however long the run when the
+/// step lengths don't accumulate rounding.
+#[cfg(test)]
+pub fn max_drift(times: &[u64], sample_rate: f32, bpm: f32, first: u64) -> f64 {
+    let sps = samples_per_step(sample_rate, bpm);
+    times.iter().enumerate()
+        .map(|(n, &t)| (t as f64 - (first + n as u64) as f64 * sps).abs())
+        .fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tempos whose step length is far from a whole number of samples.
+    const ODD_TEMPOS: [f32; 4] = [133.0, 127.3, 97.0, 174.9];
+
+    #[test]
+    fn step_start_does_not_drift_over_64_bars() {
+        for sr in [44_100.0, 48_000.0] {
+            for bpm in ODD_TEMPOS {
+                let sps = samples_per_step(sr, bpm);
+                let steps = 64 * 16;
+                let exact = steps as f64 * sps;
+                let got = step_start(steps, sps) as f64;
+                assert!((got - exact).abs() < 1.0, "{} BPM @ {} Hz: {} samples, want {}", bpm, sr, got, exact);
+                // Every step is floor or ceil of the ideal length.
+                for k in 0..steps {
+                    let len = step_start(k + 1, sps) - step_start(k, sps);
+                    assert!(len == sps.floor() as u64 || len == sps.ceil() as u64, "step {} is {} samples", k, len);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn step_at_inverts_step_start() {
+        for bpm in ODD_TEMPOS {
+            let sps = samples_per_step(44_100.0, bpm);
+            for k in 1..2000 {
+                let start = step_start(k, sps);
+                assert_eq!(step_at(start, sps), k);
+                assert_eq!(step_at(start - 1, sps), k - 1);
+            }
+        }
+    }
+
+    /// Clock values at which `seq` fires over `steps` steps.
+    fn fire_times(seq: &mut Sequencer, bpm: f32, steps: u64) -> Vec<u64> {
+        let end = step_start(steps, samples_per_step(seq.sample_rate, bpm));
+        seq.run(bpm, 0.0, 0..end, &mut Rng::new(1)).into_iter().map(|(c, _)| c).collect()
+    }
+
+    #[test]
+    fn steps_follow_a_48k_device_rate() {
+        assert_eq!(samples_per_step(48_000.0, 120.0), 6000.0);
+        assert_eq!(step_start(16, 6000.0), 96_000);
+
+        // Built at the 44.1 kHz default, then switched as AudioEngine does.
+        let mut seq = Sequencer::new(44_100.0);
+        seq.set_sample_rate(48_000.0);
+        seq.playing = true;
+        for bpm in [120.0].into_iter().chain(ODD_TEMPOS) {
+            let times = fire_times(&mut seq, bpm, 64);
+            assert_eq!(times.len(), 64, "{} BPM", bpm);
+            check_step_times(&times, 48_000.0, bpm, 0).unwrap();
+        }
+    }
+
+    #[test]
+    fn step_zero_fires_on_sample_zero() {
+        for sr in [44_100.0, 48_000.0] {
+            for bpm in [120.0].into_iter().chain(ODD_TEMPOS) {
+                let mut seq = Sequencer::new(sr);
+                seq.steps[0] = Some(60);
+                seq.playing = true;
+                let evs = seq.run(bpm, 0.0, 0..step_start(2, samples_per_step(sr, bpm)), &mut Rng::new(1));
+                assert_eq!(evs.len(), 2, "{} BPM @ {} Hz", bpm, sr);
+                assert_eq!((evs[0].0, evs[0].1.note_on), (0, Some(60)), "{} BPM @ {} Hz", bpm, sr);
+            }
+        }
+    }
+
+    #[test]
+    fn five_minutes_stay_within_a_sample_of_the_grid() {
+        for (sr, bpm) in [(44_100.0, 133.0), (48_000.0, 127.3)] {
+            let mut seq = Sequencer::new(sr);
+            seq.playing = true;
+            let end = (300.0 * sr) as u64;
+            let times: Vec<u64> = seq.run(bpm, 0.0, 0..end, &mut Rng::new(1)).into_iter().map(|(c, _)| c).collect();
+            assert_eq!(times.len() as u64, step_at(end - 1, samples_per_step(sr, bpm)) + 1);
+            check_step_times(&times, sr, bpm, 0).unwrap();
+            let drift = max_drift(&times, sr, bpm, 0);
+            assert!(drift < 1.0, "{} BPM @ {} Hz drifted {} samples", bpm, sr, drift);
+        }
+    }
+
+    #[test]
+    fn swing_delays_odd_steps_only() {
+        let mut seq = Sequencer::new(48_000.0);
+        seq.playing = true;
+        let sps = samples_per_step(48_000.0, 133.0);
+        let end = step_start(32, sps);
+        let times: Vec<u64> = seq.run(133.0, 0.25, 0..end, &mut Rng::new(1)).into_iter().map(|(c, _)| c).collect();
+        assert_eq!(times.len(), 32);
+        for (k, &t) in times.iter().enumerate() {
+            let want = step_start(k as u64, sps) as i64 + swing_samples(k, 0.25, sps);
+            assert_eq!(t as i64, want, "step {}", k);
+        }
+        assert_eq!(swing_samples(3, MAX_SWING, sps), (sps / 2.0).round() as i64);
+        assert_eq!(swing_samples(4, MAX_SWING, sps), 0);
+    }
+
+    /// A synth 2 bass line: a slide from step 0 into 1, a slide flag on 1
+    /// that has only a rest to go to, and an accent on 3.
+    fn bass_line(sr: f32) -> Sequencer {
+        let mut seq = Sequencer::new(sr);
+        seq.steps[..4].copy_from_slice(&[Some(48), Some(51), None, Some(55)]);
+        (seq.slide[0], seq.slide[1], seq.accent[3]) = (true, true, true);
+        seq.playing = true;
+        seq
+    }
+
+    #[test]
+    fn slide_needs_a_note_on_both_sides() {
+        let mut seq = bass_line(48_000.0);
+        let end = step_start(4, samples_per_step(48_000.0, 120.0));
+        let evs: Vec<StepEvent> = seq.run(120.0, 0.0, 0..end, &mut Rng::new(1)).into_iter().map(|(_, e)| e).collect();
+        let flags: Vec<(bool, bool)> = evs.iter().map(|e| (e.slide, e.accent)).collect();
+        assert_eq!(flags, [(false, false), (true, false), (false, false), (false, true)]);
+        assert_eq!((evs[1].note_off, evs[1].note_on), (Some(48), Some(51)));
+    }
+
+    #[test]
+    fn remap_step_keeps_the_place_in_the_bar() {
+        assert_eq!(remap_step(4, 0, 16, 24), (6, 0));
+        assert_eq!(remap_step(14, 20, 16, 24), (21, 30));
+        assert_eq!(remap_step(3, 0, 16, 8), (2, -50));
+        assert_eq!(remap_step(6, 0, 24, 16), (4, 0));
+        // A late last step rounds past the end and wraps to the downbeat.
+        assert_eq!(remap_step(15, 40, 16, 8), (0, -30));
+        // Never nudged past a step either way.
+        for i in 0..32 {
+            for nudge in [-MAX_NUDGE, 0, MAX_NUDGE] {
+                let (j, n) = remap_step(i, nudge, 32, 24);
+                assert!(j < 24 && n.abs() <= MAX_NUDGE);
+            }
+        }
+    }
+
+    fn order(dir: Direction, len: usize, n: u64) -> Vec<usize> {
+        (0..n).map(|k| dir.position(k, len)).collect()
+    }
+
+    #[test]
+    fn ping_pong_plays_the_ends_once_per_turn() {
+        assert_eq!(order(Direction::PingPong, 4, 12), [0, 1, 2, 3, 2, 1, 0, 1, 2, 3, 2, 1]);
+        assert_eq!(order(Direction::PingPong, 2, 5), [0, 1, 0, 1, 0]);
+        assert_eq!(order(Direction::PingPong, 1, 3), [0, 0, 0]);
+        assert_eq!(order(Direction::PingPong, 0, 3), [0, 0, 0]);
+    }
+
+    #[test]
+    fn every_direction_stays_in_range() {
+        assert_eq!(order(Direction::Forward, 3, 4), [0, 1, 2, 0]);
+        assert_eq!(order(Direction::Backward, 3, 4), [2, 1, 0, 2]);
+        for dir in Direction::ALL {
+            assert_eq!(order(dir, 1, 8), [0; 8], "{} with one step", dir.name());
+            for len in 2..=32 {
+                assert!(order(dir, len, 500).iter().all(|&p| p < len), "{} left 0..{}", dir.name(), len);
+            }
+        }
+        assert_eq!(order(Direction::Random, 16, 64), order(Direction::Random, 16, 64), "random is not repeatable");
+    }
+
+    /// Events of `seq` over `steps` steps from grid step `from`, at 300 BPM
+    /// to keep long runs short.
+    fn events(seq: &mut Sequencer, rng: &mut Rng, from: u64, steps: u64) -> Vec<StepEvent> {
+        let sps = samples_per_step(seq.sample_rate, 300.0);
+        let start = step_start(from, sps);
+        seq.run(300.0, 0.0, start..start + step_start(steps, sps), rng).into_iter().map(|(_, e)| e).collect()
+    }
+
+    #[test]
+    fn fire_rate_follows_the_probability() {
+        for pct in [25, 50, 75] {
+            let mut seq = Sequencer::new(48_000.0);
+            seq.steps = vec![Some(60); 16];
+            seq.prob = vec![pct; 16];
+            seq.playing = true;
+            let evs = events(&mut seq, &mut Rng::new(7), 0, 16 * 200);
+            let rate = evs.iter().filter(|e| e.note_on.is_some()).count() as f32 / evs.len() as f32;
+            assert!((rate * 100.0 - pct as f32).abs() < 4.0, "{}% fired {:.1}% of 3200 steps", pct, rate * 100.0);
+        }
+    }
+
+    #[test]
+    fn full_and_zero_probability_are_deterministic() {
+        let mut seq = Sequencer::new(48_000.0);
+        seq.steps = vec![Some(60); 16];
+        seq.playing = true;
+        let mut rng = Rng::new(7);
+        assert!(events(&mut seq, &mut rng, 0, 64).iter().all(|e| e.note_on == Some(60)));
+        assert_eq!(rng.next_u64(), Rng::new(7).next_u64(), "an all-100 pattern consumed the rng");
+
+        seq.prob = vec![0; 16];
+        assert!(events(&mut seq, &mut rng, 64, 64).iter().all(|e| e.note_on.is_none()));
+    }
+
+    #[test]
+    fn a_note_that_never_started_is_not_released() {
+        let mut seq = Sequencer::new(48_000.0);
+        seq.steps[..3].copy_from_slice(&[Some(60), Some(62), Some(64)]);
+        seq.steps[15] = Some(72);
+        seq.prob[1] = 0;
+        seq.playing = true;
+        let evs = events(&mut seq, &mut Rng::new(7), 0, 4);
+        let offs: Vec<Option<u8>> = evs.iter().map(|e| e.note_off).collect();
+        // Step 15 never played before step 0, and step 1 lost its roll.
+        assert_eq!(offs, [None, Some(60), None, Some(64)]);
+        assert_eq!(evs.iter().map(|e| e.note_on).collect::<Vec<_>>(), [Some(60), None, Some(64), None]);
+
+        // Stop and pause release what is held, so the first event after
+        // either releases nothing again.
+        assert_eq!(seq.stop(), None, "step 3 holds nothing");
+        seq.playing = true;
+        events(&mut seq, &mut Rng::new(7), 0, 1);
+        assert_eq!(seq.toggle_play(), Some(60));
+        seq.toggle_play();
+        assert_eq!(events(&mut seq, &mut Rng::new(7), 1, 1)[0].note_off, None);
+    }
+
+    /// A 16-step line with notes on 0/4/8/12/15, an accent on 12 and a
+    /// half-probability note on 8.
+    fn melody() -> Sequencer {
+        let mut seq = Sequencer::new(48_000.0);
+        for (s, n) in [(0, 48), (4, 50), (8, 52), (12, 53), (15, 55)] { seq.steps[s] = Some(n); }
+        seq.accent[12] = true;
+        seq.prob[8] = 50;
+        seq
+    }
+
+    /// Steps that hold a note, with the note and its nudge.
+    fn notes(seq: &Sequencer) -> Vec<(usize, u8, i8)> {
+        (0..seq.num_steps).filter_map(|s| seq.steps[s].map(|n| (s, n, seq.nudge[s]))).collect()
+    }
+
+    #[test]
+    fn length_change_without_lock_keeps_step_indices() {
+        let mut seq = melody();
+        seq.cycle_num_steps(false);
+        assert_eq!(seq.num_steps, 24);
+        assert_eq!(notes(&seq), [(0, 48, 0), (4, 50, 0), (8, 52, 0), (12, 53, 0), (15, 55, 0)]);
+        assert!(seq.accent[12]);
+        assert_eq!(seq.prob[8], 50);
+        assert_eq!((seq.steps.len(), seq.prob[20]), (24, FULL_PROB));
+    }
+
+    #[test]
+    fn length_lock_keeps_notes_at_their_place_in_the_bar() {
+        let mut seq = melody();
+        seq.cycle_num_steps(true);
+        assert_eq!(notes(&seq), [(0, 48, 0), (6, 50, 0), (12, 52, 0), (18, 53, 0), (23, 55, -50)]);
+        assert_eq!((seq.accent[12], seq.accent[18]), (false, true), "the accent should move with its note");
+        assert_eq!((seq.prob[8], seq.prob[12]), (FULL_PROB, 50), "the probability should move with its note");
+
+        // 32 → 8: the notes on 0 and 1 both land on step 0; the earlier one stays.
+        let mut seq = Sequencer::new(48_000.0);
+        for _ in 0..2 { seq.cycle_num_steps(false); }
+        (seq.steps[0], seq.steps[1]) = (Some(48), Some(60));
+        seq.cycle_num_steps(true);
+        assert_eq!(seq.num_steps, 8);
+        assert_eq!(notes(&seq), [(0, 48, 0)]);
     }
 }
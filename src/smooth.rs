@@ -0,0 +1,145 @@
+// ── Parameter smoothing ───────────────────────────────────────────────────────
+//
+// `Smoothed` glides a parameter to a new value in a straight line over a
+// fixed time, so a jump from a key, the panel, automation or a controller
+// doesn't click.  The owner keeps the plain `f32` the UI edits, saves and
+// reads back, hands it to `set` and takes `next` once per sample.
+//
+// The first value set is taken as is (nothing to glide from), so a project
+// loaded into a fresh `Synth` renders exactly as if it had never moved.
+
+#[derive(Clone, Copy, Debug)]
+pub struct Smoothed {
+    target:  f32,
+    current: f32,
+    /// Per-sample increment of the ramp under way (signed).
+    step:    f32,
+    /// Ramp length in samples (0 = no smoothing).
+    len:     f32,
+    ramp_ms: f32,
+    primed:  bool,
+}
+
+impl Smoothed {
+    /// A smoother taking `ramp_ms` to reach a new value.
+    pub fn new(ramp_ms: f32, sample_rate: f32) -> Self {
+        let mut s = Self { target: 0.0, current: 0.0, step: 0.0, len: 0.0, ramp_ms, primed: false };
+        s.set_sample_rate(sample_rate);
+        s
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.len = (self.ramp_ms * 0.001 * sample_rate).round().max(0.0);
+    }
+
+    /// Glide to `target` (from wherever the current ramp has got to).
+    #[inline]
+    pub fn set(&mut self, target: f32) {
+        if !self.primed || self.len < 1.0 { return self.snap(target); }
+        if target != self.target {
+            self.target = target;
+            self.step   = (target - self.current) / self.len;
+        }
+    }
+
+    /// Jump to `value` with no ramp.
+    pub fn snap(&mut self, value: f32) {
+        self.target  = value;
+        self.current = value;
+        self.step    = 0.0;
+        self.primed  = true;
+    }
+
+    /// Advance one sample and return the smoothed value; exactly the target
+    /// once the ramp has finished.
+    #[inline]
+    pub fn next(&mut self) -> f32 {
+        if self.current != self.target {
+            if (self.target - self.current).abs() <= self.step.abs() {
+                self.current = self.target;
+            } else {
+                self.current += self.step;
+            }
+        }
+        self.current
+    }
+
+    /// The smoothed value as of the last `next`.
+    pub fn value(&self) -> f32 { self.current }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SR: f32 = 48_000.0;
+
+    /// `n` values from `s`, one per sample.
+    fn ramp(s: &mut Smoothed, n: usize) -> Vec<f32> {
+        (0..n).map(|_| s.next()).collect()
+    }
+
+    #[test]
+    fn first_set_is_taken_as_is() {
+        let mut s = Smoothed::new(10.0, SR);
+        s.set(0.7);
+        assert_eq!(ramp(&mut s, 3), [0.7; 3]);
+    }
+
+    #[test]
+    fn ramp_lands_on_the_target_at_the_ramp_time() {
+        let mut s = Smoothed::new(10.0, SR);
+        s.set(0.0);
+        s.set(1.0);
+        let out = ramp(&mut s, 600);
+        let len = out.iter().position(|&v| v == 1.0).unwrap() + 1;
+        assert!(len.abs_diff(480) <= 1, "took {} samples, not 480", len);
+        assert!(out[len..].iter().all(|&v| v == 1.0), "overshot or moved off the target");
+        assert_eq!(s.value(), 1.0);
+    }
+
+    #[test]
+    fn no_sample_moves_more_than_one_ramp_step() {
+        let mut s = Smoothed::new(10.0, SR);
+        s.set(0.0);
+        s.set(1.0);
+        let mut prev = s.value();
+        let mut max = 0.0f32;
+        for i in 0..1500 {
+            // Turn round halfway through the first ramp, then jump far.
+            if i == 240 { s.set(-0.5); }
+            if i == 900 { s.set(4.0); }
+            let v = s.next();
+            max = max.max((v - prev).abs());
+            prev = v;
+        }
+        assert_eq!(s.value(), 4.0);
+        // The largest move is 4.5 spread over 480 samples.
+        assert!(max <= 4.5 / 480.0 * 1.001, "a sample moved {}", max);
+    }
+
+    #[test]
+    fn zero_ramp_and_snap_jump_straight_there() {
+        let mut s = Smoothed::new(0.0, SR);
+        s.set(0.2);
+        s.set(0.9);
+        assert_eq!(s.next(), 0.9);
+
+        let mut s = Smoothed::new(10.0, SR);
+        s.set(0.0);
+        s.set(1.0);
+        s.next();
+        s.snap(0.3);
+        assert_eq!(ramp(&mut s, 2), [0.3; 2]);
+    }
+
+    #[test]
+    fn ramp_time_follows_the_sample_rate() {
+        let mut s = Smoothed::new(10.0, SR);
+        s.set_sample_rate(96_000.0);
+        s.set(0.0);
+        s.set(1.0);
+        let len = ramp(&mut s, 1200).iter().position(|&v| v == 1.0).unwrap() + 1;
+        assert!(len.abs_diff(960) <= 1, "took {} samples at 96 kHz", len);
+    }
+}
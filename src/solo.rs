@@ -0,0 +1,158 @@
+use crate::drums::DrumKind;
+
+// ── Channel solo ──────────────────────────────────────────────────────────────
+//
+// One solo across the app: drum tracks, the synth 1 bus, single synth 1
+// lanes and synth 2.  With nothing soloed everything plays; otherwise only
+// the soloed elements are heard.  It is applied where each element is mixed
+// (`Synth::generate_frame`, `DrumMachine::generate_sample`) on top of the
+// track mutes, which it leaves alone, so clearing it restores the mix as it
+// was.  Not the sequencers' step solo (`Sequencer::audition`).  Not saved.
+
+/// Something that can be soloed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SoloTarget {
+    Drum(usize),
+    /// The whole synth 1 bus: keyboard, every lane, the drone.
+    Synth1,
+    /// One synth 1 lane; the rest of the bus stays audible but the other
+    /// lanes don't start notes.
+    Lane(usize),
+    Synth2,
+}
+
+impl SoloTarget {
+    pub fn label(self) -> String {
+        match self {
+            Self::Drum(i) => DrumKind::ALL.get(i).map_or("?", |k| k.name().trim()).to_string(),
+            Self::Synth1  => "Synth 1".to_string(),
+            Self::Lane(i) => format!("Lane {}", i + 1),
+            Self::Synth2  => "Synth 2".to_string(),
+        }
+    }
+}
+
+/// How a soloable element stands, for the panels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SoloState {
+    /// Heard as normal (no solo, or a solo that includes it).
+    Open,
+    Soloed,
+    /// Silenced by someone else's solo.
+    Out,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Solo {
+    /// Soloed elements, in the order they were soloed.
+    pub targets: Vec<SoloTarget>,
+}
+
+impl Solo {
+    pub fn is_active(&self) -> bool { !self.targets.is_empty() }
+
+    /// Solo or un-solo `target`.  Returns true if it is now soloed.
+    pub fn toggle(&mut self, target: SoloTarget) -> bool {
+        if let Some(i) = self.targets.iter().position(|&t| t == target) {
+            self.targets.remove(i);
+            false
+        } else {
+            self.targets.push(target);
+            true
+        }
+    }
+
+    pub fn clear(&mut self) { self.targets.clear(); }
+
+    /// True when `target` is silenced by the solo.  A lane solo keeps the
+    /// synth 1 bus open; a synth 1 solo opens every lane.
+    pub fn mutes(&self, target: SoloTarget) -> bool {
+        if self.targets.is_empty() { return false; }
+        let has = |t: SoloTarget| self.targets.contains(&t);
+        match target {
+            SoloTarget::Synth1  => !has(SoloTarget::Synth1) && !self.targets.iter().any(|t| matches!(t, SoloTarget::Lane(_))),
+            SoloTarget::Lane(_) => !has(SoloTarget::Synth1) && !has(target),
+            _                   => !has(target),
+        }
+    }
+
+    pub fn state(&self, target: SoloTarget) -> SoloState {
+        if self.targets.contains(&target) { SoloState::Soloed }
+        else if self.mutes(target) { SoloState::Out }
+        else { SoloState::Open }
+    }
+
+    /// Follow the removal of synth 1 lane `i`: its solo goes, later lanes
+    /// move down one.
+    pub fn remove_lane(&mut self, i: usize) {
+        self.targets.retain(|&t| t != SoloTarget::Lane(i));
+        for t in &mut self.targets {
+            if let SoloTarget::Lane(j) = t { if *j > i { *j -= 1; } }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `solo` with `targets` soloed in order.
+    fn solo(targets: &[SoloTarget]) -> Solo {
+        let mut s = Solo::default();
+        for &t in targets { assert!(s.toggle(t)); }
+        s
+    }
+
+    #[test]
+    fn nothing_soloed_mutes_nothing() {
+        let s = Solo::default();
+        assert!(!s.is_active());
+        for t in [SoloTarget::Drum(0), SoloTarget::Synth1, SoloTarget::Lane(2), SoloTarget::Synth2] {
+            assert!(!s.mutes(t));
+            assert_eq!(s.state(t), SoloState::Open);
+        }
+    }
+
+    #[test]
+    fn a_solo_silences_everything_else() {
+        let s = solo(&[SoloTarget::Drum(1), SoloTarget::Synth2]);
+        assert_eq!(s.state(SoloTarget::Drum(1)), SoloState::Soloed);
+        assert_eq!(s.state(SoloTarget::Synth2), SoloState::Soloed);
+        assert_eq!(s.state(SoloTarget::Drum(0)), SoloState::Out);
+        assert_eq!(s.state(SoloTarget::Synth1), SoloState::Out);
+        assert_eq!(s.state(SoloTarget::Lane(0)), SoloState::Out);
+    }
+
+    #[test]
+    fn lane_and_synth1_solos_open_each_other() {
+        // A lane solo keeps the bus open but starts no other lane.
+        let s = solo(&[SoloTarget::Lane(1)]);
+        assert!(!s.mutes(SoloTarget::Synth1));
+        assert!(!s.mutes(SoloTarget::Lane(1)));
+        assert!(s.mutes(SoloTarget::Lane(0)));
+        assert!(s.mutes(SoloTarget::Drum(0)));
+
+        // A bus solo opens every lane.
+        let s = solo(&[SoloTarget::Synth1]);
+        assert!((0..4).all(|i| !s.mutes(SoloTarget::Lane(i))));
+        assert!(s.mutes(SoloTarget::Synth2));
+    }
+
+    #[test]
+    fn toggle_and_clear_restore_the_mix() {
+        let mut s = solo(&[SoloTarget::Drum(0)]);
+        assert!(!s.toggle(SoloTarget::Drum(0)), "second toggle should un-solo");
+        assert!(!s.is_active());
+        s.toggle(SoloTarget::Synth2);
+        s.toggle(SoloTarget::Drum(3));
+        s.clear();
+        assert!(!s.mutes(SoloTarget::Drum(0)) && !s.mutes(SoloTarget::Synth1));
+    }
+
+    #[test]
+    fn removing_a_lane_renumbers_the_lane_solos() {
+        let mut s = solo(&[SoloTarget::Lane(0), SoloTarget::Lane(1), SoloTarget::Lane(3), SoloTarget::Drum(2)]);
+        s.remove_lane(1);
+        assert_eq!(s.targets, [SoloTarget::Lane(0), SoloTarget::Lane(2), SoloTarget::Drum(2)]);
+    }
+}
@@ -1,9 +1,19 @@
 use std::collections::HashMap;
 use std::f32::consts::PI;
 
+use crate::click::StepClick;
 use crate::drums::DrumMachine;
-use crate::effects::{AudioEffect, BiquadFilter, Delay, Distortion, EffectChain, Reverb};
-use crate::sequencer::Sequencer;
+use crate::effects::{
+    pan_gains, AudioEffect, BiquadFilter, DcBlocker, Delay, Distortion, EffectChain, Limiter, MasterClipper, MixMeter,
+    Oversampler, Reverb, StereoEffect, StereoWidener,
+};
+use crate::freeze::Freeze;
+use crate::rng::{Rng, DEFAULT_SEED};
+use crate::sequencer::{rescale_clock, samples_per_step, step_at, Sequencer, StepEvent, FULL_PROB, MAX_VELOCITY};
+use crate::smooth::Smoothed;
+use crate::solo::{Solo, SoloTarget};
+use crate::tempo::{TempoRamp, COMMON_TIME_SIGS};
+use crate::tuner::Tuner;
 
 // ── Waveform ──────────────────────────────────────────────────────────────────
 
@@ -57,7 +67,15 @@ impl ChordType {
     }
 }
 
+/// Default per-waveform gain trims, indexed by `WaveType as usize`.  Rough
+/// loudness match to Sine: the bright, full-scale Square and Sawtooth are
+/// pulled down, the duller Triangle nudged up.
+pub const DEFAULT_WAVE_TRIMS: [f32; 4] = [1.0, 0.55, 0.7, 1.1];
+pub const MAX_WAVE_TRIM: f32 = 2.0;
+
 impl WaveType {
+    pub const ALL: [WaveType; 4] = [Self::Sine, Self::Square, Self::Sawtooth, Self::Triangle];
+
     pub fn next(self) -> Self {
         match self {
             Self::Sine => Self::Square, Self::Square => Self::Sawtooth,
@@ -72,13 +90,164 @@ impl WaveType {
     }
 }
 
+// ── Retrigger mode ────────────────────────────────────────────────────────────
+
+/// What `note_on` does when the note already has a sounding voice (held or
+/// still releasing).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Retrigger {
+    /// Start a fresh voice: phase and envelope restart from 0.
+    Replace,
+    /// Re-enter Attack on the existing voice from its current level, keeping
+    /// the oscillator phase continuous.
+    Reuse,
+}
+
+impl Retrigger {
+    pub fn name(self) -> &'static str {
+        match self { Self::Replace => "Replace", Self::Reuse => "Reuse" }
+    }
+    /// Stable integer index (0=Replace 1=Reuse), as stored in save files.
+    pub fn index(self) -> u8 {
+        match self { Self::Replace => 0, Self::Reuse => 1 }
+    }
+    pub fn from_index(i: u8) -> Self {
+        if i == 1 { Self::Reuse } else { Self::Replace }
+    }
+}
+
 // ── ADSR envelope ─────────────────────────────────────────────────────────────
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum EnvelopeStage { Attack, Decay, Sustain, Release, Off }
 
+/// Shape of the attack and release segments.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EnvCurve {
+    /// Straight ramp (the original envelope).
+    Linear,
+    /// RC-style curve: fast at first, easing into the target, like an
+    /// analog envelope.  Still lands exactly at the ADSR time.
+    Exponential,
+}
+
+impl EnvCurve {
+    pub fn name(self) -> &'static str {
+        match self { Self::Linear => "Linear", Self::Exponential => "Exp" }
+    }
+    /// Stable integer index (0=Linear 1=Exponential), as stored in save files.
+    pub fn index(self) -> u8 {
+        match self { Self::Linear => 0, Self::Exponential => 1 }
+    }
+    pub fn from_index(i: u8) -> Self {
+        if i == 1 { Self::Exponential } else { Self::Linear }
+    }
+}
+
+/// How melodic voices make their tone.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SynthMode {
+    /// The selected waveform straight into the filters (the original synth).
+    Subtractive,
+    /// 2-operator FM: a sine modulator at `fm_ratio` × the note frequency
+    /// bends the carrier (the selected waveform) by `fm_index`.
+    Fm,
+}
+
+impl SynthMode {
+    pub fn name(self) -> &'static str {
+        match self { Self::Subtractive => "Subtractive", Self::Fm => "FM" }
+    }
+    /// Stable integer index (0=Subtractive 1=FM), as stored in save files.
+    pub fn index(self) -> u8 {
+        match self { Self::Subtractive => 0, Self::Fm => 1 }
+    }
+    pub fn from_index(i: u8) -> Self {
+        if i == 1 { Self::Fm } else { Self::Subtractive }
+    }
+}
+
+/// FM modulator ratio and modulation index: defaults and accepted ranges.
+pub const DEFAULT_FM_RATIO: f32 = 2.0;
+pub const DEFAULT_FM_INDEX: f32 = 2.0;
+pub const FM_RATIO_RANGE: std::ops::RangeInclusive<f32> = 0.25..=16.0;
+pub const MAX_FM_INDEX: f32 = 10.0;
+
+/// Synth-wide settings every melodic voice renders with, besides its ADSR
+/// and wave (`Synth::voice_globals`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VoiceGlobals {
+    pub attack_curve:  EnvCurve,
+    pub release_curve: EnvCurve,
+    /// FM modulator `(ratio, index)`; `None` = the plain carrier.
+    pub fm: Option<(f32, f32)>,
+}
+
+/// Overshoot of the exponential segments' targets, as a fraction of the
+/// segment: the attack aims at 1.0 + ratio, the release at -ratio × its
+/// start level, and each stops where it crosses 1.0 / 0.0.  Smaller =
+/// more curved; the release reaches -60 dB of its start at ~90% of its time.
+const EXP_ATTACK_RATIO:  f32 = 0.3;
+const EXP_RELEASE_RATIO: f32 = 0.001;
+
+/// Per-sample step of an exponential segment of `time` seconds at `sr`:
+/// `level` moves this fraction of the way to `target`.
+fn exp_step(ratio: f32, time: f32, sr: f32) -> f32 {
+    1.0 - (-((1.0 + ratio) / ratio).ln() / (time * sr)).exp()
+}
+
 // ── Melodic voice ─────────────────────────────────────────────────────────────
 
+/// Default voice limit per melodic bus, and the accepted range.
+pub const DEFAULT_MAX_VOICES: usize = 32;
+pub const MAX_VOICES_RANGE: std::ops::RangeInclusive<usize> = 1..=64;
+
+/// Most melodic lanes synth 1 can play at once.
+pub const MAX_LANES: usize = 8;
+
+/// Default minimum attack/decay/release time, in milliseconds (anti-click).
+pub const DEFAULT_ANTI_CLICK_MS: f32 = 3.0;
+pub const MAX_ANTI_CLICK_MS: f32 = 10.0;
+
+/// Fade-in on the master output when the audio stream starts, in
+/// milliseconds, so the first buffer does not click.
+pub const STARTUP_FADE_MS: f32 = 5.0;
+
+/// Longest attack/decay/release a lane patch accepts, in seconds.
+pub const MAX_ENV_S: f32 = 10.0;
+
+/// Level of an accented Synth 2 step relative to a plain one.
+const ACCENT_GAIN: f32 = 1.4;
+/// How far an accent opens filter 2, in octaves, and how fast that decays.
+const ACCENT_OCTAVES: f32 = 1.5;
+const ACCENT_DECAY_MS: f32 = 180.0;
+/// Time constant of a 303-style slide between two Synth 2 notes (~95% of
+/// the way there after three, like the 303's ~60 ms glide).
+const SLIDE_MS: f32 = 20.0;
+
+/// Glide time of a bus volume to a new value.
+const VOLUME_RAMP_MS: f32 = 20.0;
+/// Fade of a melodic bus in or out of a solo (as the drum mute fade).
+const SOLO_FADE_MS: f32 = 5.0;
+
+/// Waveform and envelope a voice plays with.  Voices without one use their
+/// bus's settings (`wave_type`, `attack`…), so they follow live changes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Patch {
+    pub wave:    WaveType,
+    pub attack:  f32,
+    pub decay:   f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Patch {
+    /// Short form for headers and the status line, e.g. `Sawtooth A0.01 D0.10 S0.70 R0.30`.
+    pub fn label(&self) -> String {
+        format!("{} A{:.2} D{:.2} S{:.2} R{:.2}", self.wave.name(), self.attack, self.decay, self.sustain, self.release)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Voice {
     pub frequency:     f32,
@@ -86,12 +255,49 @@ pub struct Voice {
     pub stage:         EnvelopeStage,
     pub level:         f32,
     pub release_level: f32,
+    /// Shortest envelope ramp in seconds, so a ~0 ADSR time still fades
+    /// instead of stepping (which clicks on square/saw).
+    min_fade: f32,
+    /// Set by `force_off`: release over `min_fade` regardless of the ADSR.
+    forced: bool,
+    /// Output gain from the note velocity (1.0 = full).
+    pub velocity: f32,
+    /// Sound of the lane that started the voice (`None` = the bus sound).
+    pub patch: Option<Patch>,
+    /// Slide target (Hz) and per-sample glide coefficient (0.0 = none).
+    glide_to: f32,
+    glide:    f32,
+    /// FM modulator phase, 0.0..1.0.
+    mod_phase: f32,
+    /// Position among the voices sounding when it started, for the stereo
+    /// spread (`spread_pan`).
+    pub slot: u8,
 }
 
 impl Voice {
-    pub fn new(note: u8) -> Self {
+    pub fn new(note: u8, min_fade: f32) -> Self {
         Self { frequency: note_to_freq(note), phase: 0.0,
-               stage: EnvelopeStage::Attack, level: 0.0, release_level: 0.0 }
+               stage: EnvelopeStage::Attack, level: 0.0, release_level: 0.0,
+               min_fade, forced: false, velocity: 1.0, patch: None, glide_to: 0.0, glide: 0.0, mod_phase: 0.0,
+               slot: 0 }
+    }
+
+    /// Bend to `freq` without restarting the envelope; `coef` is the
+    /// per-sample glide coefficient.
+    pub fn glide_to(&mut self, freq: f32, coef: f32) {
+        self.glide_to = freq;
+        self.glide = coef;
+    }
+
+    /// Stop as fast as possible without clicking: a `min_fade` release.
+    pub fn force_off(&mut self) {
+        self.release();
+        self.forced = true;
+    }
+
+    /// Restart the envelope from the current level without touching phase.
+    pub fn retrigger(&mut self) {
+        self.stage = EnvelopeStage::Attack;
     }
 
     pub fn release(&mut self) {
@@ -103,12 +309,21 @@ impl Voice {
 
     pub fn is_finished(&self) -> bool { self.stage == EnvelopeStage::Off }
 
+    /// One output sample.  `g` shapes the attack and release and sets the
+    /// FM modulator.
+    #[allow(clippy::too_many_arguments)]
     pub fn next_sample(&mut self, sr: f32, wave: WaveType,
-                       attack: f32, decay: f32, sustain: f32, release: f32) -> f32 {
+                       attack: f32, decay: f32, sustain: f32, release: f32, g: VoiceGlobals) -> f32 {
         let dt = 1.0 / sr;
+        let attack  = attack.max(self.min_fade);
+        let decay   = decay.max(self.min_fade);
+        let release = if self.forced { self.min_fade } else { release.max(self.min_fade) };
         match self.stage {
             EnvelopeStage::Attack => {
-                self.level += dt / attack;
+                self.level += match g.attack_curve {
+                    EnvCurve::Linear      => dt / attack,
+                    EnvCurve::Exponential => (1.0 + EXP_ATTACK_RATIO - self.level) * exp_step(EXP_ATTACK_RATIO, attack, sr),
+                };
                 if self.level >= 1.0 { self.level = 1.0; self.stage = EnvelopeStage::Decay; }
             }
             EnvelopeStage::Decay => {
@@ -117,24 +332,84 @@ impl Voice {
             }
             EnvelopeStage::Sustain => { self.level = sustain; }
             EnvelopeStage::Release => {
-                self.level -= dt * self.release_level / release;
+                self.level -= match g.release_curve {
+                    EnvCurve::Linear      => dt * self.release_level / release,
+                    EnvCurve::Exponential => (self.level + EXP_RELEASE_RATIO * self.release_level)
+                                             * exp_step(EXP_RELEASE_RATIO, release, sr),
+                };
                 if self.level <= 0.0 { self.level = 0.0; self.stage = EnvelopeStage::Off; }
             }
             EnvelopeStage::Off => return 0.0,
         }
 
+        // FM as phase modulation of the carrier read position: the index is
+        // the peak deviation in radians, so index 0 reads `phase` untouched.
+        let phase = match g.fm {
+            Some((ratio, index)) => {
+                let m = index * (self.mod_phase * 2.0 * PI).sin() / (2.0 * PI);
+                self.mod_phase = (self.mod_phase + self.frequency * ratio / sr).fract();
+                (self.phase + m).rem_euclid(1.0)
+            }
+            None => self.phase,
+        };
         let sample = match wave {
-            WaveType::Sine     => (self.phase * 2.0 * PI).sin(),
-            WaveType::Square   => if (self.phase * 2.0 * PI).sin() >= 0.0 { 1.0 } else { -1.0 },
-            WaveType::Sawtooth => 2.0 * self.phase - 1.0,
+            WaveType::Sine     => (phase * 2.0 * PI).sin(),
+            WaveType::Square   => if (phase * 2.0 * PI).sin() >= 0.0 { 1.0 } else { -1.0 },
+            WaveType::Sawtooth => 2.0 * phase - 1.0,
             WaveType::Triangle => {
-                if self.phase < 0.5 { 4.0 * self.phase - 1.0 } else { 3.0 - 4.0 * self.phase }
+                if phase < 0.5 { 4.0 * phase - 1.0 } else { 3.0 - 4.0 * phase }
             }
         };
 
+        if self.glide > 0.0 { self.frequency += (self.glide_to - self.frequency) * self.glide; }
         self.phase += self.frequency / sr;
         if self.phase >= 1.0 { self.phase -= 1.0; }
-        sample * self.level
+        sample * self.level * self.velocity
+    }
+}
+
+// ── Drone ─────────────────────────────────────────────────────────────────────
+
+/// Drone level relative to the synth 1 volume (sits under played notes).
+const DRONE_LEVEL: f32 = 0.35;
+const DRONE_ATTACK_S: f32 = 0.5;
+const DRONE_RELEASE_S: f32 = 0.8;
+
+/// One sustained reference note on melodic bus 1.  Kept outside `voices`, so
+/// `note_off`/`release_all` never touch it; only `on = false` (or panic) ends it.
+pub struct Drone {
+    pub on:   bool,
+    pub note: u8,
+    voice: Option<Voice>,
+}
+
+impl Drone {
+    pub fn new() -> Self {
+        Self { on: false, note: 36, voice: None }
+    }
+
+    /// Change pitch; a sounding drone bends straight to the new note.
+    pub fn set_note(&mut self, note: u8) {
+        self.note = note;
+        if let Some(v) = &mut self.voice { v.frequency = note_to_freq(note); }
+    }
+
+    pub fn reset(&mut self) {
+        self.on = false;
+        self.voice = None;
+    }
+
+    fn next_sample(&mut self, sr: f32, wave: WaveType, g: VoiceGlobals) -> f32 {
+        match (&mut self.voice, self.on) {
+            (None, true) => self.voice = Some(Voice::new(self.note, DEFAULT_ANTI_CLICK_MS * 0.001)),
+            (Some(v), false) if v.stage != EnvelopeStage::Release => v.release(),
+            (Some(v), true) if v.stage == EnvelopeStage::Release => v.retrigger(),
+            _ => {}
+        }
+        let Some(v) = &mut self.voice else { return 0.0 };
+        let x = v.next_sample(sr, wave, DRONE_ATTACK_S, 1.0, 1.0, DRONE_RELEASE_S, g);
+        if v.is_finished() { self.voice = None; }
+        x * DRONE_LEVEL
     }
 }
 
@@ -168,13 +443,88 @@ pub struct Sidechain {
     pub duck_s1:    bool,
     pub duck_s2:    bool,
     pub envelope:   f32,    // per-sample state (0.0–1.0)
+    /// Gain currently applied to the ducked buses (1.0 = no ducking); read by the UI.
+    pub gain:       f32,
 }
 
 impl Sidechain {
     pub fn new() -> Self {
         Self { enabled: false, depth: 0.8, release_ms: 150.0,
-               duck_s1: true, duck_s2: true, envelope: 0.0 }
+               duck_s1: true, duck_s2: true, envelope: 0.0, gain: 1.0 }
+    }
+
+    /// Current gain reduction in dB (0.0 when idle or disabled).
+    pub fn reduction_db(&self) -> f32 {
+        if !self.enabled { return 0.0; }
+        20.0 * self.gain.max(1e-4).log10()
+    }
+}
+
+/// Start `voice` (fresh, with its velocity and patch set) as `note` in
+/// `voices` according to the retrigger mode.  A voice that gets replaced is
+/// moved to `fading` and force-released rather than cut.  Returns true if a
+/// voice had to be stolen to stay within `max_voices`.
+fn start_voice(voices: &mut HashMap<u8, Voice>, fading: &mut Vec<Voice>,
+               note: u8, mut voice: Voice, mode: Retrigger, max_voices: usize) -> bool {
+    match voices.get_mut(&note) {
+        // Reuse keeps the phase, so only a voice of the same sound qualifies.
+        Some(v) if mode == Retrigger::Reuse && v.patch == voice.patch => { v.retrigger(); v.velocity = voice.velocity; false }
+        _ => {
+            let stolen = !voices.contains_key(&note) && steal_voices(voices, fading, max_voices.saturating_sub(1));
+            voice.slot = free_slot(voices, note);
+            if let Some(mut old) = voices.insert(note, voice) {
+                if !old.is_finished() {
+                    old.force_off();
+                    fading.push(old);
+                }
+            }
+            stolen
+        }
+    }
+}
+
+/// Lowest spread slot not held by another note's voice.
+fn free_slot(voices: &HashMap<u8, Voice>, note: u8) -> u8 {
+    let used = voices.iter().filter(|(&n, _)| n != note).fold(0u64, |m, (_, v)| m | 1 << v.slot.min(63));
+    (!used).trailing_zeros().min(63) as u8
+}
+
+/// Stereo spread: pan of each voice slot, from the centre outwards and
+/// alternating sides, so the first voice stays centred and a chord fans out.
+const SPREAD_PANS: [f32; 8] = [0.0, 1.0, -1.0, 0.5, -0.5, 0.75, -0.75, 0.25];
+
+/// Pan (-1 = left … 1 = right) of voice slot `slot` at full spread.
+fn spread_pan(slot: u8) -> f32 {
+    SPREAD_PANS[slot as usize % SPREAD_PANS.len()]
+}
+
+/// `note` followed by the rest of its chord (just `note` when chords are off).
+fn chord_notes(note: u8, chord: ChordType) -> impl Iterator<Item = u8> {
+    std::iter::once(note).chain(chord.intervals().iter().map(move |&iv| (note as i32 + iv).clamp(0, 127) as u8))
+}
+
+/// Voice stealing: while more than `keep` voices sound, the quietest one is
+/// moved to `fading` for an anti-click fade-out.  Releasing voices go first
+/// and attacking ones last, so the notes of a chord just started survive.
+/// Returns true if anything was stolen.
+fn steal_voices(voices: &mut HashMap<u8, Voice>, fading: &mut Vec<Voice>, keep: usize) -> bool {
+    let rank = |v: &Voice| match v.stage {
+        EnvelopeStage::Release | EnvelopeStage::Off => 0,
+        EnvelopeStage::Decay | EnvelopeStage::Sustain => 1,
+        EnvelopeStage::Attack => 2,
+    };
+    let mut stolen = false;
+    while voices.len() > keep {
+        let Some(note) = voices.iter()
+            .min_by(|(_, a), (_, b)| rank(a).cmp(&rank(b)).then(a.level.total_cmp(&b.level)))
+            .map(|(&n, _)| n) else { break };
+        if let Some(mut v) = voices.remove(&note) {
+            v.force_off();
+            fading.push(v);
+        }
+        stolen = true;
     }
+    stolen
 }
 
 // ── Synth ─────────────────────────────────────────────────────────────────────
@@ -182,31 +532,55 @@ impl Sidechain {
 pub struct Synth {
     pub sample_rate: f32,
     pub bpm:         f32,       // master clock shared by all sequencers
+    /// Swing of the melodic sequencers (every synth 1 lane and synth 2),
+    /// 0.0 ..= `MAX_SWING`; the drum machine has its own.
+    pub seq_swing:   f32,
     pub master_clock: u64,      // incremented every sample
+    /// Frames rendered since the engine started (never rewound, unlike
+    /// `master_clock`); drives the startup fade-in.
+    pub frames_out:  u64,
+    /// Active BPM automation, advanced once per grid step until it finishes.
+    pub tempo_ramp:  Option<TempoRamp>,
+    /// Time signature as (beats, note value); sets the bar length (`tempo.rs`).
+    pub time_sig:    (u8, u8),
+    /// Shared RNG for every probabilistic feature (see `rng.rs`).
+    pub rng:         Rng,
 
     // ── Synth 1 ───────────────────────────────────────────────────────────
     pub wave_type:   WaveType,
     pub voices:      HashMap<u8, Voice>,
+    /// Replaced voices finishing their anti-click fade-out.
+    pub fading:      Vec<Voice>,
     pub attack:  f32,
     pub decay:   f32,
     pub sustain: f32,
     pub release: f32,
     pub volume:  f32,
-    pub sequencer:    Sequencer,
+    /// `volume` as heard (also scales the drum bus).
+    volume_s: Smoothed,
+    /// Melodic step lanes, all played through synth 1 on one transport.
+    /// Never empty; `lanes[0]` is the lane the pattern banks switch.
+    pub lanes:        Vec<Sequencer>,
+    /// Per-lane sound, index-aligned with `lanes` (`None` = synth 1's own).
+    pub lane_patches: Vec<Option<Patch>>,
     /// Insert effects applied to the melodic synth 1 bus.
     pub fx: EffectChain,
 
     // ── Synth 2 (sequencer-driven) ────────────────────────────────────────
     pub wave_type2:  WaveType,
     pub voices2:     HashMap<u8, Voice>,
+    pub fading2:     Vec<Voice>,
     pub attack2:  f32,
     pub decay2:   f32,
     pub sustain2: f32,
     pub release2: f32,
     pub volume2:  f32,
+    volume2_s: Smoothed,
     pub sequencer2:   Sequencer,
     /// Insert effects applied to the melodic synth 2 bus.
     pub fx2: EffectChain,
+    /// Accent sweep on filter 2: 1.0 on an accented step, decaying to 0.
+    accent_env2: f32,
 
     // ── Drum machine ──────────────────────────────────────────────────────
     pub drum_machine: DrumMachine,
@@ -226,10 +600,65 @@ pub struct Synth {
     // ── Chord mode ────────────────────────────────────────────────────────
     pub chord1: ChordType,
     pub chord2: ChordType,
+    /// Repeated note-on behaviour for both melodic buses.
+    pub retrigger: Retrigger,
+    /// Attack and release shapes of every melodic voice (both buses, lanes
+    /// and the drone).
+    pub attack_curve:  EnvCurve,
+    pub release_curve: EnvCurve,
+    /// Subtractive or 2-operator FM tone for both melodic buses and the
+    /// lanes; the FM modulator runs at `fm_ratio` × the note with `fm_index`.
+    pub synth_mode: SynthMode,
+    pub fm_ratio:   f32,
+    pub fm_index:   f32,
+    /// Minimum envelope ramp for melodic voices, in ms (0 = off).
+    pub anti_click_ms: f32,
+    /// Polyphony limit per melodic bus; a note-on past it steals a voice.
+    pub max_voices: usize,
+    /// Voices stolen so far (both buses); the UI flashes when it changes.
+    pub voice_steals: u64,
+    /// Gain trim per waveform (`WaveType as usize`), applied to each melodic
+    /// bus so switching waveforms keeps roughly the same loudness.
+    pub wave_trims: [f32; 4],
+    /// Sustained reference note mixed into melodic bus 1.
+    pub drone: Drone,
 
     // ── Sidechain compressor ──────────────────────────────────────────────
     pub sidechain: Sidechain,
 
+    // ── Master bus ────────────────────────────────────────────────────────
+    /// Meters the dry pre-`tanh` sum, with the optional auto-gain.
+    pub mix_meter: MixMeter,
+    /// Stereo spread of the melodic voices, 0.0 (centre, default) – 1.0:
+    /// each voice is panned by its slot (`spread_pan`).
+    pub voice_spread: f32,
+    /// Right-channel copies of `filter1` / `filter2` (the left channel is
+    /// the panel filter itself).
+    right_filters: [BiquadFilter; 2],
+    /// Channel solo across drums, buses and lanes (session only).
+    pub solo: Solo,
+    /// Solo gain of the two melodic buses (1.0 = heard).
+    solo_gains: [Smoothed; 2],
+    /// Insert effects applied to the full mix (synths + drums + sends).
+    pub master_fx: EffectChain,
+    /// Stereo stage applied after `master_fx`, before the final clipper.
+    pub widener: StereoWidener,
+    /// Removes DC offset from the widened mix before it reaches the clipper.
+    pub dc_block: DcBlocker,
+    /// Optional peak limiter (off by default) between the DC blocker and the clipper.
+    pub limiter: Limiter,
+    /// Latched when the signal into the limiter goes over 0 dBFS; only the
+    /// UI clears it (`clip clear`).
+    pub clip_latch: bool,
+    /// Final drive + saturation stage (tanh by default), with a peak meter.
+    pub clipper: MasterClipper,
+    /// Looped grain of captured master output, layered in before the widener.
+    pub freeze: Freeze,
+    /// Reference tone for tuning external instruments (bypasses every effect).
+    pub tuner: Tuner,
+    /// Debug click on every melodic step event (bypasses every effect).
+    pub step_click: StepClick,
+
     // ── Oscilloscope ring buffer ──────────────────────────────────────────
     pub scope_buf: Vec<f32>,
     pub scope_pos: usize,
@@ -240,28 +669,39 @@ impl Synth {
         Self {
             sample_rate,
             bpm:          120.0,
+            seq_swing:    0.0,
             master_clock: 0,
+            frames_out:   0,
+            tempo_ramp:   None,
+            time_sig:     COMMON_TIME_SIGS[0],
+            rng:          Rng::new(DEFAULT_SEED),
 
             wave_type:  WaveType::Sine,
             voices:     HashMap::new(),
+            fading:     Vec::with_capacity(16),
             attack:  0.01, decay: 0.1, sustain: 0.7, release: 0.3,
             volume:  0.5,
-            sequencer:    Sequencer::new(sample_rate),
+            volume_s: Smoothed::new(VOLUME_RAMP_MS, sample_rate),
+            lanes:        vec![Sequencer::new(sample_rate)],
+            lane_patches: vec![None],
             fx:           EffectChain::new(),
 
             wave_type2: WaveType::Sine,
             voices2:    HashMap::new(),
+            fading2:    Vec::with_capacity(16),
             attack2: 0.01, decay2: 0.1, sustain2: 0.7, release2: 0.3,
             volume2: 0.5,
+            volume2_s: Smoothed::new(VOLUME_RAMP_MS, sample_rate),
             sequencer2:   Sequencer::new(sample_rate),
             fx2:          EffectChain::new(),
+            accent_env2:  0.0,
 
             drum_machine: DrumMachine::new(sample_rate),
 
             filter1: BiquadFilter::new(sample_rate),
             filter2: BiquadFilter::new(sample_rate),
 
-            reverb:      Reverb::new(),
+            reverb:      Reverb::new(sample_rate),
             delay:       Delay::new(sample_rate),
             distortion:  Distortion::new(),
 
@@ -269,8 +709,33 @@ impl Synth {
 
             chord1: ChordType::Off,
             chord2: ChordType::Off,
+            retrigger: Retrigger::Replace,
+            attack_curve:  EnvCurve::Linear,
+            release_curve: EnvCurve::Linear,
+            synth_mode: SynthMode::Subtractive,
+            fm_ratio:   DEFAULT_FM_RATIO,
+            fm_index:   DEFAULT_FM_INDEX,
+            anti_click_ms: DEFAULT_ANTI_CLICK_MS,
+            max_voices:    DEFAULT_MAX_VOICES,
+            voice_steals:  0,
+            wave_trims:    DEFAULT_WAVE_TRIMS,
+            drone:     Drone::new(),
 
             sidechain:  Sidechain::new(),
+            mix_meter:  MixMeter::new(sample_rate),
+            voice_spread: 0.0,
+            right_filters: [BiquadFilter::new(sample_rate), BiquadFilter::new(sample_rate)],
+            solo:       Solo::default(),
+            solo_gains: [Smoothed::new(SOLO_FADE_MS, sample_rate); 2],
+            master_fx:  EffectChain::new(),
+            widener:    StereoWidener::new(),
+            dc_block:   DcBlocker::new(sample_rate),
+            limiter:    Limiter::new(sample_rate),
+            clip_latch: false,
+            clipper:    MasterClipper::new(sample_rate),
+            freeze:     Freeze::new(sample_rate),
+            tuner:      Tuner::new(sample_rate),
+            step_click: StepClick::new(sample_rate),
             scope_buf:  vec![0.0f32; 512],
             scope_pos:  0,
         }
@@ -278,12 +743,26 @@ impl Synth {
 
     // ── Synth 1 note control ──────────────────────────────────────────────
 
-    pub fn note_on(&mut self, note: u8) {
-        self.voices.insert(note, Voice::new(note));
-        for &iv in self.chord1.intervals() {
-            let cn = (note as i32 + iv).clamp(0, 127) as u8;
-            self.voices.insert(cn, Voice::new(cn));
+    pub fn note_on(&mut self, note: u8) { self.note_on_vel(note, MAX_VELOCITY); }
+
+    /// A fresh melodic voice for `note` with the current anti-click fade.
+    fn new_voice(&self, note: u8, velocity: u8, patch: Option<Patch>) -> Voice {
+        let mut voice = Voice::new(note, self.anti_click_ms * 0.001);
+        voice.velocity = velocity as f32 / MAX_VELOCITY as f32;
+        voice.patch = patch;
+        voice
+    }
+
+    pub fn note_on_vel(&mut self, note: u8, velocity: u8) { self.note_on_patch(note, velocity, None); }
+
+    /// `note_on_vel` with the sound of a sequencer lane (`None` = synth 1's own).
+    pub fn note_on_patch(&mut self, note: u8, velocity: u8, patch: Option<Patch>) {
+        let mut steals = 0;
+        for cn in chord_notes(note, self.chord1) {
+            let voice = self.new_voice(cn, velocity, patch);
+            steals += start_voice(&mut self.voices, &mut self.fading, cn, voice, self.retrigger, self.max_voices) as u64;
         }
+        self.voice_steals += steals;
     }
 
     pub fn note_off(&mut self, note: u8) {
@@ -300,12 +779,13 @@ impl Synth {
 
     // ── Synth 2 note control ──────────────────────────────────────────────
 
-    pub fn note_on2(&mut self, note: u8) {
-        self.voices2.insert(note, Voice::new(note));
-        for &iv in self.chord2.intervals() {
-            let cn = (note as i32 + iv).clamp(0, 127) as u8;
-            self.voices2.insert(cn, Voice::new(cn));
+    pub fn note_on2(&mut self, note: u8, velocity: u8) {
+        let mut steals = 0;
+        for cn in chord_notes(note, self.chord2) {
+            let voice = self.new_voice(cn, velocity, None);
+            steals += start_voice(&mut self.voices2, &mut self.fading2, cn, voice, self.retrigger, self.max_voices) as u64;
         }
+        self.voice_steals += steals;
     }
 
     pub fn note_off2(&mut self, note: u8) {
@@ -316,52 +796,350 @@ impl Synth {
         }
     }
 
+    /// Play one Synth 2 sequencer step: accents play louder and sweep
+    /// filter 2 open; a slide bends the sounding note into the next one
+    /// (`slide2`) instead of releasing it.
+    fn step2(&mut self, ev: StepEvent) {
+        let gain = if ev.accent { ACCENT_GAIN } else { 1.0 };
+        match (ev.note_off, ev.note_on) {
+            (Some(from), Some(to)) if ev.slide => self.slide2(from, to, ev.velocity, gain),
+            (off, on) => {
+                if let Some(n) = off { self.note_off2(n); }
+                if let Some(n) = on {
+                    self.note_on2(n, ev.velocity);
+                    for cn in chord_notes(n, self.chord2) {
+                        if let Some(v) = self.voices2.get_mut(&cn) { v.velocity *= gain; }
+                    }
+                }
+            }
+        }
+        if ev.accent && ev.note_on.is_some() { self.accent_env2 = 1.0; }
+    }
+
+    /// Legato slide: each of `from`'s voices (chord notes pair up) glides to
+    /// the matching note of `to` over `SLIDE_MS`, keeping its envelope.  A
+    /// voice that is already releasing or gone is replaced by a fresh note.
+    fn slide2(&mut self, from: u8, to: u8, velocity: u8, gain: f32) {
+        let coef = 1.0 - (-1.0 / (SLIDE_MS * 0.001 * self.sample_rate)).exp();
+        let level = velocity as f32 / MAX_VELOCITY as f32 * gain;
+        // Take every old voice out first, so a target that is also a source
+        // (chord notes overlapping) is not overwritten before it moves.
+        let mut held: [Option<Voice>; 4] = Default::default();
+        for (slot, a) in held.iter_mut().zip(chord_notes(from, self.chord2)) {
+            *slot = self.voices2.remove(&a).filter(|v| v.stage != EnvelopeStage::Release);
+        }
+        for (slot, b) in held.iter_mut().zip(chord_notes(to, self.chord2)) {
+            match slot.take() {
+                Some(mut v) => {
+                    v.glide_to(note_to_freq(b), coef);
+                    v.velocity = level;
+                    self.voices2.insert(b, v);
+                }
+                None => {
+                    let mut v = self.new_voice(b, velocity, None);
+                    v.velocity *= gain;
+                    start_voice(&mut self.voices2, &mut self.fading2, b, v, self.retrigger, self.max_voices);
+                }
+            }
+        }
+    }
+
     #[allow(dead_code)]
     pub fn active_notes2(&self) -> Vec<u8> {
         self.voices2.keys().copied().collect()
     }
 
+    // ── Insert chains ─────────────────────────────────────────────────────
+
+    /// Every insert chain in a fixed order: master, S1, S2, drum bus, then one
+    /// per drum track.  Used to snapshot/restore chains generically.
+    pub fn chains(&self) -> Vec<&EffectChain> {
+        let mut v = vec![&self.master_fx, &self.fx, &self.fx2, &self.drum_machine.fx];
+        v.extend(self.drum_machine.tracks.iter().map(|t| &t.fx));
+        v
+    }
+
+    /// Mutable counterpart of [`Synth::chains`], same order.
+    pub fn chains_mut(&mut self) -> Vec<&mut EffectChain> {
+        let mut v = vec![&mut self.master_fx, &mut self.fx, &mut self.fx2, &mut self.drum_machine.fx];
+        v.extend(self.drum_machine.tracks.iter_mut().map(|t| &mut t.fx));
+        v
+    }
+
+    // ── Oversampling ──────────────────────────────────────────────────────
+
+    /// Oversampling factor of the saturation stages (master distortion send
+    /// and the master clipper).
+    pub fn oversample(&self) -> usize { self.clipper.os.0.factor() }
+
+    /// Rebuild both saturation stages' oversamplers at `factor` (1, 2 or 4).
+    pub fn set_oversample(&mut self, factor: usize) {
+        self.distortion.os = Oversampler::new(factor);
+        self.clipper.os = (Oversampler::new(factor), Oversampler::new(factor));
+    }
+
+    /// Change tempo without moving the playhead: the master clock is rescaled
+    /// so every sequencer stays on the same step, the same fraction through it.
+    pub fn set_bpm(&mut self, bpm: f32) {
+        let bpm = bpm.clamp(30.0, 300.0);
+        let old = samples_per_step(self.sample_rate, self.bpm);
+        let new = samples_per_step(self.sample_rate, bpm);
+        self.master_clock = rescale_clock(self.master_clock, old, new);
+        self.bpm = bpm;
+    }
+
+    /// Switch every rate-dependent part of the engine to `sample_rate` (the
+    /// output device's rate): the sequencers and drum machine keep their step
+    /// length in seconds, effects resize their buffers and recompute their
+    /// coefficients.  The playhead stays on the same step, like `set_bpm`.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        if sample_rate == self.sample_rate { return; }
+        let old = samples_per_step(self.sample_rate, self.bpm);
+        let new = samples_per_step(sample_rate, self.bpm);
+        self.master_clock = rescale_clock(self.master_clock, old, new);
+        self.sample_rate = sample_rate;
+
+        for lane in &mut self.lanes { lane.set_sample_rate(sample_rate); }
+        self.sequencer2.set_sample_rate(sample_rate);
+        self.drum_machine.set_sample_rate(sample_rate);
+        self.fx.set_sample_rate(sample_rate);
+        self.fx2.set_sample_rate(sample_rate);
+        self.master_fx.set_sample_rate(sample_rate);
+        self.filter1.set_sample_rate(sample_rate);
+        self.filter2.set_sample_rate(sample_rate);
+        self.volume_s.set_sample_rate(sample_rate);
+        self.volume2_s.set_sample_rate(sample_rate);
+        self.reverb.set_sample_rate(sample_rate);
+        self.delay.set_sample_rate(sample_rate);
+        self.dc_block.set_sample_rate(sample_rate);
+        self.limiter.set_sample_rate(sample_rate);
+        self.mix_meter.set_sample_rate(sample_rate);
+        for f in &mut self.right_filters { f.set_sample_rate(sample_rate); }
+        for g in &mut self.solo_gains { g.set_sample_rate(sample_rate); }
+        self.clipper.set_sample_rate(sample_rate);
+        self.freeze.set_sample_rate(sample_rate);
+        self.tuner.set_sample_rate(sample_rate);
+        self.step_click.set_sample_rate(sample_rate);
+    }
+
+    // ── Synth 1 lanes ─────────────────────────────────────────────────────
+
+    /// Append an empty lane matching the main lane's length and transport.
+    /// Returns its index, or `None` at `MAX_LANES`.
+    pub fn add_lane(&mut self) -> Option<usize> {
+        if self.lanes.len() >= MAX_LANES { return None; }
+        let n = self.lanes[0].num_steps;
+        let mut lane = Sequencer::new(self.sample_rate);
+        lane.num_steps = n;
+        lane.steps.resize(n, None);
+        lane.nudge.resize(n, 0);
+        lane.accent.resize(n, false);
+        lane.slide.resize(n, false);
+        lane.prob.resize(n, FULL_PROB);
+        lane.loop_end = n - 1;
+        lane.playing = self.lanes[0].playing;
+        self.lanes.push(lane);
+        self.lane_patches.push(None);
+        Some(self.lanes.len() - 1)
+    }
+
+    /// Remove lane `i`, releasing its sounding note.  The last lane stays.
+    pub fn remove_lane(&mut self, i: usize) -> bool {
+        if self.lanes.len() <= 1 || i >= self.lanes.len() { return false; }
+        if let Some(note) = self.lanes.remove(i).stop() { self.note_off(note); }
+        self.lane_patches.remove(i);
+        self.solo.remove_lane(i);
+        true
+    }
+
+    /// What every melodic voice renders with this sample besides its patch.
+    pub fn voice_globals(&self) -> VoiceGlobals {
+        VoiceGlobals {
+            attack_curve:  self.attack_curve,
+            release_curve: self.release_curve,
+            fm: (self.synth_mode == SynthMode::Fm).then_some((self.fm_ratio, self.fm_index)),
+        }
+    }
+
+    /// Synth 1's own sound as a patch (what a lane without one plays).
+    pub fn patch1(&self) -> Patch {
+        Patch { wave: self.wave_type, attack: self.attack, decay: self.decay, sustain: self.sustain, release: self.release }
+    }
+
+    /// Start or stop every lane together.  Returns true if they are now playing.
+    pub fn toggle_lanes(&mut self) -> bool {
+        let play = !self.lanes[0].playing;
+        for i in 0..self.lanes.len() {
+            if self.lanes[i].playing == play { continue; }
+            if let Some(note) = self.lanes[i].toggle_play() { self.note_off(note); }
+        }
+        play
+    }
+
+    // ── Panic ─────────────────────────────────────────────────────────────
+
+    /// Silence everything immediately: drop all sounding voices and clear the
+    /// state of every filter and effect (delay lines, reverb tails, envelopes).
+    /// Sequencer transport and pattern data are left untouched.
+    pub fn panic(&mut self) {
+        self.voices.clear();
+        self.voices2.clear();
+        self.fading.clear();
+        self.fading2.clear();
+        self.accent_env2 = 0.0;
+        self.filter2.cutoff_mod = 1.0;
+        self.drum_machine.panic();
+        self.filter1.reset_state();
+        self.filter2.reset_state();
+        for chain in self.chains_mut() { chain.reset_all(); }
+        self.reverb.reset();
+        self.delay.reset();
+        self.distortion.reset();
+        self.widener.reset();
+        self.dc_block.reset();
+        self.limiter.reset();
+        self.mix_meter.reset();
+        for f in &mut self.right_filters { f.reset_state(); }
+        self.clipper.reset();
+        self.freeze.reset();
+        self.drone.reset();
+        self.tuner.reset();
+        self.step_click.reset();
+        self.sidechain.envelope = 0.0;
+        self.sidechain.gain     = 1.0;
+    }
+
+    /// Soft stop: halt every sequencer and the drums, and send every melodic
+    /// voice (and the drone) into its envelope release.  Unlike `panic`,
+    /// nothing is cut — drum hits, voice releases and effect tails ring out.
+    pub fn soft_stop(&mut self) {
+        for lane in &mut self.lanes { lane.stop(); }
+        self.sequencer2.stop();
+        // Not `toggle_play`: stopping that way also cuts the ringing hits.
+        self.drum_machine.playing = false;
+        for v in self.voices.values_mut().chain(self.voices2.values_mut()) { v.release(); }
+        self.drone.on = false;
+    }
+
     // ── Audio render ──────────────────────────────────────────────────────
 
-    pub fn generate_sample(&mut self) -> f32 {
+    /// Length of the startup fade-in, in frames.
+    pub fn startup_fade_frames(&self) -> u64 {
+        (self.sample_rate * STARTUP_FADE_MS * 0.001).ceil() as u64
+    }
+
+    /// Render one stereo frame `(left, right)`.
+    ///
+    /// Every bus runs in stereo.  Drum tracks are panned after their own
+    /// chain; with `voice_spread` each melodic voice is panned by its slot
+    /// (`spread_pan`) ahead of its bus filter and inserts, which run one
+    /// instance per channel (`right_filters`, `EffectChain::process_stereo`).
+    /// The sends take each bus's mono average.  Every master stage (mix
+    /// meter, `tanh`, `master_fx`, widener, DC blocker, limiter and clipper)
+    /// runs on both channels.
+    pub fn generate_frame(&mut self) -> (f32, f32) {
+        // ── Tempo ramp (before the clock is read, so a change lands on the
+        //    first sample of a step) ──────────────────────────────────────
+        if let Some(ramp) = &mut self.tempo_ramp {
+            let k = step_at(self.master_clock, samples_per_step(self.sample_rate, self.bpm));
+            let bpm = ramp.bpm_at(k);
+            let finished = ramp.is_finished();
+            if let Some(bpm) = bpm { self.set_bpm(bpm); }
+            if finished { self.tempo_ramp = None; }
+        }
+
         let clock = self.master_clock;
         self.master_clock += 1;
 
-        // ── Sequencer 1 ───────────────────────────────────────────────────
-        if let Some(ev) = self.sequencer.tick(self.bpm, clock) {
-            if let Some(n) = ev.note_off { self.note_off(n); }
-            if let Some(n) = ev.note_on  { self.note_on(n); }
+        // ── Sequencer 1 lanes ─────────────────────────────────────────────
+        // Every lane's note-off lands before any note-on, so a lane starting
+        // the pitch another lane just released keeps its voice.  A lane out
+        // of the solo keeps time but neither starts notes nor clicks.
+        let mut ons = [None; MAX_LANES];
+        for (i, on) in ons.iter_mut().enumerate().take(self.lanes.len()) {
+            let muted = self.solo.mutes(SoloTarget::Lane(i));
+            if let Some(ev) = self.lanes[i].tick(self.bpm, clock, self.seq_swing, muted, &mut self.rng) {
+                if !muted { self.step_click.trigger(); }
+                if let Some(n) = ev.note_off { self.note_off(n); }
+                *on = ev.note_on.map(|n| (n, ev.velocity, self.lane_patches[i]));
+            }
         }
+        for (n, vel, patch) in ons.into_iter().flatten() { self.note_on_patch(n, vel, patch); }
 
         // ── Sequencer 2 ───────────────────────────────────────────────────
-        if let Some(ev) = self.sequencer2.tick(self.bpm, clock) {
-            if let Some(n) = ev.note_off { self.note_off2(n); }
-            if let Some(n) = ev.note_on  { self.note_on2(n); }
+        if let Some(ev) = self.sequencer2.tick(self.bpm, clock, self.seq_swing, false, &mut self.rng) {
+            self.step_click.trigger();
+            self.step2(ev);
+        }
+        if self.accent_env2 > 0.0 {
+            self.accent_env2 *= (-1.0 / (ACCENT_DECAY_MS * 0.001 * self.sample_rate)).exp();
+            if self.accent_env2 < 1e-3 { self.accent_env2 = 0.0; }
+            self.filter2.cutoff_mod = (ACCENT_OCTAVES * self.accent_env2).exp2();
         }
 
+        self.volume_s.set(self.volume);
+        self.volume2_s.set(self.volume2);
+        let (vol1, vol2) = (self.volume_s.next(), self.volume2_s.next());
+        // The buses' level with the solo (the drums take plain `vol1`).
+        self.solo_gains[0].set(if self.solo.mutes(SoloTarget::Synth1) { 0.0 } else { 1.0 });
+        self.solo_gains[1].set(if self.solo.mutes(SoloTarget::Synth2) { 0.0 } else { 1.0 });
+        let (bus1, bus2) = (vol1 * self.solo_gains[0].next(), vol2 * self.solo_gains[1].next());
+
         // ── Melodic bus 1 ─────────────────────────────────────────────────
-        let sr   = self.sample_rate;
-        let wave = self.wave_type;
-        let (a, d, s, r) = (self.attack, self.decay, self.sustain, self.release);
-        let mut mel1 = 0.0f32;
-        for v in self.voices.values_mut() { mel1 += v.next_sample(sr, wave, a, d, s, r); }
+        // Lane voices carry their own patch, so the wave trim is per voice.
+        let sr    = self.sample_rate;
+        let wave  = self.wave_type;
+        let bus   = self.patch1();
+        let trims = self.wave_trims;
+        let g     = self.voice_globals();
+        // The drone stays a plain reference tone.
+        let drone = VoiceGlobals { fm: None, ..g };
+        let play  = |v: &mut Voice| {
+            let p = v.patch.unwrap_or(bus);
+            v.next_sample(sr, p.wave, p.attack, p.decay, p.sustain, p.release, g) * trims[p.wave as usize]
+        };
+        // Each voice is panned by its slot at the spread; at 0 both channels
+        // carry the same centred mix.
+        let spread = self.voice_spread.clamp(0.0, 1.0);
+        let pan    = |v: &Voice| pan_gains(spread_pan(v.slot) * spread);
+        let (mut mel1_l, mut mel1_r) = (0.0f32, 0.0f32);
+        for v in self.voices.values_mut().chain(&mut self.fading) {
+            let (x, (gl, gr)) = (play(v), pan(v));
+            mel1_l += x * gl;
+            mel1_r += x * gr;
+        }
         self.voices.retain(|_, v| !v.is_finished());
-        let mel1_scaled   = mel1 * self.volume / (self.voices.len().max(1) as f32).sqrt();
-        let mel1_filtered = self.filter1.process(mel1_scaled);
-        let mel1_out      = self.fx.process(mel1_filtered);
+        self.fading.retain(|v| !v.is_finished());
+        let norm1    = self.mix_meter.voice_norm(0, self.voices.len());
+        let drone1   = self.drone.next_sample(sr, wave, drone) * trims[wave as usize];
+        let mel1_l   = self.filter1.process((mel1_l / norm1 + drone1) * bus1);
+        self.right_filters[0].follow(&self.filter1);
+        let mel1_r   = self.right_filters[0].process((mel1_r / norm1 + drone1) * bus1);
+        let (mel1_l, mel1_r) = self.fx.process_stereo(mel1_l, mel1_r);
 
         // ── Melodic bus 2 ─────────────────────────────────────────────────
         let wave2 = self.wave_type2;
         let (a2, d2, s2, r2) = (self.attack2, self.decay2, self.sustain2, self.release2);
-        let mut mel2 = 0.0f32;
-        for v in self.voices2.values_mut() { mel2 += v.next_sample(sr, wave2, a2, d2, s2, r2); }
+        let (mut mel2_l, mut mel2_r) = (0.0f32, 0.0f32);
+        for v in self.voices2.values_mut().chain(&mut self.fading2) {
+            let (x, (gl, gr)) = (v.next_sample(sr, wave2, a2, d2, s2, r2, g), pan(v));
+            mel2_l += x * gl;
+            mel2_r += x * gr;
+        }
         self.voices2.retain(|_, v| !v.is_finished());
-        let mel2_scaled   = mel2 * self.volume2 / (self.voices2.len().max(1) as f32).sqrt();
-        let mel2_filtered = self.filter2.process(mel2_scaled);
-        let mel2_out      = self.fx2.process(mel2_filtered);
+        self.fading2.retain(|v| !v.is_finished());
+        let norm2    = self.mix_meter.voice_norm(1, self.voices2.len());
+        let trim2    = self.wave_trims[wave2 as usize];
+        let mel2_l   = self.filter2.process(mel2_l * bus2 * trim2 / norm2);
+        self.right_filters[1].follow(&self.filter2);
+        let mel2_r   = self.right_filters[1].process(mel2_r * bus2 * trim2 / norm2);
+        let (mel2_l, mel2_r) = self.fx2.process_stereo(mel2_l, mel2_r);
 
         // ── Drum bus ──────────────────────────────────────────────────────
-        let drum_out = self.drum_machine.generate_sample(self.bpm, clock) * self.volume;
+        let (drum_l, drum_r) = self.drum_machine.generate_sample(self.bpm, clock, &mut self.rng, &self.solo);
+        let (drum_l, drum_r) = (drum_l * vol1, drum_r * vol1);
+        // What the sends take: the channels' average, the unpanned bus.
+        let drum_out = (drum_l + drum_r) * 0.5;
 
         // ── Sidechain ─────────────────────────────────────────────────────
         let kick = self.drum_machine.kick_triggered;
@@ -370,11 +1148,24 @@ impl Synth {
         let rel_c = (-1.0_f32 / (self.sidechain.release_ms * 0.001 * self.sample_rate)).exp();
         self.sidechain.envelope = (self.sidechain.envelope * rel_c).clamp(0.0, 1.0);
         let sc_gain = 1.0 - self.sidechain.envelope * self.sidechain.depth;
-        let mel1_out = if self.sidechain.enabled && self.sidechain.duck_s1 { mel1_out * sc_gain } else { mel1_out };
-        let mel2_out = if self.sidechain.enabled && self.sidechain.duck_s2 { mel2_out * sc_gain } else { mel2_out };
+        self.sidechain.gain = sc_gain;
+        let (mel1_l, mel1_r) = if self.sidechain.enabled && self.sidechain.duck_s1 {
+            (mel1_l * sc_gain, mel1_r * sc_gain)
+        } else {
+            (mel1_l, mel1_r)
+        };
+        let (mel2_l, mel2_r) = if self.sidechain.enabled && self.sidechain.duck_s2 {
+            (mel2_l * sc_gain, mel2_r * sc_gain)
+        } else {
+            (mel2_l, mel2_r)
+        };
+        // What the sends take, as for the drums.
+        let mel1_out = (mel1_l + mel1_r) * 0.5;
+        let mel2_out = (mel2_l + mel2_r) * 0.5;
 
         // ── Master mix (always dry) ───────────────────────────────────────
-        let dry = (mel1_out + mel2_out + drum_out).tanh();
+        let (dry_l, dry_r) = self.mix_meter.process(mel1_l + mel2_l + drum_l, mel1_r + mel2_r + drum_r);
+        let (dry_l, dry_r) = (dry_l.tanh(), dry_r.tanh());
 
         // ── FX sends (wet-only, parallel) ─────────────────────────────────
         // Copy routing values out to avoid split-borrow conflicts.
@@ -394,10 +1185,29 @@ impl Synth {
         let dst_wet = self.distortion.process(
             (s1_dst * mel1_out + s2_dst * mel2_out + dr_dst * drum_out).tanh());
 
-        let out = (dry + rev_wet + dly_wet + dst_wet).tanh();
-        self.scope_buf[self.scope_pos % 512] = out;
+        let (l, r) = self.master_fx.process_stereo(dry_l + rev_wet + dly_wet + dst_wet,
+                                                   dry_r + rev_wet + dly_wet + dst_wet);
+        let grain = self.freeze.next_sample();
+
+        // ── Stereo master ─────────────────────────────────────────────────
+        let (l, r) = self.widener.process(l + grain, r + grain);
+        let (l, r) = self.dc_block.process(l, r);
+        if l.abs().max(r.abs()) > 1.0 { self.clip_latch = true; }
+        let (l, r) = self.limiter.process(l, r);
+        // The tuner tone and step click skip the effects; only the clipper
+        // guards the sum.
+        let tone   = self.tuner.next_sample() + self.step_click.next_sample();
+        let (mut l, mut r) = self.clipper.process(l + tone, r + tone);
+        let fade_len = self.startup_fade_frames();
+        if self.frames_out < fade_len {
+            let g = self.frames_out as f32 / fade_len as f32;
+            l *= g;
+            r *= g;
+        }
+        self.frames_out += 1;
+        self.scope_buf[self.scope_pos % 512] = (l + r) * 0.5;
         self.scope_pos = self.scope_pos.wrapping_add(1);
-        out
+        (l, r)
     }
 }
 
@@ -411,3 +1221,409 @@ pub fn note_name(note: u8) -> String {
     let names = ["C","C#","D","D#","E","F","F#","G","G#","A","A#","B"];
     format!("{}{}", names[(note % 12) as usize], (note / 12) as i32 - 1)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::InsertKind;
+    use crate::render::{first_onset, render_mono, rewind_and_play};
+    use crate::sequencer::{nudge_samples, step_start, swing_samples};
+
+    const SR: f32 = 44_100.0;
+
+    /// A synth past its startup fade, so output levels are the real ones.
+    fn synth() -> Synth {
+        let mut s = Synth::new(SR);
+        s.frames_out = s.startup_fade_frames();
+        s
+    }
+
+    fn run(s: &mut Synth, n: usize) {
+        for _ in 0..n { s.generate_frame(); }
+    }
+
+    #[test]
+    fn replace_restarts_a_sounding_note() {
+        let mut s = synth();
+        s.note_on(60);
+        run(&mut s, 300);
+        s.note_on(60);
+        let v = &s.voices[&60];
+        assert_eq!((v.level, v.phase, v.stage), (0.0, 0.0, EnvelopeStage::Attack));
+        assert_eq!(s.fading.len(), 1, "the old voice should fade out, not be cut");
+    }
+
+    #[test]
+    fn reuse_keeps_level_and_phase() {
+        let mut s = synth();
+        s.retrigger = Retrigger::Reuse;
+        s.note_on(60);
+        run(&mut s, 300);
+        s.note_off(60);
+        run(&mut s, 100);
+        let (level, phase) = (s.voices[&60].level, s.voices[&60].phase);
+        s.note_on(60);
+        let v = &s.voices[&60];
+        assert_eq!((v.level, v.phase, v.stage), (level, phase, EnvelopeStage::Attack));
+        assert!(level > 0.0 && s.fading.is_empty());
+    }
+
+    /// Envelope levels of `v` over `n` samples of a sine with a zero-time ADSR.
+    fn levels(v: &mut Voice, n: usize) -> Vec<f32> {
+        let g = Synth::new(SR).voice_globals();
+        (0..n).map(|_| { v.next_sample(SR, WaveType::Sine, 0.0, 0.0, 1.0, 0.0, g); v.level }).collect()
+    }
+
+    #[test]
+    fn zero_adsr_still_ramps_over_the_minimum_fade() {
+        let fade = DEFAULT_ANTI_CLICK_MS * 0.001;
+        let len = (fade * SR) as usize;
+        let mut v = Voice::new(60, fade);
+
+        let up = levels(&mut v, len + 2);
+        assert!(up[0] < 0.01, "attack stepped to {}", up[0]);
+        assert!(up[len - 2] < 1.0 && up[len + 1] == 1.0, "attack should take ~{} samples", len);
+
+        v.release();
+        let down = levels(&mut v, len + 2);
+        assert!(down[0] > 0.99, "release stepped to {}", down[0]);
+        assert!(down[len - 2] > 0.0 && v.is_finished(), "release should take ~{} samples", len);
+    }
+
+    #[test]
+    fn force_off_ignores_a_long_release() {
+        let fade = DEFAULT_ANTI_CLICK_MS * 0.001;
+        let g = Synth::new(SR).voice_globals();
+        let mut v = Voice::new(60, fade);
+        levels(&mut v, 1000);
+        v.force_off();
+        for _ in 0..(fade * SR) as usize + 2 {
+            v.next_sample(SR, WaveType::Sine, 0.0, 0.0, 1.0, 5.0, g);
+        }
+        assert!(v.is_finished(), "forced release still at {}", v.level);
+    }
+
+    /// 100 ms of synth 1 playing a C major triad on a saw in `mode` at `index`.
+    fn fm_render(mode: SynthMode, index: f32) -> Vec<(f32, f32)> {
+        let mut s = synth();
+        (s.wave_type, s.synth_mode, s.fm_index) = (WaveType::Sawtooth, mode, index);
+        for n in [60, 64, 67] { s.note_on(n); }
+        (0..4410).map(|_| s.generate_frame()).collect()
+    }
+
+    /// Largest sample difference between two renders.
+    fn max_diff(a: &[(f32, f32)], b: &[(f32, f32)]) -> f32 {
+        a.iter().zip(b).map(|(x, y)| (x.0 - y.0).abs().max((x.1 - y.1).abs())).fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn fm_index_zero_is_the_plain_carrier() {
+        // Voices mix in hash order, so two renders match only to rounding.
+        let plain = fm_render(SynthMode::Subtractive, 0.0);
+        assert!(max_diff(&fm_render(SynthMode::Fm, 0.0), &plain) < 1e-6);
+        assert!(max_diff(&fm_render(SynthMode::Fm, 2.0), &plain) > 0.05);
+        assert!(max_diff(&fm_render(SynthMode::Subtractive, 2.0), &plain) < 1e-6, "the index should only matter in FM mode");
+    }
+
+    /// 100 ms of a C major triad on synth 1 at `spread`, after its attack.
+    fn spread_render(spread: f32) -> (Synth, Vec<(f32, f32)>) {
+        spread_render_with(spread, |_| {})
+    }
+
+    /// `spread_render` with `setup` applied to the synth first.
+    fn spread_render_with(spread: f32, setup: impl FnOnce(&mut Synth)) -> (Synth, Vec<(f32, f32)>) {
+        let mut s = synth();
+        setup(&mut s);
+        (s.voice_spread, s.volume) = (spread, 0.1);
+        for n in [60, 64, 67] { s.note_on(n); }
+        run(&mut s, 2000);
+        let out = (0..4410).map(|_| s.generate_frame()).collect();
+        (s, out)
+    }
+
+    #[test]
+    fn voice_spread_fans_a_chord_out_and_keeps_the_mid() {
+        let (s, centred) = spread_render(0.0);
+        assert!(centred.iter().all(|&(l, r)| l == r), "spread 0 is not mono");
+        let mut slots: Vec<u8> = s.voices.values().map(|v| v.slot).collect();
+        slots.sort_unstable();
+        assert_eq!(slots, [0, 1, 2]);
+
+        let (_, wide) = spread_render(1.0);
+        let side: Vec<f32> = wide.iter().map(|&(l, r)| (l - r) * 0.5).collect();
+        assert!(crate::render::rms(&side) > 0.01, "full spread left the chord centred");
+        let mid_diff = wide.iter().zip(&centred).map(|(w, c)| ((w.0 + w.1) - (c.0 + c.1)).abs() * 0.5).fold(0.0, f32::max);
+        // To within the master clipper's slight curve.
+        assert!(mid_diff < 1e-3, "the spread changed the mid by {}", mid_diff);
+    }
+
+    #[test]
+    fn synth_1_inserts_process_the_spread_voices() {
+        // A gate set above the chord's level never opens, in either channel.
+        let (_, gated) = spread_render_with(1.0, |s| {
+            let mut gate = InsertKind::NoiseGate.build(SR);
+            gate.set_param("threshold_db", 0.0);
+            s.fx.push(gate);
+        });
+        assert!(gated.iter().all(|&f| f == (0.0, 0.0)), "the spread chord got past a closed S1 gate");
+
+        // A low-pass insert darkens the channels alike: a centred chord stays mono.
+        let (_, filtered) = spread_render_with(0.0, |s| {
+            let mut lp = InsertKind::Filter.build(SR);
+            lp.set_param("cutoff", 300.0);
+            s.fx.push(lp);
+        });
+        assert!(filtered.iter().all(|&(l, r)| l == r), "a centred chord came out of the S1 filter in stereo");
+    }
+
+    /// Levels of a full-level note released over `release` seconds on
+    /// `curve`, up to and including the sample it finishes on.
+    fn release_levels(curve: EnvCurve, release: f32) -> Vec<f32> {
+        let g = VoiceGlobals { release_curve: curve, ..Synth::new(SR).voice_globals() };
+        let mut v = Voice::new(60, DEFAULT_ANTI_CLICK_MS * 0.001);
+        levels(&mut v, 1000);
+        v.release();
+        let mut out = Vec::new();
+        while !v.is_finished() && out.len() < 2 * (release * SR) as usize {
+            v.next_sample(SR, WaveType::Sine, 0.0, 0.0, 1.0, release, g);
+            out.push(v.level);
+        }
+        out
+    }
+
+    #[test]
+    fn exponential_release_lands_at_the_release_time() {
+        let len = (0.5 * SR) as usize;
+        let lin = release_levels(EnvCurve::Linear, 0.5);
+        let exp = release_levels(EnvCurve::Exponential, 0.5);
+        for (name, l) in [("linear", &lin), ("exponential", &exp)] {
+            // Within 0.1%: the per-sample steps add up in f32.
+            assert!(l.len().abs_diff(len) <= len / 1000, "{} release took {} samples, not {}", name, l.len(), len);
+            assert_eq!(*l.last().unwrap(), 0.0);
+        }
+        // -60 dB by 90% of the time; the linear ramp is only halfway down at half time.
+        assert!(exp[len * 9 / 10] < 0.001 * 1.01, "exponential release at 90%: {}", exp[len * 9 / 10]);
+        assert!(exp[len / 2] < 0.05, "exponential release at half time: {}", exp[len / 2]);
+        assert!((lin[len / 2] - 0.5).abs() < 0.01, "linear release at half time: {}", lin[len / 2]);
+        assert!(exp.windows(2).all(|w| w[1] <= w[0]), "exponential release should only fall");
+    }
+
+    #[test]
+    fn exponential_attack_reaches_full_level_at_the_attack_time() {
+        let len = (0.2 * SR) as usize;
+        for curve in [EnvCurve::Linear, EnvCurve::Exponential] {
+            let g = VoiceGlobals { attack_curve: curve, ..Synth::new(SR).voice_globals() };
+            let mut v = Voice::new(60, DEFAULT_ANTI_CLICK_MS * 0.001);
+            let mut n = 0;
+            while v.stage == EnvelopeStage::Attack && n < 2 * len {
+                v.next_sample(SR, WaveType::Sine, 0.2, 0.1, 1.0, 0.1, g);
+                n += 1;
+            }
+            assert!(n.abs_diff(len) <= 1, "{} attack took {} samples, not {}", curve.name(), n, len);
+        }
+    }
+
+    /// RMS of 200 ms of note 60 with `wave`, on synth 2 if `bus2` (else synth 1).
+    fn wave_rms(wave: WaveType, trims: [f32; 4], bus2: bool) -> f32 {
+        let mut s = synth();
+        (s.wave_type, s.wave_type2, s.wave_trims) = (wave, wave, trims);
+        // Quiet enough that the master clipper stays linear.
+        (s.volume, s.volume2) = (0.05, 0.05);
+        if bus2 { s.note_on2(60, MAX_VELOCITY) } else { s.note_on(60) }
+        run(&mut s, 2000);
+        let buf: Vec<f32> = (0..8820).map(|_| s.generate_frame().0).collect();
+        crate::render::rms(&buf)
+    }
+
+    #[test]
+    fn wave_trim_scales_its_own_wave_only() {
+        for bus2 in [false, true] {
+            for w in WaveType::ALL {
+                let base = wave_rms(w, DEFAULT_WAVE_TRIMS, bus2);
+                let mut half = DEFAULT_WAVE_TRIMS;
+                half[w as usize] *= 0.5;
+                let ratio = wave_rms(w, half, bus2) / base;
+                assert!((ratio - 0.5).abs() < 0.02, "{} (bus 2: {}) scaled by {}", w.name(), bus2, ratio);
+
+                let mut other = DEFAULT_WAVE_TRIMS;
+                other[(w as usize + 1) % 4] = 0.0;
+                assert_eq!(wave_rms(w, other, bus2), base, "{} followed another wave's trim", w.name());
+            }
+        }
+    }
+
+    #[test]
+    fn startup_fade_ramps_the_first_frames() {
+        let mut fresh = Synth::new(48_000.0);
+        let mut warm = Synth::new(48_000.0);
+        warm.frames_out = warm.startup_fade_frames();
+        let len = fresh.startup_fade_frames() as usize;
+        assert_eq!(len, 241);
+        fresh.note_on(60);
+        warm.note_on(60);
+        for i in 0..len + 100 {
+            let ((l, r), (wl, wr)) = (fresh.generate_frame(), warm.generate_frame());
+            let g = (i as f32 / len as f32).min(1.0);
+            assert!((l - wl * g).abs() < 1e-6 && (r - wr * g).abs() < 1e-6, "frame {}: {} vs {} × {}", i, l, wl, g);
+        }
+    }
+
+    /// Sample at which a synth switched to 48 kHz first sounds, with the
+    /// pattern set up by `arm`.
+    fn onset_at_48k(arm: impl FnOnce(&mut Synth)) -> usize {
+        let mut s = Synth::new(SR);
+        s.set_sample_rate(48_000.0);
+        arm(&mut s);
+        rewind_and_play(&mut s);
+        let sps = samples_per_step(48_000.0, s.bpm);
+        first_onset(&render_mono(&mut s, step_start(8, sps) as usize)).expect("nothing sounded")
+    }
+
+    #[test]
+    fn set_sample_rate_retimes_the_sequencers_and_drums() {
+        let sps = samples_per_step(48_000.0, Synth::new(SR).bpm);
+        let lane = onset_at_48k(|s| s.lanes[0].steps[4] = Some(60));
+        let seq2 = onset_at_48k(|s| s.sequencer2.steps[4] = Some(48));
+        let kick = onset_at_48k(|s| s.drum_machine.toggle_step(0, 4));
+        let at = step_start(4, sps) as usize;
+        for (what, onset) in [("lane", lane), ("synth 2", seq2), ("kick", kick)] {
+            assert!((at..at + 8).contains(&onset), "{} step 4 due at {}, sounded at {}", what, at, onset);
+        }
+    }
+
+    #[test]
+    fn step_click_lands_on_the_step_sample() {
+        let mut s = Synth::new(SR);
+        s.step_click.on = true;
+        s.seq_swing = 0.2;
+        s.sequencer2.nudge[2] = 10;
+        rewind_and_play(&mut s);
+        let sps = samples_per_step(SR, s.bpm);
+        let buf = render_mono(&mut s, step_start(4, sps) as usize);
+        // Empty steps still fire an event, so the click is the only sound.
+        let starts: Vec<usize> = (0..buf.len())
+            .filter(|&i| buf[i] != 0.0 && (i == 0 || buf[i - 1] == 0.0))
+            .collect();
+        // Lane 1 and synth 2 share every step time except synth 2's nudged step 2.
+        let at = |k: u64, nudge: i8| {
+            (step_start(k, sps) as i64 + swing_samples(k as usize, 0.2, sps) + nudge_samples(nudge, sps)) as usize
+        };
+        assert_eq!(starts, [at(0, 0), at(1, 0), at(2, 0), at(2, 10), at(3, 0)]);
+    }
+
+    #[test]
+    fn synth2_slides_legato_and_accents_louder() {
+        let mut s = Synth::new(SR);
+        s.sequencer2.steps[..4].copy_from_slice(&[Some(48), Some(51), None, Some(55)]);
+        (s.sequencer2.slide[0], s.sequencer2.accent[3]) = (true, true);
+        rewind_and_play(&mut s);
+        let sps = samples_per_step(SR, s.bpm);
+
+        render_mono(&mut s, step_start(1, sps) as usize + 1);
+        let v = &s.voices2[&51];
+        assert!(s.voices2.len() == 1 && s.fading2.is_empty(), "slide retriggered instead of gliding");
+        assert!(v.level > 0.5 && v.stage != EnvelopeStage::Attack, "slide restarted the envelope");
+        render_mono(&mut s, (SLIDE_MS * 0.001 * SR * 5.0) as usize);
+        assert!((s.voices2[&51].frequency / note_to_freq(51) - 1.0).abs() < 0.01, "glide did not arrive");
+
+        let to_step3 = (step_start(3, sps) - s.master_clock) as usize;
+        render_mono(&mut s, to_step3 + 1);
+        assert_eq!(s.voices2[&55].velocity, ACCENT_GAIN);
+        assert!(s.accent_env2 > 0.9 && s.filter2.cutoff_mod > 2.0, "accent did not open the filter");
+    }
+
+    #[test]
+    fn melodic_and_drum_swing_are_independent() {
+        let sps = samples_per_step(48_000.0, Synth::new(SR).bpm);
+        let lane = onset_at_48k(|s| { s.lanes[0].steps[1] = Some(60); s.seq_swing = 0.25; s.drum_machine.swing = 0.5; });
+        let kick = onset_at_48k(|s| { s.drum_machine.toggle_step(0, 1); s.seq_swing = 0.5; });
+        let at = step_start(1, sps) as usize;
+        let swung = at + (0.25 * sps).round() as usize;
+        assert!((swung..swung + 8).contains(&lane), "lane step 1 due at {}, sounded at {}", swung, lane);
+        assert!((at..at + 8).contains(&kick), "melodic swing moved the kick to {}", kick);
+    }
+
+    #[test]
+    fn solo_silences_the_rest_of_the_mix() {
+        use crate::render::is_silent;
+        let mut s = synth();
+        s.note_on(60);
+        run(&mut s, 2000);
+        s.solo.toggle(SoloTarget::Drum(0));
+        // The bus gain fades out rather than cutting; the filter and DC
+        // blocker have settled 100 ms on.
+        assert!(!is_silent(&render_mono(&mut s, 100)), "the solo cut the synth");
+        assert!(is_silent(&render_mono(&mut s, 8820)[4410..]), "synth 1 still heard under a drum solo");
+        s.solo.clear();
+        assert!(!is_silent(&render_mono(&mut s, 8820)[4410..]), "clearing the solo did not bring synth 1 back");
+
+        // Snare hits: silent under a kick solo, heard under their own.
+        let snare = |solo: SoloTarget| {
+            let mut s = synth();
+            for k in 0..4 { s.drum_machine.toggle_step(1, k * 4); }
+            s.solo.toggle(solo);
+            // Stopped frames, as the audio thread runs them, let the mute fades settle.
+            run(&mut s, 1000);
+            rewind_and_play(&mut s);
+            render_mono(&mut s, 44_100)
+        };
+        assert!(is_silent(&snare(SoloTarget::Drum(0))));
+        assert!(!is_silent(&snare(SoloTarget::Drum(1))));
+    }
+
+    #[test]
+    fn a_lane_out_of_the_solo_never_releases_the_soloed_note() {
+        let mut s = synth();
+        s.add_lane();
+        s.lanes[0].steps[0] = Some(60);
+        // Lane 1 plays the same pitch, and its next step fires half a step
+        // early, while lane 0's note should still be held.
+        s.lanes[1].steps[0] = Some(60);
+        s.lanes[1].nudge[1] = -50;
+        s.solo.toggle(SoloTarget::Lane(0));
+        rewind_and_play(&mut s);
+        let sps = samples_per_step(SR, s.bpm) as usize;
+        run(&mut s, sps * 3 / 4);
+        assert!(s.voices.get(&60).is_some_and(|v| v.stage != EnvelopeStage::Release),
+                "the muted lane released the soloed lane's note");
+
+        // Its early step makes no step click either: only lane 0's, on 0.
+        s.lanes.iter_mut().for_each(|l| l.steps[0] = None);
+        s.panic();
+        s.step_click.on = true;
+        rewind_and_play(&mut s);
+        let out = render_mono(&mut s, sps * 3 / 4);
+        assert!(!crate::render::is_silent(&out[..sps / 4]), "the soloed lane did not click");
+        assert!(crate::render::is_silent(&out[sps / 4..]), "the muted lane clicked");
+    }
+
+    #[test]
+    fn auto_gain_keeps_a_hard_panned_hit_out_of_the_other_channel() {
+        let mut s = synth();
+        s.mix_meter.set_auto_gain(true);
+        s.volume = 1.0;
+        // Every track hard right on every step: loud enough to be levelled.
+        s.set_bpm(300.0);
+        for t in &mut s.drum_machine.tracks {
+            (t.pan, t.volume) = (1.0, 1.0);
+            t.steps.fill(100);
+        }
+        rewind_and_play(&mut s);
+        let out = crate::render::render(&mut s, 88_200);
+        assert!(s.mix_meter.gain < 0.95, "the leveler never cut: gain {}", s.mix_meter.gain);
+        assert!(out.iter().all(|&(l, _)| l == 0.0), "a hard-right mix leaked into the left channel");
+        assert!(out.iter().any(|&(_, r)| r.abs() > 0.1));
+    }
+
+    #[test]
+    fn set_sample_rate_keeps_the_playhead_on_its_step() {
+        let mut s = Synth::new(SR);
+        let old = samples_per_step(SR, s.bpm);
+        s.master_clock = step_start(37, old) + (old * 0.5) as u64;
+        s.set_sample_rate(48_000.0);
+        let new = samples_per_step(48_000.0, s.bpm);
+        assert_eq!(step_at(s.master_clock, new), 37);
+        let frac = (s.master_clock - step_start(37, new)) as f64 / new;
+        assert!((frac - 0.5).abs() < 0.01, "playhead moved to {} of the step", frac);
+    }
+}
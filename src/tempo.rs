@@ -0,0 +1,72 @@
+// ── Tempo ramp ────────────────────────────────────────────────────────────────
+//
+// Accelerando / ritardando automation: glide the master BPM from its current
+// value to a target over a number of bars, then hold.  The ramp advances once
+// per 16th-note grid step, so every step plays at one tempo and the change is
+// applied exactly on a step boundary.  Bars are of the time signature the
+// ramp started in.
+
+/// Longest ramp accepted, in bars.
+pub const MAX_RAMP_BARS: u32 = 64;
+
+pub struct TempoRamp {
+    pub from: f32,
+    pub to:   f32,
+    /// Ramp length in grid steps, and the bar length it was given in.
+    steps: u64,
+    bar:   u64,
+    /// Grid step the ramp started on (set on the first step it sees).
+    start: Option<u64>,
+    /// Ramp steps applied so far.
+    done:  u64,
+}
+
+impl TempoRamp {
+    /// Ramp over `bars` bars of `bar` grid steps each.
+    pub fn new(from: f32, to: f32, bars: u32, bar: u64) -> Self {
+        let bar = bar.max(1);
+        Self { from, to, steps: bars.max(1) as u64 * bar, bar, start: None, done: 0 }
+    }
+
+    /// Length of the ramp in bars.
+    pub fn bars(&self) -> u64 { self.steps / self.bar }
+
+    /// Fraction of the ramp completed, 0.0–1.0.
+    pub fn progress(&self) -> f32 { self.done as f32 / self.steps as f32 }
+
+    pub fn is_finished(&self) -> bool { self.done >= self.steps }
+
+    /// BPM for grid step `k`, or `None` if the ramp hasn't moved on since
+    /// the last call.  Linear in steps: the first step already moves one
+    /// increment and the last lands exactly on `to`.
+    pub fn bpm_at(&mut self, k: u64) -> Option<f32> {
+        let start = *self.start.get_or_insert(k);
+        let n = (k.saturating_sub(start) + 1).min(self.steps);
+        if n == self.done { return None; }
+        self.done = n;
+        Some(self.from + (self.to - self.from) * n as f32 / self.steps as f32)
+    }
+}
+
+// ── Time signature ────────────────────────────────────────────────────────────
+//
+// `Synth::time_sig` as (beats, note value) on the 16th-note step grid: a bar
+// is `beats × 16 / unit` steps.  It sets the bar for the transport's
+// bar.beat.step counter, the downbeat flash, the grid dividers and tempo
+// ramps; the sequencers themselves keep playing their own step counts.
+
+/// Accepted signatures: 1 to this many beats of a quarter or an eighth note.
+pub const MAX_TIME_SIG_BEATS: u8 = 16;
+pub const TIME_SIG_UNITS: [u8; 2] = [4, 8];
+/// Signatures a bare `timesig` cycles through; the first is the default.
+pub const COMMON_TIME_SIGS: [(u8, u8); 5] = [(4, 4), (3, 4), (6, 8), (5, 4), (7, 8)];
+
+/// Grid steps in a bar of `sig` (16 in 4/4, 12 in 3/4 or 6/8).
+pub fn bar_steps((beats, unit): (u8, u8)) -> u64 {
+    beats as u64 * 16 / unit as u64
+}
+
+/// True for a signature `timesig` accepts.
+pub fn valid_time_sig((beats, unit): (u8, u8)) -> bool {
+    (1..=MAX_TIME_SIG_BEATS).contains(&beats) && TIME_SIG_UNITS.contains(&unit)
+}
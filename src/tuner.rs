@@ -0,0 +1,97 @@
+use std::f64::consts::TAU;
+
+use crate::synth::{note_name, note_to_freq};
+
+/// Concert pitch for A4 until `tuner ref` changes it.
+pub const DEFAULT_A4: f32 = 440.0;
+/// Accepted range for the A4 reference, in Hz.
+pub const A4_RANGE: std::ops::RangeInclusive<f32> = 415.0..=466.0;
+/// Accepted range for a fixed tuner frequency, in Hz.
+pub const HZ_RANGE: std::ops::RangeInclusive<f32> = 20.0..=20000.0;
+/// Output level of the reference tone.
+const TUNER_LEVEL: f32 = 0.25;
+/// Toggle fade, in milliseconds (long enough not to click, too short to hear).
+const TOGGLE_FADE_MS: f32 = 10.0;
+
+// ── Tuner (reference tone) ────────────────────────────────────────────────────
+//
+// A steady sine for tuning external instruments.  It is not a `Voice`: no
+// envelope, no waveform, no filter or effects — it joins the output after
+// the master chain, so what you hear is exactly `freq()`.
+
+/// What the tuner plays.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TunerPitch {
+    /// A note, tuned relative to the A4 reference.
+    Note(u8),
+    /// A fixed frequency in Hz (ignores the reference).
+    Hz(f32),
+}
+
+pub struct Tuner {
+    pub on:    bool,
+    pub pitch: TunerPitch,
+    /// Reference pitch for A4, in Hz.
+    pub a4:    f32,
+    phase:     f64,
+    gain:      f32,
+    fade_step: f32,
+    sample_rate: f32,
+}
+
+impl Tuner {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            on: false,
+            pitch: TunerPitch::Note(69),
+            a4: DEFAULT_A4,
+            phase: 0.0,
+            gain: 0.0,
+            fade_step: 1.0 / (TOGGLE_FADE_MS * 0.001 * sample_rate).max(1.0),
+            sample_rate,
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.fade_step = 1.0 / (TOGGLE_FADE_MS * 0.001 * sample_rate).max(1.0);
+        self.sample_rate = sample_rate;
+    }
+
+    /// Frequency of the tone, in Hz.
+    pub fn freq(&self) -> f32 {
+        match self.pitch {
+            TunerPitch::Note(n) => note_to_freq(n) * self.a4 / DEFAULT_A4,
+            TunerPitch::Hz(hz)  => hz,
+        }
+    }
+
+    /// Readout such as `A4 440.00 Hz` or `1000.00 Hz`.
+    pub fn label(&self) -> String {
+        match self.pitch {
+            TunerPitch::Note(n) => format!("{} {:.2} Hz", note_name(n), self.freq()),
+            TunerPitch::Hz(_)   => format!("{:.2} Hz", self.freq()),
+        }
+    }
+
+    /// Stop immediately, without the fade-out.
+    pub fn reset(&mut self) {
+        self.on = false;
+        self.gain = 0.0;
+    }
+
+    /// Next sample of the tone (0.0 while off).
+    pub fn next_sample(&mut self) -> f32 {
+        let target = if self.on { 1.0 } else { 0.0 };
+        if self.gain < target {
+            self.gain = (self.gain + self.fade_step).min(target);
+        } else if self.gain > target {
+            self.gain = (self.gain - self.fade_step).max(target);
+        }
+        if self.gain == 0.0 { self.phase = 0.0; return 0.0; }
+
+        // f64 phase so a tone left running for minutes stays exact.
+        let x = (self.phase * TAU).sin() as f32;
+        self.phase = (self.phase + self.freq() as f64 / self.sample_rate as f64).fract();
+        x * self.gain * TUNER_LEVEL
+    }
+}
@@ -6,17 +6,29 @@ use ratatui::{
     Frame,
 };
 use std::collections::HashSet;
-
-use crate::app::{App, AppMode, InputMode};
-use crate::drums::DrumKind;
-use crate::effects::FilterMode;
-use crate::synth::{ChordType, note_name};
+use std::ops::RangeInclusive;
+
+use crate::app::{ab_name, note_key, pan_label, App, AppMode, InputMode, KeyLayout, LayoutMode, DRUM_FX_ROW, FALLBACK_RELEASE_THRESHOLD,
+                MASTER_FX_ROW, SYNTH_FX_ROW, METER_LEVELS};
+use crate::automation::{AutoState, Automation, Target};
+use crate::command;
+use crate::drums::{DrumKind, DEFAULT_DECAY};
+use crate::effects::{ClipMode, FilterMode, CLIP_DRIVE_MAX, MAX_INSERTS, WIDENER_MAX_WIDTH};
+use crate::scale::{detect_chord, ScaleQuantizer};
+use crate::sequencer;
+use crate::solo::{SoloState, SoloTarget};
+use crate::synth::{ChordType, WaveType, note_name};
 
 // ── Top-level routing ─────────────────────────────────────────────────────────
 
 /// Draw all panels simultaneously.  `app.mode` controls which panel has
-/// keyboard focus (highlighted border), not what is visible.
+/// keyboard focus (highlighted border), not what is visible — except in
+/// the compact layout, which shows only the focused panel.
 pub fn draw(f: &mut Frame, app: &App, enhanced: bool) {
+    if app.layout == LayoutMode::Compact {
+        draw_compact(f, app);
+        return;
+    }
     let area = f.area();
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -26,7 +38,7 @@ pub fn draw(f: &mut Frame, app: &App, enhanced: bool) {
             Constraint::Length(8),  // synth seq 1     chunks[2]
             Constraint::Length(8),  // synth seq 2     chunks[3]
             Constraint::Length(12), // drum machine    chunks[4]
-            Constraint::Length(8),  // effects         chunks[5]
+            Constraint::Length(12), // effects         chunks[5]
             Constraint::Length(4),  // status          chunks[6]
             Constraint::Length(6),  // scope           chunks[7]
             Constraint::Min(0),     // help            chunks[8]
@@ -44,32 +56,118 @@ pub fn draw(f: &mut Frame, app: &App, enhanced: bool) {
     draw_help(f, chunks[8], app);
 }
 
-// ── Title bar ─────────────────────────────────────────────────────────────────
+/// Compact layout: one transport line, the focused panel, and the help
+/// area (which also carries the prompts and overlays).
+fn draw_compact(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // transport line
+            Constraint::Min(0),    // focused panel
+            Constraint::Length(4), // help
+        ])
+        .split(f.area());
+
+    draw_transport(f, chunks[0], app);
+    match app.mode {
+        AppMode::Play      => draw_piano(f, chunks[1], app),
+        AppMode::SynthSeq  => draw_synth_seq(f, chunks[1], app),
+        AppMode::SynthSeq2 => draw_synth_seq2(f, chunks[1], app),
+        AppMode::Drums     => draw_drums(f, chunks[1], app),
+        AppMode::Effects   => draw_effects(f, chunks[1], app),
+    }
+    draw_help(f, chunks[2], app);
+}
 
-fn draw_title(f: &mut Frame, area: Rect, enhanced: bool, app: &App) {
-    let focus_label = match app.mode {
+fn focus_label(mode: &AppMode) -> &'static str {
+    match mode {
         AppMode::Play      => "Keyboard",
         AppMode::SynthSeq  => "Synth Seq",
         AppMode::SynthSeq2 => "Synth Seq 2",
         AppMode::Drums     => "Drums",
         AppMode::Effects   => "Effects",
+    }
+}
+
+// ── Transport line (compact layout) ───────────────────────────────────────────
+
+fn draw_transport(f: &mut Frame, area: Rect, app: &App) {
+    let d = Style::default().fg(Color::DarkGray);
+    let sep = || Span::styled(" │ ", d);
+    let (bpm, sig, wave, vol) = {
+        let s = app.synth.lock().unwrap();
+        (s.bpm, s.time_sig, s.wave_type.name(), s.volume)
+    };
+    let (bar, beat, step) = app.bar_position();
+    let running: Vec<&str> = [(app.seq_playing(), "SEQ"), (app.seq2_playing(), "SEQ2"), (app.drum_playing(), "DRUM")]
+        .into_iter().filter_map(|(on, name)| on.then_some(name)).collect();
+    let play = if running.is_empty() {
+        Span::styled("■ STOP", Style::default().fg(Color::Gray))
+    } else {
+        Span::styled(format!("▶ {}", running.join(" ")), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))
     };
+
+    let mut line = vec![
+        play,
+        sep(),
+        Span::styled("BPM ", d),
+        Span::styled(format!("{:.0}", bpm), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+        sep(),
+        Span::styled(format!("{}/{} ", sig.0, sig.1), d),
+        Span::styled(format!("{:>3}.{}.{}", bar, beat, step), Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+        sep(),
+        Span::styled("Wave ", d),
+        Span::styled(wave, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+        sep(),
+        Span::styled("Vol ", d),
+        Span::styled(format!("{:.0}%", vol * 100.0), Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
+        sep(),
+        Span::styled(focus_label(&app.mode), Style::default().fg(Color::Cyan)),
+    ];
+    if !app.status_msg.is_empty() {
+        line.push(sep());
+        line.push(Span::styled(app.status_msg.as_str(), Style::default().fg(Color::Yellow)));
+    }
+    f.render_widget(Paragraph::new(Line::from(line)), area);
+}
+
+// ── Title bar ─────────────────────────────────────────────────────────────────
+
+fn draw_title(f: &mut Frame, area: Rect, enhanced: bool, app: &App) {
+    let focus_label = focus_label(&app.mode);
     let kb_mode  = if enhanced { "enhanced" } else { "fallback" };
     let seq_ind  = if app.seq_playing()  { "  ▶SEQ"  } else { "" };
     let seq2_ind = if app.seq2_playing() { "  ▶SEQ2" } else { "" };
     let drum_ind = if app.drum_playing() { "  ▶DRUM" } else { "" };
     let fx_ind   = app.fx_indicators();
+    let ll_ind   = match app.audio.as_ref() {
+        Some(a) if a.low_latency => match a.buffer_frames {
+            Some(n) => format!("  ⚡LOW-LAT {}f", n),
+            None    => "  ⚡LOW-LAT (default buf)".to_string(),
+        },
+        Some(_) => String::new(),
+        None    => "  ✕NO AUDIO".to_string(),
+    };
+    let project  = app.project.as_deref()
+        .map(|p| format!("  ─  {}", std::path::Path::new(p).file_name().map_or(p.into(), |n| n.to_string_lossy())))
+        .unwrap_or_default();
 
     let text = format!(
-        "  RustTuiSynth  ─  Focus: {}{}{}{}{}  ─  [{}]  ─  Tab/F2: cycle focus  F1: wave  F3: drums",
-        focus_label, seq_ind, seq2_ind, drum_ind, fx_ind, kb_mode
+        "  RustTuiSynth{}  ─  Focus: {}{}{}{}{}{}  ─  [{}]  ─  Tab/F2: cycle focus  Alt+1–5: jump  F1: wave  F3: drums",
+        project, focus_label, seq_ind, seq2_ind, drum_ind, fx_ind, ll_ind, kb_mode
     );
     let color = if enhanced { Color::Cyan } else { Color::Yellow };
+    // Downbeat flash: border lights up on bar 1 and fades over a few frames
+    let border = match app.beat_flash_level() {
+        2 => Style::default().fg(Color::LightMagenta).add_modifier(Modifier::BOLD),
+        1 => Style::default().fg(Color::Magenta),
+        _ => Style::default(),
+    };
     f.render_widget(
         Paragraph::new(text)
             .style(Style::default().fg(color).add_modifier(Modifier::BOLD))
             .alignment(Alignment::Center)
-            .block(Block::default().borders(Borders::ALL)),
+            .block(Block::default().borders(Borders::ALL).border_style(border)),
         area,
     );
 }
@@ -78,10 +176,21 @@ fn draw_title(f: &mut Frame, area: Rect, enhanced: bool, app: &App) {
 
 fn draw_piano(f: &mut Frame, area: Rect, app: &App) {
     let focused = app.mode == AppMode::Play;
+    let chords = if app.chord_keys { " · ♫ Chord keys" } else { "" };
+    let fold = match (app.note_fold, app.fold_flash()) {
+        (false, _)    => "",
+        (true, false) => " · Fold",
+        (true, true)  => " · Fold ↺",
+    };
+    let solo = match app.solo_state(SoloTarget::Synth1) {
+        SoloState::Soloed => " · ◉ SOLO",
+        SoloState::Out    => " · solo'd out",
+        SoloState::Open   => "",
+    };
     let title = if focused {
-        " ► Keyboard — [←→] Octave  [↑↓] Volume  [Z-M / Q-P] Play notes "
+        format!(" ► Keyboard — [←→] Octave  [↑↓] Volume  [^K] Layout: {}  [^G] Chords{}{}{} ", app.key_layout.name(), chords, fold, solo)
     } else {
-        " Keyboard "
+        format!(" Keyboard · {}{}{}{} ", app.key_layout.name(), chords, fold, solo)
     };
     let block = Block::default()
         .title(title)
@@ -93,10 +202,25 @@ fn draw_piano(f: &mut Frame, area: Rect, app: &App) {
         });
     let inner = block.inner(area);
     f.render_widget(block, area);
-    render_piano_widget(f, inner, app.base_octave, &app.highlighted_notes());
+    render_piano_widget(f, inner, app.base_octave, app.key_layout, &app.highlighted_notes(), &app.scale_q);
 }
 
-fn render_piano_widget(f: &mut Frame, area: Rect, base_octave: i32, active: &HashSet<u8>) {
+/// Idle white key outside the active scale: grey instead of white.
+fn dim_white(sty: Style, in_scale: bool) -> Style {
+    if in_scale { sty } else { sty.bg(Color::Gray).fg(Color::DarkGray) }
+}
+
+/// Idle black key outside the active scale.
+fn dim_black(sty: Style, in_scale: bool) -> Style {
+    if in_scale { sty } else { sty.fg(Color::DarkGray).add_modifier(Modifier::DIM) }
+}
+
+/// Two-octave piano labelled with the keys of `layout`.  Sounding notes light
+/// up; with a scale active, keys outside it are dimmed (`ScaleQuantizer::contains`
+/// is true for every note when the scale is Off, so nothing dims then).
+fn render_piano_widget(
+    f: &mut Frame, area: Rect, base_octave: i32, layout: KeyLayout, active: &HashSet<u8>, scale: &ScaleQuantizer,
+) {
     let white_sem = [0u8, 2, 4, 5, 7, 9, 11];
     let has_black = [true, true, false, true, true, true, false];
     let black_sem = [1u8, 3, 0, 6, 8, 10, 0];
@@ -104,11 +228,10 @@ fn render_piano_widget(f: &mut Frame, area: Rect, base_octave: i32, active: &Has
     let n_white   = white_sem.len() * num_oct + 1;
     let base_midi = (base_octave * 12 + 12) as u8;
 
-    let lower_white = ["z","x","c","v","b","n","m"];
-    let upper_white = ["q","w","e","r","t","y","u"];
-    let lower_black = ["s","d"," ","g","h","j"," "];
+    // Number-row hint under the white keys (two-octave layout only).
     let upper_black = ["2","3"," ","5","6","7"," "];
     let note_names  = ["C","D","E","F","G","A","B"];
+    let key_label = |midi: u8| note_key(midi, base_octave, layout).map(String::from);
 
     let mut lines: Vec<Line> = Vec::new();
 
@@ -143,17 +266,19 @@ fn render_piano_widget(f: &mut Frame, area: Rect, base_octave: i32, active: &Has
             let rb_active  = hb && active.contains(&midi_rb);
 
             let ws_style = if w_active { Style::default().bg(Color::Yellow).fg(Color::Black) }
-                           else        { Style::default().bg(Color::White).fg(Color::Black) };
+                           else        { dim_white(Style::default().bg(Color::White).fg(Color::Black), scale.contains(midi_w)) };
             let bk_active_sty = Style::default().bg(Color::Yellow).fg(Color::Black);
             let bk_sty        = Style::default().bg(Color::Black).fg(Color::White);
 
-            let lc = if left_black { Span::styled("█", if lb_active { bk_active_sty } else { bk_sty }) }
+            let lc = if left_black { Span::styled("█", if lb_active { bk_active_sty } else { dim_black(bk_sty, scale.contains(midi_lb)) }) }
                      else          { Span::styled(" ", ws_style) };
             let mc = if row == 3 {
-                let label = if oct < num_oct { upper_black.get(local_wi).copied().unwrap_or(" ") } else { " " };
+                let label = if oct < num_oct && layout == KeyLayout::TwoOctave {
+                    upper_black.get(local_wi).copied().unwrap_or(" ")
+                } else { " " };
                 Span::styled(label, ws_style)
             } else { Span::styled(" ", ws_style) };
-            let rc = if hb { Span::styled("█", if rb_active { bk_active_sty } else { bk_sty }) }
+            let rc = if hb { Span::styled("█", if rb_active { bk_active_sty } else { dim_black(bk_sty, scale.contains(midi_rb)) }) }
                      else  { Span::styled(" ", ws_style) };
             s.push(lc); s.push(mc); s.push(rc); s.push(Span::raw("│"));
         }
@@ -176,22 +301,20 @@ fn render_piano_widget(f: &mut Frame, area: Rect, base_octave: i32, active: &Has
             let midi_rb = if hb { base_midi + (oct as u8) * 12 + black_sem[local_wi] } else { 0 };
             let rb_active = hb && active.contains(&midi_rb);
 
-            let ll = if local_wi > 0 && has_black[local_wi-1] {
-                if oct == 0 { lower_black[local_wi-1] } else { upper_black[local_wi-1] }
-            } else { "" };
-            let rl = if hb { if oct == 0 { lower_black[local_wi] } else { upper_black[local_wi] } } else { "" };
 
             let ws_sty   = if w_active { Style::default().bg(Color::Yellow).fg(Color::Black) }
-                           else        { Style::default().bg(Color::White).fg(Color::Black) };
+                           else        { dim_white(Style::default().bg(Color::White).fg(Color::Black), scale.contains(midi_w)) };
             let bk_a_sty = Style::default().bg(Color::Yellow).fg(Color::Black).add_modifier(Modifier::BOLD);
             let bk_sty   = Style::default().bg(Color::Black).fg(Color::DarkGray);
 
             let lhb = local_wi > 0 && has_black[local_wi-1];
             let midi_la = if lhb { base_midi + (oct as u8) * 12 + black_sem[local_wi - 1] } else { 0 };
+            let ll = key_label(midi_la).unwrap_or_else(|| " ".to_string());
+            let rl = key_label(midi_rb).unwrap_or_else(|| " ".to_string());
             let la  = lhb && active.contains(&midi_la);
-            let lc  = if lhb { Span::styled(ll, if la { bk_a_sty } else { bk_sty }) } else { Span::styled(" ", ws_sty) };
+            let lc  = if lhb { Span::styled(ll, if la { bk_a_sty } else { dim_black(bk_sty, scale.contains(midi_la)) }) } else { Span::styled(" ", ws_sty) };
             let mc  = Span::styled(" ", ws_sty);
-            let rc  = if hb { Span::styled(rl, if rb_active { bk_a_sty } else { bk_sty }) } else { Span::styled(" ", ws_sty) };
+            let rc  = if hb { Span::styled(rl, if rb_active { bk_a_sty } else { dim_black(bk_sty, scale.contains(midi_rb)) }) } else { Span::styled(" ", ws_sty) };
             s.push(lc); s.push(mc); s.push(rc); s.push(Span::raw("│"));
         }
         lines.push(Line::from(s));
@@ -210,7 +333,7 @@ fn render_piano_widget(f: &mut Frame, area: Rect, base_octave: i32, active: &Has
             };
             let w_active = active.contains(&midi_w);
             let sty = if w_active { Style::default().bg(Color::Yellow).fg(Color::Black) }
-                      else        { Style::default().bg(Color::White).fg(Color::Black) };
+                      else        { dim_white(Style::default().bg(Color::White).fg(Color::Black), scale.contains(midi_w)) };
             let hbl = local_wi > 0 && has_black[local_wi-1];
             let hbr = wi < n_white-1 && has_black[local_wi];
             s.push(Span::styled(if hbl { "┘" } else { " " }, sty));
@@ -233,8 +356,8 @@ fn render_piano_widget(f: &mut Frame, area: Rect, base_octave: i32, active: &Has
             };
             let w_active = active.contains(&midi_w);
             let sty = if w_active { Style::default().bg(Color::Yellow).fg(Color::Black).add_modifier(Modifier::BOLD) }
-                      else        { Style::default().bg(Color::White).fg(Color::DarkGray) };
-            let label = if wi == n_white-1 { "" } else if oct == 0 { lower_white[local_wi] } else { upper_white[local_wi] };
+                      else        { dim_white(Style::default().bg(Color::White).fg(Color::DarkGray), scale.contains(midi_w)) };
+            let label = key_label(midi_w).unwrap_or_default();
             s.push(Span::styled(format!("{:^3}", label), sty));
             s.push(Span::raw("│"));
         }
@@ -254,7 +377,7 @@ fn render_piano_widget(f: &mut Frame, area: Rect, base_octave: i32, active: &Has
             };
             let w_active = active.contains(&midi_w);
             let sty = if w_active { Style::default().bg(Color::Yellow).fg(Color::Black).add_modifier(Modifier::BOLD) }
-                      else        { Style::default().bg(Color::White).fg(Color::Black) };
+                      else        { dim_white(Style::default().bg(Color::White).fg(Color::Black), scale.contains(midi_w)) };
             let name = if wi == n_white-1 { "C" } else { note_names[local_wi] };
             s.push(Span::styled(format!("{:^3}", name), sty));
             s.push(Span::raw("│"));
@@ -278,23 +401,32 @@ fn render_piano_widget(f: &mut Frame, area: Rect, base_octave: i32, active: &Has
 fn draw_synth_seq(f: &mut Frame, area: Rect, app: &App) {
     let focused = app.mode == AppMode::SynthSeq;
     let title = if focused {
-        " ► Synth Seq — [←→] Cursor  [↑↓] BPM  [Enter/Space] Play  [Del] Clear  []] Steps  [-=] Vol  [[{] Oct  [F8] Chord  [F9] Bank "
+        " ► Synth Seq — [←→] Cursor  [⇧←→] Nudge  [M-↑↓] Prob  [^←→] Shift  [↑↓] BPM  [Enter/Space] Play  [Del] Clear  []] Steps  [()] Loop  [\\|] Solo  [<>] Swing  [-=] Vol  [[{] Oct  [F8] Chord  [F9] Bank "
     } else {
         " Synth Seq "
     };
 
-    let (bpm, num_steps, current_step, playing, steps, volume, chord_name) = {
+    let (bpm, num_steps, current_step, playing, direction, vel_ramp, steps, nudge, solo, loop_pts, volume, chord_name, lanes, lane_wave, seq_swing,
+         channel_solo, prob) = {
         let s = app.synth.lock().unwrap();
-        (s.bpm, s.sequencer.num_steps, s.sequencer.current_step,
-         s.sequencer.playing, s.sequencer.steps.clone(), s.volume,
-         s.chord1.name())
+        let sq = &s.lanes[app.seq_lane.min(s.lanes.len() - 1)];
+        (s.bpm, sq.num_steps, sq.current_step, sq.playing, sq.direction, sq.velocity_ramp, sq.steps.clone(), sq.nudge.clone(),
+         sq.audition.and_then(|r| partial_span(r, sq.num_steps)),
+         partial_span((sq.loop_start, sq.loop_end), sq.num_steps),
+         s.volume, s.chord1.name(), s.lanes.len(),
+         s.lane_patches.get(app.seq_lane).copied().flatten().map(|p| p.wave), s.seq_swing,
+         s.solo.state(if s.lanes.len() > 1 { SoloTarget::Lane(app.seq_lane) } else { SoloTarget::Synth1 }), sq.prob.clone())
     };
+    let [swing_label, swing_value] = swing_spans(seq_swing);
+    let [pending, pending_gap] = pending_spans(app, focused);
     let cursor = app.seq_cursor;
     let mut lines: Vec<Line> = Vec::new();
 
     let (status_str, status_color) =
         if playing { ("▶ PLAYING", Color::Green) } else { ("■ STOPPED", Color::DarkGray) };
     lines.push(Line::from(vec![
+        pending,
+        pending_gap,
         Span::styled("BPM: ", Style::default().fg(Color::DarkGray)),
         Span::styled(format!("{:.0}", bpm), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
         Span::raw("  "),
@@ -302,6 +434,11 @@ fn draw_synth_seq(f: &mut Frame, area: Rect, app: &App) {
         Span::styled(format!("{}", num_steps), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
         Span::raw("  "),
         Span::styled(status_str, Style::default().fg(status_color).add_modifier(Modifier::BOLD)),
+        direction_span(direction),
+        velocity_span(vel_ramp),
+        Span::raw("  "),
+        swing_label,
+        swing_value,
         Span::raw("  "),
         Span::styled("Vol: ", Style::default().fg(Color::DarkGray)),
         Span::styled(format!("{:.0}%", volume * 100.0), Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
@@ -317,6 +454,11 @@ fn draw_synth_seq(f: &mut Frame, area: Rect, app: &App) {
         Span::raw("  "),
         Span::styled("Bank: ", Style::default().fg(Color::DarkGray)),
         Span::styled(format!("{}", app.seq1_bank + 1), Style::default().fg(Color::White)),
+        lane_span(app.seq_lane, lanes, lane_wave),
+        advance_span(app.seq_advance),
+        Span::styled(range_label("Loop", loop_pts.as_ref()), Style::default().fg(Color::White)),
+        Span::styled(range_label("Solo", solo.as_ref()), Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
+        solo_span(channel_solo),
     ]));
 
     let per_row = if num_steps <= 8 { 8 } else { 16 };
@@ -325,40 +467,46 @@ fn draw_synth_seq(f: &mut Frame, area: Rect, app: &App) {
 
         let mut nums = Vec::new();
         for i in chunk_start..chunk_end {
+            nums.extend(beat_separator(i, chunk_start, app.grid_beat));
             let is_ph = playing && i == current_step;
             let is_cu = i == cursor;
             let sty = if is_ph && is_cu { Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD) }
                       else if is_ph     { Style::default().fg(Color::Black).bg(Color::Green) }
                       else if is_cu     { Style::default().fg(Color::Black).bg(Color::Yellow) }
-                      else              { Style::default().fg(Color::DarkGray) };
-            nums.push(Span::styled(format!("{:^5}", i + 1), sty));
+                      else if solo.as_ref().is_some_and(|r| r.contains(&i)) { Style::default().fg(Color::Magenta) }
+                      else if loop_pts.as_ref().is_some_and(|r| r.contains(&i)) { Style::default().fg(Color::Gray) }
+                      else              { Style::default().fg(grid_shade(i, app.grid_beat)) };
+            nums.push(Span::styled(seq_step_label(i, loop_pts.as_ref()), sty));
         }
         lines.push(Line::from(nums));
 
         let mut cells = Vec::new();
-        for i in chunk_start..chunk_end {
+        for (i, step) in steps.iter().enumerate().take(chunk_end).skip(chunk_start) {
+            cells.extend(beat_separator(i, chunk_start, app.grid_beat));
             let is_ph = playing && i == current_step;
             let is_cu = i == cursor;
-            let cell = match steps[i] {
-                Some(n) => format!("[{:<3}]", note_name(n)),
+            let cell = match step {
+                Some(n) => format!("[{:<3}]", note_name(*n)),
                 None    => "[ · ]".to_string(),
             };
             let sty = if is_ph && is_cu   { Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD) }
                       else if is_ph       { Style::default().fg(Color::Black).bg(Color::Green).add_modifier(Modifier::BOLD) }
                       else if is_cu       { Style::default().fg(Color::Black).bg(Color::Yellow) }
-                      else if steps[i].is_some() { Style::default().fg(Color::White) }
-                      else               { Style::default().fg(Color::DarkGray) };
-            cells.push(Span::styled(cell, sty));
+                      else if step.is_some() && step_prob(&prob, i) < sequencer::FULL_PROB { Style::default().fg(Color::DarkGray) }
+                      else if step.is_some() { Style::default().fg(Color::White) }
+                      else               { Style::default().fg(grid_shade(i, app.grid_beat)) };
+            cells.push(Span::styled(cell, nudged(sty, nudge.get(i).copied().unwrap_or(0))));
         }
         lines.push(Line::from(cells));
     }
 
     let note_disp = steps.get(cursor).copied().flatten()
-        .map(|n| note_name(n)).unwrap_or_else(|| "·".to_string());
+        .map(note_name).unwrap_or_else(|| "·".to_string());
     lines.push(Line::from(vec![
         Span::styled("Cursor: ", Style::default().fg(Color::DarkGray)),
         Span::styled(
-            format!("step {}/{}  note: {}", cursor + 1, num_steps, note_disp),
+            format!("step {}/{}  note: {}{}{}", cursor + 1, num_steps, note_disp,
+                    nudge_label(nudge.get(cursor).copied().unwrap_or(0)), prob_label(step_prob(&prob, cursor))),
             Style::default().fg(Color::White),
         ),
     ]));
@@ -380,25 +528,34 @@ fn draw_synth_seq(f: &mut Frame, area: Rect, app: &App) {
 
 fn draw_synth_seq2(f: &mut Frame, area: Rect, app: &App) {
     let focused = app.mode == AppMode::SynthSeq2;
-    let title = if focused {
-        " ► Synth Seq 2 — [←→] Cursor  [↑↓] BPM  [Enter/Space] Play  [Del] Clear  []] Steps  [F5] Wave  [-=] Vol  [[{] Oct  [F8] Chord  [F9] Bank "
-    } else {
-        " Synth Seq 2 "
+    let title = match (focused, app.bass_view) {
+        (true, false) => " ► Synth Seq 2 — [←→] Cursor  [⇧←→] Nudge  [M-↑↓] Prob  [^←→] Shift  [↑↓] BPM  [Enter/Space] Play  [Del] Clear  []] Steps  [()] Loop  [\\|] Solo  [<>] Swing  [F5] Wave  [-=] Vol  [[{] Oct  [F8] Chord  [F9] Bank  ['] 303 ",
+        (true, true)  => " ► Synth Seq 2 · 303 — [←→] Cursor  [⇧↑] Accent  [⇧↓] Slide  [⇧←→] Nudge  [M-↑↓] Prob  [↑↓] BPM  [Enter/Space] Play  [Del] Clear  []] Steps  [<>] Swing  [F5] Wave  [-=] Vol  [[{] Oct  ['] Grid ",
+        (false, false) => " Synth Seq 2 ",
+        (false, true)  => " Synth Seq 2 · 303 ",
     };
 
-    let (bpm, num_steps, current_step, playing, steps, wave_name, volume2, chord_name) = {
+    let (bpm, num_steps, current_step, playing, direction, vel_ramp, steps, nudge, solo, loop_pts, wave_name, volume2, chord_name, seq_swing,
+         accent, slide, channel_solo, prob) = {
         let s = app.synth.lock().unwrap();
-        (s.bpm, s.sequencer2.num_steps, s.sequencer2.current_step,
-         s.sequencer2.playing, s.sequencer2.steps.clone(),
+        let sq = &s.sequencer2;
+        (s.bpm, sq.num_steps, sq.current_step, sq.playing, sq.direction, sq.velocity_ramp, sq.steps.clone(), sq.nudge.clone(),
+         sq.audition.and_then(|r| partial_span(r, sq.num_steps)),
+         partial_span((sq.loop_start, sq.loop_end), sq.num_steps),
          s.wave_type2.name().to_string(), s.volume2,
-         s.chord2.name())
+         s.chord2.name(), s.seq_swing, sq.accent.clone(), sq.slide.clone(), s.solo.state(SoloTarget::Synth2), sq.prob.clone())
     };
+    let flag = |v: &[bool], i: usize| v.get(i).copied().unwrap_or(false);
+    let [swing_label, swing_value] = swing_spans(seq_swing);
+    let [pending, pending_gap] = pending_spans(app, focused);
     let cursor = app.seq2_cursor;
     let mut lines: Vec<Line> = Vec::new();
 
     let (status_str, status_color) =
         if playing { ("▶ PLAYING", Color::Green) } else { ("■ STOPPED", Color::DarkGray) };
     lines.push(Line::from(vec![
+        pending,
+        pending_gap,
         Span::styled("BPM: ", Style::default().fg(Color::DarkGray)),
         Span::styled(format!("{:.0}", bpm), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
         Span::raw("  "),
@@ -406,6 +563,11 @@ fn draw_synth_seq2(f: &mut Frame, area: Rect, app: &App) {
         Span::styled(format!("{}", num_steps), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
         Span::raw("  "),
         Span::styled(status_str, Style::default().fg(status_color).add_modifier(Modifier::BOLD)),
+        direction_span(direction),
+        velocity_span(vel_ramp),
+        Span::raw("  "),
+        swing_label,
+        swing_value,
         Span::raw("  "),
         Span::styled("Wave: ", Style::default().fg(Color::DarkGray)),
         Span::styled(wave_name, Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
@@ -424,48 +586,76 @@ fn draw_synth_seq2(f: &mut Frame, area: Rect, app: &App) {
         Span::raw("  "),
         Span::styled("Bank: ", Style::default().fg(Color::DarkGray)),
         Span::styled(format!("{}", app.seq2_bank + 1), Style::default().fg(Color::White)),
+        advance_span(app.seq_advance),
+        Span::styled(range_label("Loop", loop_pts.as_ref()), Style::default().fg(Color::White)),
+        Span::styled(range_label("Solo", solo.as_ref()), Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
+        solo_span(channel_solo),
     ]));
 
+    // The 303 view has room for one row of steps (plus its accent and
+    // slide rows), so it shows the row holding the cursor.
     let per_row = if num_steps <= 8 { 8 } else { 16 };
-    for chunk_start in (0..num_steps).step_by(per_row) {
+    let rows: Vec<usize> = if app.bass_view { vec![cursor / per_row * per_row] }
+                           else             { (0..num_steps).step_by(per_row).collect() };
+    for chunk_start in rows {
         let chunk_end = (chunk_start + per_row).min(num_steps);
 
         let mut nums = Vec::new();
         for i in chunk_start..chunk_end {
+            nums.extend(beat_separator(i, chunk_start, app.grid_beat));
             let is_ph = playing && i == current_step;
             let is_cu = i == cursor;
             let sty = if is_ph && is_cu { Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD) }
                       else if is_ph     { Style::default().fg(Color::Black).bg(Color::Green) }
                       else if is_cu     { Style::default().fg(Color::Black).bg(Color::Yellow) }
-                      else              { Style::default().fg(Color::DarkGray) };
-            nums.push(Span::styled(format!("{:^5}", i + 1), sty));
+                      else if solo.as_ref().is_some_and(|r| r.contains(&i)) { Style::default().fg(Color::Magenta) }
+                      else if loop_pts.as_ref().is_some_and(|r| r.contains(&i)) { Style::default().fg(Color::Gray) }
+                      else              { Style::default().fg(grid_shade(i, app.grid_beat)) };
+            nums.push(Span::styled(seq_step_label(i, loop_pts.as_ref()), sty));
         }
         lines.push(Line::from(nums));
 
         let mut cells = Vec::new();
-        for i in chunk_start..chunk_end {
+        let mut accents = Vec::new();
+        let mut slides = Vec::new();
+        for (i, step) in steps.iter().enumerate().take(chunk_end).skip(chunk_start) {
+            cells.extend(beat_separator(i, chunk_start, app.grid_beat));
             let is_ph = playing && i == current_step;
             let is_cu = i == cursor;
-            let cell = match steps[i] {
-                Some(n) => format!("[{:<3}]", note_name(n)),
+            let cell = match step {
+                Some(n) => format!("[{:<3}]", note_name(*n)),
                 None    => "[ · ]".to_string(),
             };
             let sty = if is_ph && is_cu   { Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD) }
                       else if is_ph       { Style::default().fg(Color::Black).bg(Color::Green).add_modifier(Modifier::BOLD) }
                       else if is_cu       { Style::default().fg(Color::Black).bg(Color::Yellow) }
-                      else if steps[i].is_some() { Style::default().fg(Color::White) }
-                      else               { Style::default().fg(Color::DarkGray) };
-            cells.push(Span::styled(cell, sty));
+                      else if step.is_some() && step_prob(&prob, i) < sequencer::FULL_PROB { Style::default().fg(Color::DarkGray) }
+                      else if step.is_some() { Style::default().fg(Color::White) }
+                      else               { Style::default().fg(grid_shade(i, app.grid_beat)) };
+            cells.push(Span::styled(cell, nudged(sty, nudge.get(i).copied().unwrap_or(0))));
+            if app.bass_view {
+                accents.extend(beat_separator(i, chunk_start, app.grid_beat));
+                accents.push(bass_flag_span(flag(&accent, i), "  ●  ", Color::Red, i, cursor, app.grid_beat));
+                slides.extend(beat_separator(i, chunk_start, app.grid_beat));
+                slides.push(bass_flag_span(flag(&slide, i), " ───▸", Color::Cyan, i, cursor, app.grid_beat));
+            }
         }
         lines.push(Line::from(cells));
+        if app.bass_view {
+            lines.push(Line::from(accents));
+            lines.push(Line::from(slides));
+        }
     }
 
     let note_disp = steps.get(cursor).copied().flatten()
-        .map(|n| note_name(n)).unwrap_or_else(|| "·".to_string());
+        .map(note_name).unwrap_or_else(|| "·".to_string());
+    let flags: String = [(flag(&accent, cursor), "  accent"), (flag(&slide, cursor), "  slide")]
+        .into_iter().filter_map(|(on, s)| on.then_some(s)).collect();
     lines.push(Line::from(vec![
         Span::styled("Cursor: ", Style::default().fg(Color::DarkGray)),
         Span::styled(
-            format!("step {}/{}  note: {}", cursor + 1, num_steps, note_disp),
+            format!("step {}/{}  note: {}{}{}{}", cursor + 1, num_steps, note_disp,
+                    nudge_label(nudge.get(cursor).copied().unwrap_or(0)), prob_label(step_prob(&prob, cursor)), flags),
             Style::default().fg(Color::White),
         ),
     ]));
@@ -483,7 +673,143 @@ fn draw_synth_seq2(f: &mut Frame, area: Rect, app: &App) {
     );
 }
 
-// ── Drum machine grid ─────────────────────────────────────────────────────────
+// ── Step grid helpers ─────────────────────────────────────────────────────────
+
+/// Steps of `(first, last)` when it is valid and narrower than the pattern.
+/// One accent / slide cell of the 303 view: `on` glyph in `color`, a dot
+/// when unset, highlighted under the cursor.
+fn bass_flag_span(on: bool, glyph: &'static str, color: Color, i: usize, cursor: usize, beat: usize) -> Span<'static> {
+    let (text, fg) = if on { (glyph, color) } else { ("  ·  ", grid_shade(i, beat)) };
+    let sty = if i == cursor { Style::default().fg(if on { color } else { Color::Black }).bg(Color::Yellow) }
+              else           { Style::default().fg(fg) };
+    Span::styled(text, if on { sty.add_modifier(Modifier::BOLD) } else { sty })
+}
+
+fn partial_span((first, last): (usize, usize), num_steps: usize) -> Option<RangeInclusive<usize>> {
+    let (start, len) = sequencer::loop_span(first, last, num_steps)?;
+    (len < num_steps).then_some(start..=start + len - 1)
+}
+
+/// Header span for a play direction: dim when forward, highlighted otherwise.
+fn direction_span(dir: sequencer::Direction) -> Span<'static> {
+    let style = if dir == sequencer::Direction::Forward {
+        Style::default().fg(Color::DarkGray)
+    } else {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    };
+    Span::styled(format!(" {}", dir.label()), style)
+}
+
+/// Header span for a velocity ramp (empty when velocity is flat).
+/// "Swing: n%" header pair, highlighted while swing is on.
+fn swing_spans(swing: f32) -> [Span<'static>; 2] {
+    let pct = (swing * 100.0).round() as u32;
+    [
+        Span::styled("Swing: ", Style::default().fg(Color::DarkGray)),
+        Span::styled(format!("{}%", pct), if pct > 0 {
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        }),
+    ]
+}
+
+fn velocity_span(ramp: Option<sequencer::VelocityRamp>) -> Span<'static> {
+    match ramp {
+        Some(r) => Span::styled(format!("  Vel {} {}", if r.end >= r.start { "◢" } else { "◣" }, r.label()),
+                                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        None    => Span::raw(""),
+    }
+}
+
+/// Header span for the synth 1 lane being edited, with its waveform if it has
+/// its own sound (empty for a single lane playing synth 1's sound).
+fn lane_span(lane: usize, lanes: usize, wave: Option<WaveType>) -> Span<'static> {
+    if lanes <= 1 && wave.is_none() { return Span::raw(""); }
+    let wave = wave.map_or(String::new(), |w| format!(" {}", w.name()));
+    Span::styled(format!("  Lane: {}/{}{}", lane + 1, lanes, wave), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))
+}
+
+/// Header spans for the pending pitch-entry note, shown on the focused
+/// melodic sequencer while pitch entry is on (empty otherwise).
+fn pending_spans(app: &App, focused: bool) -> [Span<'static>; 2] {
+    if !(focused && app.pitch_entry) { return [Span::raw(""), Span::raw("")]; }
+    [Span::styled(format!(" ✎ {}  [↑↓] pitch  [Enter] place ", note_name(app.scale_q.quantize(app.pending_note))),
+        Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD)),
+     Span::raw("  ")]
+}
+
+/// Header span for the step-entry auto-advance (empty at the default of one).
+fn advance_span(advance: usize) -> Span<'static> {
+    match advance {
+        1 => Span::raw(""),
+        0 => Span::styled("  Adv: off", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        n => Span::styled(format!("  Adv: +{}", n), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    }
+}
+
+/// Header badge for a panel's channel solo: `◉ SOLO` when soloed, a dim
+/// `solo'd out` when another element's solo silences it.
+fn solo_span(state: SoloState) -> Span<'static> {
+    match state {
+        SoloState::Soloed => Span::styled("  ◉ SOLO", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        SoloState::Out    => Span::styled("  solo'd out", Style::default().fg(Color::DarkGray)),
+        SoloState::Open   => Span::raw(""),
+    }
+}
+
+/// Header suffix such as `  Solo: 3` or `  Loop: 1–8` (empty for `None`).
+fn range_label(name: &str, span: Option<&RangeInclusive<usize>>) -> String {
+    match span {
+        Some(r) if r.start() == r.end() => format!("  {}: {}", name, r.start() + 1),
+        Some(r)                         => format!("  {}: {}–{}", name, r.start() + 1, r.end() + 1),
+        None                            => String::new(),
+    }
+}
+
+/// 5-wide melodic step number, bracketed at the loop start / end.
+fn seq_step_label(i: usize, loop_pts: Option<&RangeInclusive<usize>>) -> String {
+    match loop_pts {
+        Some(r) if *r.start() == i && *r.end() == i => format!("[{:^3}]", i + 1),
+        Some(r) if *r.start() == i                  => format!("[{:^3} ", i + 1),
+        Some(r) if *r.end() == i                    => format!(" {:^3}]", i + 1),
+        _                                           => format!("{:^5}", i + 1),
+    }
+}
+
+/// Thin divider drawn before step `i` when it starts a new beat of `beat`
+/// steps within the row that began at `row_start` — in the step-number and
+/// note rows alike, so the two stay aligned.
+fn beat_separator(i: usize, row_start: usize, beat: usize) -> Option<Span<'static>> {
+    (i > row_start && i.is_multiple_of(beat))
+        .then(|| Span::styled("┆", Style::default().fg(Color::DarkGray)))
+}
+
+/// Colour of an unlit step (number or empty cell): a shade lighter when it
+/// starts a beat, so downbeats stand out from the offbeats.
+fn grid_shade(i: usize, beat: usize) -> Color {
+    if i.is_multiple_of(beat) { Color::Gray } else { Color::DarkGray }
+}
+
+/// Underline a step cell whose timing is nudged off the grid.
+fn nudged(sty: Style, nudge: i8) -> Style {
+    if nudge != 0 { sty.add_modifier(Modifier::UNDERLINED) } else { sty }
+}
+
+/// Cursor-line suffix for a nudged step (empty when on the grid).
+fn nudge_label(nudge: i8) -> String {
+    if nudge != 0 { format!("  nudge: {:+}%", nudge) } else { String::new() }
+}
+
+/// A melodic step's note probability (`FULL_PROB` past the end).
+fn step_prob(prob: &[u8], i: usize) -> u8 {
+    prob.get(i).copied().unwrap_or(sequencer::FULL_PROB)
+}
+
+/// Cursor-line suffix for a step that doesn't always play.
+fn prob_label(prob: u8) -> String {
+    if prob < sequencer::FULL_PROB { format!("  prob: {}%", prob) } else { String::new() }
+}
 
 fn drum_color(kind: DrumKind) -> Color {
     match kind {
@@ -501,24 +827,29 @@ fn drum_color(kind: DrumKind) -> Color {
 fn draw_drums(f: &mut Frame, area: Rect, app: &App) {
     let focused = app.mode == AppMode::Drums;
     let title = if focused {
-        " ► Drum Machine — [↑↓] Track  [←→] Step  [Space] Toggle  [\\] Mute  [-=] Vol  []] Steps  [p/[] Prob  [e] Euclid  [F9] Bank "
+        " ► Drum Machine — [↑↓] Track  [←→] Step  [⇧←→] Nudge  [^←→] Shift  [Space] Toggle  [\\] Mute  [-=] Vol  [{}] Pan  [;'] Decay  [./] Tune  [a] Auto-pan  [g] Groove  [r] Reverse  [w] Rec  []] Steps  [p/[] Prob  [e] Euclid  [()] Loop  [F9] Bank "
     } else {
         " Drum Machine "
     };
 
-    let (bpm, num_steps, current_step, playing, swing, tracks) = {
+    let (bpm, num_steps, current_step, playing, direction, swing, groove, loop_pts, tracks) = {
         let s = app.synth.lock().unwrap();
         let dm = &s.drum_machine;
-        let tracks: Vec<(DrumKind, Vec<u8>, bool, f32)> =
-            dm.tracks.iter().map(|t| (t.kind, t.steps.clone(), t.muted, t.volume)).collect();
-        (s.bpm, dm.num_steps, dm.current_step, dm.playing, dm.swing, tracks)
+        let loop_pts = partial_span((dm.loop_start, dm.loop_end), dm.num_steps);
+        let tracks: Vec<_> = dm.tracks.iter().enumerate()
+            .map(|(i, t)| (t.kind, t.steps.clone(), t.nudge.clone(), t.decay.clone(), t.muted, t.volume, t.pan, t.tune,
+                      t.sample.as_ref().map(|smp| smp.name.clone()), t.reverse,
+                      t.fit_steps.zip(dm.stretch_ratio(i, s.bpm)), s.solo.state(SoloTarget::Drum(i))))
+            .collect();
+        let groove = dm.groove.as_ref().map(|g| g.name.clone());
+        (s.bpm, dm.num_steps, dm.current_step, dm.playing, dm.direction, dm.swing, groove, loop_pts, tracks)
     };
     let sel_track = app.drum_track;
     let sel_step  = app.drum_step;
 
     let mut lines: Vec<Line> = Vec::new();
 
-    let swing_pct = (swing * 100.0).round() as u32;
+    let [swing_label, swing_value] = swing_spans(swing);
     let (status_str, status_color) =
         if playing { ("▶ PLAYING", Color::Green) } else { ("■ STOPPED", Color::DarkGray) };
     lines.push(Line::from(vec![
@@ -529,26 +860,47 @@ fn draw_drums(f: &mut Frame, area: Rect, app: &App) {
         Span::styled(format!("{}", num_steps), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
         Span::raw("  "),
         Span::styled(status_str, Style::default().fg(status_color).add_modifier(Modifier::BOLD)),
+        if app.drum_rec {
+            Span::styled("  ● REC", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+        } else {
+            Span::raw("")
+        },
+        direction_span(direction),
         Span::raw("  "),
-        Span::styled("Swing: ", Style::default().fg(Color::DarkGray)),
-        Span::styled(
-            format!("{}%", swing_pct),
-            if swing_pct > 0 {
-                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
-            } else {
-                Style::default().fg(Color::DarkGray)
-            },
-        ),
+        swing_label,
+        swing_value,
+        Span::raw("  "),
+        Span::styled("Groove: ", Style::default().fg(Color::DarkGray)),
+        match &groove {
+            Some(name) => Span::styled(name.clone(), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            None       => Span::styled("off", Style::default().fg(Color::DarkGray)),
+        },
         Span::raw("  "),
         Span::styled("Bank: ", Style::default().fg(Color::DarkGray)),
         Span::styled(format!("{}", app.drum_bank + 1), Style::default().fg(Color::White)),
+        Span::styled(range_label("Loop", loop_pts.as_ref()), Style::default().fg(Color::White)),
+        match tracks.get(sel_track) {
+            Some((.., Some(name), reverse, fit, _)) => Span::styled(
+                format!("  Sample: {}{}{}", name, if *reverse { " ◂rev" } else { "" },
+                    fit.map(|(n, ratio)| format!(" ↔{} steps ×{:.2}", n, ratio)).unwrap_or_default()),
+                Style::default().fg(Color::Cyan)),
+            _ => Span::raw(""),
+        },
     ]));
 
     {
-        let mut s = vec![Span::styled("              ", Style::default())];
+        let mut s = vec![Span::styled("                       ", Style::default())];
         for i in 0..num_steps {
             let is_ph = playing && i == current_step;
-            let label = if i % 4 == 0 { format!("{:>2}", i + 1) } else { " .".to_string() };
+            let label = if i.is_multiple_of(app.grid_beat) { format!("{:>2}", i + 1) } else { " .".to_string() };
+            // Loop brackets replace the leading pad / trailing char of the label
+            let tail = &label[1..];
+            let label = match &loop_pts {
+                Some(r) if *r.start() == i && *r.end() == i => "[]".to_string(),
+                Some(r) if *r.start() == i => format!("[{}", tail),
+                Some(r) if *r.end() == i   => format!("{}]", tail),
+                _ => label,
+            };
             let sty = if is_ph { Style::default().fg(Color::Green).add_modifier(Modifier::BOLD) }
                       else     { Style::default().fg(Color::DarkGray) };
             s.push(Span::styled(label, sty));
@@ -556,34 +908,55 @@ fn draw_drums(f: &mut Frame, area: Rect, app: &App) {
         lines.push(Line::from(s));
     }
 
-    for (ti, (kind, steps, muted, volume)) in tracks.iter().enumerate() {
+    for (ti, (kind, steps, nudge, decay, muted, volume, pan, tune, sample, reverse, _, solo)) in tracks.iter().enumerate() {
         let is_selected = ti == sel_track;
         let track_color = drum_color(*kind);
         let vol_pct = (volume * 100.0).round() as u32;
 
-        let mute_char  = if *muted { 'M' } else { '·' };
+        // A track out of the solo shows as muted; `S` marks a soloed one.
+        let muted = *muted || *solo == SoloState::Out;
+        let mute_char  = match (muted, solo) {
+            (true, SoloState::Out) => 's',
+            (true, _)              => 'M',
+            (_, SoloState::Soloed) => 'S',
+            _                      => '·',
+        };
         let name_style = if is_selected && !muted {
             Style::default().fg(track_color).add_modifier(Modifier::BOLD)
         } else if is_selected {
             Style::default().fg(Color::DarkGray).add_modifier(Modifier::BOLD)
-        } else if *muted {
+        } else if muted {
             Style::default().fg(Color::DarkGray)
         } else {
             Style::default().fg(track_color)
         };
-        let mute_style = Style::default().fg(Color::DarkGray);
+        let mute_style = if mute_char == 'S' {
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
         let vol_style = if is_selected && focused {
             Style::default().fg(Color::White).add_modifier(Modifier::BOLD)
         } else {
             Style::default().fg(Color::DarkGray)
         };
 
+        // Sample marker in the leading column: ♪ sampled, ◂ sampled + reversed.
+        let source = match (sample, reverse) {
+            (Some(_), true)  => '◂',
+            (Some(_), false) => '♪',
+            (None, _)        => ' ',
+        };
         let mut row: Vec<Span> = vec![
-            Span::styled(format!(" {:5}", kind.name()), name_style),
+            Span::styled(format!("{}{:5}", source, kind.name()), name_style),
             Span::styled("[", Style::default().fg(Color::DarkGray)),
             Span::styled(mute_char.to_string(), mute_style),
             Span::styled("]", Style::default().fg(Color::DarkGray)),
             Span::styled(format!("{:3}%", vol_pct), vol_style),
+            Span::styled(format!(" {:>3}", pan_label(*pan)), vol_style),
+            // Tune in semitones, blank when untuned.
+            Span::styled(if *tune == 0 { "    ".to_string() } else { format!(" {:>+3}", tune) }, vol_style),
+            meter_span(app.drum_meters.get(ti).copied().unwrap_or(0), track_color),
             Span::styled("│", Style::default().fg(Color::DarkGray)),
         ];
 
@@ -609,14 +982,17 @@ fn draw_drums(f: &mut Frame, area: Rect, app: &App) {
                 Style::default().fg(Color::Black).bg(Color::Yellow)
             } else if active && !muted {
                 Style::default().fg(track_color).add_modifier(Modifier::BOLD)
-            } else {
+            } else if active {
                 Style::default().fg(Color::DarkGray)
+            } else {
+                Style::default().fg(grid_shade(i, app.grid_beat))
             };
 
-            if i > 0 && i % 4 == 0 {
-                row.push(Span::styled("┆", Style::default().fg(Color::DarkGray)));
-            }
-            row.push(Span::styled(format!("{} ", cell_char), sty));
+            row.extend(beat_separator(i, 0, app.grid_beat));
+            // A trailing tilde marks a hit that rings longer than its natural decay.
+            let long = active && decay.get(i).is_some_and(|&d| d > DEFAULT_DECAY);
+            let tail = if long { "~" } else { " " };
+            row.push(Span::styled(format!("{}{}", cell_char, tail), nudged(sty, nudge.get(i).copied().unwrap_or(0))));
         }
 
         lines.push(Line::from(row));
@@ -635,6 +1011,12 @@ fn draw_drums(f: &mut Frame, area: Rect, app: &App) {
     );
 }
 
+/// One-character level meter for a drum track (blank when silent).
+fn meter_span(level: u8, color: Color) -> Span<'static> {
+    const BARS: [&str; METER_LEVELS as usize + 1] = [" ", "▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+    Span::styled(BARS[level.min(METER_LEVELS) as usize], Style::default().fg(color))
+}
+
 // ── Effects panel ─────────────────────────────────────────────────────────────
 
 /// 8-character progress bar.
@@ -667,7 +1049,8 @@ fn draw_effects(f: &mut Frame, area: Rect, app: &App) {
          s1_dst, s2_dst, dr_dst,
          sc_en, sc_depth, sc_rel, sc_s1, sc_s2,
          f1_en, f1_mode, f1_cut, f1_q,
-         f2_en, f2_mode, f2_cut, f2_q) = {
+         f2_en, f2_mode, f2_cut, f2_q,
+         output, limiter, master_chain, master_rack, drum_fx_name, drum_chain, synth_chain) = {
         let s = app.synth.lock().unwrap();
         (s.reverb.enabled, s.reverb.room_size, s.reverb.damping, s.reverb.mix,
          s.delay.enabled,  s.delay.time_ms,    s.delay.feedback,  s.delay.mix,
@@ -678,8 +1061,17 @@ fn draw_effects(f: &mut Frame, area: Rect, app: &App) {
          s.sidechain.enabled, s.sidechain.depth, s.sidechain.release_ms,
          s.sidechain.duck_s1, s.sidechain.duck_s2,
          s.filter1.enabled, s.filter1.mode, s.filter1.cutoff, s.filter1.q,
-         s.filter2.enabled, s.filter2.mode, s.filter2.cutoff, s.filter2.q)
+         s.filter2.enabled, s.filter2.mode, s.filter2.cutoff, s.filter2.q,
+         (s.widener.width, s.clipper.mode, s.clipper.drive, s.clipper.peak, s.voice_spread),
+         (s.limiter.enabled, s.limiter.ceiling_db, s.limiter.lookahead, s.limiter.gain),
+         s.master_fx.names(), s.master_fx.rack.clone(),
+         s.drum_machine.tracks.get(app.fx_drum_track).map(|t| t.kind.name()).unwrap_or("-----"),
+         s.drum_machine.tracks.get(app.fx_drum_track)
+             .map(|t| (t.fx.names(), t.fx.rack.clone())).unwrap_or_default(),
+         if app.fx_synth_bus == 0 { (s.fx.names(), s.fx.rack.clone()) }
+         else                     { (s.fx2.names(), s.fx2.rack.clone()) })
     };
+    let master_chain = (master_chain, master_rack);
 
     let sel = app.effects_sel;
     let par = app.effects_param;
@@ -803,6 +1195,95 @@ fn draw_effects(f: &mut Frame, area: Rect, app: &App) {
         ])
     };
 
+    // Render the stereo output row (master bus, always active)
+    let make_output_row = |fi: usize, (width, clip, drive, peak, spread): (f32, ClipMode, f32, f32, f32)| -> Line {
+        let is_sel = fi == sel;
+        let name_sty = if is_sel {
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let psty = |pi: usize| -> Style {
+            if is_sel && pi == par && focused {
+                Style::default().fg(Color::White).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Gray)
+            }
+        };
+        let width_disp = if width <= 0.0 { "Mono".to_string() }
+                         else            { format!("{:.0}%", width * 100.0) };
+
+        // Pre-clip peak meter (-48..+6 dBFS).  Over full scale the clipper is
+        // engaged, or — with clipping off — the output itself is over.
+        let db = 20.0 * peak.max(1e-6).log10();
+        let (flag, flag_sty) = match (peak > 1.0, clip) {
+            (false, _)            => ("    ", Style::default()),
+            (true, ClipMode::Off) => ("OVER", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            (true, _)             => ("CLIP", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        };
+        Line::from(vec![
+            Span::styled("[MST] ",         Style::default().fg(Color::Yellow)),
+            Span::styled("OUTPUT ",        name_sty),
+            Span::raw("  "),
+            Span::styled(format!("Wdth: [{}] {:>5}  ", pbar(width, WIDENER_MAX_WIDTH), width_disp), psty(0)),
+            Span::styled(format!("Clip: [{:^8}]  ", clip.name()), psty(1)),
+            Span::styled(format!("Drv:  [{}] {:>4.1}x  ", pbar(drive, CLIP_DRIVE_MAX), drive), psty(2)),
+            Span::styled(format!("Peak: [{}] {:>5.1}dB ", pbar(((db + 48.0) / 54.0).clamp(0.0, 1.0), 1.0), db.max(-99.0)),
+                         Style::default().fg(Color::Gray)),
+            Span::styled(flag, flag_sty),
+            if spread > 0.0 {
+                Span::styled(format!("  Sprd {:.0}%", spread * 100.0), Style::default().fg(Color::Cyan))
+            } else {
+                Span::raw("")
+            },
+            match limiter {
+                (true, ceiling, lookahead, gain) => Span::styled(
+                    format!("  Lim {:.1}dB{} GR {:.1}dB", ceiling, if lookahead { " ⇥LA" } else { "" },
+                        20.0 * gain.max(1e-6).log10()),
+                    Style::default().fg(if gain < 0.999 { Color::Yellow } else { Color::Gray })),
+                _ => Span::raw(""),
+            },
+        ])
+    };
+
+    // Render one insert-chain row: pending insert type, target, rack + chain contents
+    let rack_pick = app.racks.get(app.rack_pick).map(|r| r.name.as_str()).unwrap_or("---");
+    let make_chain_row = |fi: usize, name: &str, target: (&str, &str),
+                          (chain, rack): &(Vec<&'static str>, Option<String>)| -> Line {
+        let is_sel = fi == sel;
+        let active = !chain.is_empty();
+        let name_sty = if is_sel {
+            Style::default().fg(Color::LightYellow).add_modifier(Modifier::BOLD)
+        } else if active {
+            Style::default().fg(Color::LightYellow)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        let psty = |pi: usize| -> Style {
+            if is_sel && pi == par && focused {
+                Style::default().fg(Color::White).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Gray)
+            }
+        };
+        let chain_str = if active { chain.join(" → ") } else { "(empty)".to_string() };
+        let mut spans = vec![
+            Span::styled(format!("[{}/{}] ", chain.len(), MAX_INSERTS),
+                         Style::default().fg(if active { Color::Green } else { Color::DarkGray })),
+            Span::styled(name.to_string(), name_sty),
+            Span::raw("  "),
+            Span::styled(format!("Add:  [{:^10}]  ", app.insert_pick.name()), psty(0)),
+            Span::styled(format!("{}:  [{:^6}]  ", target.0, target.1.trim_end()), psty(1)),
+            Span::styled(format!("Rack: [{:^10}]  ", rack_pick), psty(2)),
+            Span::styled(format!("Chain: {}", chain_str),
+                         Style::default().fg(if active { Color::Gray } else { Color::DarkGray })),
+        ];
+        if let Some(r) = rack {
+            spans.push(Span::styled(format!("  ‹{}›", r), Style::default().fg(Color::LightYellow)));
+        }
+        Line::from(spans)
+    };
+
     let lines = vec![
         make_row(0, rev_en, Color::Blue,    "REVERB ", &["Room","Damp","Mix "],
                  &[rev_room, rev_damp, rev_mix], &[1.0, 1.0, 1.0], &rev_d,
@@ -818,6 +1299,11 @@ fn draw_effects(f: &mut Frame, area: Rect, app: &App) {
                  &[sc_s1 as u8 as f32, sc_s2 as u8 as f32, 0.0]),
         make_filter_row(4, f1_en, Color::Cyan,  "FILT-S1", f1_mode, f1_cut, f1_q),
         make_filter_row(5, f2_en, Color::Green, "FILT-S2", f2_mode, f2_cut, f2_q),
+        make_output_row(6, output),
+        make_chain_row(MASTER_FX_ROW, "MST-FX ", ("Bus", "Mix"), &master_chain),
+        make_chain_row(DRUM_FX_ROW,   "DRM-FX ", ("Trk", drum_fx_name), &drum_chain),
+        make_chain_row(SYNTH_FX_ROW,  "SYN-FX ", ("Bus", if app.fx_synth_bus == 0 { "S1" } else { "S2" }),
+                       &synth_chain),
     ];
 
     f.render_widget(
@@ -838,14 +1324,14 @@ fn draw_effects(f: &mut Frame, area: Rect, app: &App) {
 fn draw_status(f: &mut Frame, area: Rect, app: &App) {
     let wave    = app.wave_name();
     let vol     = app.volume();
-    let bpm     = { app.synth.lock().unwrap().bpm };
+    let (bpm, sig) = { let s = app.synth.lock().unwrap(); (s.bpm, s.time_sig) };
     let notes   = app.active_note_names();
     let notes_s = if notes.is_empty() { "—".to_string() } else { notes.join(" ") };
     let extra   = if app.status_msg.is_empty() { String::new() } else { format!("  │  {}", app.status_msg) };
 
     let scale_active = app.scale_q.active();
     let scale_str = if scale_active {
-        format!("{} {}", app.scale_q.root_name(), app.scale_q.scale.name())
+        format!("{} {}", app.scale_q.root_name(), app.scale_q.scale.short_name())
     } else {
         "Off".to_string()
     };
@@ -855,26 +1341,132 @@ fn draw_status(f: &mut Frame, area: Rect, app: &App) {
         Style::default().fg(Color::DarkGray)
     };
 
-    let text = vec![
-        Line::from(vec![
-            Span::styled("Wave: ",   Style::default().fg(Color::DarkGray)),
-            Span::styled(&wave,      Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-            Span::raw("  │  "),
-            Span::styled("BPM: ",    Style::default().fg(Color::DarkGray)),
-            Span::styled(format!("{:.0}", bpm), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+    let (sc_enabled, sc_db, seed, limiter) = {
+        let s = app.synth.lock().unwrap();
+        (s.sidechain.enabled, s.sidechain.reduction_db(), s.rng.seed(), s.limiter.enabled.then_some(s.limiter.ceiling_db))
+    };
+    let duck_spans = if sc_enabled {
+        vec![
             Span::raw("  │  "),
-            Span::styled("Vol: ",    Style::default().fg(Color::DarkGray)),
-            Span::styled(format!("{:.0}%", vol * 100.0),
+            Span::styled("Duck: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(pbar4((-sc_db / 24.0).clamp(0.0, 1.0)), Style::default().fg(Color::Magenta)),
+            Span::styled(format!(" {:>5.1}dB", sc_db),
                          Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
-            Span::raw("  │  "),
-            Span::styled("Scale: ",  Style::default().fg(Color::DarkGray)),
-            Span::styled(scale_str,  scale_style),
-            Span::styled(&extra,     Style::default().fg(Color::Yellow)),
+        ]
+    } else {
+        Vec::new()
+    };
+
+    // A/B snapshot indicator: live slot bold green, stored slot gray, empty dim
+    let ab_spans: Vec<Span> = [0, 1].iter().map(|&i| {
+        let sty = if app.ab_active == Some(i) {
+            Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)
+        } else if app.ab_stored(i) {
+            Style::default().fg(Color::Gray)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        let label = if app.ab_active == Some(i) { format!("[{}]", ab_name(i)) }
+                    else                        { format!(" {} ", ab_name(i)) };
+        Span::styled(label, sty)
+    }).collect();
+
+    let [v1, v2] = app.voice_count;
+    let voices_style = if app.steal_flash() {
+        Style::default().fg(Color::Black).bg(Color::Red).add_modifier(Modifier::BOLD)
+    } else if v1.max(v2) >= app.max_voices {
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::Gray)
+    };
+    // Pre-tanh mix level; the bar fills at `MIX_CLIP_LEVEL`, and the warning
+    // holds for a moment after an over so a short one is still seen.
+    let mix_style = match app.mix_level {
+        _ if app.clip_warning()   => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        l if l + 2 > METER_LEVELS => Style::default().fg(Color::Yellow),
+        _                         => Style::default().fg(Color::Green),
+    };
+    let mut mix_spans = vec![
+        Span::styled("Mix: ", Style::default().fg(Color::DarkGray)),
+        Span::styled(pbar4(app.mix_level as f32 / METER_LEVELS as f32), mix_style),
+    ];
+    if app.mix_cut_db < 0 {
+        mix_spans.push(Span::styled(format!(" AG{}dB", app.mix_cut_db), Style::default().fg(Color::Cyan)));
+    }
+    if app.clip_warning() {
+        mix_spans.push(Span::styled(" ⚠ CLIP", Style::default().fg(Color::Black).bg(Color::Red).add_modifier(Modifier::BOLD)));
+    }
+
+    // Output stage: the limiter ceiling, and the clip LED (latched by an
+    // over into the limiter until `clip clear`).
+    let out_spans = [
+        Span::styled("Out: ", Style::default().fg(Color::DarkGray)),
+        match limiter {
+            Some(db) => Span::styled(format!("Lim {:.1}dB", db), Style::default().fg(Color::Cyan)),
+            None     => Span::styled("Lim off", Style::default().fg(Color::DarkGray)),
+        },
+        if app.clip_led {
+            Span::styled(" ● CLIP", Style::default().fg(Color::Black).bg(Color::Red).add_modifier(Modifier::BOLD))
+        } else {
+            Span::styled(" ○ clip", Style::default().fg(Color::DarkGray))
+        },
+    ];
+
+    let mut playing_line = vec![
+        Span::styled("Voices: ", Style::default().fg(Color::DarkGray)),
+        Span::styled(format!("{}·{}/{}", v1, v2, app.max_voices), voices_style),
+        Span::raw("  │  "),
+    ];
+    playing_line.extend(mix_spans);
+    playing_line.push(Span::raw("  │  "));
+    playing_line.extend(out_spans);
+    playing_line.extend([
+        Span::raw("  │  "),
+        Span::styled("Playing: ", Style::default().fg(Color::DarkGray)),
+        Span::styled(notes_s,     Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    ]);
+    // The held notes' chord; a cluster that spells none shows "?".
+    match detect_chord(&app.active_notes) {
+        Some(name) => playing_line.extend([
+            Span::styled("  Chord: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(name, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
         ]),
-        Line::from(vec![
-            Span::styled("Playing: ", Style::default().fg(Color::DarkGray)),
-            Span::styled(notes_s,     Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        None if app.active_notes.len() >= 3 => playing_line.extend([
+            Span::styled("  Chord: ", Style::default().fg(Color::DarkGray)),
+            Span::styled("?", Style::default().fg(Color::DarkGray)),
         ]),
+        None => {}
+    }
+    playing_line.extend(duck_spans);
+
+    let mut info_line = vec![
+        Span::styled("Wave: ",   Style::default().fg(Color::DarkGray)),
+        Span::styled(&wave,      Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+        Span::raw("  │  "),
+        Span::styled("BPM: ",    Style::default().fg(Color::DarkGray)),
+        Span::styled(format!("{:.0}", bpm), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+        Span::styled(format!(" {}/{}", sig.0, sig.1), Style::default().fg(Color::Gray)),
+        Span::raw("  │  "),
+        Span::styled("Vol: ",    Style::default().fg(Color::DarkGray)),
+        Span::styled(format!("{:.0}%", vol * 100.0),
+                     Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
+        Span::raw("  │  "),
+        Span::styled("Scale: ",  Style::default().fg(Color::DarkGray)),
+        Span::styled(scale_str,  scale_style),
+        Span::styled(if app.scale_run { " ↗Run" } else { "" },
+                     Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        Span::raw("  │  "),
+        Span::styled("A/B:",     Style::default().fg(Color::DarkGray)),
+    ];
+    info_line.extend(ab_spans);
+    info_line.push(Span::raw("  │  "));
+    info_line.push(Span::styled("Seed: ", Style::default().fg(Color::DarkGray)));
+    info_line.push(Span::styled(seed.to_string(), Style::default().fg(Color::Gray)));
+    info_line.push(Span::styled(&extra, Style::default().fg(Color::Yellow)));
+
+    let text = vec![
+        Line::from(info_line),
+        Line::from(playing_line),
     ];
 
     f.render_widget(
@@ -900,6 +1492,11 @@ fn draw_oscilloscope(f: &mut Frame, area: Rect, app: &App) {
         .border_style(Style::default().fg(Color::DarkGray));
     let inner = block.inner(area);
     f.render_widget(block, area);
+    if app.low_latency() {
+        f.render_widget(Paragraph::new("off in low-latency mode  (Ctrl+U)")
+            .style(Style::default().fg(Color::DarkGray)).alignment(Alignment::Center), inner);
+        return;
+    }
 
     let (buf, pos) = {
         let s = app.synth.lock().unwrap();
@@ -937,13 +1534,63 @@ fn draw_oscilloscope(f: &mut Frame, area: Rect, app: &App) {
 
 // ── Unified help panel ────────────────────────────────────────────────────────
 
+/// Automation lanes: one row per recorded target, one cell per grid step
+/// showing the value in effect there (bar height; `■` = muted).
+fn draw_automation(f: &mut Frame, area: Rect, auto: &Automation) {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let w = Style::default().fg(Color::White);
+    let (state, sty) = match auto.state {
+        AutoState::Stopped => ("■ STOPPED".to_string(), Style::default().fg(Color::DarkGray)),
+        AutoState::Armed   => ("◌ ARMED".to_string(), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        AutoState::Recording { done, .. } => (format!("● REC {}/{}", done.floor() as usize + 1, auto.steps),
+            Style::default().fg(Color::Black).bg(Color::Red).add_modifier(Modifier::BOLD)),
+        AutoState::Playing => ("▶ PLAYING".to_string(), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+    };
+    let lanes = auto.lanes();
+    let mut lines = vec![Line::from(vec![
+        Span::styled(format!(" {} ", state), sty),
+        Span::raw(format!("  {} steps  {} lane{}  {} events", auto.steps, lanes.len(),
+            if lanes.len() == 1 { "" } else { "s" }, auto.events.len())),
+    ])];
+    if lanes.is_empty() {
+        lines.push(Line::from(Span::raw("No lanes — auto arm (next loop) or auto rec (now), then move faders, knobs, mutes")));
+    }
+    let playhead = auto.pos.floor() as usize;
+    for target in lanes {
+        let mut row = vec![Span::styled(format!("{:<13}", target.label()), w)];
+        for step in 0..auto.steps {
+            let v = auto.value_at(target, step as f64).unwrap_or(0.0);
+            let c = match target {
+                Target::Mute(_) => if v >= 0.5 { '■' } else { '·' },
+                Target::Param(_) => BARS[((v * 7.0).round() as usize).min(7)],
+            };
+            let style = if step == playhead && auto.state != AutoState::Stopped {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::Cyan)
+            };
+            row.push(Span::styled(c.to_string(), style));
+        }
+        lines.push(Line::from(row));
+    }
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(Block::default().title(" Automation — auto [arm|rec|play|stop|clear] ").borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red)))
+            .style(Style::default().fg(Color::DarkGray)),
+        area,
+    );
+}
+
 fn draw_help(f: &mut Frame, area: Rect, app: &App) {
     // File path prompt overlay — replaces help when save/load is active.
     if app.input_mode != InputMode::None {
         let action = match app.input_mode {
-            InputMode::Save => "Save to file",
-            InputMode::Load => "Load from file",
-            InputMode::None => "",
+            InputMode::Save     => "Save to file",
+            InputMode::Load     => "Load from file",
+            InputMode::RackSave => "Save rack as",
+            InputMode::Command  => "Command",
+            InputMode::None     => "",
         };
         let w = Style::default().fg(Color::White);
         let prompt = Line::from(vec![
@@ -957,58 +1604,226 @@ fn draw_help(f: &mut Frame, area: Rect, app: &App) {
             ),
             Span::styled("█", Style::default().fg(Color::White)),
         ]);
-        let hint = Line::from(vec![
+        let mut hint = vec![
             Span::styled("[Enter] ", w), Span::raw("Confirm  │  "),
             Span::styled("[Esc] ",   w), Span::raw("Cancel  │  "),
             Span::styled("[Bksp] ",  w), Span::raw("Delete char"),
+        ];
+        let title = if app.input_mode == InputMode::Command {
+            hint.push(Span::raw(format!("  │  {}", command::COMMANDS.join(", "))));
+            " Command Palette "
+        } else {
+            " File Path "
+        };
+        f.render_widget(
+            Paragraph::new(vec![prompt, Line::from(hint)])
+                .block(Block::default().title(title).borders(Borders::ALL))
+                .style(Style::default().fg(Color::DarkGray)),
+            area,
+        );
+        return;
+    }
+
+    // Output device picker — one row per device, the default first.
+    if let Some(p) = &app.device_picker {
+        let w = Style::default().fg(Color::White);
+        let current = app.audio.as_ref().map(|a| a.device.as_str());
+        let rows = std::iter::once(("Default output device", app.audio_device.is_none() && current.is_some()))
+            .chain(p.names.iter().map(|n| (n.as_str(), app.audio_device.is_some() && Some(n.as_str()) == current)));
+        let mut lines: Vec<Line> = rows.enumerate().map(|(i, (name, playing))| {
+            let sty = if i == p.sel { Style::default().fg(Color::Black).bg(Color::Yellow) }
+                      else if playing { Style::default().fg(Color::Green).add_modifier(Modifier::BOLD) }
+                      else { Style::default().fg(Color::White) };
+            Line::from(vec![
+                Span::raw(if playing { " ● " } else { "   " }),
+                Span::styled(name.to_string(), sty),
+            ])
+        }).collect();
+        // Keep the selection in view above the hint line.
+        let visible = (area.height as usize).saturating_sub(3).max(1);
+        let skip = (p.sel + 1).saturating_sub(visible);
+        lines = lines.into_iter().skip(skip).take(visible).collect();
+        lines.push(Line::from(vec![
+            Span::styled("[↑↓] ",   w), Span::raw("Select  │  "),
+            Span::styled("[Enter] ", w), Span::raw("Switch  │  "),
+            Span::styled("[r] ",     w), Span::raw("Rescan  │  "),
+            Span::styled("[Esc] ",   w), Span::raw("Close"),
+        ]));
+        f.render_widget(
+            Paragraph::new(lines)
+                .block(Block::default().title(" Output Device ").borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan)))
+                .style(Style::default().fg(Color::DarkGray)),
+            area,
+        );
+        return;
+    }
+
+    // Performance view overlay — mute groups toggled by number keys.
+    if app.perf_view {
+        let w = Style::default().fg(Color::White);
+        let mut groups: Vec<Span> = Vec::new();
+        for (i, (g, state)) in app.mute_groups.iter().zip(app.mute_group_states()).enumerate() {
+            let (label, sty) = match state {
+                Some(true)  => ("MUTED", Style::default().fg(Color::Black).bg(Color::Red).add_modifier(Modifier::BOLD)),
+                Some(false) => ("LIVE ", Style::default().fg(Color::Black).bg(Color::Green).add_modifier(Modifier::BOLD)),
+                None        => ("PART ", Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            };
+            groups.push(Span::styled(format!("[{}] ", i + 1), w));
+            groups.push(Span::styled(format!("{} ", g.name), Style::default().fg(Color::Cyan)));
+            groups.push(Span::styled(format!(" {} ", label), sty));
+            groups.push(Span::raw("   "));
+        }
+        let hint = Line::from(vec![
+            Span::styled("[1-9] ",    w), Span::raw("Toggle group  │  "),
+            Span::styled("[F4/Esc] ", w), Span::raw("Close"),
         ]);
         f.render_widget(
-            Paragraph::new(vec![prompt, hint])
-                .block(Block::default().title(" File Path ").borders(Borders::ALL))
+            Paragraph::new(vec![Line::from(groups), hint])
+                .block(Block::default().title(" Performance — Mute Groups ").borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Magenta)))
                 .style(Style::default().fg(Color::DarkGray)),
             area,
         );
         return;
     }
 
+    // MIDI mappings panel — shown by `learn` and while a learn is pending.
+    if app.cc_panel || app.cc_learn.is_some() {
+        let w = Style::default().fg(Color::White);
+        let c = Style::default().fg(Color::Cyan);
+        let mut status = vec![
+            Span::styled("Input: ", w),
+            match &app.midi_in {
+                Some(m) => Span::styled(m.path.clone(), Style::default().fg(Color::Green)),
+                None    => Span::raw("none  (midiin <device>)"),
+            },
+        ];
+        if let Some(p) = app.cc_learn {
+            status.push(Span::raw("  │  "));
+            status.push(Span::styled(format!(" LEARN {} — move a controller ", p.label()),
+                Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        }
+        let mut maps: Vec<Span> = Vec::new();
+        for b in &app.cc_map {
+            maps.push(Span::styled(format!("CC{:<3} ch{:<2}", b.cc, b.channel + 1), w));
+            maps.push(Span::styled(format!(" → {}", b.param.label()), c));
+            maps.push(Span::raw(b.last.map(|v| format!(" {:>3}", v)).unwrap_or_default()));
+            maps.push(Span::raw("   "));
+        }
+        if maps.is_empty() { maps.push(Span::raw("No mappings — learn <param>, then move a knob")); }
+        f.render_widget(
+            Paragraph::new(vec![Line::from(status), Line::from(maps)])
+                .wrap(Wrap { trim: false })
+                .block(Block::default().title(" MIDI Mappings — learn [off|<param>]  unlearn <param>|all ")
+                    .borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)))
+                .style(Style::default().fg(Color::DarkGray)),
+            area,
+        );
+        return;
+    }
+
+    // Automation lanes — shown by `auto`, and while arming/recording.
+    let auto = &app.automation;
+    if app.auto_panel || matches!(auto.state, AutoState::Armed | AutoState::Recording { .. }) {
+        draw_automation(f, area, auto);
+        return;
+    }
+
+    // Fallback-keyboard notice — until dismissed, in place of the help.
+    if app.fallback_notice {
+        let w = Style::default().fg(Color::White);
+        let ms = FALLBACK_RELEASE_THRESHOLD.as_millis();
+        let lines = vec![
+            Line::from(format!(
+                "This terminal does not report key releases, so a held note stops {} ms after its key \
+                 stops repeating: taps ring on briefly, and a hold can cut out before the key repeat starts.", ms)),
+            Line::from("For exact note releases use a terminal with the kitty keyboard protocol \
+                 (kitty, WezTerm, foot, Ghostty, recent Alacritty or iTerm2)."),
+            Line::from(vec![Span::styled("[^W] ", w), Span::raw("Dismiss")]),
+        ];
+        f.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().title(" Fallback Keyboard Mode ").borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Yellow)))
+                .style(Style::default().fg(Color::Yellow)),
+            area,
+        );
+        return;
+    }
+
     let w = Style::default().fg(Color::White);
     let d = Style::default().fg(Color::DarkGray);
 
     let global = Line::from(vec![
         Span::styled("[Tab/F2] ", w), Span::raw("Cycle focus  │  "),
+        Span::styled("[Alt+1–5] ",w), Span::raw("Focus Keys/Seq/Seq2/Drums/FX  │  "),
         Span::styled("[F1] ",     w), Span::raw("Waveform  │  "),
         Span::styled("[F3] ",     w), Span::raw("Drum play/stop  │  "),
-        Span::styled("[PgUp/Dn] ",w), Span::raw("BPM  │  "),
+        Span::styled("[F4] ",     w), Span::raw("Perf/mute groups  │  "),
+        Span::styled("[M-s/M-S] ",w), Span::raw("Solo / clear solos  │  "),
+        Span::styled("[PgUp/Dn] ",w), Span::raw("BPM (⇧ fine)  │  "),
         Span::styled("[F6] ",     w), Span::raw("Scale  │  "),
         Span::styled("[F7] ",     w), Span::raw("Root  │  "),
         Span::styled("[F8] ",     w), Span::raw("Chord  │  "),
         Span::styled("[F9] ",     w), Span::raw("Bank  │  "),
+        Span::styled("[F10] ",    w), Span::raw("A/B  │  "),
+        Span::styled("[^A/^B] ",  w), Span::raw("Store A/B  │  "),
+        Span::styled("[F11] ",    w), Span::raw("Stop (tails ring)  │  "),
+        Span::styled("[F12] ",    w), Span::raw("Panic (cut all)  │  "),
+        Span::styled("[^F] ",     w), Span::raw("Freeze  │  "),
+        Span::styled("[^R] ",     w), Span::raw("Scale run  │  "),
+        Span::styled("[^D] ",     w), Span::raw("Drone  │  "),
+        Span::styled("[^T] ",     w), Span::raw("Tuner  │  "),
+        Span::styled("[^Z] ",     w), Span::raw("Undo  │  "),
+        Span::styled("[^Y] ",     w), Span::raw("Repeat edit  │  "),
+        Span::styled("[^U] ",     w), Span::raw("Low latency  │  "),
+        Span::styled("[^N] ",     w), Span::raw(if app.layout == LayoutMode::Compact { "Full layout  │  " } else { "Compact  │  " }),
+        Span::styled("[^O] ",     w), Span::raw("Direction  │  "),
+        Span::styled("[^E] ",     w), Span::raw("Mutate  │  "),
         Span::styled("[^S] ",     w), Span::raw("Save  │  "),
         Span::styled("[^L] ",     w), Span::raw("Load  │  "),
+        Span::styled("[^P] ",     w), Span::raw("Commands  │  "),
         Span::styled("[Esc] ",    w), Span::raw("Quit"),
     ]);
 
     let focus_line = match app.mode {
         AppMode::Play => Line::from(vec![
             Span::styled("Keys: ", d),
-            Span::raw("Z X C V B N M  (white)  S D G H J  (black)  │  upper row: Q-P / 2-0"),
+            Span::raw(match app.key_layout {
+                KeyLayout::TwoOctave    => "Z X C V B N M  (white)  S D G H J  (black)  │  upper row: Q-P / 2-0",
+                KeyLayout::SingleOctave => "A S D F G H J K  (white)  W E T Y U  (black)  │  O L P ; continue up",
+                KeyLayout::Isomorphic   => "one semitone per key  │  each row a fourth above: Z-row, A-row, Q-row, 1-row",
+            }),
+            Span::raw("  │  "),
+            Span::styled("[^K] ", w), Span::raw("Layout  "),
+            Span::styled("[^G] ", w), Span::raw(if app.chord_keys { "Chord keys (on)" } else { "Chord keys" }),
         ]),
         AppMode::SynthSeq => Line::from(vec![
             Span::styled("Piano keys: ", d),
             Span::raw("set note at cursor (advances)  │  "),
+            Span::styled("[Ins] ", w), Span::raw(if app.pitch_entry { "Pitch entry off  │  " } else { "Pitch entry  │  " }),
             Span::styled("[Enter/Space] ", w), Span::raw("Play/Pause  │  "),
             Span::styled("[Del] ",   w), Span::raw("Clear  │  "),
             Span::styled("[]] ",     w), Span::raw("Cycle steps  │  "),
+            Span::styled("[()] ",    w), Span::raw("Loop start/end  │  "),
+            Span::styled("[\\/|] ",   w), Span::raw("Solo step/extend  │  "),
             Span::styled("[-=] ",    w), Span::raw("Vol  │  "),
             Span::styled("[[{] ",    w), Span::raw("Oct down/up"),
         ]),
         AppMode::SynthSeq2 => Line::from(vec![
             Span::styled("Piano keys: ", d),
             Span::raw("set note at cursor (advances)  │  "),
+            Span::styled("[Ins] ", w), Span::raw(if app.pitch_entry { "Pitch entry off  │  " } else { "Pitch entry  │  " }),
             Span::styled("[Enter/Space] ", w), Span::raw("Play/Pause  │  "),
             Span::styled("[Del] ",   w), Span::raw("Clear  │  "),
             Span::styled("[]] ",     w), Span::raw("Cycle steps  │  "),
+            Span::styled("[()] ",    w), Span::raw("Loop start/end  │  "),
+            Span::styled("[\\/|] ",   w), Span::raw("Solo step/extend  │  "),
             Span::styled("[F5] ",    w), Span::raw("Wave  │  "),
+            Span::styled("[⇧↑/⇧↓] ", w), Span::raw("Accent/Slide  │  "),
+            Span::styled("['] ",     w), Span::raw(if app.bass_view { "Grid view  │  " } else { "303 view  │  " }),
             Span::styled("[-=] ",    w), Span::raw("Vol  │  "),
             Span::styled("[[{] ",    w), Span::raw("Oct down/up"),
         ]),
@@ -1027,13 +1842,17 @@ fn draw_help(f: &mut Frame, area: Rect, app: &App) {
             Span::styled("[Del] ",  w), Span::raw("Clear  │  "),
             Span::styled("[p/[] ", w),  Span::raw("Prob +/-25%  │  "),
             Span::styled("[e] ",    w), Span::raw("Euclidean fill  │  "),
-            Span::styled("[</>] ",  w), Span::raw("Swing ±5%"),
+            Span::styled("[()] ",   w), Span::raw("Loop start/end  │  "),
+            Span::styled("[</>] ",  w), Span::raw("Swing ±5%  │  "),
+            Span::styled("[g] ",    w), Span::raw("Groove"),
         ]),
         AppMode::Effects => Line::from(vec![
-            Span::styled("[↑↓] ", w), Span::raw("Select (1-2=Rev/Dly  3=Dist  4=SC  5-6=Filt S1/S2)  │  "),
+            Span::styled("[↑↓] ", w), Span::raw("Select (1-2=Rev/Dly  3=Dist  4=SC  5-6=Filt S1/S2  7=Out  8-10=Mst/Drum/Synth FX)  │  "),
             Span::styled("[←→] ", w), Span::raw("Param  │  "),
             Span::styled("[-=] ", w), Span::raw("Adjust  │  "),
-            Span::styled("[Enter] ", w), Span::raw("On/Off  │  "),
+            Span::styled("[Enter] ", w), Span::raw("On/Off (chain: add insert / recall rack)  │  "),
+            Span::styled("[Del] ", w), Span::raw("Remove last insert  │  "),
+            Span::styled("[w] ", w), Span::raw("Save chain as rack  │  "),
             Span::styled("[Space] ", w), Span::raw("Route 0↔100%  │  "),
             Span::styled("Filt params: ", d), Span::raw("Type / Cutoff / Q"),
         ]),
@@ -1046,3 +1865,63 @@ fn draw_help(f: &mut Frame, area: Rect, app: &App) {
         area,
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synth::Synth;
+    use ratatui::{backend::TestBackend, Terminal};
+    use std::sync::{Arc, Mutex};
+
+    /// The screen rows `draw` produces for `app` on a `w`×`h` terminal.
+    fn screen(app: &App, w: u16, h: u16) -> Vec<String> {
+        let mut term = Terminal::new(TestBackend::new(w, h)).unwrap();
+        term.draw(|f| draw(f, app, false)).unwrap();
+        let buf = term.backend().buffer();
+        (0..h).map(|y| (0..w).map(|x| buf[(x, y)].symbol()).collect()).collect()
+    }
+
+    #[test]
+    fn beat_dividers_follow_the_grid_beat() {
+        let dividers = |beat, start| (0..24).filter(|&i| beat_separator(i, start, beat).is_some()).collect::<Vec<_>>();
+        assert_eq!(dividers(4, 0), [4, 8, 12, 16, 20]);
+        assert_eq!(dividers(6, 0), [6, 12, 18]);
+        assert_eq!(dividers(4, 8), [12, 16, 20], "no divider at the start of a wrapped row");
+        assert_eq!((grid_shade(12, 6), grid_shade(8, 6)), (Color::Gray, Color::DarkGray));
+
+        let mut app = App::new(Arc::new(Mutex::new(Synth::new(44_100.0))));
+        app.mode = AppMode::Drums;
+        let count = |app: &App| screen(app, 200, 90).concat().matches('┆').count();
+        let common = count(&app);
+        app.set_time_sig(Some((6, 8)));
+        assert!(count(&app) < common, "6/8 should draw fewer dividers than 4/4");
+    }
+
+    #[test]
+    fn status_bar_names_the_held_chord() {
+        let mut app = App::new(Arc::new(Mutex::new(Synth::new(44_100.0))));
+        let status = |app: &App| screen(app, 200, 90).concat();
+        app.active_notes = vec![52, 57, 60, 67];
+        assert!(status(&app).contains("Chord: Am7/E"));
+        app.active_notes = vec![60, 61, 62];
+        assert!(status(&app).contains("Chord: ?"), "a cluster should show ?");
+        app.active_notes = vec![60, 61];
+        assert!(!status(&app).contains("Chord: ?"), "two notes that spell nothing should show no chord");
+    }
+
+    #[test]
+    fn compact_layout_shows_the_transport_and_the_focused_panel() {
+        let mut app = App::new(Arc::new(Mutex::new(Synth::new(44_100.0))));
+        app.mode = AppMode::Drums;
+        app.set_compact(Some(true));
+        let rows = screen(&app, 200, 24);
+        assert!(rows[0].contains("■ STOP") && rows[0].contains("BPM 120") && rows[0].contains("4/4   1.1.1"), "{}", rows[0]);
+        let text = rows.concat();
+        assert!(text.contains("Drum Machine"), "focused panel missing");
+        assert!(!text.contains("Synth Seq") && !text.contains("Keyboard ") , "unfocused panels drawn");
+
+        app.set_compact(None);
+        assert_eq!(app.layout, LayoutMode::Full);
+        assert!(screen(&app, 200, 90).concat().contains("Synth Seq"));
+    }
+}